@@ -129,7 +129,8 @@ fn intern_write(
 }
 
 async fn do_write(op: &mut Operator, path: String, content: Vec<u8>) -> Result<()> {
-    Ok(op.write(&path, content).await?)
+    op.write(&path, content).await?;
+    Ok(())
 }
 
 /// # Safety
@@ -170,7 +171,8 @@ fn intern_append(
 }
 
 async fn do_append(op: &mut Operator, path: String, content: Vec<u8>) -> Result<()> {
-    Ok(op.write_with(&path, content).append(true).await?)
+    op.write_with(&path, content).append(true).await?;
+    Ok(())
 }
 
 /// # Safety
@@ -184,7 +184,11 @@ impl Operator {
             Either::A(buf) => buf.as_ref().to_owned(),
             Either::B(s) => s.into_bytes(),
         };
-        self.0.write(&path, c).await.map_err(format_napi_error)
+        self.0
+            .write(&path, c)
+            .await
+            .map(|_| ())
+            .map_err(format_napi_error)
     }
 
     /// Write bytes into path synchronously.
@@ -228,6 +232,7 @@ impl Operator {
             .write_with(&path, c)
             .append(true)
             .await
+            .map(|_| ())
             .map_err(format_napi_error)
     }
 
@@ -16,11 +16,12 @@
 // under the License.
 
 mod read;
+mod stat;
 mod utils;
 mod write;
 
 use criterion::criterion_group;
 use criterion::criterion_main;
 
-criterion_group!(benches, read::bench, write::bench);
+criterion_group!(benches, read::bench, stat::bench, write::bench);
 criterion_main!(benches);
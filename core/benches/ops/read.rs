@@ -18,6 +18,7 @@
 use criterion::Criterion;
 use futures::io;
 use futures::AsyncReadExt;
+use opendal::layers::ReadAheadLayer;
 use opendal::Operator;
 use rand::prelude::*;
 use size::Size;
@@ -29,6 +30,7 @@ pub fn bench(c: &mut Criterion) {
         bench_read_full(c, op.info().scheme().into_static(), op.clone());
         bench_read_part(c, op.info().scheme().into_static(), op.clone());
         bench_read_parallel(c, op.info().scheme().into_static(), op.clone());
+        bench_read_sequential(c, op.info().scheme().into_static(), op.clone());
     }
 }
 
@@ -149,3 +151,49 @@ fn bench_read_parallel(c: &mut Criterion, name: &str, op: Operator) {
 
     group.finish()
 }
+
+/// Read the whole file in small buffers with some cpu work between reads,
+/// with and without read ahead.
+fn bench_read_sequential(c: &mut Criterion, name: &str, op: Operator) {
+    let mut group = c.benchmark_group(format!("service_{name}_read_sequential"));
+
+    let mut rng = thread_rng();
+
+    for size in [Size::from_mebibytes(4), Size::from_mebibytes(16)] {
+        let content = gen_bytes(&mut rng, size.bytes() as usize);
+        let path = uuid::Uuid::new_v4().to_string();
+        let temp_data = TempData::generate(op.clone(), &path, content.clone());
+
+        let read_ahead_op = op.clone().layer(ReadAheadLayer::new(4 * 1024 * 1024));
+        for (desc, op) in [("plain", op.clone()), ("read_ahead", read_ahead_op)] {
+            group.throughput(criterion::Throughput::Bytes(size.bytes() as u64));
+            group.bench_with_input(
+                format!("{desc}/{}", size.to_string()),
+                &(op, &path),
+                |b, (op, path)| {
+                    b.to_async(&*TOKIO).iter(|| async {
+                        let mut r = op.reader(path).await.unwrap();
+                        let mut buf = vec![0; 64 * 1024];
+                        loop {
+                            let n = r.read(&mut buf).await.unwrap();
+                            if n == 0 {
+                                break;
+                            }
+
+                            // mock some cpu work on every buffer
+                            let mut d = 0u64;
+                            for c in &buf[..n] {
+                                d = d.wrapping_mul(31).wrapping_add(*c as u64);
+                            }
+                            let _ = d;
+                        }
+                    })
+                },
+            );
+        }
+
+        drop(temp_data);
+    }
+
+    group.finish()
+}
@@ -0,0 +1,53 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use criterion::Criterion;
+use opendal::layers::StatCacheLayer;
+use opendal::Operator;
+use rand::prelude::*;
+use size::Size;
+
+use super::utils::*;
+
+pub fn bench(c: &mut Criterion) {
+    if let Some(op) = init_service() {
+        bench_stat(c, op.info().scheme().into_static(), op.clone());
+    }
+}
+
+/// Stat the same file repeatedly with and without the cache layer.
+fn bench_stat(c: &mut Criterion, name: &str, op: Operator) {
+    let mut group = c.benchmark_group(format!("service_{name}_stat"));
+
+    let mut rng = thread_rng();
+
+    let content = gen_bytes(&mut rng, Size::from_kibibytes(4).bytes() as usize);
+    let path = uuid::Uuid::new_v4().to_string();
+    let temp_data = TempData::generate(op.clone(), &path, content);
+
+    let cached = op.clone().layer(StatCacheLayer::default());
+    for (input, op) in [("no_cache", op), ("cache", cached)] {
+        group.bench_with_input(input, &(op, &path), |b, (op, path)| {
+            b.to_async(&*TOKIO).iter(|| async {
+                op.stat(path).await.unwrap();
+            })
+        });
+    }
+
+    drop(temp_data);
+    group.finish()
+}
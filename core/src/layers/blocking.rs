@@ -317,6 +317,10 @@ impl<I: oio::Page> oio::BlockingPage for BlockingWrapper<I> {
     fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
         self.handle.block_on(self.inner.next())
     }
+
+    fn continuation_token(&self) -> Option<String> {
+        self.inner.continuation_token()
+    }
 }
 
 #[cfg(test)]
@@ -324,6 +328,7 @@ mod tests {
     use once_cell::sync::Lazy;
 
     use super::*;
+    use crate::services;
     use crate::types::Result;
 
     static RUNTIME: Lazy<tokio::runtime::Runtime> = Lazy::new(|| {
@@ -349,6 +354,24 @@ mod tests {
         assert!(layer.is_ok())
     }
 
+    #[test]
+    fn test_blocking_layer_read_write_list() -> Result<()> {
+        let layer = create_blocking_layer()?;
+        // Operations are forwarded to the async APIs of memory.
+        let op = Operator::new(services::Memory::default())?
+            .layer(layer)
+            .finish()
+            .blocking();
+
+        op.write("dir/file", "Hello, World!")?;
+        assert_eq!(op.read("dir/file")?, b"Hello, World!");
+
+        let entries = op.list("dir/")?;
+        assert!(entries.iter().any(|e| e.path() == "dir/file"));
+
+        Ok(())
+    }
+
     #[test]
     fn test_blocking_layer_in_async_context() {
         // create and drop in an async context
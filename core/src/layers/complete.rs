@@ -149,6 +149,89 @@ impl<A: Accessor> CompleteReaderAccessor<A> {
         .with_operation(op)
     }
 
+    /// Build an unsupported error that names the exact capability missing,
+    /// like `write_with_content_type`.
+    fn new_unsupported_capability_error(
+        &self,
+        op: impl Into<&'static str>,
+        capability: &'static str,
+    ) -> Error {
+        let scheme = self.meta.scheme();
+        Error::new(
+            ErrorKind::Unsupported,
+            &format!("service {scheme} doesn't support {capability}"),
+        )
+        .with_operation(op)
+        .with_context("capability", capability)
+    }
+
+    /// Check write args against capability, so that we can fail fast instead
+    /// of sending requests that service will reject or silently ignore.
+    fn check_write_args(&self, op: Operation, cap: &Capability, args: &OpWrite) -> Result<()> {
+        let checks = [
+            (args.append(), cap.write_can_append, "write_can_append"),
+            (
+                args.content_type().is_some(),
+                cap.write_with_content_type,
+                "write_with_content_type",
+            ),
+            (
+                args.content_disposition().is_some(),
+                cap.write_with_content_disposition,
+                "write_with_content_disposition",
+            ),
+            (
+                args.cache_control().is_some(),
+                cap.write_with_cache_control,
+                "write_with_cache_control",
+            ),
+            (
+                args.checksum().is_some(),
+                cap.write_with_checksum,
+                "write_with_checksum",
+            ),
+            (
+                args.encryption().is_some(),
+                cap.write_with_encryption,
+                "write_with_encryption",
+            ),
+            (
+                args.user_metadata().is_some(),
+                cap.write_with_user_metadata,
+                "write_with_user_metadata",
+            ),
+            (
+                args.if_not_exists(),
+                cap.write_with_if_not_exists,
+                "write_with_if_not_exists",
+            ),
+        ];
+
+        for (used, supported, name) in checks {
+            if used && !supported {
+                return Err(self.new_unsupported_capability_error(op, name));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check list args against capability.
+    ///
+    /// `limit` and `page_size` are only hints of page size, so they will not
+    /// be checked here.
+    fn check_list_args(&self, op: Operation, cap: &Capability, args: &OpList) -> Result<()> {
+        if args.start_after().is_some() && !cap.list_with_start_after {
+            return Err(self.new_unsupported_capability_error(op, "list_with_start_after"));
+        }
+        if args.start_after_token().is_some() && !cap.list_with_start_after_token {
+            return Err(self.new_unsupported_capability_error(op, "list_with_start_after_token"));
+        }
+        if args.include_self() && !cap.list_with_include_self {
+            return Err(self.new_unsupported_capability_error(op, "list_with_include_self"));
+        }
+        Ok(())
+    }
+
     async fn complete_reader(
         &self,
         path: &str,
@@ -158,6 +241,15 @@ impl<A: Accessor> CompleteReaderAccessor<A> {
         if !capability.read {
             return Err(self.new_unsupported_error(Operation::Read));
         }
+        if args.encryption().is_some() && !capability.read_with_encryption {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                &format!(
+                    "service {} doesn't support operation read with encryption",
+                    self.info().scheme()
+                ),
+            ));
+        }
 
         let seekable = capability.read_can_seek;
         let streamable = capability.read_can_next;
@@ -172,6 +264,15 @@ impl<A: Accessor> CompleteReaderAccessor<A> {
                 let r = oio::into_streamable_read(r, 256 * 1024);
                 Ok((rp, CompleteReader::NeedStreamable(r)))
             }
+            // Without range support, we can't seek or resume the reader by
+            // sending new requests, return the reader as is.
+            (false, true) if !capability.read_with_range => {
+                Ok((rp, CompleteReader::AlreadyComplete(r)))
+            }
+            (false, false) if !capability.read_with_range => {
+                let r = oio::into_streamable_read(r, 256 * 1024);
+                Ok((rp, CompleteReader::NeedStreamable(r)))
+            }
             _ => {
                 let (offset, size) = match (range.offset(), range.size()) {
                     (Some(offset), _) => (offset, content_length),
@@ -211,6 +312,15 @@ impl<A: Accessor> CompleteReaderAccessor<A> {
         if !capability.read || !capability.blocking {
             return Err(self.new_unsupported_error(Operation::BlockingRead));
         }
+        if args.encryption().is_some() && !capability.read_with_encryption {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                &format!(
+                    "service {} doesn't support operation read with encryption",
+                    self.info().scheme()
+                ),
+            ));
+        }
 
         let seekable = capability.read_can_seek;
         let streamable = capability.read_can_next;
@@ -225,6 +335,15 @@ impl<A: Accessor> CompleteReaderAccessor<A> {
                 let r = oio::into_streamable_read(r, 256 * 1024);
                 Ok((rp, CompleteReader::NeedStreamable(r)))
             }
+            // Without range support, we can't seek or resume the reader by
+            // sending new requests, return the reader as is.
+            (false, true) if !capability.read_with_range => {
+                Ok((rp, CompleteReader::AlreadyComplete(r)))
+            }
+            (false, false) if !capability.read_with_range => {
+                let r = oio::into_streamable_read(r, 256 * 1024);
+                Ok((rp, CompleteReader::NeedStreamable(r)))
+            }
             _ => {
                 let (offset, size) = match (range.offset(), range.size()) {
                     (Some(offset), _) => (offset, content_length),
@@ -267,6 +386,7 @@ impl<A: Accessor> CompleteReaderAccessor<A> {
         if !cap.list {
             return Err(self.new_unsupported_error(Operation::List));
         }
+        self.check_list_args(Operation::List, &cap, &args)?;
 
         let delimiter = args.delimiter();
 
@@ -312,6 +432,7 @@ impl<A: Accessor> CompleteReaderAccessor<A> {
         if !cap.list {
             return Err(self.new_unsupported_error(Operation::BlockingList));
         }
+        self.check_list_args(Operation::BlockingList, &cap, &args)?;
 
         let delimiter = args.delimiter();
 
@@ -355,9 +476,10 @@ impl<A: Accessor> LayeredAccessor for CompleteReaderAccessor<A> {
     type Inner = A;
     type Reader = CompleteReader<A, A::Reader>;
     type BlockingReader = CompleteReader<A, A::BlockingReader>;
-    type Writer = oio::TwoWaysWriter<
+    type Writer = oio::ThreeWaysWriter<
         CompleteWriter<A::Writer>,
         oio::ExactBufWriter<CompleteWriter<A::Writer>>,
+        oio::BufferedWriter<CompleteWriter<A::Writer>>,
     >;
     type BlockingWriter = CompleteWriter<A::BlockingWriter>;
     type Pager = CompletePager<A, A::Pager>;
@@ -382,6 +504,12 @@ impl<A: Accessor> LayeredAccessor for CompleteReaderAccessor<A> {
         if !capability.create_dir {
             return Err(self.new_unsupported_error(Operation::CreateDir));
         }
+        if args.user_metadata().is_some() && !capability.create_dir_with_user_metadata {
+            return Err(self.new_unsupported_capability_error(
+                Operation::CreateDir,
+                "create_dir_with_user_metadata",
+            ));
+        }
 
         self.inner().create_dir(path, args).await
     }
@@ -395,15 +523,7 @@ impl<A: Accessor> LayeredAccessor for CompleteReaderAccessor<A> {
         if !capability.write {
             return Err(self.new_unsupported_error(Operation::Write));
         }
-        if args.append() && !capability.write_can_append {
-            return Err(Error::new(
-                ErrorKind::Unsupported,
-                &format!(
-                    "service {} doesn't support operation write with append",
-                    self.info().scheme()
-                ),
-            ));
-        }
+        self.check_write_args(Operation::Write, &capability, &args)?;
 
         // Calculate buffer size.
         let buffer_size = args.buffer().map(|mut size| {
@@ -426,8 +546,17 @@ impl<A: Accessor> LayeredAccessor for CompleteReaderAccessor<A> {
         let w = CompleteWriter::new(w);
 
         let w = match buffer_size {
-            None => oio::TwoWaysWriter::One(w),
-            Some(size) => oio::TwoWaysWriter::Two(oio::ExactBufWriter::new(w, size)),
+            None => oio::ThreeWaysWriter::One(w),
+            Some(size) if capability.write_can_multi => {
+                oio::ThreeWaysWriter::Two(oio::ExactBufWriter::new(w, size))
+            }
+            // Appends are sent as separate requests, coalesce them by buffer size.
+            Some(size) if args.append() => {
+                oio::ThreeWaysWriter::Three(oio::BufferedWriter::new(w, size))
+            }
+            // Services that can't accept multiple writes will get all data in
+            // one write while closing, data beyond buffer size will be rejected.
+            Some(size) => oio::ThreeWaysWriter::Three(oio::BufferedWriter::one_shot(w, size)),
         };
 
         Ok((rp, w))
@@ -456,6 +585,11 @@ impl<A: Accessor> LayeredAccessor for CompleteReaderAccessor<A> {
         if !capability.stat {
             return Err(self.new_unsupported_error(Operation::Stat));
         }
+        if args.raw_headers() && !capability.stat_with_raw_headers {
+            return Err(
+                self.new_unsupported_capability_error(Operation::Stat, "stat_with_raw_headers")
+            );
+        }
 
         self.inner.stat(path, args).await.map(|v| {
             v.map_metadata(|m| {
@@ -470,6 +604,11 @@ impl<A: Accessor> LayeredAccessor for CompleteReaderAccessor<A> {
         if !capability.delete {
             return Err(self.new_unsupported_error(Operation::Delete));
         }
+        if args.if_match().is_some() && !capability.delete_with_if_match {
+            return Err(
+                self.new_unsupported_capability_error(Operation::Delete, "delete_with_if_match")
+            );
+        }
 
         self.inner().delete(path, args).await
     }
@@ -520,15 +659,7 @@ impl<A: Accessor> LayeredAccessor for CompleteReaderAccessor<A> {
             return Err(self.new_unsupported_error(Operation::BlockingWrite));
         }
 
-        if args.append() && !capability.write_can_append {
-            return Err(Error::new(
-                ErrorKind::Unsupported,
-                &format!(
-                    "service {} doesn't support operation write with append",
-                    self.info().scheme()
-                ),
-            ));
-        }
+        self.check_write_args(Operation::BlockingWrite, &capability, &args)?;
 
         self.inner
             .blocking_write(path, args)
@@ -558,6 +689,12 @@ impl<A: Accessor> LayeredAccessor for CompleteReaderAccessor<A> {
         if !capability.stat || !capability.blocking {
             return Err(self.new_unsupported_error(Operation::BlockingStat));
         }
+        if args.raw_headers() && !capability.stat_with_raw_headers {
+            return Err(self.new_unsupported_capability_error(
+                Operation::BlockingStat,
+                "stat_with_raw_headers",
+            ));
+        }
 
         self.inner.blocking_stat(path, args).map(|v| {
             v.map_metadata(|m| {
@@ -572,6 +709,12 @@ impl<A: Accessor> LayeredAccessor for CompleteReaderAccessor<A> {
         if !capability.delete || !capability.blocking {
             return Err(self.new_unsupported_error(Operation::BlockingDelete));
         }
+        if args.if_match().is_some() && !capability.delete_with_if_match {
+            return Err(self.new_unsupported_capability_error(
+                Operation::BlockingDelete,
+                "delete_with_if_match",
+            ));
+        }
 
         self.inner().blocking_delete(path, args)
     }
@@ -692,6 +835,16 @@ where
             NeedHierarchy(p) => p.next().await,
         }
     }
+
+    fn continuation_token(&self) -> Option<String> {
+        use CompletePager::*;
+
+        // Only pages returned by services as is could be resumed.
+        match self {
+            AlreadyComplete(p) => p.continuation_token(),
+            NeedFlat(_) | NeedHierarchy(_) => None,
+        }
+    }
 }
 
 impl<A, P> oio::BlockingPage for CompletePager<A, P>
@@ -708,15 +861,30 @@ where
             NeedHierarchy(p) => p.next(),
         }
     }
+
+    fn continuation_token(&self) -> Option<String> {
+        use CompletePager::*;
+
+        // Only pages returned by services as is could be resumed.
+        match self {
+            AlreadyComplete(p) => p.continuation_token(),
+            NeedFlat(_) | NeedHierarchy(_) => None,
+        }
+    }
 }
 
 pub struct CompleteWriter<W> {
     inner: Option<W>,
+    /// Metadata returned by inner writer, kept after inner has been closed.
+    metadata: Option<Metadata>,
 }
 
 impl<W> CompleteWriter<W> {
     pub fn new(inner: W) -> CompleteWriter<W> {
-        CompleteWriter { inner: Some(inner) }
+        CompleteWriter {
+            inner: Some(inner),
+            metadata: None,
+        }
     }
 }
 
@@ -752,6 +920,7 @@ where
         })?;
 
         ready!(w.poll_close(cx))?;
+        self.metadata = w.metadata();
         self.inner = None;
 
         Poll::Ready(Ok(()))
@@ -767,6 +936,10 @@ where
 
         Poll::Ready(Ok(()))
     }
+
+    fn metadata(&self) -> Option<Metadata> {
+        self.metadata.clone()
+    }
 }
 
 impl<W> oio::BlockingWrite for CompleteWriter<W>
@@ -991,6 +1164,76 @@ mod tests {
         assert!(res.is_ok())
     }
 
+    #[tokio::test]
+    async fn test_write_with_unsupported_args() {
+        let op = new_test_operator(Capability {
+            write: true,
+            ..Default::default()
+        });
+        let err = op
+            .write_with("path", vec![])
+            .content_type("text/plain")
+            .await
+            .expect_err("must fail");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+        assert!(err.to_string().contains("write_with_content_type"));
+
+        let op = new_test_operator(Capability {
+            write: true,
+            write_with_content_type: true,
+            ..Default::default()
+        });
+        let res = op
+            .write_with("path", vec![])
+            .content_type("text/plain")
+            .await;
+        assert!(res.is_ok())
+    }
+
+    #[tokio::test]
+    async fn test_stat_with_unsupported_args() {
+        let op = new_test_operator(Capability {
+            stat: true,
+            ..Default::default()
+        });
+        let err = op
+            .stat_with("path")
+            .raw_headers(true)
+            .await
+            .expect_err("must fail");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+        assert!(err.to_string().contains("stat_with_raw_headers"));
+
+        let op = new_test_operator(Capability {
+            stat: true,
+            stat_with_raw_headers: true,
+            ..Default::default()
+        });
+        let res = op.stat_with("path").raw_headers(true).await;
+        assert!(res.is_ok())
+    }
+
+    #[tokio::test]
+    async fn test_list_with_unsupported_args() {
+        let op = new_test_operator(Capability {
+            list: true,
+            ..Default::default()
+        });
+        let err = op
+            .list_with("path/")
+            .start_after("path/a")
+            .await
+            .expect_err("must fail");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+        assert!(err.to_string().contains("list_with_start_after"));
+
+        // limit and page_size are only hints, should not be rejected.
+        let res = op.list_with("path/").limit(10).await;
+        assert!(res.is_ok());
+        let res = op.list_with("path/").page_size(10).await;
+        assert!(res.is_ok())
+    }
+
     #[tokio::test]
     async fn test_presign() {
         let op = new_test_operator(Capability::default());
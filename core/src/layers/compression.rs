@@ -0,0 +1,558 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::collections::HashMap;
+use std::io;
+use std::io::Write;
+use std::task::ready;
+use std::task::Context;
+use std::task::Poll;
+
+use async_trait::async_trait;
+use bytes::Buf;
+use bytes::Bytes;
+use flate2::write::GzEncoder;
+
+use crate::raw::oio::WriteBuf;
+use crate::raw::*;
+use crate::types::parse_encoding;
+use crate::types::DecompressReader;
+use crate::types::Encoding;
+use crate::*;
+
+/// The user metadata key used to mark content as compressed.
+///
+/// Only letters, digits and `_` are used so that it's accepted by services
+/// with strict key rules like azfile.
+const COMPRESSION_METADATA_KEY: &str = "opendal_compression";
+
+/// Compress content transparently while writing and decompress it while
+/// reading.
+///
+/// # Notes
+///
+/// - The algorithm is stored in user metadata as `opendal_compression`, so
+///   the underlying service must support `write_with_user_metadata`.
+/// - Content without the marker will be read as is, so that existing files
+///   could still be read through this layer.
+/// - Content is compressed and decompressed in streaming, no full buffering
+///   is required.
+/// - `stat` and `list` report the stored (compressed) size, and the read
+///   length isn't known in advance.
+/// - Compressed content can't be read by range or seeked, reading compressed
+///   content with range will return [`ErrorKind::InvalidInput`].
+/// - Write with append will return [`ErrorKind::Unsupported`].
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::CompressionLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// let _ = Operator::new(services::Memory::default())
+///     .expect("must init")
+///     .layer(CompressionLayer::zstd())
+///     .finish();
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct CompressionLayer {
+    encoding: Encoding,
+}
+
+impl Default for CompressionLayer {
+    fn default() -> Self {
+        Self::gzip()
+    }
+}
+
+impl CompressionLayer {
+    /// Compress content with gzip, which is the default.
+    pub fn gzip() -> Self {
+        Self {
+            encoding: Encoding::Gzip,
+        }
+    }
+
+    /// Compress content with zstd.
+    pub fn zstd() -> Self {
+        Self {
+            encoding: Encoding::Zstd,
+        }
+    }
+}
+
+impl<A: Accessor> Layer<A> for CompressionLayer {
+    type LayeredAccessor = CompressionAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        CompressionAccessor {
+            inner,
+            encoding: self.encoding,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CompressionAccessor<A: Accessor> {
+    inner: A,
+    encoding: Encoding,
+}
+
+impl<A: Accessor> CompressionAccessor<A> {
+    /// Add the compression marker into write args.
+    fn with_marker(&self, path: &str, args: OpWrite) -> Result<OpWrite> {
+        let reason = if args.append() {
+            Some("compression layer doesn't support write with append")
+        } else if !self.inner.info().full_capability().write_with_user_metadata {
+            Some("compression layer requires write with user metadata")
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            return Err(Error::new(ErrorKind::Unsupported, reason)
+                .with_operation(Operation::Write)
+                .with_context("service", self.inner.info().scheme())
+                .with_context("path", path));
+        }
+
+        let mut user_metadata = args.user_metadata().cloned().unwrap_or_default();
+        user_metadata.insert(
+            COMPRESSION_METADATA_KEY.to_string(),
+            encoding_name(self.encoding).to_string(),
+        );
+        Ok(args.with_user_metadata(user_metadata))
+    }
+}
+
+#[async_trait]
+impl<A: Accessor> LayeredAccessor for CompressionAccessor<A> {
+    type Inner = A;
+    type Reader = CompressionReader<A::Reader>;
+    type BlockingReader = CompressionReader<A::BlockingReader>;
+    type Writer = CompressionWriter<A::Writer>;
+    type BlockingWriter = CompressionWriter<A::BlockingWriter>;
+    type Pager = A::Pager;
+    type BlockingPager = A::BlockingPager;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        let range = args.range();
+        let (rp, r) = self.inner.read(path, args).await?;
+
+        // Services may not return user metadata while reading.
+        let encoding = if rp.metadata().contains_metakey(Metakey::UserMetadata) {
+            parse_marker(rp.metadata())?
+        } else {
+            let rp = self.inner.stat(path, OpStat::new()).await?;
+            parse_marker(&rp.into_metadata())?
+        };
+
+        match encoding {
+            None => Ok((rp, CompressionReader::Raw(r))),
+            Some(encoding) => {
+                check_full_range(path, range)?;
+                let r = DecompressReader::new(r, encoding)?;
+                Ok((decompressed_rp(rp), CompressionReader::Decompress(r)))
+            }
+        }
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        let args = self.with_marker(path, args)?;
+        let (rp, w) = self.inner.write(path, args).await?;
+        Ok((rp, CompressionWriter::new(w, self.encoding)?))
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        self.inner.list(path, args).await
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        let range = args.range();
+        let (rp, r) = self.inner.blocking_read(path, args)?;
+
+        let encoding = if rp.metadata().contains_metakey(Metakey::UserMetadata) {
+            parse_marker(rp.metadata())?
+        } else {
+            let rp = self.inner.blocking_stat(path, OpStat::new())?;
+            parse_marker(&rp.into_metadata())?
+        };
+
+        match encoding {
+            None => Ok((rp, CompressionReader::Raw(r))),
+            Some(encoding) => {
+                check_full_range(path, range)?;
+                let r = DecompressReader::new(r, encoding)?;
+                Ok((decompressed_rp(rp), CompressionReader::Decompress(r)))
+            }
+        }
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        let args = self.with_marker(path, args)?;
+        let (rp, w) = self.inner.blocking_write(path, args)?;
+        Ok((rp, CompressionWriter::new(w, self.encoding)?))
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        self.inner.blocking_list(path, args)
+    }
+}
+
+fn encoding_name(encoding: Encoding) -> &'static str {
+    match encoding {
+        Encoding::Gzip => "gzip",
+        Encoding::Deflate => "deflate",
+        Encoding::Zstd => "zstd",
+    }
+}
+
+/// Parse the compression marker from user metadata.
+fn parse_marker(meta: &Metadata) -> Result<Option<Encoding>> {
+    if !meta.contains_metakey(Metakey::UserMetadata) {
+        return Ok(None);
+    }
+
+    match meta
+        .user_metadata()
+        .and_then(|v| v.get(COMPRESSION_METADATA_KEY))
+    {
+        Some(v) => parse_encoding(v),
+        None => Ok(None),
+    }
+}
+
+fn check_full_range(path: &str, range: BytesRange) -> Result<()> {
+    if range.is_full() {
+        return Ok(());
+    }
+
+    Err(Error::new(
+        ErrorKind::InvalidInput,
+        "compressed content can't be read with range",
+    )
+    .with_operation(Operation::Read)
+    .with_context("path", path)
+    .with_context("range", range.to_string()))
+}
+
+/// Drop the content length of compressed content, since the decompressed
+/// length is unknown until EOF.
+fn decompressed_rp(rp: RpRead) -> RpRead {
+    let meta = rp.into_metadata();
+
+    let mut decompressed = Metadata::new(EntryMode::FILE);
+    if meta.contains_metakey(Metakey::UserMetadata) {
+        if let Some(v) = meta.user_metadata() {
+            let mut v: HashMap<String, String> = v.clone();
+            v.remove(COMPRESSION_METADATA_KEY);
+            decompressed.set_user_metadata(v);
+        }
+    }
+    RpRead::with_metadata(decompressed)
+}
+
+/// Reader returned by [`CompressionLayer`], content without the marker
+/// will be read as is.
+pub enum CompressionReader<R> {
+    Raw(R),
+    Decompress(DecompressReader<R>),
+}
+
+impl<R: oio::Read> oio::Read for CompressionReader<R> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        match self {
+            CompressionReader::Raw(r) => r.poll_read(cx, buf),
+            CompressionReader::Decompress(r) => r.poll_read(cx, buf),
+        }
+    }
+
+    fn poll_seek(&mut self, cx: &mut Context<'_>, pos: io::SeekFrom) -> Poll<Result<u64>> {
+        match self {
+            CompressionReader::Raw(r) => r.poll_seek(cx, pos),
+            CompressionReader::Decompress(r) => r.poll_seek(cx, pos),
+        }
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        match self {
+            CompressionReader::Raw(r) => r.poll_next(cx),
+            CompressionReader::Decompress(r) => r.poll_next(cx),
+        }
+    }
+}
+
+impl<R: oio::BlockingRead> oio::BlockingRead for CompressionReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        match self {
+            CompressionReader::Raw(r) => r.read(buf),
+            CompressionReader::Decompress(r) => r.read(buf),
+        }
+    }
+
+    fn seek(&mut self, pos: io::SeekFrom) -> Result<u64> {
+        match self {
+            CompressionReader::Raw(r) => r.seek(pos),
+            CompressionReader::Decompress(r) => r.seek(pos),
+        }
+    }
+
+    fn next(&mut self) -> Option<Result<Bytes>> {
+        match self {
+            CompressionReader::Raw(r) => r.next(),
+            CompressionReader::Decompress(r) => r.next(),
+        }
+    }
+}
+
+fn new_compress_error(err: io::Error) -> Error {
+    Error::new(ErrorKind::Unexpected, "compress content failed").set_source(err)
+}
+
+/// Encoder accepts raw content and collects compressed output.
+enum Encoder {
+    Gzip(GzEncoder<Vec<u8>>),
+    Zstd(zstd::stream::write::Encoder<'static, Vec<u8>>),
+}
+
+impl Encoder {
+    fn new(encoding: Encoding) -> Result<Self> {
+        let encoder = match encoding {
+            Encoding::Gzip => {
+                Encoder::Gzip(GzEncoder::new(Vec::new(), flate2::Compression::default()))
+            }
+            Encoding::Zstd => Encoder::Zstd(
+                zstd::stream::write::Encoder::new(Vec::new(), 0).map_err(new_compress_error)?,
+            ),
+            Encoding::Deflate => {
+                return Err(Error::new(
+                    ErrorKind::Unsupported,
+                    "compression layer doesn't support deflate",
+                ))
+            }
+        };
+        Ok(encoder)
+    }
+
+    fn write(&mut self, bs: &[u8]) -> Result<()> {
+        let res = match self {
+            Encoder::Gzip(e) => e.write_all(bs),
+            Encoder::Zstd(e) => e.write_all(bs),
+        };
+        res.map_err(new_compress_error)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let res = match self {
+            Encoder::Gzip(e) => e.try_finish(),
+            Encoder::Zstd(e) => e.do_finish(),
+        };
+        res.map_err(new_compress_error)
+    }
+
+    /// Take all compressed output so far.
+    fn take(&mut self) -> Bytes {
+        let buf = match self {
+            Encoder::Gzip(e) => e.get_mut(),
+            Encoder::Zstd(e) => e.get_mut(),
+        };
+        Bytes::from(std::mem::take(buf))
+    }
+}
+
+/// CompressionWriter compresses content before writing into inner writer.
+///
+/// Compressed output will be written before accepting more content, so at
+/// most one chunk of compressed output will be held in memory.
+pub struct CompressionWriter<W> {
+    inner: W,
+    encoder: Encoder,
+    /// Compressed output that hasn't been written into inner writer.
+    output: Bytes,
+    finished: bool,
+}
+
+/// # Safety
+///
+/// Encoder will only be accessed via `&mut self`.
+unsafe impl<W: Sync> Sync for CompressionWriter<W> {}
+
+impl<W> CompressionWriter<W> {
+    fn new(inner: W, encoding: Encoding) -> Result<Self> {
+        Ok(Self {
+            inner,
+            encoder: Encoder::new(encoding)?,
+            output: Bytes::new(),
+            finished: false,
+        })
+    }
+}
+
+impl<W: oio::Write> CompressionWriter<W> {
+    fn poll_write_output(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        while !self.output.is_empty() {
+            let n = ready!(self.inner.poll_write(cx, &self.output))?;
+            self.output.advance(n);
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<W: oio::Write> oio::Write for CompressionWriter<W> {
+    fn poll_write(&mut self, cx: &mut Context<'_>, bs: &dyn WriteBuf) -> Poll<Result<usize>> {
+        ready!(self.poll_write_output(cx))?;
+
+        let chunk = bs.chunk();
+        self.encoder.write(chunk)?;
+        self.output = self.encoder.take();
+        Poll::Ready(Ok(chunk.len()))
+    }
+
+    fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        ready!(self.poll_write_output(cx))?;
+        if !self.finished {
+            self.encoder.finish()?;
+            self.output = self.encoder.take();
+            self.finished = true;
+            ready!(self.poll_write_output(cx))?;
+        }
+
+        self.inner.poll_close(cx)
+    }
+
+    fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.output.clear();
+        self.inner.poll_abort(cx)
+    }
+
+    fn metadata(&self) -> Option<Metadata> {
+        self.inner.metadata()
+    }
+}
+
+impl<W: oio::BlockingWrite> CompressionWriter<W> {
+    fn write_output(&mut self) -> Result<()> {
+        while !self.output.is_empty() {
+            let n = self.inner.write(&self.output)?;
+            self.output.advance(n);
+        }
+        Ok(())
+    }
+}
+
+impl<W: oio::BlockingWrite> oio::BlockingWrite for CompressionWriter<W> {
+    fn write(&mut self, bs: &dyn WriteBuf) -> Result<usize> {
+        self.write_output()?;
+
+        let chunk = bs.chunk();
+        self.encoder.write(chunk)?;
+        self.output = self.encoder.take();
+        Ok(chunk.len())
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.write_output()?;
+        if !self.finished {
+            self.encoder.finish()?;
+            self.output = self.encoder.take();
+            self.finished = true;
+            self.write_output()?;
+        }
+
+        self.inner.close()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Collect written content in memory.
+    #[derive(Default)]
+    struct MockWriter {
+        buf: Vec<u8>,
+    }
+
+    impl oio::BlockingWrite for MockWriter {
+        fn write(&mut self, bs: &dyn WriteBuf) -> Result<usize> {
+            // Accept one byte at most to test partial writes.
+            let n = bs.chunk().len().min(1);
+            self.buf.extend_from_slice(&bs.chunk()[..n]);
+            Ok(n)
+        }
+
+        fn close(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_compress_and_decompress() {
+        let content = "hello, world! ".repeat(1024);
+
+        for encoding in [Encoding::Gzip, Encoding::Zstd] {
+            let mut w = CompressionWriter::new(MockWriter::default(), encoding).unwrap();
+            for chunk in content.as_bytes().chunks(100) {
+                let mut bs = Bytes::copy_from_slice(chunk);
+                while bs.has_remaining() {
+                    let n = oio::BlockingWrite::write(&mut w, &bs).unwrap();
+                    bs.advance(n);
+                }
+            }
+            oio::BlockingWrite::close(&mut w).unwrap();
+            let compressed = w.inner.buf;
+            assert!(compressed.len() < content.len(), "{encoding:?}");
+
+            let r: oio::BlockingReader = Box::new(oio::Cursor::from(compressed));
+            let mut r = DecompressReader::new(r, encoding).unwrap();
+            let mut actual = Vec::new();
+            while let Some(bs) = oio::BlockingRead::next(&mut r) {
+                actual.extend_from_slice(&bs.unwrap());
+            }
+            assert_eq!(actual, content.as_bytes(), "{encoding:?}");
+        }
+    }
+
+    #[test]
+    fn test_parse_marker() {
+        let meta = Metadata::new(EntryMode::FILE);
+        assert_eq!(parse_marker(&meta).unwrap(), None);
+
+        let meta = Metadata::new(EntryMode::FILE).with_user_metadata(HashMap::from([(
+            COMPRESSION_METADATA_KEY.to_string(),
+            "zstd".to_string(),
+        )]));
+        assert_eq!(parse_marker(&meta).unwrap(), Some(Encoding::Zstd));
+
+        let meta = Metadata::new(EntryMode::FILE)
+            .with_user_metadata(HashMap::from([("owner".to_string(), "alice".to_string())]));
+        assert_eq!(parse_marker(&meta).unwrap(), None);
+    }
+
+    #[test]
+    fn test_check_full_range() {
+        assert!(check_full_range("a", BytesRange::default()).is_ok());
+
+        let err = check_full_range("a", BytesRange::new(Some(0), Some(10))).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,531 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::cmp::min;
+use std::io::SeekFrom;
+use std::sync::Arc;
+use std::task::ready;
+use std::task::Context;
+use std::task::Poll;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use tokio::sync::OwnedSemaphorePermit;
+use tokio::sync::Semaphore;
+
+use crate::raw::*;
+use crate::*;
+
+/// Add a limit on the total bytes in flight.
+///
+/// # Notes
+///
+/// [`ConcurrentLimitLayer`](super::ConcurrentLimitLayer) limits the number
+/// of requests, which doesn't help when a workload mixes tiny and huge
+/// objects. This layer limits the total bytes of in-flight reads and writes
+/// instead.
+///
+/// - Readers reserve the bytes of every read call before pulling them from
+///   services, and release them once the call has returned. Idle readers
+///   hold nothing, so keeping a reader open while opening another one
+///   will not block forever.
+/// - Writers of services that buffer content until close (without
+///   `write_can_multi`) or writers with `buffer` set hold the bytes that
+///   could be buffered until the writer has been closed or aborted. Other
+///   writers reserve the bytes of every write call and release them once
+///   the call has returned.
+/// - Writers that need more bytes than available give back the bytes they
+///   hold before waiting, so concurrent writers will never wait for each
+///   other forever. Bytes buffered by such writers are not counted until
+///   they have been reserved again.
+///
+/// New operations will wait until enough bytes have been released. A single
+/// read or write larger than the limit reserves the whole limit, so it
+/// will run alone instead of waiting forever.
+///
+/// Holding a buffered writer open while reading or writing others within
+/// the same task could wait forever if the writer holds the whole limit.
+///
+/// Blocking operations can't wait, they will return an error with
+/// [`ErrorKind::RateLimited`] if the limit has been reached.
+///
+/// The limit must not be larger than `u32::MAX`, larger values will be
+/// clamped.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::ConcurrentBytesLimitLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+/// use opendal::Scheme;
+///
+/// let _ = Operator::new(services::Memory::default())
+///     .expect("must init")
+///     .layer(ConcurrentBytesLimitLayer::new(256 * 1024 * 1024))
+///     .finish();
+/// ```
+#[derive(Clone)]
+pub struct ConcurrentBytesLimitLayer {
+    max_bytes: u32,
+}
+
+impl ConcurrentBytesLimitLayer {
+    /// Create a new ConcurrentBytesLimitLayer will specify the max bytes in flight.
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes: min(max_bytes, u32::MAX as usize) as u32,
+        }
+    }
+}
+
+impl<A: Accessor> Layer<A> for ConcurrentBytesLimitLayer {
+    type LayeredAccessor = ConcurrentBytesLimitAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        ConcurrentBytesLimitAccessor {
+            inner,
+            semaphore: Arc::new(Semaphore::new(self.max_bytes as usize)),
+            max_bytes: self.max_bytes,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConcurrentBytesLimitAccessor<A: Accessor> {
+    inner: A,
+    semaphore: Arc<Semaphore>,
+    max_bytes: u32,
+}
+
+impl<A: Accessor> ConcurrentBytesLimitAccessor<A> {
+    /// Calculate the bytes that could be buffered by the writer, which
+    /// should be held until close.
+    fn buffered(&self, args: &OpWrite) -> Option<u32> {
+        let size = if !self.inner.info().full_capability().write_can_multi {
+            self.max_bytes as usize
+        } else {
+            args.buffer()?
+        };
+        Some(min(size, self.max_bytes as usize) as u32)
+    }
+
+    fn new_wrapper<R>(&self, inner: R) -> ConcurrentBytesLimitWrapper<R> {
+        ConcurrentBytesLimitWrapper {
+            inner,
+            semaphore: self.semaphore.clone(),
+            max_bytes: self.max_bytes,
+            remaining: None,
+            buffered: None,
+            written: 0,
+            held: Vec::new(),
+            held_bytes: 0,
+            acquiring: None,
+        }
+    }
+}
+
+#[async_trait]
+impl<A: Accessor> LayeredAccessor for ConcurrentBytesLimitAccessor<A> {
+    type Inner = A;
+    type Reader = ConcurrentBytesLimitWrapper<A::Reader>;
+    type BlockingReader = ConcurrentBytesLimitWrapper<A::BlockingReader>;
+    type Writer = ConcurrentBytesLimitWrapper<A::Writer>;
+    type BlockingWriter = ConcurrentBytesLimitWrapper<A::BlockingWriter>;
+    type Pager = A::Pager;
+    type BlockingPager = A::BlockingPager;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        // Content will only be transferred while reading, bytes will be
+        // reserved before every read call.
+        let (rp, r) = self.inner.read(path, args).await?;
+
+        let mut r = self.new_wrapper(r);
+        r.remaining = rp.metadata().content_length_raw();
+        Ok((rp, r))
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        let buffered = self.buffered(&args);
+        let (rp, w) = self.inner.write(path, args).await?;
+
+        let mut w = self.new_wrapper(w);
+        w.buffered = buffered;
+        Ok((rp, w))
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        self.inner.list(path, args).await
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        let (rp, r) = self.inner.blocking_read(path, args)?;
+
+        let mut r = self.new_wrapper(r);
+        r.remaining = rp.metadata().content_length_raw();
+        Ok((rp, r))
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        let buffered = self.buffered(&args);
+        let (rp, w) = self.inner.blocking_write(path, args)?;
+
+        let mut w = self.new_wrapper(w);
+        w.buffered = buffered;
+        Ok((rp, w))
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        self.inner.blocking_list(path, args)
+    }
+}
+
+fn new_bytes_limit_error(err: tokio::sync::TryAcquireError) -> Error {
+    Error::new(ErrorKind::RateLimited, "bytes in flight reached the limit")
+        .with_operation("ConcurrentBytesLimitLayer::acquire")
+        .set_temporary()
+        .set_source(err)
+}
+
+pub struct ConcurrentBytesLimitWrapper<R> {
+    inner: R,
+    semaphore: Arc<Semaphore>,
+    max_bytes: u32,
+
+    // Size of content that hasn't been read, only used by readers.
+    remaining: Option<u64>,
+    // Bytes that could be buffered by the inner writer, which will be held
+    // until close or abort. `None` means nothing will be buffered.
+    buffered: Option<u32>,
+    written: u64,
+
+    held: Vec<OwnedSemaphorePermit>,
+    held_bytes: u32,
+    acquiring: Option<(u32, BoxFuture<'static, OwnedSemaphorePermit>)>,
+}
+
+/// # Safety
+///
+/// We will only take `&mut Self` reference for the acquiring future.
+unsafe impl<R: Sync> Sync for ConcurrentBytesLimitWrapper<R> {}
+
+impl<R> ConcurrentBytesLimitWrapper<R> {
+    /// Calculate the bytes needed before reading into a buffer of `size`.
+    fn wanted_by_read(&self, size: usize) -> u32 {
+        let size = match self.remaining {
+            Some(remaining) => min(size as u64, remaining),
+            None => size as u64,
+        };
+        min(size, self.max_bytes as u64) as u32
+    }
+
+    /// Calculate the bytes needed before writing `size` bytes.
+    fn wanted_by_write(&self, size: usize) -> u32 {
+        let size = match self.buffered {
+            Some(buffered) => min(self.written + size as u64, buffered as u64),
+            None => size as u64,
+        };
+        min(size, self.max_bytes as u64) as u32
+    }
+
+    /// Mark `n` bytes of content as read.
+    fn consume(&mut self, n: usize) {
+        if let Some(remaining) = self.remaining.as_mut() {
+            *remaining = remaining.saturating_sub(n as u64);
+        }
+    }
+
+    fn release(&mut self) {
+        self.held.clear();
+        self.held_bytes = 0;
+        self.acquiring = None;
+    }
+
+    /// Try to reserve `n` bytes in total without waiting.
+    fn try_reserve(&mut self, n: u32) -> Result<()> {
+        if self.held_bytes >= n {
+            return Ok(());
+        }
+
+        let permit = self
+            .semaphore
+            .clone()
+            .try_acquire_many_owned(n - self.held_bytes)
+            .map_err(new_bytes_limit_error)?;
+        self.held.push(permit);
+        self.held_bytes = n;
+        Ok(())
+    }
+
+    /// Reserve `n` bytes in total.
+    ///
+    /// If the bytes can't be reserved right now, all bytes held will be
+    /// given back before waiting so that we never hold some bytes while
+    /// waiting for more.
+    fn poll_reserve(&mut self, cx: &mut Context<'_>, n: u32) -> Poll<()> {
+        if self.acquiring.is_none() && self.try_reserve(n).is_ok() {
+            return Poll::Ready(());
+        }
+
+        if !matches!(&self.acquiring, Some((wanted, _)) if *wanted == n) {
+            self.release();
+
+            let semaphore = self.semaphore.clone();
+            let fut = async move {
+                semaphore
+                    .acquire_many_owned(n)
+                    .await
+                    .expect("semaphore must be valid")
+            };
+            self.acquiring = Some((n, Box::pin(fut)));
+        }
+
+        let (_, fut) = self.acquiring.as_mut().expect("acquiring must be set");
+        let permit = ready!(fut.poll_unpin(cx));
+        self.acquiring = None;
+        self.held.push(permit);
+        self.held_bytes = n;
+        Poll::Ready(())
+    }
+
+    /// Release the bytes reserved by current write call if nothing will
+    /// be buffered.
+    fn release_unbuffered(&mut self) {
+        if self.buffered.is_none() {
+            self.release();
+        }
+    }
+}
+
+impl<R: oio::Read> oio::Read for ConcurrentBytesLimitWrapper<R> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        let n = self.wanted_by_read(buf.len());
+        ready!(self.poll_reserve(cx, n));
+
+        let res = ready!(self.inner.poll_read(cx, buf));
+        // Bytes have been delivered to users, release them so that others
+        // could go on.
+        self.release();
+        if let Ok(n) = res {
+            self.consume(n);
+        }
+        Poll::Ready(res)
+    }
+
+    fn poll_seek(&mut self, cx: &mut Context<'_>, pos: SeekFrom) -> Poll<Result<u64>> {
+        let res = ready!(self.inner.poll_seek(cx, pos));
+        // We don't know the remaining size after seeking.
+        self.remaining = None;
+        Poll::Ready(res)
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        let n = self.wanted_by_read(self.max_bytes as usize);
+        ready!(self.poll_reserve(cx, n));
+
+        let res = ready!(self.inner.poll_next(cx));
+        self.release();
+        if let Some(Ok(bs)) = &res {
+            self.consume(bs.len());
+        }
+        Poll::Ready(res)
+    }
+}
+
+impl<R: oio::BlockingRead> oio::BlockingRead for ConcurrentBytesLimitWrapper<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.try_reserve(self.wanted_by_read(buf.len()))?;
+
+        let res = self.inner.read(buf);
+        self.release();
+        if let Ok(n) = res {
+            self.consume(n);
+        }
+        res
+    }
+
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        let res = self.inner.seek(pos);
+        self.remaining = None;
+        res
+    }
+
+    fn next(&mut self) -> Option<Result<Bytes>> {
+        if let Err(err) = self.try_reserve(self.wanted_by_read(self.max_bytes as usize)) {
+            return Some(Err(err));
+        }
+
+        let res = self.inner.next();
+        self.release();
+        if let Some(Ok(bs)) = &res {
+            self.consume(bs.len());
+        }
+        res
+    }
+}
+
+#[async_trait]
+impl<R: oio::Write> oio::Write for ConcurrentBytesLimitWrapper<R> {
+    fn poll_write(&mut self, cx: &mut Context<'_>, bs: &dyn oio::WriteBuf) -> Poll<Result<usize>> {
+        let n = self.wanted_by_write(bs.remaining());
+        ready!(self.poll_reserve(cx, n));
+
+        let res = ready!(self.inner.poll_write(cx, bs));
+        if let Ok(n) = res {
+            self.written += n as u64;
+        }
+        self.release_unbuffered();
+        Poll::Ready(res)
+    }
+
+    fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let res = ready!(self.inner.poll_close(cx));
+        self.release();
+        Poll::Ready(res)
+    }
+
+    fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let res = ready!(self.inner.poll_abort(cx));
+        self.release();
+        Poll::Ready(res)
+    }
+
+    fn metadata(&self) -> Option<Metadata> {
+        self.inner.metadata()
+    }
+}
+
+impl<R: oio::BlockingWrite> oio::BlockingWrite for ConcurrentBytesLimitWrapper<R> {
+    fn write(&mut self, bs: &dyn oio::WriteBuf) -> Result<usize> {
+        self.try_reserve(self.wanted_by_write(bs.remaining()))?;
+
+        let res = self.inner.write(bs);
+        if let Ok(n) = res {
+            self.written += n as u64;
+        }
+        self.release_unbuffered();
+        res
+    }
+
+    fn close(&mut self) -> Result<()> {
+        let res = self.inner.close();
+        self.release();
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use anyhow::Result;
+    use tokio::time::timeout;
+
+    use super::*;
+    use crate::services::Memory;
+    use crate::Operator;
+
+    #[tokio::test]
+    async fn test_read_blocks_until_bytes_released() -> Result<()> {
+        let op = Operator::new(Memory::default())?
+            .layer(ConcurrentBytesLimitLayer::new(16))
+            .finish();
+        op.write("b", vec![0; 8]).await?;
+
+        // Memory buffers content until close, so the writer will hold the
+        // bytes written.
+        let mut w = op.writer("a").await?;
+        w.write(vec![0; 16]).await?;
+        let res = timeout(Duration::from_millis(100), op.read("b")).await;
+        assert!(res.is_err(), "read must wait for the bytes in flight");
+
+        w.close().await?;
+        let bs = timeout(Duration::from_millis(100), op.read("b")).await??;
+        assert_eq!(bs.len(), 8);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_idle_reader_holds_nothing() -> Result<()> {
+        let op = Operator::new(Memory::default())?
+            .layer(ConcurrentBytesLimitLayer::new(16))
+            .finish();
+        op.write("a", vec![0; 16]).await?;
+        op.write("b", vec![0; 16]).await?;
+
+        let _r = op.reader("a").await?;
+        let bs = timeout(Duration::from_millis(100), op.read("b")).await??;
+        assert_eq!(bs.len(), 16);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_writes_larger_than_half_limit() -> Result<()> {
+        let op = Operator::new(Memory::default())?
+            .layer(ConcurrentBytesLimitLayer::new(100))
+            .finish();
+
+        // Both writers will write more than half of the limit concurrently,
+        // and Memory buffers content until close.
+        let write = |path: &'static str| {
+            let op = op.clone();
+            async move {
+                let mut w = op.writer(path).await?;
+                for _ in 0..8 {
+                    w.write(vec![0; 10]).await?;
+                }
+                w.close().await
+            }
+        };
+        let (ra, rb) = timeout(
+            Duration::from_millis(100),
+            futures::future::join(write("a"), write("b")),
+        )
+        .await?;
+        ra?;
+        rb?;
+
+        assert_eq!(op.read("a").await?.len(), 80);
+        assert_eq!(op.read("b").await?.len(), 80);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_larger_than_limit() -> Result<()> {
+        let op = Operator::new(Memory::default())?
+            .layer(ConcurrentBytesLimitLayer::new(16))
+            .finish();
+
+        let res = timeout(Duration::from_millis(100), op.write("a", vec![0; 64])).await;
+        assert!(res.is_ok(), "write larger than limit must not wait forever");
+        res??;
+
+        let bs = timeout(Duration::from_millis(100), op.read("a")).await??;
+        assert_eq!(bs.len(), 64);
+
+        Ok(())
+    }
+}
@@ -296,6 +296,10 @@ impl<R: oio::Write> oio::Write for ConcurrentLimitWrapper<R> {
     fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
         self.inner.poll_abort(cx)
     }
+
+    fn metadata(&self) -> Option<Metadata> {
+        self.inner.metadata()
+    }
 }
 
 impl<R: oio::BlockingWrite> oio::BlockingWrite for ConcurrentLimitWrapper<R> {
@@ -313,10 +317,18 @@ impl<R: oio::Page> oio::Page for ConcurrentLimitWrapper<R> {
     async fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
         self.inner.next().await
     }
+
+    fn continuation_token(&self) -> Option<String> {
+        self.inner.continuation_token()
+    }
 }
 
 impl<R: oio::BlockingPage> oio::BlockingPage for ConcurrentLimitWrapper<R> {
     fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
         self.inner.next()
     }
+
+    fn continuation_token(&self) -> Option<String> {
+        self.inner.continuation_token()
+    }
 }
@@ -0,0 +1,385 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::io::SeekFrom;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::task::ready;
+use std::task::Context;
+use std::task::Poll;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::raw::*;
+use crate::*;
+
+/// Add live counters of operations and bytes.
+///
+/// # Notes
+///
+/// This layer maintains atomic counters without any metrics backend, the
+/// counters could be polled via the [`CountingHandle`] returned by
+/// [`CountingLayer::new`]. It's useful for lightweight self-monitoring and for
+/// asserting how many requests have been sent in tests.
+///
+/// - reads: calls of `read`
+/// - writes: calls of `write`
+/// - lists: pages returned by `list`, which usually equals the requests sent
+/// - deletes: calls of `delete` and every path deleted by `batch`
+/// - bytes read: bytes returned by readers
+/// - bytes written: bytes accepted by writers
+///
+/// Failed operations are counted too. Operators share the same counters if
+/// they are built with the same layer.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::CountingLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// let (layer, handle) = CountingLayer::new();
+/// let _ = Operator::new(services::Memory::default())
+///     .expect("must init")
+///     .layer(layer)
+///     .finish();
+///
+/// assert_eq!(handle.reads(), 0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CountingLayer {
+    counters: Arc<Counters>,
+}
+
+impl CountingLayer {
+    /// Create a new CountingLayer and the handle to read its counters.
+    pub fn new() -> (Self, CountingHandle) {
+        let counters = Arc::new(Counters::default());
+
+        (
+            Self {
+                counters: counters.clone(),
+            },
+            CountingHandle { counters },
+        )
+    }
+}
+
+impl<A: Accessor> Layer<A> for CountingLayer {
+    type LayeredAccessor = CountingAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        CountingAccessor {
+            inner,
+            counters: self.counters.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    reads: AtomicU64,
+    writes: AtomicU64,
+    lists: AtomicU64,
+    deletes: AtomicU64,
+    bytes_read: AtomicU64,
+    bytes_written: AtomicU64,
+}
+
+impl Counters {
+    #[inline]
+    fn incr(counter: &AtomicU64, n: u64) {
+        counter.fetch_add(n, Ordering::Relaxed);
+    }
+}
+
+/// Handle to read the live counters of [`CountingLayer`].
+///
+/// Counters are updated with relaxed ordering, so values read from different
+/// counters may not be consistent with each other.
+#[derive(Debug, Clone)]
+pub struct CountingHandle {
+    counters: Arc<Counters>,
+}
+
+impl CountingHandle {
+    /// Get the count of read operations.
+    pub fn reads(&self) -> u64 {
+        self.counters.reads.load(Ordering::Relaxed)
+    }
+
+    /// Get the count of write operations.
+    pub fn writes(&self) -> u64 {
+        self.counters.writes.load(Ordering::Relaxed)
+    }
+
+    /// Get the count of pages returned by list operations.
+    pub fn lists(&self) -> u64 {
+        self.counters.lists.load(Ordering::Relaxed)
+    }
+
+    /// Get the count of deleted paths.
+    pub fn deletes(&self) -> u64 {
+        self.counters.deletes.load(Ordering::Relaxed)
+    }
+
+    /// Get the total bytes read from service.
+    pub fn bytes_read(&self) -> u64 {
+        self.counters.bytes_read.load(Ordering::Relaxed)
+    }
+
+    /// Get the total bytes written into service.
+    pub fn bytes_written(&self) -> u64 {
+        self.counters.bytes_written.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CountingAccessor<A: Accessor> {
+    inner: A,
+    counters: Arc<Counters>,
+}
+
+#[async_trait]
+impl<A: Accessor> LayeredAccessor for CountingAccessor<A> {
+    type Inner = A;
+    type Reader = CountingWrapper<A::Reader>;
+    type BlockingReader = CountingWrapper<A::BlockingReader>;
+    type Writer = CountingWrapper<A::Writer>;
+    type BlockingWriter = CountingWrapper<A::BlockingWriter>;
+    type Pager = CountingWrapper<A::Pager>;
+    type BlockingPager = CountingWrapper<A::BlockingPager>;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        Counters::incr(&self.counters.reads, 1);
+        self.inner
+            .read(path, args)
+            .await
+            .map(|(rp, r)| (rp, CountingWrapper::new(r, self.counters.clone())))
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        Counters::incr(&self.counters.writes, 1);
+        self.inner
+            .write(path, args)
+            .await
+            .map(|(rp, w)| (rp, CountingWrapper::new(w, self.counters.clone())))
+    }
+
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        Counters::incr(&self.counters.deletes, 1);
+        self.inner.delete(path, args).await
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        self.inner
+            .list(path, args)
+            .await
+            .map(|(rp, p)| (rp, CountingWrapper::new(p, self.counters.clone())))
+    }
+
+    async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
+        let deletes = args
+            .operation()
+            .iter()
+            .filter(|(_, op)| matches!(op, BatchOperation::Delete(_)))
+            .count();
+        Counters::incr(&self.counters.deletes, deletes as u64);
+        self.inner.batch(args).await
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        Counters::incr(&self.counters.reads, 1);
+        self.inner
+            .blocking_read(path, args)
+            .map(|(rp, r)| (rp, CountingWrapper::new(r, self.counters.clone())))
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        Counters::incr(&self.counters.writes, 1);
+        self.inner
+            .blocking_write(path, args)
+            .map(|(rp, w)| (rp, CountingWrapper::new(w, self.counters.clone())))
+    }
+
+    fn blocking_delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        Counters::incr(&self.counters.deletes, 1);
+        self.inner.blocking_delete(path, args)
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        self.inner
+            .blocking_list(path, args)
+            .map(|(rp, p)| (rp, CountingWrapper::new(p, self.counters.clone())))
+    }
+}
+
+pub struct CountingWrapper<R> {
+    inner: R,
+    counters: Arc<Counters>,
+}
+
+impl<R> CountingWrapper<R> {
+    fn new(inner: R, counters: Arc<Counters>) -> Self {
+        Self { inner, counters }
+    }
+}
+
+impl<R: oio::Read> oio::Read for CountingWrapper<R> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        let res = ready!(self.inner.poll_read(cx, buf));
+        if let Ok(n) = res {
+            Counters::incr(&self.counters.bytes_read, n as u64);
+        }
+        Poll::Ready(res)
+    }
+
+    fn poll_seek(&mut self, cx: &mut Context<'_>, pos: SeekFrom) -> Poll<Result<u64>> {
+        self.inner.poll_seek(cx, pos)
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        let res = ready!(self.inner.poll_next(cx));
+        if let Some(Ok(bs)) = &res {
+            Counters::incr(&self.counters.bytes_read, bs.len() as u64);
+        }
+        Poll::Ready(res)
+    }
+}
+
+impl<R: oio::BlockingRead> oio::BlockingRead for CountingWrapper<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let n = self.inner.read(buf)?;
+        Counters::incr(&self.counters.bytes_read, n as u64);
+        Ok(n)
+    }
+
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        self.inner.seek(pos)
+    }
+
+    fn next(&mut self) -> Option<Result<Bytes>> {
+        let res = self.inner.next();
+        if let Some(Ok(bs)) = &res {
+            Counters::incr(&self.counters.bytes_read, bs.len() as u64);
+        }
+        res
+    }
+}
+
+#[async_trait]
+impl<R: oio::Write> oio::Write for CountingWrapper<R> {
+    fn poll_write(&mut self, cx: &mut Context<'_>, bs: &dyn oio::WriteBuf) -> Poll<Result<usize>> {
+        let res = ready!(self.inner.poll_write(cx, bs));
+        if let Ok(n) = res {
+            Counters::incr(&self.counters.bytes_written, n as u64);
+        }
+        Poll::Ready(res)
+    }
+
+    fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.inner.poll_close(cx)
+    }
+
+    fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.inner.poll_abort(cx)
+    }
+
+    fn metadata(&self) -> Option<Metadata> {
+        self.inner.metadata()
+    }
+}
+
+impl<R: oio::BlockingWrite> oio::BlockingWrite for CountingWrapper<R> {
+    fn write(&mut self, bs: &dyn oio::WriteBuf) -> Result<usize> {
+        let n = self.inner.write(bs)?;
+        Counters::incr(&self.counters.bytes_written, n as u64);
+        Ok(n)
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.inner.close()
+    }
+}
+
+#[async_trait]
+impl<P: oio::Page> oio::Page for CountingWrapper<P> {
+    async fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        let res = self.inner.next().await;
+        if let Ok(Some(_)) = &res {
+            Counters::incr(&self.counters.lists, 1);
+        }
+        res
+    }
+
+    fn continuation_token(&self) -> Option<String> {
+        self.inner.continuation_token()
+    }
+}
+
+impl<P: oio::BlockingPage> oio::BlockingPage for CountingWrapper<P> {
+    fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        let res = self.inner.next();
+        if let Ok(Some(_)) = &res {
+            Counters::incr(&self.counters.lists, 1);
+        }
+        res
+    }
+
+    fn continuation_token(&self) -> Option<String> {
+        self.inner.continuation_token()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+    use crate::services::Memory;
+    use crate::Operator;
+
+    #[tokio::test]
+    async fn test_counting() -> Result<()> {
+        let (layer, handle) = CountingLayer::new();
+        let op = Operator::new(Memory::default())?.layer(layer).finish();
+
+        op.write("dir/a", vec![0; 16]).await?;
+        op.write("dir/b", vec![0; 8]).await?;
+        let bs = op.read("dir/a").await?;
+        assert_eq!(bs.len(), 16);
+        let _ = op.list("dir/").await?;
+        op.delete("dir/b").await?;
+
+        assert_eq!(handle.writes(), 2);
+        assert_eq!(handle.bytes_written(), 24);
+        assert_eq!(handle.reads(), 1);
+        assert_eq!(handle.bytes_read(), 16);
+        assert_eq!(handle.lists(), 1);
+        assert_eq!(handle.deletes(), 1);
+
+        Ok(())
+    }
+}
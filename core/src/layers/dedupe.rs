@@ -0,0 +1,483 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::sync::Arc;
+use std::task::ready;
+use std::task::Context;
+use std::task::Poll;
+
+use async_trait::async_trait;
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use md5::Digest;
+use md5::Md5;
+
+use crate::raw::oio::WriteBuf;
+use crate::raw::oio::WriteExt;
+use crate::raw::*;
+use crate::*;
+
+/// Skip writes whose content is the same as the existing file.
+///
+/// Writes will be buffered in memory. While closing, the target will be
+/// stated and compared with the buffered content, the write to the inner
+/// service will be skipped if they are the same.
+///
+/// # Comparison
+///
+/// - Files with different `content_length` are always treated as changed,
+///   so no hash will be calculated for them.
+/// - If the service returns `content_md5` (like azfile), it will be compared
+///   with the MD5 of the buffered content.
+/// - Otherwise, the `etag` will be compared with the hex MD5 of the buffered
+///   content, which works for services using MD5 as etag of plain uploads.
+///
+/// Content that can't be proved as unchanged will be written. So there
+/// could be false negatives that unchanged content is written again, like
+/// services that return neither of them or files uploaded in multipart.
+/// There will be no false positive unless hash collision happens.
+///
+/// # Notes
+///
+/// - The whole content will be buffered in memory before closing.
+/// - Appends, writes with `if_not_exists` and writes carrying metadata like
+///   `content_type`, `cache_control`, `content_disposition` or
+///   `user_metadata` will bypass this layer, since the metadata of existing
+///   file may need to be updated.
+/// - Blocking writes will bypass this layer.
+/// - Errors returned by `stat` will be ignored, the content will be written.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::DedupeLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// let _ = Operator::new(services::Memory::default())
+///     .expect("must init")
+///     .layer(DedupeLayer)
+///     .finish();
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DedupeLayer;
+
+impl<A: Accessor> Layer<A> for DedupeLayer {
+    type LayeredAccessor = DedupeAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        DedupeAccessor {
+            inner: Arc::new(inner),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DedupeAccessor<A: Accessor> {
+    inner: Arc<A>,
+}
+
+impl<A: Accessor> DedupeAccessor<A> {
+    /// Only plain writes could be deduplicated.
+    fn is_dedupable(args: &OpWrite) -> bool {
+        !args.append()
+            && !args.if_not_exists()
+            && args.content_type().is_none()
+            && args.cache_control().is_none()
+            && args.content_disposition().is_none()
+            && args.user_metadata().is_none()
+    }
+}
+
+#[async_trait]
+impl<A: Accessor> LayeredAccessor for DedupeAccessor<A> {
+    type Inner = A;
+    type Reader = A::Reader;
+    type BlockingReader = A::BlockingReader;
+    type Writer = DedupeWriter<A>;
+    type BlockingWriter = A::BlockingWriter;
+    type Pager = A::Pager;
+    type BlockingPager = A::BlockingPager;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        self.inner.read(path, args).await
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        if !Self::is_dedupable(&args) || !self.inner.info().full_capability().stat {
+            return self
+                .inner
+                .write(path, args)
+                .await
+                .map(|(rp, w)| (rp, DedupeWriter::Direct(w)));
+        }
+
+        Ok((
+            RpWrite::default(),
+            DedupeWriter::Buffered(BufferedWriter {
+                inner: self.inner.clone(),
+                path: path.to_string(),
+                args,
+                buf: oio::ChunkedBytes::default(),
+                fut: None,
+                meta: None,
+            }),
+        ))
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        self.inner.list(path, args).await
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.inner.blocking_read(path, args)
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        self.inner.blocking_write(path, args)
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        self.inner.blocking_list(path, args)
+    }
+}
+
+/// Check if the existing file described by `meta` has the same content
+/// as `buf`.
+fn is_unchanged(meta: &Metadata, buf: &oio::ChunkedBytes) -> bool {
+    if !meta.mode().is_file() || meta.content_length() != buf.len() as u64 {
+        return false;
+    }
+
+    let mut hasher = Md5::new();
+    for bs in buf.vectored_chunk() {
+        hasher.update(&*bs);
+    }
+    let digest = hasher.finalize();
+
+    if let Some(md5) = meta.content_md5() {
+        return md5 == BASE64_STANDARD.encode(digest);
+    }
+    if let Some(etag) = meta.etag() {
+        let etag = etag.trim_start_matches("W/").trim_matches('"');
+        return etag.eq_ignore_ascii_case(&format!("{digest:x}"));
+    }
+
+    false
+}
+
+/// Write `buf` into `path` unless its content is unchanged.
+///
+/// Returns the metadata of the existing file if the write is skipped,
+/// otherwise the metadata returned by the inner writer.
+async fn write_if_changed<A: Accessor>(
+    inner: Arc<A>,
+    path: String,
+    args: OpWrite,
+    mut buf: oio::ChunkedBytes,
+) -> Result<Option<Metadata>> {
+    if let Ok(rp) = inner.stat(&path, OpStat::new()).await {
+        let meta = rp.into_metadata();
+        if is_unchanged(&meta, &buf) {
+            return Ok(Some(meta));
+        }
+    }
+
+    let (_, mut w) = inner.write(&path, args).await?;
+    while buf.remaining() > 0 {
+        let n = w.write(&buf).await?;
+        buf.advance(n);
+    }
+    w.close().await?;
+    Ok(w.metadata())
+}
+
+pub enum DedupeWriter<A: Accessor> {
+    Direct(A::Writer),
+    Buffered(BufferedWriter<A>),
+}
+
+impl<A: Accessor> oio::Write for DedupeWriter<A> {
+    fn poll_write(&mut self, cx: &mut Context<'_>, bs: &dyn WriteBuf) -> Poll<Result<usize>> {
+        match self {
+            Self::Direct(w) => w.poll_write(cx, bs),
+            Self::Buffered(w) => w.poll_write(cx, bs),
+        }
+    }
+
+    fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        match self {
+            Self::Direct(w) => w.poll_close(cx),
+            Self::Buffered(w) => w.poll_close(cx),
+        }
+    }
+
+    fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        match self {
+            Self::Direct(w) => w.poll_abort(cx),
+            Self::Buffered(w) => w.poll_abort(cx),
+        }
+    }
+
+    fn metadata(&self) -> Option<Metadata> {
+        match self {
+            Self::Direct(w) => w.metadata(),
+            Self::Buffered(w) => w.meta.clone(),
+        }
+    }
+}
+
+pub struct BufferedWriter<A: Accessor> {
+    inner: Arc<A>,
+    path: String,
+    args: OpWrite,
+    buf: oio::ChunkedBytes,
+    fut: Option<BoxFuture<'static, Result<Option<Metadata>>>>,
+    meta: Option<Metadata>,
+}
+
+/// # Safety
+///
+/// The write future will only be accessed via `&mut self`.
+unsafe impl<A: Accessor> Sync for BufferedWriter<A> {}
+
+impl<A: Accessor> BufferedWriter<A> {
+    fn poll_write(&mut self, _: &mut Context<'_>, bs: &dyn WriteBuf) -> Poll<Result<usize>> {
+        let size = self.buf.extend_from_write_buf(bs.remaining(), bs);
+        Poll::Ready(Ok(size))
+    }
+
+    fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        loop {
+            if let Some(fut) = self.fut.as_mut() {
+                let res = ready!(fut.poll_unpin(cx));
+                self.fut = None;
+                return Poll::Ready(res.map(|meta| self.meta = meta));
+            }
+
+            let buf = std::mem::take(&mut self.buf);
+            self.fut = Some(
+                write_if_changed(
+                    self.inner.clone(),
+                    self.path.clone(),
+                    self.args.clone(),
+                    buf,
+                )
+                .boxed(),
+            );
+        }
+    }
+
+    fn poll_abort(&mut self, _: &mut Context<'_>) -> Poll<Result<()>> {
+        self.fut = None;
+        self.buf.clear();
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use std::sync::Mutex;
+
+    use bytes::Bytes;
+
+    use super::*;
+
+    #[test]
+    fn test_is_unchanged() {
+        let content = Bytes::from("Hello, World!");
+        let buf = oio::ChunkedBytes::from_vec(vec![content.slice(..5), content.slice(5..)]);
+        let md5 = format_content_md5(&content);
+        let etag = format!("\"{:x}\"", Md5::digest(&content));
+
+        let file = |size: u64| {
+            let mut meta = Metadata::new(EntryMode::FILE);
+            meta.set_content_length(size);
+            meta.with_metakey(Metakey::Complete)
+        };
+
+        let cases = vec![
+            (
+                "same content md5",
+                file(13).with_content_md5(md5.clone()),
+                true,
+            ),
+            (
+                "different content md5",
+                file(13).with_content_md5("XrY7u+Ae7tCTyyK7j1rNww==".to_string()),
+                false,
+            ),
+            ("same md5 etag", file(13).with_etag(etag.clone()), true),
+            (
+                "different etag",
+                file(13).with_etag("\"0x8D9C1D2E3F4A5B6\"".to_string()),
+                false,
+            ),
+            (
+                "different length",
+                file(12).with_content_md5(md5.clone()),
+                false,
+            ),
+            ("no hash", file(13), false),
+            (
+                "dir",
+                Metadata::new(EntryMode::DIR).with_etag(etag.clone()),
+                false,
+            ),
+        ];
+
+        for (desc, meta, expected) in cases {
+            assert_eq!(is_unchanged(&meta, &buf), expected, "{desc}");
+        }
+    }
+
+    #[derive(Debug, Clone, Default)]
+    struct MockBuilder {
+        content: Arc<Mutex<Option<Vec<u8>>>>,
+        writes: Arc<AtomicUsize>,
+    }
+
+    impl Builder for MockBuilder {
+        const SCHEME: Scheme = Scheme::Custom("mock");
+        type Accessor = MockService;
+
+        fn from_map(_: HashMap<String, String>) -> Self {
+            Self::default()
+        }
+
+        fn build(&mut self) -> Result<Self::Accessor> {
+            Ok(MockService {
+                content: self.content.clone(),
+                writes: self.writes.clone(),
+            })
+        }
+    }
+
+    /// MockService stores a single file and returns its `content_md5` in stat.
+    #[derive(Debug, Clone, Default)]
+    struct MockService {
+        content: Arc<Mutex<Option<Vec<u8>>>>,
+        writes: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Accessor for MockService {
+        type Reader = ();
+        type BlockingReader = ();
+        type Writer = MockWriter;
+        type BlockingWriter = ();
+        type Pager = ();
+        type BlockingPager = ();
+
+        fn info(&self) -> AccessorInfo {
+            let mut am = AccessorInfo::default();
+            am.set_native_capability(Capability {
+                stat: true,
+                write: true,
+                ..Default::default()
+            });
+
+            am
+        }
+
+        async fn stat(&self, _: &str, _: OpStat) -> Result<RpStat> {
+            match self.content.lock().unwrap().as_ref() {
+                Some(bs) => {
+                    let mut meta = Metadata::new(EntryMode::FILE);
+                    meta.set_content_length(bs.len() as u64);
+                    meta.set_content_md5(&format_content_md5(bs));
+                    Ok(RpStat::new(meta.with_metakey(Metakey::Complete)))
+                }
+                None => Err(Error::new(ErrorKind::NotFound, "file not found")),
+            }
+        }
+
+        async fn write(&self, _: &str, _: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+            Ok((
+                RpWrite::default(),
+                MockWriter {
+                    content: self.content.clone(),
+                    writes: self.writes.clone(),
+                    buf: Vec::new(),
+                },
+            ))
+        }
+    }
+
+    struct MockWriter {
+        content: Arc<Mutex<Option<Vec<u8>>>>,
+        writes: Arc<AtomicUsize>,
+        buf: Vec<u8>,
+    }
+
+    impl oio::Write for MockWriter {
+        fn poll_write(&mut self, _: &mut Context<'_>, bs: &dyn WriteBuf) -> Poll<Result<usize>> {
+            let chunk = bs.chunk();
+            self.buf.extend_from_slice(chunk);
+            Poll::Ready(Ok(chunk.len()))
+        }
+
+        fn poll_close(&mut self, _: &mut Context<'_>) -> Poll<Result<()>> {
+            self.writes.fetch_add(1, Ordering::SeqCst);
+            *self.content.lock().unwrap() = Some(std::mem::take(&mut self.buf));
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_abort(&mut self, _: &mut Context<'_>) -> Poll<Result<()>> {
+            self.buf.clear();
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dedupe_write() -> Result<()> {
+        let builder = MockBuilder::default();
+        let op = Operator::new(builder.clone())?.layer(DedupeLayer).finish();
+
+        op.write("test", "Hello, World!").await?;
+        assert_eq!(builder.writes.load(Ordering::SeqCst), 1);
+
+        // The same content should be skipped, and the metadata of existing
+        // file should be returned.
+        let mut w = op.writer("test").await?;
+        w.write("Hello, World!").await?;
+        let meta = w.close().await?;
+        assert_eq!(builder.writes.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            meta.content_md5(),
+            Some(format_content_md5(b"Hello, World!").as_str())
+        );
+
+        op.write("test", "Hello, OpenDAL!").await?;
+        assert_eq!(builder.writes.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            builder.content.lock().unwrap().as_deref(),
+            Some(b"Hello, OpenDAL!".as_slice())
+        );
+        Ok(())
+    }
+}
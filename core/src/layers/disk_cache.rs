@@ -0,0 +1,713 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::cmp::min;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::io::SeekFrom;
+use std::sync::Arc;
+use std::task::ready;
+use std::task::Context;
+use std::task::Poll;
+
+use async_trait::async_trait;
+use bytes::Buf;
+use bytes::Bytes;
+use bytes::BytesMut;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use parking_lot::Mutex;
+
+use crate::raw::oio::ReadExt;
+use crate::raw::*;
+use crate::services::Fs;
+use crate::*;
+
+/// Add a read-through cache on local disk.
+///
+/// Objects are split into fixed size blocks, every block fetched from the
+/// inner service will be stored on local disk and later reads of the same
+/// block will be served from disk directly. Reads that only hit part of
+/// the cached blocks will fetch the missing blocks and fill the cache.
+///
+/// # Notes
+///
+/// - Blocks are keyed by path and etag, so stale blocks will be bypassed
+///   naturally after the object has been changed. Objects without etag
+///   will not be cached.
+/// - Every read will `stat` the inner service to get the latest etag,
+///   users can add [`StatCacheLayer`](crate::layers::StatCacheLayer)
+///   under this layer to reduce the cost.
+/// - Total size of cached blocks is bounded by `capacity`, the least
+///   recently used blocks will be evicted first.
+/// - The cache index lives in memory, existing files under `root` will
+///   not be reused and could be overwritten. Please use a dedicated dir.
+/// - Blocking reads and reads with conditions (like `if_match` or
+///   `version`) will bypass the cache.
+///
+/// # Examples
+///
+/// ```no_run
+/// use anyhow::Result;
+/// use opendal::layers::DiskCacheLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// # fn main() -> Result<()> {
+/// let _ = Operator::new(services::Memory::default())?
+///     .layer(
+///         DiskCacheLayer::new("/tmp/opendal-cache")?
+///             .with_capacity(1024 * 1024 * 1024)
+///             .with_block_size(4 * 1024 * 1024),
+///     )
+///     .finish();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct DiskCacheLayer {
+    op: Operator,
+    capacity: u64,
+    block_size: u64,
+}
+
+impl DiskCacheLayer {
+    /// Create a new disk cache layer which stores blocks under `root`.
+    pub fn new(root: &str) -> Result<Self> {
+        let mut builder = Fs::default();
+        builder.root(root);
+        let op = Operator::new(builder)?.finish();
+
+        Ok(Self {
+            op,
+            capacity: 1024 * 1024 * 1024,
+            block_size: 4 * 1024 * 1024,
+        })
+    }
+
+    /// Set the max bytes of cached blocks.
+    ///
+    /// Default to 1GiB.
+    pub fn with_capacity(mut self, capacity: u64) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Set the size of every cached block.
+    ///
+    /// Default to 4MiB.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block_size` is 0.
+    pub fn with_block_size(mut self, block_size: u64) -> Self {
+        assert!(block_size > 0, "block size must be larger than 0");
+
+        self.block_size = block_size;
+        self
+    }
+}
+
+impl<A: Accessor> Layer<A> for DiskCacheLayer {
+    type LayeredAccessor = DiskCacheAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        DiskCacheAccessor {
+            inner: Arc::new(inner),
+            cache: Arc::new(DiskCache::new(
+                self.op.clone(),
+                self.capacity,
+                self.block_size,
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DiskCacheAccessor<A: Accessor> {
+    inner: Arc<A>,
+    cache: Arc<DiskCache>,
+}
+
+impl<A: Accessor> DiskCacheAccessor<A> {
+    /// Only read without any conditions can be served from cache.
+    fn is_cacheable(args: &OpRead) -> bool {
+        args.if_match().is_none() && args.if_none_match().is_none() && args.version().is_none()
+    }
+}
+
+#[async_trait]
+impl<A: Accessor> LayeredAccessor for DiskCacheAccessor<A> {
+    type Inner = A;
+    type Reader = DiskCacheReader<A>;
+    type BlockingReader = A::BlockingReader;
+    type Writer = A::Writer;
+    type BlockingWriter = A::BlockingWriter;
+    type Pager = A::Pager;
+    type BlockingPager = A::BlockingPager;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        if !Self::is_cacheable(&args) {
+            return self
+                .inner
+                .read(path, args)
+                .await
+                .map(|(rp, r)| (rp, DiskCacheReader::Direct(r)));
+        }
+
+        let meta = self.inner.stat(path, OpStat::new()).await?.into_metadata();
+        let etag = match meta.etag() {
+            Some(etag) if meta.mode().is_file() => etag.to_string(),
+            _ => {
+                return self
+                    .inner
+                    .read(path, args)
+                    .await
+                    .map(|(rp, r)| (rp, DiskCacheReader::Direct(r)))
+            }
+        };
+
+        let total = meta.content_length();
+        let range = args.range();
+        let (start, end) = match (range.offset(), range.size()) {
+            (None, None) => (0, total),
+            (None, Some(size)) => (total.saturating_sub(size), total),
+            (Some(offset), None) => (min(offset, total), total),
+            (Some(offset), Some(size)) => (min(offset, total), min(offset + size, total)),
+        };
+
+        let r = CachedReader {
+            inner: self.inner.clone(),
+            cache: self.cache.clone(),
+            path: Arc::new(path.to_string()),
+            etag: Arc::new(etag),
+            total,
+            start,
+            end,
+            cur: start,
+            buf: Bytes::new(),
+            fut: None,
+        };
+
+        Ok((RpRead::new(end - start), DiskCacheReader::Cached(r)))
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        self.inner.write(path, args).await
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        self.inner.list(path, args).await
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.inner.blocking_read(path, args)
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        self.inner.blocking_write(path, args)
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        self.inner.blocking_list(path, args)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct BlockKey {
+    path: Arc<String>,
+    etag: Arc<String>,
+    index: u64,
+}
+
+#[derive(Debug)]
+struct BlockEntry {
+    file: String,
+    size: u64,
+    tick: u64,
+}
+
+/// DiskCache maintains the index of blocks stored on local disk.
+#[derive(Debug)]
+struct DiskCache {
+    op: Operator,
+    capacity: u64,
+    block_size: u64,
+    index: Mutex<DiskCacheIndex>,
+}
+
+/// DiskCacheIndex tracks the recently used order by an increasing tick.
+#[derive(Debug, Default)]
+struct DiskCacheIndex {
+    tick: u64,
+    next_file: u64,
+    size: u64,
+    entries: HashMap<BlockKey, BlockEntry>,
+    order: BTreeMap<u64, BlockKey>,
+}
+
+impl DiskCache {
+    fn new(op: Operator, capacity: u64, block_size: u64) -> Self {
+        Self {
+            op,
+            capacity,
+            block_size,
+            index: Mutex::new(DiskCacheIndex::default()),
+        }
+    }
+
+    /// Get the file of given block and mark it as recently used.
+    fn get(&self, key: &BlockKey) -> Option<String> {
+        let mut index = self.index.lock();
+        index.tick += 1;
+        let tick = index.tick;
+
+        let DiskCacheIndex { entries, order, .. } = &mut *index;
+        let entry = entries.get_mut(key)?;
+        order.remove(&entry.tick);
+        order.insert(tick, key.clone());
+        entry.tick = tick;
+        Some(entry.file.clone())
+    }
+
+    /// Allocate a new file name for block.
+    ///
+    /// File names are never reused in the same process, so concurrent
+    /// fills of the same block will not overwrite each other.
+    fn allocate(&self) -> String {
+        let mut index = self.index.lock();
+        index.next_file += 1;
+        format!("{:020}", index.next_file)
+    }
+
+    /// Insert a block into index and return the files that have been evicted.
+    fn insert(&self, key: BlockKey, file: String, size: u64) -> Vec<String> {
+        let mut index = self.index.lock();
+        let mut evicted = Vec::new();
+
+        if let Some(old) = index.remove(&key) {
+            evicted.push(old.file);
+        }
+
+        index.tick += 1;
+        let tick = index.tick;
+        index.size += size;
+        index.order.insert(tick, key.clone());
+        index.entries.insert(key, BlockEntry { file, size, tick });
+
+        while index.size > self.capacity {
+            let key = match index.order.values().next() {
+                Some(key) => key.clone(),
+                None => break,
+            };
+            if let Some(entry) = index.remove(&key) {
+                evicted.push(entry.file);
+            }
+        }
+
+        evicted
+    }
+
+    fn remove(&self, key: &BlockKey) -> Option<String> {
+        self.index.lock().remove(key).map(|entry| entry.file)
+    }
+}
+
+impl DiskCacheIndex {
+    fn remove(&mut self, key: &BlockKey) -> Option<BlockEntry> {
+        let entry = self.entries.remove(key)?;
+        self.order.remove(&entry.tick);
+        self.size -= entry.size;
+        Some(entry)
+    }
+}
+
+/// Read a block from disk cache, or fetch it from inner and fill the cache.
+async fn read_block<A: Accessor>(
+    inner: Arc<A>,
+    cache: Arc<DiskCache>,
+    key: BlockKey,
+    size: u64,
+) -> Result<Bytes> {
+    if let Some(file) = cache.get(&key) {
+        match cache.op.read(&file).await {
+            Ok(bs) if bs.len() as u64 == size => return Ok(Bytes::from(bs)),
+            // The cached file is missing or broken, drop it and fetch again.
+            _ => {
+                if let Some(file) = cache.remove(&key) {
+                    let _ = cache.op.delete(&file).await;
+                }
+            }
+        }
+    }
+
+    let offset = key.index * cache.block_size;
+    let mut args = OpRead::new().with_range(BytesRange::new(Some(offset), Some(size)));
+    // Make sure we don't mix up contents of different versions.
+    if inner.info().full_capability().read_with_if_match {
+        args = args.with_if_match(&key.etag);
+    }
+
+    let (_, mut r) = inner.read(&key.path, args).await?;
+    let mut buf = BytesMut::with_capacity(size as usize);
+    while let Some(bs) = r.next().await {
+        buf.extend_from_slice(&bs?);
+    }
+    if buf.len() as u64 != size {
+        return Err(
+            Error::new(ErrorKind::Unexpected, "block read less data than expected")
+                .with_operation("DiskCache::read_block")
+                .with_context("path", key.path.as_str())
+                .with_context("offset", offset.to_string())
+                .with_context("expect", size.to_string())
+                .with_context("actual", buf.len().to_string()),
+        );
+    }
+    let bs = buf.freeze();
+
+    // Failing to fill the cache should not fail the read.
+    let file = cache.allocate();
+    if cache.op.write(&file, bs.clone()).await.is_ok() {
+        for file in cache.insert(key, file, size) {
+            let _ = cache.op.delete(&file).await;
+        }
+    }
+
+    Ok(bs)
+}
+
+pub enum DiskCacheReader<A: Accessor> {
+    Direct(A::Reader),
+    Cached(CachedReader<A>),
+}
+
+impl<A: Accessor> oio::Read for DiskCacheReader<A> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        match self {
+            Self::Direct(r) => r.poll_read(cx, buf),
+            Self::Cached(r) => r.poll_read(cx, buf),
+        }
+    }
+
+    fn poll_seek(&mut self, cx: &mut Context<'_>, pos: SeekFrom) -> Poll<Result<u64>> {
+        match self {
+            Self::Direct(r) => r.poll_seek(cx, pos),
+            Self::Cached(r) => r.poll_seek(cx, pos),
+        }
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        match self {
+            Self::Direct(r) => r.poll_next(cx),
+            Self::Cached(r) => r.poll_next(cx),
+        }
+    }
+}
+
+/// CachedReader reads the range `[start, end)` block by block.
+pub struct CachedReader<A: Accessor> {
+    inner: Arc<A>,
+    cache: Arc<DiskCache>,
+    path: Arc<String>,
+    etag: Arc<String>,
+    total: u64,
+
+    start: u64,
+    end: u64,
+    cur: u64,
+    buf: Bytes,
+    fut: Option<BoxFuture<'static, Result<Bytes>>>,
+}
+
+/// # Safety
+///
+/// We will only take `&mut Self` reference for the block future.
+unsafe impl<A: Accessor> Sync for CachedReader<A> {}
+
+impl<A: Accessor> CachedReader<A> {
+    /// Make sure `buf` is filled, returns `false` if we reach the end.
+    fn poll_fill(&mut self, cx: &mut Context<'_>) -> Poll<Result<bool>> {
+        loop {
+            if !self.buf.is_empty() {
+                return Poll::Ready(Ok(true));
+            }
+            if self.cur >= self.end {
+                return Poll::Ready(Ok(false));
+            }
+
+            let block_size = self.cache.block_size;
+            let index = self.cur / block_size;
+            let block_start = index * block_size;
+
+            match &mut self.fut {
+                Some(fut) => {
+                    let res = ready!(fut.poll_unpin(cx));
+                    self.fut = None;
+                    let bs = res?;
+
+                    let from = (self.cur - block_start) as usize;
+                    let to = min(bs.len() as u64, self.end - block_start) as usize;
+                    if from >= to {
+                        return Poll::Ready(Err(Error::new(
+                            ErrorKind::Unexpected,
+                            "cached block is shorter than expected",
+                        )
+                        .with_operation(oio::ReadOperation::Next)
+                        .with_context("path", self.path.as_str())
+                        .with_context("offset", self.cur.to_string())));
+                    }
+                    self.buf = bs.slice(from..to);
+                }
+                None => {
+                    let key = BlockKey {
+                        path: self.path.clone(),
+                        etag: self.etag.clone(),
+                        index,
+                    };
+                    let size = min(block_size, self.total - block_start);
+                    self.fut = Some(Box::pin(read_block(
+                        self.inner.clone(),
+                        self.cache.clone(),
+                        key,
+                        size,
+                    )));
+                }
+            }
+        }
+    }
+}
+
+impl<A: Accessor> oio::Read for CachedReader<A> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        if !ready!(self.poll_fill(cx))? {
+            return Poll::Ready(Ok(0));
+        }
+
+        let n = min(buf.len(), self.buf.len());
+        buf[..n].copy_from_slice(&self.buf[..n]);
+        self.buf.advance(n);
+        self.cur += n as u64;
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_seek(&mut self, _: &mut Context<'_>, pos: SeekFrom) -> Poll<Result<u64>> {
+        let base = match pos {
+            SeekFrom::Start(n) => self.start as i64 + n as i64,
+            SeekFrom::End(n) => self.end as i64 + n,
+            SeekFrom::Current(n) => self.cur as i64 + n,
+        };
+        if base < self.start as i64 {
+            return Poll::Ready(Err(Error::new(
+                ErrorKind::InvalidInput,
+                "invalid seek to a negative position",
+            )
+            .with_operation(oio::ReadOperation::Seek)));
+        }
+
+        let pos = base as u64;
+        if pos != self.cur {
+            self.cur = pos;
+            self.buf = Bytes::new();
+            self.fut = None;
+        }
+        Poll::Ready(Ok(self.cur - self.start))
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        match ready!(self.poll_fill(cx)) {
+            Err(err) => Poll::Ready(Some(Err(err))),
+            Ok(false) => Poll::Ready(None),
+            Ok(true) => {
+                let bs = std::mem::take(&mut self.buf);
+                self.cur += bs.len() as u64;
+                Poll::Ready(Some(Ok(bs)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+
+    use super::*;
+
+    #[derive(Clone)]
+    struct MockBuilder {
+        content: Bytes,
+        etag: Arc<Mutex<String>>,
+        reads: Arc<AtomicUsize>,
+    }
+
+    impl Default for MockBuilder {
+        fn default() -> Self {
+            Self {
+                content: Bytes::from((0..100).collect::<Vec<u8>>()),
+                etag: Arc::new(Mutex::new("\"v1\"".to_string())),
+                reads: Arc::default(),
+            }
+        }
+    }
+
+    impl Builder for MockBuilder {
+        const SCHEME: Scheme = Scheme::Custom("mock");
+        type Accessor = MockService;
+
+        fn from_map(_: HashMap<String, String>) -> Self {
+            Self::default()
+        }
+
+        fn build(&mut self) -> Result<Self::Accessor> {
+            Ok(MockService {
+                content: self.content.clone(),
+                etag: self.etag.clone(),
+                reads: self.reads.clone(),
+            })
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct MockService {
+        content: Bytes,
+        etag: Arc<Mutex<String>>,
+        reads: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Accessor for MockService {
+        type Reader = oio::Cursor;
+        type BlockingReader = ();
+        type Writer = ();
+        type BlockingWriter = ();
+        type Pager = ();
+        type BlockingPager = ();
+
+        fn info(&self) -> AccessorInfo {
+            let mut am = AccessorInfo::default();
+            am.set_native_capability(Capability {
+                stat: true,
+                read: true,
+                read_can_seek: true,
+                read_with_range: true,
+                ..Default::default()
+            });
+
+            am
+        }
+
+        async fn stat(&self, _: &str, _: OpStat) -> Result<RpStat> {
+            Ok(RpStat::new(
+                Metadata::new(EntryMode::FILE)
+                    .with_content_length(self.content.len() as u64)
+                    .with_etag(self.etag.lock().clone()),
+            ))
+        }
+
+        async fn read(&self, _: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            let bs = args.range().apply_on_bytes(self.content.clone());
+            Ok((RpRead::new(bs.len() as u64), oio::Cursor::from(bs)))
+        }
+    }
+
+    fn new_operator(builder: &MockBuilder, capacity: u64) -> Result<Operator> {
+        let root =
+            std::env::temp_dir().join(format!("opendal-disk-cache-{}", uuid::Uuid::new_v4()));
+
+        Ok(Operator::new(builder.clone())?
+            .layer(
+                DiskCacheLayer::new(&root.to_string_lossy())?
+                    .with_capacity(capacity)
+                    .with_block_size(16),
+            )
+            .finish())
+    }
+
+    #[tokio::test]
+    async fn test_disk_cache_read() -> Result<()> {
+        let builder = MockBuilder::default();
+        let op = new_operator(&builder, 1024)?;
+
+        let bs = op.read("test").await?;
+        assert_eq!(bs, builder.content.to_vec());
+        // 100 bytes will be split into 7 blocks.
+        assert_eq!(builder.reads.load(Ordering::SeqCst), 7);
+
+        let bs = op.read("test").await?;
+        assert_eq!(bs, builder.content.to_vec());
+        let bs = op.read_with("test").range(10..50).await?;
+        assert_eq!(bs, builder.content[10..50].to_vec());
+        assert_eq!(builder.reads.load(Ordering::SeqCst), 7);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_disk_cache_fill_missing_blocks() -> Result<()> {
+        let builder = MockBuilder::default();
+        let op = new_operator(&builder, 1024)?;
+
+        let bs = op.read_with("test").range(0..20).await?;
+        assert_eq!(bs, builder.content[0..20].to_vec());
+        assert_eq!(builder.reads.load(Ordering::SeqCst), 2);
+
+        // Block 0 and 1 are cached, only block 2 and 3 will be fetched.
+        let bs = op.read_with("test").range(10..60).await?;
+        assert_eq!(bs, builder.content[10..60].to_vec());
+        assert_eq!(builder.reads.load(Ordering::SeqCst), 4);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_disk_cache_bypass_stale_etag() -> Result<()> {
+        let builder = MockBuilder::default();
+        let op = new_operator(&builder, 1024)?;
+
+        op.read_with("test").range(0..16).await?;
+        assert_eq!(builder.reads.load(Ordering::SeqCst), 1);
+
+        *builder.etag.lock() = "\"v2\"".to_string();
+        op.read_with("test").range(0..16).await?;
+        assert_eq!(builder.reads.load(Ordering::SeqCst), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_disk_cache_evict() -> Result<()> {
+        let builder = MockBuilder::default();
+        let op = new_operator(&builder, 32)?;
+
+        op.read_with("test").range(0..48).await?;
+        assert_eq!(builder.reads.load(Ordering::SeqCst), 3);
+
+        // Block 0 has been evicted while block 1 and 2 are still cached.
+        op.read_with("test").range(16..48).await?;
+        assert_eq!(builder.reads.load(Ordering::SeqCst), 3);
+        op.read_with("test").range(0..16).await?;
+        assert_eq!(builder.reads.load(Ordering::SeqCst), 4);
+
+        Ok(())
+    }
+}
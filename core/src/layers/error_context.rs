@@ -430,6 +430,10 @@ impl<T: oio::Write> oio::Write for ErrorContextWrapper<T> {
                 .with_context("path", &self.path)
         })
     }
+
+    fn metadata(&self) -> Option<Metadata> {
+        self.inner.metadata()
+    }
 }
 
 impl<T: oio::BlockingWrite> oio::BlockingWrite for ErrorContextWrapper<T> {
@@ -460,6 +464,10 @@ impl<T: oio::Page> oio::Page for ErrorContextWrapper<T> {
                 .with_context("path", &self.path)
         })
     }
+
+    fn continuation_token(&self) -> Option<String> {
+        self.inner.continuation_token()
+    }
 }
 
 impl<T: oio::BlockingPage> oio::BlockingPage for ErrorContextWrapper<T> {
@@ -470,4 +478,8 @@ impl<T: oio::BlockingPage> oio::BlockingPage for ErrorContextWrapper<T> {
                 .with_context("path", &self.path)
         })
     }
+
+    fn continuation_token(&self) -> Option<String> {
+        self.inner.continuation_token()
+    }
 }
@@ -0,0 +1,636 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::raw::oio::WriteBuf;
+use crate::raw::*;
+use crate::*;
+
+/// Fail over to the next operator if current one returns temporary or not
+/// found error.
+///
+/// # Notes
+///
+/// - Backends are tried in order: the inner accessor first, then each
+///   fallback in the order they have been added.
+/// - Failover happens only while starting an operation. Errors returned by
+///   readers, writers and pagers that have been created will not fail over.
+/// - If all backends failed, the returned error will have the kind of the
+///   first backend's error, and list every backend's failure in context.
+/// - With [`FailoverLayer::with_write_all`], writes and other operations
+///   that change content will be sent to all backends and fail if any of
+///   them fails.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::FailoverLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// let secondary = Operator::new(services::Memory::default())
+///     .expect("must init")
+///     .finish();
+///
+/// let _ = Operator::new(services::Memory::default())
+///     .expect("must init")
+///     .layer(FailoverLayer::new().with_fallback(secondary))
+///     .finish();
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct FailoverLayer {
+    fallbacks: Vec<FusedAccessor>,
+    write_all: bool,
+}
+
+impl FailoverLayer {
+    /// Create a new FailoverLayer without fallbacks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a fallback operator, which will be tried after all previous ones.
+    pub fn with_fallback(mut self, op: Operator) -> Self {
+        self.fallbacks.push(op.into_inner());
+        self
+    }
+
+    /// Send operations that change content to all backends instead of the
+    /// first available one, so that all backends keep the same content.
+    ///
+    /// Covers `write`, `create_dir`, `copy`, `rename`, `delete` and `batch`.
+    /// The operation will succeed only if all backends succeed, and they
+    /// are sent one by one in order. Backends that have succeeded will not
+    /// be rolled back if a later one fails.
+    pub fn with_write_all(mut self, write_all: bool) -> Self {
+        self.write_all = write_all;
+        self
+    }
+}
+
+impl<A: Accessor> Layer<A> for FailoverLayer {
+    type LayeredAccessor = FailoverAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        FailoverAccessor {
+            inner,
+            fallbacks: Arc::new(self.fallbacks.clone()),
+            write_all: self.write_all,
+        }
+    }
+}
+
+/// Check if we should try the next backend for this error.
+fn should_failover(err: &Error) -> bool {
+    err.is_temporary() || err.kind() == ErrorKind::NotFound
+}
+
+/// Build the error returned when all backends failed.
+fn all_failed(op: Operation, errs: Vec<Error>) -> Error {
+    let temporary = errs.iter().all(|e| e.is_temporary());
+
+    let mut errs = errs.into_iter().enumerate();
+    let (_, first) = errs.next().expect("errors must not be empty");
+
+    let mut err = Error::new(first.kind(), "all backends failed")
+        .with_operation(op)
+        .with_context("backend", format!("0: {first}"));
+    for (idx, e) in errs {
+        err = err.with_context("backend", format!("{idx}: {e}"));
+    }
+    if temporary {
+        err = err.set_temporary();
+    }
+    err.set_source(first)
+}
+
+#[derive(Debug, Clone)]
+pub struct FailoverAccessor<A: Accessor> {
+    inner: A,
+    fallbacks: Arc<Vec<FusedAccessor>>,
+    write_all: bool,
+}
+
+impl<A: Accessor> FailoverAccessor<A> {
+    async fn failover<T, F, Fut>(&self, op: Operation, res: Result<T>, f: F) -> Result<T>
+    where
+        T: Send,
+        F: Fn(FusedAccessor) -> Fut + Send + Sync,
+        Fut: Future<Output = Result<T>> + Send,
+    {
+        let mut errs = match res {
+            Ok(v) => return Ok(v),
+            Err(err) if !should_failover(&err) => return Err(err),
+            Err(err) => vec![err],
+        };
+
+        for acc in self.fallbacks.iter() {
+            match f(acc.clone()).await {
+                Ok(v) => return Ok(v),
+                Err(err) if !should_failover(&err) => return Err(err),
+                Err(err) => errs.push(err),
+            }
+        }
+
+        Err(all_failed(op, errs))
+    }
+
+    /// Run the operation on all backends in order, fails if any of them fails.
+    async fn fanout<T, F, Fut>(&self, res: Result<T>, f: F) -> Result<T>
+    where
+        T: Send,
+        F: Fn(FusedAccessor) -> Fut + Send + Sync,
+        Fut: Future<Output = Result<T>> + Send,
+    {
+        let v = res.map_err(|err| err.with_context("backend", "0"))?;
+        for (idx, acc) in self.fallbacks.iter().enumerate() {
+            f(acc.clone())
+                .await
+                .map_err(|err| err.with_context("backend", (idx + 1).to_string()))?;
+        }
+        Ok(v)
+    }
+
+    fn blocking_fanout<T, F>(&self, res: Result<T>, f: F) -> Result<T>
+    where
+        F: Fn(&FusedAccessor) -> Result<T>,
+    {
+        let v = res.map_err(|err| err.with_context("backend", "0"))?;
+        for (idx, acc) in self.fallbacks.iter().enumerate() {
+            f(acc).map_err(|err| err.with_context("backend", (idx + 1).to_string()))?;
+        }
+        Ok(v)
+    }
+
+    fn blocking_failover<T, F>(&self, op: Operation, res: Result<T>, f: F) -> Result<T>
+    where
+        F: Fn(&FusedAccessor) -> Result<T>,
+    {
+        let mut errs = match res {
+            Ok(v) => return Ok(v),
+            Err(err) if !should_failover(&err) => return Err(err),
+            Err(err) => vec![err],
+        };
+
+        for acc in self.fallbacks.iter() {
+            match f(acc) {
+                Ok(v) => return Ok(v),
+                Err(err) if !should_failover(&err) => return Err(err),
+                Err(err) => errs.push(err),
+            }
+        }
+
+        Err(all_failed(op, errs))
+    }
+}
+
+#[async_trait]
+impl<A: Accessor> LayeredAccessor for FailoverAccessor<A> {
+    type Inner = A;
+    type Reader = oio::Reader;
+    type BlockingReader = oio::BlockingReader;
+    type Writer = oio::Writer;
+    type BlockingWriter = oio::BlockingWriter;
+    type Pager = oio::Pager;
+    type BlockingPager = oio::BlockingPager;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        let res = self.inner.create_dir(path, args.clone()).await;
+        let f = |acc: FusedAccessor| {
+            let args = args.clone();
+            async move { acc.create_dir(path, args).await }
+        };
+        if self.write_all {
+            return self.fanout(res, f).await;
+        }
+        self.failover(Operation::CreateDir, res, f).await
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        let res = self
+            .inner
+            .read(path, args.clone())
+            .await
+            .map(|(rp, r)| (rp, Box::new(r) as oio::Reader));
+        self.failover(Operation::Read, res, |acc| {
+            let args = args.clone();
+            async move { acc.read(path, args).await }
+        })
+        .await
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        if self.write_all {
+            let (rp, w) = self.inner.write(path, args.clone()).await?;
+            let mut writers = vec![Box::new(w) as oio::Writer];
+            for acc in self.fallbacks.iter() {
+                let (_, w) = acc.write(path, args.clone()).await?;
+                writers.push(w);
+            }
+            return Ok((rp, Box::new(FanoutWriter::new(writers))));
+        }
+
+        let res = self
+            .inner
+            .write(path, args.clone())
+            .await
+            .map(|(rp, w)| (rp, Box::new(w) as oio::Writer));
+        self.failover(Operation::Write, res, |acc| {
+            let args = args.clone();
+            async move { acc.write(path, args).await }
+        })
+        .await
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        let res = self.inner.copy(from, to, args.clone()).await;
+        let f = |acc: FusedAccessor| {
+            let args = args.clone();
+            async move { acc.copy(from, to, args).await }
+        };
+        if self.write_all {
+            return self.fanout(res, f).await;
+        }
+        self.failover(Operation::Copy, res, f).await
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        let res = self.inner.rename(from, to, args.clone()).await;
+        let f = |acc: FusedAccessor| {
+            let args = args.clone();
+            async move { acc.rename(from, to, args).await }
+        };
+        if self.write_all {
+            return self.fanout(res, f).await;
+        }
+        self.failover(Operation::Rename, res, f).await
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        let res = self.inner.stat(path, args.clone()).await;
+        self.failover(Operation::Stat, res, |acc| {
+            let args = args.clone();
+            async move { acc.stat(path, args).await }
+        })
+        .await
+    }
+
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        let res = self.inner.delete(path, args.clone()).await;
+        let f = |acc: FusedAccessor| {
+            let args = args.clone();
+            async move { acc.delete(path, args).await }
+        };
+        if self.write_all {
+            return self.fanout(res, f).await;
+        }
+        self.failover(Operation::Delete, res, f).await
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        let res = self
+            .inner
+            .list(path, args.clone())
+            .await
+            .map(|(rp, p)| (rp, Box::new(p) as oio::Pager));
+        self.failover(Operation::List, res, |acc| {
+            let args = args.clone();
+            async move { acc.list(path, args).await }
+        })
+        .await
+    }
+
+    async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
+        let res = self.inner.batch(args.clone()).await;
+        let f = |acc: FusedAccessor| {
+            let args = args.clone();
+            async move { acc.batch(args).await }
+        };
+        if self.write_all {
+            return self.fanout(res, f).await;
+        }
+        self.failover(Operation::Batch, res, f).await
+    }
+
+    async fn presign(&self, path: &str, args: OpPresign) -> Result<RpPresign> {
+        let res = self.inner.presign(path, args.clone()).await;
+        self.failover(Operation::Presign, res, |acc| {
+            let args = args.clone();
+            async move { acc.presign(path, args).await }
+        })
+        .await
+    }
+
+    fn blocking_create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        let res = self.inner.blocking_create_dir(path, args.clone());
+        let f = |acc: &FusedAccessor| acc.blocking_create_dir(path, args.clone());
+        if self.write_all {
+            return self.blocking_fanout(res, f);
+        }
+        self.blocking_failover(Operation::BlockingCreateDir, res, f)
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        let res = self
+            .inner
+            .blocking_read(path, args.clone())
+            .map(|(rp, r)| (rp, Box::new(r) as oio::BlockingReader));
+        self.blocking_failover(Operation::BlockingRead, res, |acc| {
+            acc.blocking_read(path, args.clone())
+        })
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        if self.write_all {
+            let (rp, w) = self.inner.blocking_write(path, args.clone())?;
+            let mut writers = vec![Box::new(w) as oio::BlockingWriter];
+            for acc in self.fallbacks.iter() {
+                let (_, w) = acc.blocking_write(path, args.clone())?;
+                writers.push(w);
+            }
+            return Ok((rp, Box::new(FanoutWriter::new(writers))));
+        }
+
+        let res = self
+            .inner
+            .blocking_write(path, args.clone())
+            .map(|(rp, w)| (rp, Box::new(w) as oio::BlockingWriter));
+        self.blocking_failover(Operation::BlockingWrite, res, |acc| {
+            acc.blocking_write(path, args.clone())
+        })
+    }
+
+    fn blocking_copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        let res = self.inner.blocking_copy(from, to, args.clone());
+        let f = |acc: &FusedAccessor| acc.blocking_copy(from, to, args.clone());
+        if self.write_all {
+            return self.blocking_fanout(res, f);
+        }
+        self.blocking_failover(Operation::BlockingCopy, res, f)
+    }
+
+    fn blocking_rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        let res = self.inner.blocking_rename(from, to, args.clone());
+        let f = |acc: &FusedAccessor| acc.blocking_rename(from, to, args.clone());
+        if self.write_all {
+            return self.blocking_fanout(res, f);
+        }
+        self.blocking_failover(Operation::BlockingRename, res, f)
+    }
+
+    fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        let res = self.inner.blocking_stat(path, args.clone());
+        self.blocking_failover(Operation::BlockingStat, res, |acc| {
+            acc.blocking_stat(path, args.clone())
+        })
+    }
+
+    fn blocking_delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        let res = self.inner.blocking_delete(path, args.clone());
+        let f = |acc: &FusedAccessor| acc.blocking_delete(path, args.clone());
+        if self.write_all {
+            return self.blocking_fanout(res, f);
+        }
+        self.blocking_failover(Operation::BlockingDelete, res, f)
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        let res = self
+            .inner
+            .blocking_list(path, args.clone())
+            .map(|(rp, p)| (rp, Box::new(p) as oio::BlockingPager));
+        self.blocking_failover(Operation::BlockingList, res, |acc| {
+            acc.blocking_list(path, args.clone())
+        })
+    }
+}
+
+/// FanoutWriter writes the same content to all writers.
+///
+/// Content will be reported as written only after all writers accepted it.
+pub struct FanoutWriter<W> {
+    writers: Vec<W>,
+    /// The content that is being written and how many bytes of it have been
+    /// accepted by each writer.
+    pending: Option<Bytes>,
+    written: Vec<usize>,
+    /// Whether each writer has been closed or aborted.
+    done: Vec<bool>,
+}
+
+impl<W> FanoutWriter<W> {
+    fn new(writers: Vec<W>) -> Self {
+        let size = writers.len();
+        Self {
+            writers,
+            pending: None,
+            written: vec![0; size],
+            done: vec![false; size],
+        }
+    }
+
+    fn reset(&mut self) {
+        self.pending = None;
+        self.written.iter_mut().for_each(|v| *v = 0);
+    }
+}
+
+impl<W: oio::Write> oio::Write for FanoutWriter<W> {
+    fn poll_write(&mut self, cx: &mut Context<'_>, bs: &dyn WriteBuf) -> Poll<Result<usize>> {
+        let pending = self
+            .pending
+            .get_or_insert_with(|| bs.bytes(bs.remaining()))
+            .clone();
+
+        let mut ready = true;
+        for (idx, w) in self.writers.iter_mut().enumerate() {
+            while self.written[idx] < pending.len() {
+                match w.poll_write(cx, &pending.slice(self.written[idx]..)) {
+                    Poll::Ready(Ok(n)) => self.written[idx] += n,
+                    Poll::Ready(Err(err)) => {
+                        self.pending = None;
+                        self.written.iter_mut().for_each(|v| *v = 0);
+                        return Poll::Ready(Err(err.with_context("backend", idx.to_string())));
+                    }
+                    Poll::Pending => {
+                        ready = false;
+                        break;
+                    }
+                }
+            }
+        }
+        if !ready {
+            return Poll::Pending;
+        }
+
+        self.reset();
+        Poll::Ready(Ok(pending.len()))
+    }
+
+    fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let mut ready = true;
+        for (idx, w) in self.writers.iter_mut().enumerate() {
+            if self.done[idx] {
+                continue;
+            }
+            match w.poll_close(cx) {
+                Poll::Ready(Ok(())) => self.done[idx] = true,
+                Poll::Ready(Err(err)) => {
+                    return Poll::Ready(Err(err.with_context("backend", idx.to_string())))
+                }
+                Poll::Pending => ready = false,
+            }
+        }
+
+        if ready {
+            Poll::Ready(Ok(()))
+        } else {
+            Poll::Pending
+        }
+    }
+
+    fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.reset();
+
+        let mut ready = true;
+        for (idx, w) in self.writers.iter_mut().enumerate() {
+            if self.done[idx] {
+                continue;
+            }
+            match w.poll_abort(cx) {
+                Poll::Ready(Ok(())) => self.done[idx] = true,
+                Poll::Ready(Err(err)) => {
+                    return Poll::Ready(Err(err.with_context("backend", idx.to_string())))
+                }
+                Poll::Pending => ready = false,
+            }
+        }
+
+        if ready {
+            Poll::Ready(Ok(()))
+        } else {
+            Poll::Pending
+        }
+    }
+
+    /// Metadata of the inner backend is returned, since it's always the
+    /// first one to be tried.
+    fn metadata(&self) -> Option<Metadata> {
+        self.writers.first().and_then(|w| w.metadata())
+    }
+}
+
+impl<W: oio::BlockingWrite> oio::BlockingWrite for FanoutWriter<W> {
+    fn write(&mut self, bs: &dyn WriteBuf) -> Result<usize> {
+        let pending = bs.bytes(bs.remaining());
+
+        for (idx, w) in self.writers.iter_mut().enumerate() {
+            let mut written = 0;
+            while written < pending.len() {
+                written += w
+                    .write(&pending.slice(written..))
+                    .map_err(|err| err.with_context("backend", idx.to_string()))?;
+            }
+        }
+
+        Ok(pending.len())
+    }
+
+    fn close(&mut self) -> Result<()> {
+        for (idx, w) in self.writers.iter_mut().enumerate() {
+            if self.done[idx] {
+                continue;
+            }
+            w.close()
+                .map_err(|err| err.with_context("backend", idx.to_string()))?;
+            self.done[idx] = true;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::Memory;
+
+    fn new_operator() -> Operator {
+        Operator::new(Memory::default())
+            .expect("must init")
+            .finish()
+    }
+
+    #[tokio::test]
+    async fn test_failover_on_not_found() {
+        let secondary = new_operator();
+        secondary
+            .write("file", "hello")
+            .await
+            .expect("write must succeed");
+
+        let op = new_operator().layer(FailoverLayer::new().with_fallback(secondary));
+
+        let bs = op.read("file").await.expect("read must fall back");
+        assert_eq!(bs, b"hello");
+
+        let err = op.read("not_exist").await.expect_err("read must fail");
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        assert!(err.to_string().contains("all backends failed"));
+    }
+
+    #[tokio::test]
+    async fn test_failover_write_all() {
+        let primary = new_operator();
+        let secondary = new_operator();
+
+        let op = primary.clone().layer(
+            FailoverLayer::new()
+                .with_fallback(secondary.clone())
+                .with_write_all(true),
+        );
+        op.write("file", "hello").await.expect("write must succeed");
+
+        for op in [primary.clone(), secondary.clone()] {
+            let bs = op.read("file").await.expect("read must succeed");
+            assert_eq!(bs, b"hello");
+        }
+
+        op.copy("file", "copied").await.expect("copy must succeed");
+        op.rename("copied", "renamed")
+            .await
+            .expect("rename must succeed");
+        op.delete("file").await.expect("delete must succeed");
+
+        for op in [primary, secondary] {
+            assert!(!op.is_exist("file").await.expect("stat must succeed"));
+            assert!(!op.is_exist("copied").await.expect("stat must succeed"));
+            assert!(op.is_exist("renamed").await.expect("stat must succeed"));
+        }
+    }
+}
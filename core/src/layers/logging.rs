@@ -17,6 +17,7 @@
 
 use std::fmt::Debug;
 use std::io;
+use std::sync::Arc;
 use std::task::ready;
 use std::task::Context;
 use std::task::Poll;
@@ -84,19 +85,52 @@ use crate::*;
 /// ```shell
 /// RUST_LOG="info,opendal::services=debug" ./app
 /// ```
+///
+/// # Redaction
+///
+/// Errors and presigned requests could carry full urls and headers, which
+/// may contain credentials like SAS tokens. Values of the following query
+/// parameters and headers will be replaced by `***` before logging:
+///
+/// - SAS tokens: `sig`, `se`, `st`, `skoid`, `sktid`, `skt`, `ske`, `sks`, `skv`,
+///   only matched as query parameters like `?sig=` or `&sig=`.
+/// - AWS signatures: `x-amz-signature`, `x-amz-credential`, `x-amz-security-token`
+/// - Headers: `authorization`, `proxy-authorization`, `x-ms-copy-source-authorization`
+///
+/// More names could be added by [`LoggingLayer::with_redact_patterns`].
 #[derive(Debug, Copy, Clone)]
 pub struct LoggingLayer {
     error_level: Option<Level>,
     failure_level: Option<Level>,
     backtrace_output: bool,
+    redact_patterns: &'static [&'static str],
 }
 
+/// Names of SAS query parameters whose values will be redacted.
+///
+/// Those names are too short to be matched everywhere, so they are only
+/// matched while following `?` or `&`.
+static DEFAULT_REDACT_QUERY_PARAMS: &[&str] = &[
+    "sig", "se", "st", "skoid", "sktid", "skt", "ske", "sks", "skv",
+];
+
+/// Names of query parameters and headers whose values will be redacted.
+static DEFAULT_REDACT_PATTERNS: &[&str] = &[
+    "x-amz-signature",
+    "x-amz-credential",
+    "x-amz-security-token",
+    "authorization",
+    "proxy-authorization",
+    "x-ms-copy-source-authorization",
+];
+
 impl Default for LoggingLayer {
     fn default() -> Self {
         Self {
             error_level: Some(Level::Warn),
             failure_level: Some(Level::Error),
             backtrace_output: false,
+            redact_patterns: &[],
         }
     }
 }
@@ -148,6 +182,17 @@ impl LoggingLayer {
         self.backtrace_output = enable;
         self
     }
+
+    /// Setting more names of query parameters or headers whose values should
+    /// be redacted while logging.
+    ///
+    /// Names are matched case-insensitively, and will be used together with
+    /// the default ones like `sig` and `authorization`. Calling this function
+    /// again will replace the names set before.
+    pub fn with_redact_patterns(mut self, patterns: &'static [&'static str]) -> Self {
+        self.redact_patterns = patterns;
+        self
+    }
 }
 
 impl<A: Accessor> Layer<A> for LoggingLayer {
@@ -163,6 +208,13 @@ impl<A: Accessor> Layer<A> for LoggingLayer {
                 error_level: self.error_level,
                 failure_level: self.failure_level,
                 backtrace_output: self.backtrace_output,
+                redact_patterns: Arc::new(
+                    DEFAULT_REDACT_PATTERNS
+                        .iter()
+                        .chain(self.redact_patterns)
+                        .map(|v| v.to_ascii_lowercase())
+                        .collect(),
+                ),
             },
         }
     }
@@ -174,6 +226,7 @@ pub struct LoggingContext {
     error_level: Option<Level>,
     failure_level: Option<Level>,
     backtrace_output: bool,
+    redact_patterns: Arc<Vec<String>>,
 }
 
 impl LoggingContext {
@@ -190,16 +243,129 @@ impl LoggingContext {
     #[inline]
     fn error_print(&self, err: &Error) -> String {
         // Don't print backtrace if it's not unexpected error.
-        if err.kind() != ErrorKind::Unexpected {
-            return format!("{err}");
+        let s = if err.kind() != ErrorKind::Unexpected || !self.backtrace_output {
+            format!("{err}")
+        } else {
+            format!("{err:?}")
+        };
+
+        self.redact(&s)
+    }
+
+    /// Redact values of sensitive query parameters and headers in given
+    /// string, see [`redact`].
+    #[inline]
+    fn redact(&self, s: &str) -> String {
+        redact(s, DEFAULT_REDACT_QUERY_PARAMS, &self.redact_patterns)
+    }
+}
+
+/// Replace values of query parameters named by `query_params` and values of
+/// query parameters or headers named by `patterns` with `***`.
+///
+/// The following forms are supported:
+///
+/// - query parameters: `sig=value`, value ends at `&`, whitespace or quotes.
+///   Names in `query_params` must follow `?` or `&`.
+/// - headers: `authorization: value` and `"authorization": "value"`, value
+///   ends at the end of line or the closing quote.
+///
+/// `query_params` and `patterns` must be lowercase.
+fn redact(s: &str, query_params: &[&str], patterns: &[String]) -> String {
+    let lower = s.to_ascii_lowercase();
+    let bs = lower.as_bytes();
+
+    let mut ranges = vec![];
+    for name in query_params {
+        for (start, _) in lower.match_indices(name) {
+            if start == 0 || !matches!(bs[start - 1], b'?' | b'&') {
+                continue;
+            }
+
+            let idx = start + name.len();
+            if bs.get(idx) != Some(&b'=') {
+                continue;
+            }
+            let end = value_end(bs, idx + 1, false, b'=');
+            if end > idx + 1 {
+                ranges.push((idx + 1, end));
+            }
         }
+    }
 
-        if self.backtrace_output {
-            format!("{err:?}")
-        } else {
-            format!("{err}")
+    for pattern in patterns {
+        for (start, _) in lower.match_indices(pattern.as_str()) {
+            // The name must not be a part of another name like `xsig`.
+            if start > 0 && is_name_byte(bs[start - 1]) {
+                continue;
+            }
+
+            let mut idx = start + pattern.len();
+            if bs.get(idx) == Some(&b'"') {
+                idx += 1;
+            }
+            let sep = match bs.get(idx) {
+                Some(&c) if c == b'=' || c == b':' => c,
+                _ => continue,
+            };
+            idx += 1;
+            if sep == b':' {
+                while bs.get(idx) == Some(&b' ') {
+                    idx += 1;
+                }
+            }
+
+            let quoted = bs.get(idx) == Some(&b'"');
+            if quoted {
+                idx += 1;
+            }
+            let end = value_end(bs, idx, quoted, sep);
+            if end > idx {
+                ranges.push((idx, end));
+            }
+        }
+    }
+
+    if ranges.is_empty() {
+        return s.to_string();
+    }
+
+    ranges.sort_unstable();
+    let mut output = String::with_capacity(s.len());
+    let mut pos = 0;
+    for (start, end) in ranges {
+        // Overlapped ranges have been redacted already.
+        if end <= pos {
+            continue;
+        }
+        let start = start.max(pos);
+        output.push_str(&s[pos..start]);
+        output.push_str("***");
+        pos = end;
+    }
+    output.push_str(&s[pos..]);
+    output
+}
+
+/// Find the end of value starts at `idx`.
+fn value_end(bs: &[u8], mut idx: usize, quoted: bool, sep: u8) -> usize {
+    while let Some(&c) = bs.get(idx) {
+        let end = match (quoted, sep) {
+            (true, _) => c == b'"',
+            (false, b'=') => c.is_ascii_whitespace() || b"&\"',;)}#".contains(&c),
+            (false, _) => c == b'\n' || c == b'\r' || b"\",}".contains(&c),
+        };
+        if end {
+            break;
         }
+        idx += 1;
     }
+    idx
+}
+
+#[inline]
+fn is_name_byte(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'-' || c == b'_'
 }
 
 #[derive(Clone, Debug)]
@@ -581,10 +747,11 @@ impl<A: Accessor> LayeredAccessor for LoggingAccessor<A> {
             .map(|v| {
                 debug!(
                     target: LOGGING_TARGET,
-                    "service={} operation={} path={} -> finished: {v:?}",
+                    "service={} operation={} path={} -> finished: {}",
                     self.ctx.scheme,
                     Operation::Presign,
-                    path
+                    path,
+                    self.ctx.redact(&format!("{v:?}"))
                 );
                 v
             })
@@ -1348,6 +1515,10 @@ impl<W: oio::Write> oio::Write for LoggingWriter<W> {
             }
         }
     }
+
+    fn metadata(&self) -> Option<Metadata> {
+        self.inner.metadata()
+    }
 }
 
 impl<W: oio::BlockingWrite> oio::BlockingWrite for LoggingWriter<W> {
@@ -1503,6 +1674,10 @@ impl<P: oio::Page> oio::Page for LoggingPager<P> {
 
         res
     }
+
+    fn continuation_token(&self) -> Option<String> {
+        self.inner.continuation_token()
+    }
 }
 
 impl<P: oio::BlockingPage> oio::BlockingPage for LoggingPager<P> {
@@ -1547,4 +1722,63 @@ impl<P: oio::BlockingPage> oio::BlockingPage for LoggingPager<P> {
 
         res
     }
+
+    fn continuation_token(&self) -> Option<String> {
+        self.inner.continuation_token()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact() {
+        let patterns: Vec<String> = DEFAULT_REDACT_PATTERNS
+            .iter()
+            .map(|v| v.to_string())
+            .chain(["x-custom-token".to_string()])
+            .collect();
+
+        let cases = vec![
+            (
+                "sas token",
+                "uri: https://a/s/f?sv=2022-11-02&se=2023-01-01T00%3A00%3A00Z&sp=r&sig=abc%2F",
+                "uri: https://a/s/f?sv=2022-11-02&se=***&sp=r&sig=***",
+            ),
+            (
+                "upper case",
+                "https://a/f?X-Amz-Signature=abc&X-Amz-Expires=60",
+                "https://a/f?X-Amz-Signature=***&X-Amz-Expires=60",
+            ),
+            (
+                "debug header map",
+                r#"{"authorization": "Bearer abc", "x-custom-token": "def"}"#,
+                r#"{"authorization": "***", "x-custom-token": "***"}"#,
+            ),
+            (
+                "header line",
+                "Authorization: Bearer abc\nhost: a",
+                "Authorization: ***\nhost: a",
+            ),
+            (
+                "part of other names",
+                "https://a/f?xsig=abc&design=def",
+                "https://a/f?xsig=abc&design=def",
+            ),
+            (
+                "sas names outside query",
+                "list: st=1, context { se: 2 }",
+                "list: st=1, context { se: 2 }",
+            ),
+        ];
+
+        for (name, input, expected) in cases {
+            assert_eq!(
+                redact(input, DEFAULT_REDACT_QUERY_PARAMS, &patterns),
+                expected,
+                "{name}"
+            );
+        }
+    }
 }
@@ -103,6 +103,8 @@ use crate::*;
 pub struct MadsimLayer {
     #[cfg(madsim)]
     addr: SocketAddr,
+    #[cfg(madsim)]
+    written: u64,
 }
 
 impl MadsimLayer {
@@ -211,6 +213,7 @@ impl LayeredAccessor for MadsimAccessor {
                     path: path.to_string(),
                     args,
                     addr: self.addr,
+                    written: 0,
                 },
             ))
         }
@@ -314,6 +317,7 @@ impl oio::Write for MadsimWriter {
             let (tx, mut rx) = ep.connect1(self.addr).await?;
             tx.send(Box::new(req)).await?;
             rx.recv().await?;
+            self.written += bs.remaining() as u64;
             Ok(())
         }
         #[cfg(not(madsim))]
@@ -332,6 +336,19 @@ impl oio::Write for MadsimWriter {
     fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<crate::Result<()>> {
         Poll::Ready(Ok(()))
     }
+
+    fn metadata(&self) -> Option<Metadata> {
+        #[cfg(madsim)]
+        {
+            let mut meta = Metadata::new(EntryMode::FILE);
+            meta.set_content_length(self.written);
+            Some(meta)
+        }
+        #[cfg(not(madsim))]
+        {
+            unreachable!("madsim is not enabled")
+        }
+    }
 }
 
 pub struct MadsimPager {}
@@ -53,6 +53,8 @@ static METRIC_REQUESTS_DURATION_SECONDS: &str = "opendal_requests_duration_secon
 static METRICS_ERRORS_TOTAL: &str = "opendal_errors_total";
 /// bytes_total records all bytes processed by operator.
 static METRIC_BYTES_TOTAL: &str = "opendal_bytes_total";
+/// bytes records the bytes processed by every read/write request.
+static METRIC_BYTES: &str = "opendal_bytes";
 
 /// The scheme of the service.
 static LABEL_SERVICE: &str = "service";
@@ -69,6 +71,7 @@ static LABEL_ERROR: &str = "error";
 /// - `opendal_requests_duration_seconds`: Request duration seconds.
 /// - `opendal_errors_total`: Total error numbers.
 /// - `opendal_bytes_total`: bytes read/write from/to underlying storage.
+/// - `opendal_bytes`: Histogram of bytes read/write by every request.
 ///
 /// # Labels
 ///
@@ -145,10 +148,12 @@ struct MetricsHandler {
     requests_total_read: Counter,
     requests_duration_seconds_read: Histogram,
     bytes_total_read: Counter,
+    bytes_read: Histogram,
 
     requests_total_write: Counter,
     requests_duration_seconds_write: Histogram,
     bytes_total_write: Counter,
+    bytes_write: Histogram,
 
     requests_total_stat: Counter,
     requests_duration_seconds_stat: Histogram,
@@ -171,11 +176,12 @@ struct MetricsHandler {
     requests_total_blocking_read: Counter,
     requests_duration_seconds_blocking_read: Histogram,
     bytes_total_blocking_read: Counter,
+    bytes_blocking_read: Histogram,
 
     requests_total_blocking_write: Counter,
     requests_duration_seconds_blocking_write: Histogram,
-    #[allow(dead_code)]
     bytes_total_blocking_write: Counter,
+    bytes_blocking_write: Histogram,
 
     requests_total_blocking_stat: Counter,
     requests_duration_seconds_blocking_stat: Histogram,
@@ -229,6 +235,11 @@ impl MetricsHandler {
                 LABEL_SERVICE => service,
                 LABEL_OPERATION => Operation::Read.into_static(),
             ),
+            bytes_read: register_histogram!(
+                METRIC_BYTES,
+                LABEL_SERVICE => service,
+                LABEL_OPERATION => Operation::Read.into_static(),
+            ),
 
             requests_total_write: register_counter!(
                 METRIC_REQUESTS_TOTAL,
@@ -245,6 +256,11 @@ impl MetricsHandler {
                 LABEL_SERVICE => service,
                 LABEL_OPERATION => Operation::Write.into_static(),
             ),
+            bytes_write: register_histogram!(
+                METRIC_BYTES,
+                LABEL_SERVICE => service,
+                LABEL_OPERATION => Operation::Write.into_static(),
+            ),
 
             requests_total_stat: register_counter!(
                 METRIC_REQUESTS_TOTAL,
@@ -327,6 +343,11 @@ impl MetricsHandler {
                 LABEL_SERVICE => service,
                 LABEL_OPERATION => Operation::BlockingRead.into_static(),
             ),
+            bytes_blocking_read: register_histogram!(
+                METRIC_BYTES,
+                LABEL_SERVICE => service,
+                LABEL_OPERATION => Operation::BlockingRead.into_static(),
+            ),
 
             requests_total_blocking_write: register_counter!(
                 METRIC_REQUESTS_TOTAL,
@@ -343,6 +364,11 @@ impl MetricsHandler {
                 LABEL_SERVICE => service,
                 LABEL_OPERATION => Operation::BlockingWrite.into_static(),
             ),
+            bytes_blocking_write: register_histogram!(
+                METRIC_BYTES,
+                LABEL_SERVICE => service,
+                LABEL_OPERATION => Operation::BlockingWrite.into_static(),
+            ),
 
             requests_total_blocking_stat: register_counter!(
                 METRIC_REQUESTS_TOTAL,
@@ -468,6 +494,7 @@ impl<A: Accessor> LayeredAccessor for MetricsAccessor<A> {
                             Operation::Read,
                             self.handle.clone(),
                             self.handle.bytes_total_read.clone(),
+                            self.handle.bytes_read.clone(),
                             self.handle.requests_duration_seconds_read.clone(),
                             Some(start),
                         ),
@@ -497,6 +524,7 @@ impl<A: Accessor> LayeredAccessor for MetricsAccessor<A> {
                         Operation::Write,
                         self.handle.clone(),
                         self.handle.bytes_total_write.clone(),
+                        self.handle.bytes_write.clone(),
                         self.handle.requests_duration_seconds_write.clone(),
                         Some(start),
                     ),
@@ -628,6 +656,7 @@ impl<A: Accessor> LayeredAccessor for MetricsAccessor<A> {
                     Operation::BlockingRead,
                     self.handle.clone(),
                     self.handle.bytes_total_blocking_read.clone(),
+                    self.handle.bytes_blocking_read.clone(),
                     self.handle.requests_duration_seconds_blocking_read.clone(),
                     Some(start),
                 ),
@@ -646,11 +675,6 @@ impl<A: Accessor> LayeredAccessor for MetricsAccessor<A> {
 
         let start = Instant::now();
         let result = self.inner.blocking_write(path, args);
-        let dur = start.elapsed().as_secs_f64();
-
-        self.handle
-            .requests_duration_seconds_blocking_write
-            .record(dur);
 
         result
             .map(|(rp, w)| {
@@ -660,8 +684,9 @@ impl<A: Accessor> LayeredAccessor for MetricsAccessor<A> {
                         w,
                         Operation::BlockingWrite,
                         self.handle.clone(),
-                        self.handle.bytes_total_write.clone(),
-                        self.handle.requests_duration_seconds_write.clone(),
+                        self.handle.bytes_total_blocking_write.clone(),
+                        self.handle.bytes_blocking_write.clone(),
+                        self.handle.requests_duration_seconds_blocking_write.clone(),
                         Some(start),
                     ),
                 )
@@ -733,6 +758,7 @@ pub struct MetricWrapper<R> {
 
     op: Operation,
     bytes_counter: Counter,
+    bytes_histogram: Histogram,
     requests_duration_seconds: Histogram,
     handle: Arc<MetricsHandler>,
 
@@ -746,6 +772,7 @@ impl<R> MetricWrapper<R> {
         op: Operation,
         handle: Arc<MetricsHandler>,
         bytes_counter: Counter,
+        bytes_histogram: Histogram,
         requests_duration_seconds: Histogram,
         start: Option<Instant>,
     ) -> Self {
@@ -754,6 +781,7 @@ impl<R> MetricWrapper<R> {
             op,
             handle,
             bytes_counter,
+            bytes_histogram,
             requests_duration_seconds,
             start,
             bytes: 0,
@@ -764,6 +792,7 @@ impl<R> MetricWrapper<R> {
 impl<R> Drop for MetricWrapper<R> {
     fn drop(&mut self) {
         self.bytes_counter.increment(self.bytes);
+        self.bytes_histogram.record(self.bytes as f64);
         if let Some(instant) = self.start {
             let dur = instant.elapsed().as_secs_f64();
             self.requests_duration_seconds.record(dur);
@@ -873,6 +902,10 @@ impl<R: oio::Write> oio::Write for MetricWrapper<R> {
             err
         })
     }
+
+    fn metadata(&self) -> Option<Metadata> {
+        self.inner.metadata()
+    }
 }
 
 impl<R: oio::BlockingWrite> oio::BlockingWrite for MetricWrapper<R> {
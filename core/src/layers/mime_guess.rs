@@ -0,0 +1,194 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::raw::*;
+use crate::*;
+
+/// Add content type guessed from path extension for write.
+///
+/// # Notes
+///
+/// - Content type specified by users will always be respected.
+/// - Extensions registered via [`MimeGuessLayer::with_mime`] take
+///   precedence over the builtin map of `mime_guess`.
+/// - Paths without a known extension will be written as is.
+/// - Services that don't support `write_with_content_type` will be written
+///   as is.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::MimeGuessLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// let _ = Operator::new(services::Memory::default())
+///     .expect("must init")
+///     .layer(
+///         MimeGuessLayer::default().with_mime("parquet", "application/vnd.apache.parquet"),
+///     )
+///     .finish();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MimeGuessLayer {
+    mimes: HashMap<String, String>,
+}
+
+impl MimeGuessLayer {
+    /// Register a custom content type for given extension.
+    ///
+    /// Extension should be given without the leading `.` and will be
+    /// matched case-insensitively.
+    pub fn with_mime(mut self, ext: &str, mime: &str) -> Self {
+        self.mimes.insert(ext.to_lowercase(), mime.to_string());
+        self
+    }
+}
+
+impl<A: Accessor> Layer<A> for MimeGuessLayer {
+    type LayeredAccessor = MimeGuessAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        MimeGuessAccessor {
+            inner,
+            mimes: Arc::new(self.mimes.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MimeGuessAccessor<A: Accessor> {
+    inner: A,
+    mimes: Arc<HashMap<String, String>>,
+}
+
+impl<A: Accessor> MimeGuessAccessor<A> {
+    fn guess(&self, path: &str) -> Option<String> {
+        let name = path.rsplit('/').next()?;
+        let (_, ext) = name.rsplit_once('.')?;
+        if ext.is_empty() {
+            return None;
+        }
+
+        if let Some(mime) = self.mimes.get(&ext.to_lowercase()) {
+            return Some(mime.clone());
+        }
+
+        mime_guess::from_ext(ext).first_raw().map(|v| v.to_string())
+    }
+
+    fn with_guessed_type(&self, path: &str, args: OpWrite) -> OpWrite {
+        if args.content_type().is_some() {
+            return args;
+        }
+        // Don't set content type for services that can't store it, otherwise
+        // the write will be rejected as unsupported.
+        if !self.inner.info().full_capability().write_with_content_type {
+            return args;
+        }
+
+        match self.guess(path) {
+            Some(mime) => args.with_content_type(&mime),
+            None => args,
+        }
+    }
+}
+
+#[async_trait]
+impl<A: Accessor> LayeredAccessor for MimeGuessAccessor<A> {
+    type Inner = A;
+    type Reader = A::Reader;
+    type BlockingReader = A::BlockingReader;
+    type Writer = A::Writer;
+    type BlockingWriter = A::BlockingWriter;
+    type Pager = A::Pager;
+    type BlockingPager = A::BlockingPager;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        self.inner.read(path, args).await
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        let args = self.with_guessed_type(path, args);
+        self.inner.write(path, args).await
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        self.inner.list(path, args).await
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.inner.blocking_read(path, args)
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        let args = self.with_guessed_type(path, args);
+        self.inner.blocking_write(path, args)
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        self.inner.blocking_list(path, args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::Memory;
+    use crate::Builder;
+
+    #[test]
+    fn test_guess() {
+        let inner = Memory::default().build().expect("must init");
+        let acc = MimeGuessLayer::default()
+            .with_mime("PARQUET", "application/vnd.apache.parquet")
+            .with_mime("json", "application/x-custom-json")
+            .layer(inner);
+
+        let cases = vec![
+            ("known extension", "dir/a.pdf", Some("application/pdf")),
+            ("upper case extension", "a.PNG", Some("image/png")),
+            (
+                "custom extension",
+                "a.parquet",
+                Some("application/vnd.apache.parquet"),
+            ),
+            (
+                "overridden extension",
+                "a.json",
+                Some("application/x-custom-json"),
+            ),
+            ("unknown extension", "a.unknown-ext", None),
+            ("no extension", "dir.d/file", None),
+            ("trailing dot", "file.", None),
+        ];
+
+        for (desc, path, expected) in cases {
+            assert_eq!(acc.guess(path).as_deref(), expected, "{desc}");
+        }
+    }
+}
@@ -354,6 +354,10 @@ impl<R: oio::Write> oio::Write for MinitraceWrapper<R> {
         let _span = LocalSpan::enter_with_local_parent(WriteOperation::Close.into_static());
         self.inner.poll_close(cx)
     }
+
+    fn metadata(&self) -> Option<Metadata> {
+        self.inner.metadata()
+    }
 }
 
 impl<R: oio::BlockingWrite> oio::BlockingWrite for MinitraceWrapper<R> {
@@ -381,6 +385,10 @@ impl<R: oio::Page> oio::Page for MinitraceWrapper<R> {
             ))
             .await
     }
+
+    fn continuation_token(&self) -> Option<String> {
+        self.inner.continuation_token()
+    }
 }
 
 impl<R: oio::BlockingPage> oio::BlockingPage for MinitraceWrapper<R> {
@@ -389,4 +397,8 @@ impl<R: oio::BlockingPage> oio::BlockingPage for MinitraceWrapper<R> {
         let _span = LocalSpan::enter_with_local_parent(PageOperation::BlockingNext.into_static());
         self.inner.next()
     }
+
+    fn continuation_token(&self) -> Option<String> {
+        self.inner.continuation_token()
+    }
 }
@@ -29,18 +29,47 @@ pub(crate) use complete::CompleteLayer;
 mod concurrent_limit;
 pub use concurrent_limit::ConcurrentLimitLayer;
 
+mod concurrent_bytes_limit;
+pub use concurrent_bytes_limit::ConcurrentBytesLimitLayer;
+
+mod rate_limit;
+pub use rate_limit::RateLimitLayer;
+
+mod read_ahead;
+pub use read_ahead::ReadAheadLayer;
+
+mod stat_cache;
+pub use stat_cache::StatCacheLayer;
+
 mod immutable_index;
 pub use immutable_index::ImmutableIndexLayer;
 
 mod logging;
 pub use logging::LoggingLayer;
 
+mod counting;
+pub use counting::CountingHandle;
+pub use counting::CountingLayer;
+
 mod timeout;
 pub use timeout::TimeoutLayer;
 
 mod blocking;
 pub use blocking::BlockingLayer;
 
+mod versioning;
+pub use versioning::VersioningLayer;
+
+mod record;
+pub use record::RecordLayer;
+pub use record::ReplayLayer;
+
+mod normalize_root;
+pub use normalize_root::NormalizeRootLayer;
+
+mod dedupe;
+pub use dedupe::DedupeLayer;
+
 #[cfg(feature = "layers-chaos")]
 mod chaos;
 #[cfg(feature = "layers-chaos")]
@@ -61,6 +90,9 @@ mod prometheus_client;
 #[cfg(feature = "layers-prometheus-client")]
 pub use self::prometheus_client::PrometheusClientLayer;
 
+mod failover;
+pub use failover::FailoverLayer;
+
 mod retry;
 pub use self::retry::RetryInterceptor;
 pub use self::retry::RetryLayer;
@@ -82,6 +114,11 @@ pub use self::madsim::MadsimLayer;
 #[cfg(feature = "layers-madsim")]
 pub use self::madsim::MadsimServer;
 
+#[cfg(feature = "layers-otel")]
+mod otel;
+#[cfg(feature = "layers-otel")]
+pub use self::otel::OtelLayer;
+
 #[cfg(feature = "layers-otel-trace")]
 mod oteltrace;
 #[cfg(feature = "layers-otel-trace")]
@@ -101,3 +138,23 @@ pub use self::await_tree::AwaitTreeLayer;
 mod async_backtrace;
 #[cfg(feature = "layers-async-backtrace")]
 pub use self::async_backtrace::AsyncBacktraceLayer;
+
+#[cfg(feature = "layers-disk-cache")]
+mod disk_cache;
+#[cfg(feature = "layers-disk-cache")]
+pub use self::disk_cache::DiskCacheLayer;
+
+#[cfg(feature = "layers-mime-guess")]
+mod mime_guess;
+#[cfg(feature = "layers-mime-guess")]
+pub use self::mime_guess::MimeGuessLayer;
+
+#[cfg(feature = "layers-compression")]
+mod compression;
+#[cfg(feature = "layers-compression")]
+pub use self::compression::CompressionLayer;
+
+#[cfg(feature = "layers-write-back")]
+mod write_back;
+#[cfg(feature = "layers-write-back")]
+pub use self::write_back::WriteBackLayer;
@@ -0,0 +1,256 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use async_trait::async_trait;
+
+use crate::raw::*;
+use crate::*;
+
+/// Normalize all operation paths to a single convention before they reach
+/// the underlying service.
+///
+/// Roots of services have already been normalized to `/path/to/root/` via
+/// [`normalize_root`], so that paths are always relative to root. This
+/// layer guarantees that paths passed to services are:
+///
+/// - Without leading `/`: `/abc` => `abc`
+/// - With single internal `/`: `//a//b` => `a/b`
+/// - Without `.` segments: `a/./b` => `a/b`
+/// - With `..` segments resolved: `a/../b` => `b`
+/// - Still ended with `/` for dirs: `a/b/..` => `a/`
+/// - `/` for root: `a/..` => `/`
+///
+/// Paths that escape the root like `../a` will be rejected with
+/// [`ErrorKind::InvalidInput`].
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::NormalizeRootLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// let _ = Operator::new(services::Memory::default())
+///     .expect("must init")
+///     .layer(NormalizeRootLayer)
+///     .finish();
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NormalizeRootLayer;
+
+impl<A: Accessor> Layer<A> for NormalizeRootLayer {
+    type LayeredAccessor = NormalizeRootAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        NormalizeRootAccessor { inner }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NormalizeRootAccessor<A: Accessor> {
+    inner: A,
+}
+
+#[async_trait]
+impl<A: Accessor> LayeredAccessor for NormalizeRootAccessor<A> {
+    type Inner = A;
+    type Reader = A::Reader;
+    type BlockingReader = A::BlockingReader;
+    type Writer = A::Writer;
+    type BlockingWriter = A::BlockingWriter;
+    type Pager = A::Pager;
+    type BlockingPager = A::BlockingPager;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        let path = canonicalize_path(path)?;
+        self.inner.create_dir(&path, args).await
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        let path = canonicalize_path(path)?;
+        self.inner.read(&path, args).await
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        let path = canonicalize_path(path)?;
+        self.inner.write(&path, args).await
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        let (from, to) = (canonicalize_path(from)?, canonicalize_path(to)?);
+        self.inner.copy(&from, &to, args).await
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        let (from, to) = (canonicalize_path(from)?, canonicalize_path(to)?);
+        self.inner.rename(&from, &to, args).await
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        let path = canonicalize_path(path)?;
+        self.inner.stat(&path, args).await
+    }
+
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        let path = canonicalize_path(path)?;
+        self.inner.delete(&path, args).await
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        let path = canonicalize_path(path)?;
+        self.inner.list(&path, args).await
+    }
+
+    async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
+        let ops = args
+            .into_operation()
+            .into_iter()
+            .map(|(path, op)| Ok((canonicalize_path(&path)?, op)))
+            .collect::<Result<Vec<_>>>()?;
+        self.inner.batch(OpBatch::new(ops)).await
+    }
+
+    async fn presign(&self, path: &str, args: OpPresign) -> Result<RpPresign> {
+        let path = canonicalize_path(path)?;
+        self.inner.presign(&path, args).await
+    }
+
+    fn blocking_create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        let path = canonicalize_path(path)?;
+        self.inner.blocking_create_dir(&path, args)
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        let path = canonicalize_path(path)?;
+        self.inner.blocking_read(&path, args)
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        let path = canonicalize_path(path)?;
+        self.inner.blocking_write(&path, args)
+    }
+
+    fn blocking_copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        let (from, to) = (canonicalize_path(from)?, canonicalize_path(to)?);
+        self.inner.blocking_copy(&from, &to, args)
+    }
+
+    fn blocking_rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        let (from, to) = (canonicalize_path(from)?, canonicalize_path(to)?);
+        self.inner.blocking_rename(&from, &to, args)
+    }
+
+    fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        let path = canonicalize_path(path)?;
+        self.inner.blocking_stat(&path, args)
+    }
+
+    fn blocking_delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        let path = canonicalize_path(path)?;
+        self.inner.blocking_delete(&path, args)
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        let path = canonicalize_path(path)?;
+        self.inner.blocking_list(&path, args)
+    }
+}
+
+/// Canonicalize path by the rules of [`NormalizeRootLayer`].
+fn canonicalize_path(path: &str) -> Result<String> {
+    let path = path.trim();
+
+    let mut segments: Vec<&str> = Vec::new();
+    // Whether the last meaningful segment refers to a dir.
+    let mut is_dir = false;
+    for seg in path.split('/') {
+        match seg {
+            "" => {}
+            "." => is_dir = true,
+            ".." => {
+                if segments.pop().is_none() {
+                    return Err(Error::new(
+                        ErrorKind::InvalidInput,
+                        "path escapes the root of operator",
+                    )
+                    .with_context("path", path));
+                }
+                is_dir = true;
+            }
+            _ => {
+                segments.push(seg);
+                is_dir = false;
+            }
+        }
+    }
+    if path.ends_with('/') {
+        is_dir = true;
+    }
+
+    if segments.is_empty() {
+        return Ok("/".to_string());
+    }
+
+    let mut p = segments.join("/");
+    if is_dir {
+        p.push('/');
+    }
+    Ok(p)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonicalize_path() {
+        let cases = vec![
+            ("empty path", "", "/"),
+            ("root", "/", "/"),
+            ("leading slash", "/a", "a"),
+            ("internal slashes", "//a//b", "a/b"),
+            ("dir with slashes", "//a//b//", "a/b/"),
+            ("current dir", "a/./b", "a/b"),
+            ("trailing current dir", "a/.", "a/"),
+            ("parent dir", "a/../b", "b"),
+            ("trailing parent dir", "a/b/..", "a/"),
+            ("back to root", "a/..", "/"),
+            ("whitespace", " a/b ", "a/b"),
+        ];
+
+        for (desc, input, expected) in cases {
+            assert_eq!(
+                canonicalize_path(input).expect("canonicalize must succeed"),
+                expected,
+                "{desc}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_canonicalize_path_escape_root() {
+        for input in ["..", "../a", "a/../../b", "/./.."] {
+            let err = canonicalize_path(input).expect_err("escape must fail");
+            assert_eq!(err.kind(), ErrorKind::InvalidInput, "{input}");
+        }
+    }
+}
@@ -0,0 +1,565 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::io;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use opentelemetry::global;
+use opentelemetry::metrics::Counter;
+use opentelemetry::metrics::Histogram;
+use opentelemetry::metrics::Unit;
+use opentelemetry::trace::FutureExt as TraceFutureExt;
+use opentelemetry::trace::Span;
+use opentelemetry::trace::Status;
+use opentelemetry::trace::TraceContextExt;
+use opentelemetry::trace::Tracer;
+use opentelemetry::Context as TraceContext;
+use opentelemetry::KeyValue;
+
+use crate::raw::*;
+use crate::*;
+
+/// Add [opentelemetry](https://docs.rs/opentelemetry) traces and metrics for every operations.
+///
+/// This layer combines what `OtelTraceLayer` does for
+/// spans with a set of metrics, so that otel users only need one layer.
+///
+/// # Traces
+///
+/// Every operation starts a span named after the operation with `scheme`,
+/// `operation`, `path` and `status` attributes. Spans of `read`, `write`
+/// and `list` are kept open until the returned reader, writer or pager
+/// is dropped.
+///
+/// While the span is active, outgoing http requests sent by services will
+/// carry the span context as a [W3C `traceparent`](https://www.w3.org/TR/trace-context/)
+/// header, so that requests can be correlated with server side diagnostics
+/// like Azure Storage logs.
+///
+/// # Metrics
+///
+/// | Metric Name                           | Type      | Description                          | Attributes                  |
+/// |---------------------------------------|-----------|--------------------------------------|-----------------------------|
+/// | opendal.requests.duration             | Histogram | Time spent on operation in seconds   | scheme, operation           |
+/// | opendal.requests.errors               | Counter   | Total errors returned by operation   | scheme, operation, status   |
+/// | opendal.bytes                         | Counter   | Total bytes read or written          | scheme, operation           |
+///
+/// Metrics are recorded via the global meter provider, users should install
+/// an exporter like `opentelemetry-prometheus` or `opentelemetry-otlp` to
+/// collect them.
+///
+/// # Examples
+///
+/// ```
+/// use opendal::layers::OtelLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// let _ = Operator::new(services::Memory::default())
+///     .expect("must init")
+///     .layer(OtelLayer)
+///     .finish();
+/// ```
+pub struct OtelLayer;
+
+impl<A: Accessor> Layer<A> for OtelLayer {
+    type LayeredAccessor = OtelAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        let meter = global::meter("opendal");
+        let metrics = Arc::new(OtelMetrics {
+            requests_duration: meter
+                .f64_histogram("opendal.requests.duration")
+                .with_description("Time spent on operation")
+                .with_unit(Unit::new("s"))
+                .init(),
+            requests_errors: meter
+                .u64_counter("opendal.requests.errors")
+                .with_description("Total errors returned by operation")
+                .init(),
+            bytes: meter
+                .u64_counter("opendal.bytes")
+                .with_description("Total bytes read or written")
+                .with_unit(Unit::new("By"))
+                .init(),
+        });
+
+        OtelAccessor {
+            scheme: inner.info().scheme(),
+            metrics,
+            inner,
+        }
+    }
+}
+
+struct OtelMetrics {
+    requests_duration: Histogram<f64>,
+    requests_errors: Counter<u64>,
+    bytes: Counter<u64>,
+}
+
+#[derive(Clone)]
+struct OtelHandle {
+    scheme: Scheme,
+    metrics: Arc<OtelMetrics>,
+}
+
+impl OtelHandle {
+    fn attributes(&self, op: Operation) -> [KeyValue; 2] {
+        [
+            KeyValue::new("scheme", self.scheme.into_static()),
+            KeyValue::new("operation", op.into_static()),
+        ]
+    }
+
+    /// Start a new span for this operation and return the context carrying it.
+    fn start(&self, op: Operation, path: &str) -> TraceContext {
+        let tracer = global::tracer("opendal");
+        let mut span = tracer.start(op.into_static());
+        span.set_attribute(KeyValue::new("scheme", self.scheme.into_static()));
+        span.set_attribute(KeyValue::new("operation", op.into_static()));
+        span.set_attribute(KeyValue::new("path", path.to_string()));
+        TraceContext::current_with_span(span)
+    }
+
+    /// Record the result of operation in both span and metrics.
+    fn finish<T>(&self, cx: &TraceContext, op: Operation, start: Instant, result: &Result<T>) {
+        let attributes = self.attributes(op);
+        self.metrics
+            .requests_duration
+            .record(start.elapsed().as_secs_f64(), &attributes);
+
+        let span = cx.span();
+        match result {
+            Ok(_) => {
+                span.set_attribute(KeyValue::new("status", "ok"));
+                span.set_status(Status::Ok);
+            }
+            Err(err) => {
+                let status = err.kind().into_static();
+                span.set_attribute(KeyValue::new("status", status));
+                span.set_status(Status::error(err.to_string()));
+
+                self.metrics.requests_errors.add(
+                    1,
+                    &[
+                        attributes[0].clone(),
+                        attributes[1].clone(),
+                        KeyValue::new("status", status),
+                    ],
+                );
+            }
+        }
+    }
+
+    fn record_bytes(&self, op: Operation, n: usize) {
+        self.metrics.bytes.add(n as u64, &self.attributes(op));
+    }
+}
+
+pub struct OtelAccessor<A> {
+    scheme: Scheme,
+    metrics: Arc<OtelMetrics>,
+    inner: A,
+}
+
+impl<A> OtelAccessor<A> {
+    fn handle(&self) -> OtelHandle {
+        OtelHandle {
+            scheme: self.scheme,
+            metrics: self.metrics.clone(),
+        }
+    }
+}
+
+impl<A: Accessor> Debug for OtelAccessor<A> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OtelAccessor")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl<A: Accessor> LayeredAccessor for OtelAccessor<A> {
+    type Inner = A;
+    type Reader = OtelWrapper<A::Reader>;
+    type BlockingReader = OtelWrapper<A::BlockingReader>;
+    type Writer = OtelWrapper<A::Writer>;
+    type BlockingWriter = OtelWrapper<A::BlockingWriter>;
+    type Pager = OtelWrapper<A::Pager>;
+    type BlockingPager = OtelWrapper<A::BlockingPager>;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        let handle = self.handle();
+        let cx = handle.start(Operation::CreateDir, path);
+        let start = Instant::now();
+        let result = self
+            .inner
+            .create_dir(path, args)
+            .with_context(cx.clone())
+            .await;
+        handle.finish(&cx, Operation::CreateDir, start, &result);
+        result
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        let handle = self.handle();
+        let cx = handle.start(Operation::Read, path);
+        let start = Instant::now();
+        let result = self.inner.read(path, args).with_context(cx.clone()).await;
+        handle.finish(&cx, Operation::Read, start, &result);
+        result.map(|(rp, r)| (rp, OtelWrapper::new(cx, handle, Operation::Read, r)))
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        let handle = self.handle();
+        let cx = handle.start(Operation::Write, path);
+        let start = Instant::now();
+        let result = self.inner.write(path, args).with_context(cx.clone()).await;
+        handle.finish(&cx, Operation::Write, start, &result);
+        result.map(|(rp, r)| (rp, OtelWrapper::new(cx, handle, Operation::Write, r)))
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        let handle = self.handle();
+        let cx = handle.start(Operation::Copy, from);
+        cx.span().set_attribute(KeyValue::new("to", to.to_string()));
+        let start = Instant::now();
+        let result = self
+            .inner
+            .copy(from, to, args)
+            .with_context(cx.clone())
+            .await;
+        handle.finish(&cx, Operation::Copy, start, &result);
+        result
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        let handle = self.handle();
+        let cx = handle.start(Operation::Rename, from);
+        cx.span().set_attribute(KeyValue::new("to", to.to_string()));
+        let start = Instant::now();
+        let result = self
+            .inner
+            .rename(from, to, args)
+            .with_context(cx.clone())
+            .await;
+        handle.finish(&cx, Operation::Rename, start, &result);
+        result
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        let handle = self.handle();
+        let cx = handle.start(Operation::Stat, path);
+        let start = Instant::now();
+        let result = self.inner.stat(path, args).with_context(cx.clone()).await;
+        handle.finish(&cx, Operation::Stat, start, &result);
+        result
+    }
+
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        let handle = self.handle();
+        let cx = handle.start(Operation::Delete, path);
+        let start = Instant::now();
+        let result = self.inner.delete(path, args).with_context(cx.clone()).await;
+        handle.finish(&cx, Operation::Delete, start, &result);
+        result
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        let handle = self.handle();
+        let cx = handle.start(Operation::List, path);
+        let start = Instant::now();
+        let result = self.inner.list(path, args).with_context(cx.clone()).await;
+        handle.finish(&cx, Operation::List, start, &result);
+        result.map(|(rp, p)| (rp, OtelWrapper::new(cx, handle, Operation::List, p)))
+    }
+
+    async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
+        let handle = self.handle();
+        let cx = handle.start(Operation::Batch, "");
+        let start = Instant::now();
+        let result = self.inner.batch(args).with_context(cx.clone()).await;
+        handle.finish(&cx, Operation::Batch, start, &result);
+        result
+    }
+
+    async fn presign(&self, path: &str, args: OpPresign) -> Result<RpPresign> {
+        let handle = self.handle();
+        let cx = handle.start(Operation::Presign, path);
+        let start = Instant::now();
+        let result = self
+            .inner
+            .presign(path, args)
+            .with_context(cx.clone())
+            .await;
+        handle.finish(&cx, Operation::Presign, start, &result);
+        result
+    }
+
+    fn blocking_create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        let handle = self.handle();
+        let cx = handle.start(Operation::BlockingCreateDir, path);
+        let start = Instant::now();
+        let result = {
+            let _guard = cx.clone().attach();
+            self.inner.blocking_create_dir(path, args)
+        };
+        handle.finish(&cx, Operation::BlockingCreateDir, start, &result);
+        result
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        let handle = self.handle();
+        let cx = handle.start(Operation::BlockingRead, path);
+        let start = Instant::now();
+        let result = {
+            let _guard = cx.clone().attach();
+            self.inner.blocking_read(path, args)
+        };
+        handle.finish(&cx, Operation::BlockingRead, start, &result);
+        result.map(|(rp, r)| (rp, OtelWrapper::new(cx, handle, Operation::BlockingRead, r)))
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        let handle = self.handle();
+        let cx = handle.start(Operation::BlockingWrite, path);
+        let start = Instant::now();
+        let result = {
+            let _guard = cx.clone().attach();
+            self.inner.blocking_write(path, args)
+        };
+        handle.finish(&cx, Operation::BlockingWrite, start, &result);
+        result.map(|(rp, r)| {
+            (
+                rp,
+                OtelWrapper::new(cx, handle, Operation::BlockingWrite, r),
+            )
+        })
+    }
+
+    fn blocking_copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        let handle = self.handle();
+        let cx = handle.start(Operation::BlockingCopy, from);
+        cx.span().set_attribute(KeyValue::new("to", to.to_string()));
+        let start = Instant::now();
+        let result = {
+            let _guard = cx.clone().attach();
+            self.inner.blocking_copy(from, to, args)
+        };
+        handle.finish(&cx, Operation::BlockingCopy, start, &result);
+        result
+    }
+
+    fn blocking_rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        let handle = self.handle();
+        let cx = handle.start(Operation::BlockingRename, from);
+        cx.span().set_attribute(KeyValue::new("to", to.to_string()));
+        let start = Instant::now();
+        let result = {
+            let _guard = cx.clone().attach();
+            self.inner.blocking_rename(from, to, args)
+        };
+        handle.finish(&cx, Operation::BlockingRename, start, &result);
+        result
+    }
+
+    fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        let handle = self.handle();
+        let cx = handle.start(Operation::BlockingStat, path);
+        let start = Instant::now();
+        let result = {
+            let _guard = cx.clone().attach();
+            self.inner.blocking_stat(path, args)
+        };
+        handle.finish(&cx, Operation::BlockingStat, start, &result);
+        result
+    }
+
+    fn blocking_delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        let handle = self.handle();
+        let cx = handle.start(Operation::BlockingDelete, path);
+        let start = Instant::now();
+        let result = {
+            let _guard = cx.clone().attach();
+            self.inner.blocking_delete(path, args)
+        };
+        handle.finish(&cx, Operation::BlockingDelete, start, &result);
+        result
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        let handle = self.handle();
+        let cx = handle.start(Operation::BlockingList, path);
+        let start = Instant::now();
+        let result = {
+            let _guard = cx.clone().attach();
+            self.inner.blocking_list(path, args)
+        };
+        handle.finish(&cx, Operation::BlockingList, start, &result);
+        result.map(|(rp, p)| (rp, OtelWrapper::new(cx, handle, Operation::BlockingList, p)))
+    }
+}
+
+/// OtelWrapper keeps the span of operation alive and attaches it while
+/// polling inner, so that requests sent lazily by readers, writers and
+/// pagers still carry the trace context.
+pub struct OtelWrapper<R> {
+    cx: TraceContext,
+    handle: OtelHandle,
+    op: Operation,
+    inner: R,
+}
+
+impl<R> OtelWrapper<R> {
+    fn new(cx: TraceContext, handle: OtelHandle, op: Operation, inner: R) -> Self {
+        Self {
+            cx,
+            handle,
+            op,
+            inner,
+        }
+    }
+
+    fn record_bytes<T>(&self, result: &Result<T>, size: impl FnOnce(&T) -> usize) {
+        if let Ok(v) = result {
+            self.handle.record_bytes(self.op, size(v));
+        }
+    }
+}
+
+impl<R: oio::Read> oio::Read for OtelWrapper<R> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        let _guard = self.cx.clone().attach();
+        let res = self.inner.poll_read(cx, buf);
+        if let Poll::Ready(result) = &res {
+            self.record_bytes(result, |n| *n);
+        }
+        res
+    }
+
+    fn poll_seek(&mut self, cx: &mut Context<'_>, pos: io::SeekFrom) -> Poll<Result<u64>> {
+        let _guard = self.cx.clone().attach();
+        self.inner.poll_seek(cx, pos)
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        let _guard = self.cx.clone().attach();
+        let res = self.inner.poll_next(cx);
+        if let Poll::Ready(Some(result)) = &res {
+            self.record_bytes(result, |bs| bs.len());
+        }
+        res
+    }
+}
+
+impl<R: oio::BlockingRead> oio::BlockingRead for OtelWrapper<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let _guard = self.cx.clone().attach();
+        let result = self.inner.read(buf);
+        self.record_bytes(&result, |n| *n);
+        result
+    }
+
+    fn seek(&mut self, pos: io::SeekFrom) -> Result<u64> {
+        let _guard = self.cx.clone().attach();
+        self.inner.seek(pos)
+    }
+
+    fn next(&mut self) -> Option<Result<Bytes>> {
+        let _guard = self.cx.clone().attach();
+        let result = self.inner.next();
+        if let Some(result) = &result {
+            self.record_bytes(result, |bs| bs.len());
+        }
+        result
+    }
+}
+
+#[async_trait]
+impl<R: oio::Write> oio::Write for OtelWrapper<R> {
+    fn poll_write(&mut self, cx: &mut Context<'_>, bs: &dyn oio::WriteBuf) -> Poll<Result<usize>> {
+        let _guard = self.cx.clone().attach();
+        let res = self.inner.poll_write(cx, bs);
+        if let Poll::Ready(result) = &res {
+            self.record_bytes(result, |n| *n);
+        }
+        res
+    }
+
+    fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let _guard = self.cx.clone().attach();
+        self.inner.poll_abort(cx)
+    }
+
+    fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let _guard = self.cx.clone().attach();
+        self.inner.poll_close(cx)
+    }
+
+    fn metadata(&self) -> Option<Metadata> {
+        self.inner.metadata()
+    }
+}
+
+impl<R: oio::BlockingWrite> oio::BlockingWrite for OtelWrapper<R> {
+    fn write(&mut self, bs: &dyn oio::WriteBuf) -> Result<usize> {
+        let _guard = self.cx.clone().attach();
+        let result = self.inner.write(bs);
+        self.record_bytes(&result, |n| *n);
+        result
+    }
+
+    fn close(&mut self) -> Result<()> {
+        let _guard = self.cx.clone().attach();
+        self.inner.close()
+    }
+}
+
+#[async_trait]
+impl<R: oio::Page> oio::Page for OtelWrapper<R> {
+    async fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        self.inner.next().with_context(self.cx.clone()).await
+    }
+
+    fn continuation_token(&self) -> Option<String> {
+        self.inner.continuation_token()
+    }
+}
+
+impl<R: oio::BlockingPage> oio::BlockingPage for OtelWrapper<R> {
+    fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        let _guard = self.cx.clone().attach();
+        self.inner.next()
+    }
+
+    fn continuation_token(&self) -> Option<String> {
+        self.inner.continuation_token()
+    }
+}
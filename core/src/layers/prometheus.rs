@@ -840,6 +840,10 @@ impl<R: oio::Write> oio::Write for PrometheusMetricWrapper<R> {
             err
         })
     }
+
+    fn metadata(&self) -> Option<Metadata> {
+        self.inner.metadata()
+    }
 }
 
 impl<R: oio::BlockingWrite> oio::BlockingWrite for PrometheusMetricWrapper<R> {
@@ -0,0 +1,375 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use async_trait::async_trait;
+
+use crate::raw::*;
+use crate::*;
+
+/// Add request rate limit based on token bucket.
+///
+/// # Notes
+///
+/// Unlike [`ConcurrentLimitLayer`](crate::layers::ConcurrentLimitLayer)
+/// which bounds how many operations could run at the same time, this layer
+/// bounds how many operations could be started per second.
+///
+/// Operations are divided into three kinds, and each kind has its own bucket
+/// so that a burst of one kind will not starve the others:
+///
+/// - read: `read`, `stat` and `presign`
+/// - write: `write`, `create_dir`, `delete`, `copy`, `rename` and `batch`
+/// - list: every page fetched by `list`
+///
+/// Operations will wait for a token before proceeding. Tokens are refilled
+/// by a monotonic clock, so the limit is not affected by wall clock jumps.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::RateLimitLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// let _ = Operator::new(services::Memory::default())
+///     .expect("must init")
+///     // Allow 100 operations per second with burst of 10 for each kind,
+///     // but only 10 pages per second for list.
+///     .layer(RateLimitLayer::new(100, 10).with_list(10, 10))
+///     .finish();
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitLayer {
+    read: Quota,
+    write: Quota,
+    list: Quota,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Quota {
+    ops_per_second: u32,
+    burst: u32,
+}
+
+impl Quota {
+    fn new(ops_per_second: u32, burst: u32) -> Self {
+        assert!(ops_per_second > 0, "ops_per_second must be greater than 0");
+        assert!(burst > 0, "burst must be greater than 0");
+
+        Self {
+            ops_per_second,
+            burst,
+        }
+    }
+}
+
+impl RateLimitLayer {
+    /// Create a new RateLimitLayer with the same quota for read, write and
+    /// list.
+    ///
+    /// `ops_per_second` is the rate that tokens will be refilled, and `burst`
+    /// is the max tokens that could be consumed at once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ops_per_second` or `burst` is 0.
+    pub fn new(ops_per_second: u32, burst: u32) -> Self {
+        let quota = Quota::new(ops_per_second, burst);
+
+        Self {
+            read: quota,
+            write: quota,
+            list: quota,
+        }
+    }
+
+    /// Set the quota for read operations.
+    pub fn with_read(mut self, ops_per_second: u32, burst: u32) -> Self {
+        self.read = Quota::new(ops_per_second, burst);
+        self
+    }
+
+    /// Set the quota for write operations.
+    pub fn with_write(mut self, ops_per_second: u32, burst: u32) -> Self {
+        self.write = Quota::new(ops_per_second, burst);
+        self
+    }
+
+    /// Set the quota for list operations.
+    pub fn with_list(mut self, ops_per_second: u32, burst: u32) -> Self {
+        self.list = Quota::new(ops_per_second, burst);
+        self
+    }
+}
+
+impl<A: Accessor> Layer<A> for RateLimitLayer {
+    type LayeredAccessor = RateLimitAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        RateLimitAccessor {
+            inner,
+            read: Arc::new(TokenBucket::new(self.read)),
+            write: Arc::new(TokenBucket::new(self.write)),
+            list: Arc::new(TokenBucket::new(self.list)),
+        }
+    }
+}
+
+/// TokenBucket refills `rate` tokens per second up to `burst`.
+///
+/// Tokens could go negative, which means they have been reserved by the
+/// waiting operations. Later operations need to wait longer, so that they
+/// will be served in order.
+#[derive(Debug)]
+struct TokenBucket {
+    rate: f64,
+    burst: f64,
+    state: Mutex<(f64, Instant)>,
+}
+
+impl TokenBucket {
+    fn new(quota: Quota) -> Self {
+        Self {
+            rate: quota.ops_per_second as f64,
+            burst: quota.burst as f64,
+            state: Mutex::new((quota.burst as f64, Instant::now())),
+        }
+    }
+
+    /// Reserve a token at `now`, returns the duration to wait before the
+    /// token could be used.
+    fn reserve(&self, now: Instant) -> Option<Duration> {
+        let mut state = self.state.lock().expect("lock must be valid");
+        let (tokens, last) = &mut *state;
+
+        // `Instant` is monotonic, but `now` could still be earlier than
+        // `last` while racing with other threads.
+        let elapsed = now.saturating_duration_since(*last);
+        *tokens = (*tokens + elapsed.as_secs_f64() * self.rate).min(self.burst);
+        *last = (*last).max(now);
+
+        *tokens -= 1.0;
+        if *tokens >= 0.0 {
+            None
+        } else {
+            Some(Duration::from_secs_f64(-*tokens / self.rate))
+        }
+    }
+
+    async fn acquire(&self) {
+        if let Some(dur) = self.reserve(Instant::now()) {
+            tokio::time::sleep(dur).await;
+        }
+    }
+
+    fn blocking_acquire(&self) {
+        if let Some(dur) = self.reserve(Instant::now()) {
+            std::thread::sleep(dur);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RateLimitAccessor<A: Accessor> {
+    inner: A,
+    read: Arc<TokenBucket>,
+    write: Arc<TokenBucket>,
+    list: Arc<TokenBucket>,
+}
+
+#[async_trait]
+impl<A: Accessor> LayeredAccessor for RateLimitAccessor<A> {
+    type Inner = A;
+    type Reader = A::Reader;
+    type BlockingReader = A::BlockingReader;
+    type Writer = A::Writer;
+    type BlockingWriter = A::BlockingWriter;
+    type Pager = RateLimitWrapper<A::Pager>;
+    type BlockingPager = RateLimitWrapper<A::BlockingPager>;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        self.write.acquire().await;
+        self.inner.create_dir(path, args).await
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        self.read.acquire().await;
+        self.inner.read(path, args).await
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        self.write.acquire().await;
+        self.inner.write(path, args).await
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        self.write.acquire().await;
+        self.inner.copy(from, to, args).await
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        self.write.acquire().await;
+        self.inner.rename(from, to, args).await
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.read.acquire().await;
+        self.inner.stat(path, args).await
+    }
+
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        self.write.acquire().await;
+        self.inner.delete(path, args).await
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        self.inner
+            .list(path, args)
+            .await
+            .map(|(rp, p)| (rp, RateLimitWrapper::new(p, self.list.clone())))
+    }
+
+    async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
+        self.write.acquire().await;
+        self.inner.batch(args).await
+    }
+
+    async fn presign(&self, path: &str, args: OpPresign) -> Result<RpPresign> {
+        self.read.acquire().await;
+        self.inner.presign(path, args).await
+    }
+
+    fn blocking_create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        self.write.blocking_acquire();
+        self.inner.blocking_create_dir(path, args)
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.read.blocking_acquire();
+        self.inner.blocking_read(path, args)
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        self.write.blocking_acquire();
+        self.inner.blocking_write(path, args)
+    }
+
+    fn blocking_copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        self.write.blocking_acquire();
+        self.inner.blocking_copy(from, to, args)
+    }
+
+    fn blocking_rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        self.write.blocking_acquire();
+        self.inner.blocking_rename(from, to, args)
+    }
+
+    fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.read.blocking_acquire();
+        self.inner.blocking_stat(path, args)
+    }
+
+    fn blocking_delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        self.write.blocking_acquire();
+        self.inner.blocking_delete(path, args)
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        self.inner
+            .blocking_list(path, args)
+            .map(|(rp, p)| (rp, RateLimitWrapper::new(p, self.list.clone())))
+    }
+}
+
+pub struct RateLimitWrapper<P> {
+    inner: P,
+    bucket: Arc<TokenBucket>,
+}
+
+impl<P> RateLimitWrapper<P> {
+    fn new(inner: P, bucket: Arc<TokenBucket>) -> Self {
+        Self { inner, bucket }
+    }
+}
+
+#[async_trait]
+impl<P: oio::Page> oio::Page for RateLimitWrapper<P> {
+    async fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        self.bucket.acquire().await;
+        self.inner.next().await
+    }
+
+    fn continuation_token(&self) -> Option<String> {
+        self.inner.continuation_token()
+    }
+}
+
+impl<P: oio::BlockingPage> oio::BlockingPage for RateLimitWrapper<P> {
+    fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        self.bucket.blocking_acquire();
+        self.inner.next()
+    }
+
+    fn continuation_token(&self) -> Option<String> {
+        self.inner.continuation_token()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket() {
+        let bucket = TokenBucket::new(Quota::new(10, 2));
+        let now = Instant::now();
+
+        // Burst tokens could be used at once.
+        assert_eq!(bucket.reserve(now), None);
+        assert_eq!(bucket.reserve(now), None);
+
+        // Later operations should wait in order.
+        assert_eq!(bucket.reserve(now), Some(Duration::from_millis(100)));
+        assert_eq!(bucket.reserve(now), Some(Duration::from_millis(200)));
+
+        // Reserved tokens should be paid back before refilling.
+        let now = now + Duration::from_millis(200);
+        assert_eq!(bucket.reserve(now), Some(Duration::from_millis(100)));
+
+        // Tokens should never exceed burst.
+        let now = now + Duration::from_secs(10);
+        assert_eq!(bucket.reserve(now), None);
+        assert_eq!(bucket.reserve(now), None);
+        assert_eq!(bucket.reserve(now), Some(Duration::from_millis(100)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_ops_per_second() {
+        let _ = RateLimitLayer::new(0, 1);
+    }
+}
@@ -0,0 +1,392 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::cmp::min;
+use std::io::SeekFrom;
+use std::task::ready;
+use std::task::Context;
+use std::task::Poll;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::FutureExt;
+use tokio::runtime::Handle;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::raw::oio::ReadExt;
+use crate::raw::*;
+use crate::*;
+
+/// The max size of every chunk fetched in background.
+const READ_AHEAD_CHUNK_SIZE: usize = 256 * 1024;
+
+/// Add read-ahead buffering for readers.
+///
+/// # Notes
+///
+/// Sequential reads of a large file usually issue many small reads as the
+/// consumer pulls. This layer fetches the next bytes in a background task
+/// while the consumer is processing the current buffer, so that IO could
+/// overlap with compute.
+///
+/// - At most `window` bytes will be buffered ahead (at least one chunk of up
+///   to 256 KiB), the background task will wait until the consumer catches up.
+/// - Seeking stops the background task and drops the buffered bytes, reading
+///   after seeking starts a new one from the new position.
+/// - Dropping the reader stops the background task, so it's safe to drop the
+///   reader without consuming all data.
+///
+/// The background task will be spawned in the tokio runtime that calls
+/// `read`. Readers will be returned as is if there is no tokio runtime, and
+/// blocking readers are not affected.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::ReadAheadLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// let _ = Operator::new(services::Memory::default())
+///     .expect("must init")
+///     // Prefetch up to 4 MiB ahead.
+///     .layer(ReadAheadLayer::new(4 * 1024 * 1024))
+///     .finish();
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ReadAheadLayer {
+    window: usize,
+}
+
+impl ReadAheadLayer {
+    /// Create a new ReadAheadLayer with given window in bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window` is 0.
+    pub fn new(window: usize) -> Self {
+        assert!(window > 0, "window must be greater than 0");
+
+        Self { window }
+    }
+}
+
+impl<A: Accessor> Layer<A> for ReadAheadLayer {
+    type LayeredAccessor = ReadAheadAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        ReadAheadAccessor {
+            inner,
+            window: self.window,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ReadAheadAccessor<A: Accessor> {
+    inner: A,
+    window: usize,
+}
+
+#[async_trait]
+impl<A: Accessor> LayeredAccessor for ReadAheadAccessor<A> {
+    type Inner = A;
+    type Reader = ReadAheadReader<A::Reader>;
+    type BlockingReader = A::BlockingReader;
+    type Writer = A::Writer;
+    type BlockingWriter = A::BlockingWriter;
+    type Pager = A::Pager;
+    type BlockingPager = A::BlockingPager;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        let (rp, r) = self.inner.read(path, args).await?;
+
+        Ok((rp, ReadAheadReader::new(r, self.window)))
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        self.inner.list(path, args).await
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.inner.blocking_read(path, args)
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        self.inner.blocking_list(path, args)
+    }
+}
+
+enum State<R> {
+    /// No tokio runtime found, read from inner reader directly.
+    Direct(R),
+    /// Background task is not started yet.
+    Idle(R),
+    /// Background task is fetching chunks into the channel.
+    Fetching {
+        rx: mpsc::Receiver<Result<Bytes>>,
+        task: JoinHandle<(R, u64)>,
+    },
+    /// Background task has been asked to stop, waiting for the reader back.
+    Stopping(JoinHandle<(R, u64)>),
+    /// Background task failed unexpectedly, the inner reader has been lost.
+    Failed,
+}
+
+pub struct ReadAheadReader<R> {
+    state: State<R>,
+    runtime: Option<Handle>,
+    chunk_size: usize,
+    chunks: usize,
+
+    /// The chunk that is being consumed.
+    buf: Bytes,
+    /// Bytes returned to consumer since the background task started.
+    consumed: u64,
+    /// Bytes that inner reader is ahead of the consumer, which should be
+    /// compensated while seeking from current position.
+    skew: u64,
+}
+
+impl<R: oio::Read + 'static> ReadAheadReader<R> {
+    fn new(inner: R, window: usize) -> Self {
+        let runtime = Handle::try_current().ok();
+        let chunk_size = min(window, READ_AHEAD_CHUNK_SIZE);
+
+        Self {
+            state: match runtime {
+                Some(_) => State::Idle(inner),
+                None => State::Direct(inner),
+            },
+            runtime,
+            chunk_size,
+            chunks: (window / chunk_size).max(1),
+            buf: Bytes::new(),
+            consumed: 0,
+            skew: 0,
+        }
+    }
+
+    /// Make sure there are bytes in `buf`, returns `false` if EOF reached.
+    fn poll_fill(&mut self, cx: &mut Context<'_>) -> Poll<Result<bool>> {
+        loop {
+            if !self.buf.is_empty() {
+                return Poll::Ready(Ok(true));
+            }
+
+            match &mut self.state {
+                State::Direct(_) => unreachable!("direct reader must not be buffered"),
+                State::Idle(_) => {
+                    let State::Idle(r) = std::mem::replace(&mut self.state, State::Failed) else {
+                        unreachable!()
+                    };
+                    let (tx, rx) = mpsc::channel(self.chunks);
+                    let runtime = self.runtime.as_ref().expect("runtime must be valid");
+                    let task = runtime.spawn(fetch(r, tx, self.chunk_size));
+                    self.state = State::Fetching { rx, task };
+                    self.consumed = 0;
+                }
+                State::Fetching { rx, .. } => match ready!(rx.poll_recv(cx)) {
+                    Some(Ok(bs)) => self.buf = bs,
+                    Some(Err(err)) => return Poll::Ready(Err(err)),
+                    None => return Poll::Ready(Ok(false)),
+                },
+                State::Stopping(_) | State::Failed => {
+                    return Poll::Ready(Err(Error::new(
+                        ErrorKind::Unexpected,
+                        "read ahead reader is in invalid state",
+                    )))
+                }
+            }
+        }
+    }
+
+    /// Take at most `size` bytes from `buf`.
+    fn take(&mut self, size: usize) -> Bytes {
+        let n = min(size, self.buf.len());
+        self.consumed += n as u64;
+        self.buf.split_to(n)
+    }
+}
+
+/// Fetch chunks from inner reader until EOF, error or receiver dropped.
+///
+/// Returns the inner reader and the bytes read from it.
+async fn fetch<R: oio::Read>(
+    mut r: R,
+    tx: mpsc::Sender<Result<Bytes>>,
+    chunk_size: usize,
+) -> (R, u64) {
+    let mut read = 0;
+    loop {
+        // Wait for a free slot before reading, so that we never read more
+        // than the window ahead.
+        let Ok(permit) = tx.reserve().await else {
+            return (r, read);
+        };
+
+        let mut buf = vec![0; chunk_size];
+        match r.read(&mut buf).await {
+            Ok(0) => return (r, read),
+            Ok(n) => {
+                buf.truncate(n);
+                read += n as u64;
+                permit.send(Ok(Bytes::from(buf)));
+            }
+            Err(err) => {
+                permit.send(Err(err));
+                return (r, read);
+            }
+        }
+    }
+}
+
+impl<R> Drop for ReadAheadReader<R> {
+    fn drop(&mut self) {
+        if let State::Fetching { task, .. } | State::Stopping(task) = &self.state {
+            task.abort();
+        }
+    }
+}
+
+impl<R: oio::Read + 'static> oio::Read for ReadAheadReader<R> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        if let State::Direct(r) = &mut self.state {
+            return r.poll_read(cx, buf);
+        }
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        if !ready!(self.poll_fill(cx))? {
+            return Poll::Ready(Ok(0));
+        }
+        let bs = self.take(buf.len());
+        buf[..bs.len()].copy_from_slice(&bs);
+        Poll::Ready(Ok(bs.len()))
+    }
+
+    fn poll_seek(&mut self, cx: &mut Context<'_>, pos: SeekFrom) -> Poll<Result<u64>> {
+        loop {
+            match &mut self.state {
+                State::Direct(r) => return r.poll_seek(cx, pos),
+                State::Idle(r) => {
+                    let pos = match pos {
+                        SeekFrom::Current(n) => SeekFrom::Current(n - self.skew as i64),
+                        pos => pos,
+                    };
+                    let res = ready!(r.poll_seek(cx, pos));
+                    if res.is_ok() {
+                        self.skew = 0;
+                    }
+                    return Poll::Ready(res);
+                }
+                State::Fetching { .. } => {
+                    let State::Fetching { rx, task } =
+                        std::mem::replace(&mut self.state, State::Failed)
+                    else {
+                        unreachable!()
+                    };
+                    // Dropping receiver will stop the background task.
+                    drop(rx);
+                    self.state = State::Stopping(task);
+                }
+                State::Stopping(task) => {
+                    let res = ready!(task.poll_unpin(cx));
+                    let (r, read) = match res {
+                        Ok(v) => v,
+                        Err(err) => {
+                            self.state = State::Failed;
+                            return Poll::Ready(Err(new_task_join_error(err)));
+                        }
+                    };
+                    self.skew = read - self.consumed;
+                    self.buf = Bytes::new();
+                    self.state = State::Idle(r);
+                }
+                State::Failed => {
+                    return Poll::Ready(Err(Error::new(
+                        ErrorKind::Unexpected,
+                        "read ahead reader has been failed",
+                    )))
+                }
+            }
+        }
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        if let State::Direct(r) = &mut self.state {
+            return r.poll_next(cx);
+        }
+
+        match ready!(self.poll_fill(cx)) {
+            Ok(true) => Poll::Ready(Some(Ok(self.take(self.buf.len())))),
+            Ok(false) => Poll::Ready(None),
+            Err(err) => Poll::Ready(Some(Err(err))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use futures::AsyncReadExt;
+    use futures::AsyncSeekExt;
+
+    use super::*;
+    use crate::services::Memory;
+    use crate::Operator;
+
+    #[tokio::test]
+    async fn test_read_ahead() -> Result<()> {
+        let op = Operator::new(Memory::default())?
+            .layer(ReadAheadLayer::new(16))
+            .finish();
+        let content: Vec<u8> = (0..=255).collect();
+        op.write("test", content.clone()).await?;
+
+        let mut r = op.reader("test").await?;
+        let mut buf = vec![0; 10];
+        r.read_exact(&mut buf).await?;
+        assert_eq!(buf, content[..10]);
+
+        // Wait for the background task to prefetch ahead of the consumer.
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+
+        // Seek from current must compensate the prefetched bytes.
+        r.seek(SeekFrom::Current(5)).await?;
+        r.read_exact(&mut buf).await?;
+        assert_eq!(buf, content[15..25]);
+
+        let mut rest = vec![];
+        r.read_to_end(&mut rest).await?;
+        assert_eq!(rest, content[25..]);
+
+        // Drop the reader without consuming all data.
+        let mut r = op.reader("test").await?;
+        r.read_exact(&mut buf).await?;
+        drop(r);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,1061 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+
+use async_trait::async_trait;
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use bytes::Bytes;
+use bytes::BytesMut;
+use chrono::DateTime;
+use chrono::Utc;
+use flagset::FlagSet;
+use log::warn;
+use parking_lot::Mutex;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::raw::*;
+use crate::*;
+
+/// Record every operation and its result into a [JSON Lines](https://jsonlines.org/)
+/// file for debugging.
+///
+/// Each line records the operation name, path, args, result kind and
+/// the bytes transferred. Content returned by `read`, metadata returned by
+/// `stat` and entries returned by `list` are recorded too, so that they can
+/// be replayed offline by [`ReplayLayer`].
+///
+/// # Notes
+///
+/// - Records are taken at operation level, not http level. Parsing bugs in
+///   services like `AzfilePager` will be captured as the parsed entries
+///   instead of raw response bodies.
+/// - `read` and `list` are recorded after the reader or pager is dropped,
+///   with all content and entries that have been consumed.
+/// - All content read will be kept in memory until recorded and written
+///   into the file as base64, don't use this layer with large files.
+/// - `batch` and `presign` are not recorded.
+/// - Records are written to the file synchronously.
+///
+/// # Examples
+///
+/// ```no_run
+/// use anyhow::Result;
+/// use opendal::layers::RecordLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// # fn main() -> Result<()> {
+/// let _ = Operator::new(services::Memory::default())?
+///     .layer(RecordLayer::new("/tmp/opendal-record.jsonl")?)
+///     .finish();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct RecordLayer {
+    recorder: Arc<Recorder>,
+}
+
+impl RecordLayer {
+    /// Create a new record layer which appends records into given file.
+    pub fn new(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|err| {
+                Error::new(ErrorKind::Unexpected, "open record file failed")
+                    .with_context("path", path.display().to_string())
+                    .set_source(err)
+            })?;
+
+        Ok(Self {
+            recorder: Arc::new(Recorder {
+                file: Mutex::new(file),
+            }),
+        })
+    }
+}
+
+impl<A: Accessor> Layer<A> for RecordLayer {
+    type LayeredAccessor = RecordAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        RecordAccessor {
+            inner,
+            recorder: self.recorder.clone(),
+        }
+    }
+}
+
+struct Recorder {
+    file: Mutex<File>,
+}
+
+impl Recorder {
+    fn record(&self, record: &Record) {
+        let mut bs = match serde_json::to_vec(record) {
+            Ok(bs) => bs,
+            Err(err) => {
+                warn!(
+                    "record of {} {} serialize failed: {err}",
+                    record.op, record.path
+                );
+                return;
+            }
+        };
+        bs.push(b'\n');
+
+        if let Err(err) = self.file.lock().write_all(&bs) {
+            warn!(
+                "record of {} {} write failed: {err}",
+                record.op, record.path
+            );
+        }
+    }
+}
+
+/// Record is a single line in the record file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Record {
+    op: String,
+    path: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    to: String,
+    args: String,
+    /// `ok` or the error kind.
+    result: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    message: String,
+    bytes: u64,
+    /// Base64 encoded content returned by read.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metadata: Option<RecordMetadata>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    entries: Option<Vec<RecordEntry>>,
+}
+
+impl Record {
+    fn new(op: Operation, path: &str, args: impl Debug) -> Self {
+        Self {
+            op: op.into_static().to_string(),
+            path: path.to_string(),
+            args: format!("{args:?}"),
+            result: "ok".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn with_to(mut self, to: &str) -> Self {
+        self.to = to.to_string();
+        self
+    }
+
+    fn with_result<T>(mut self, res: &Result<T>) -> Self {
+        if let Err(err) = res {
+            self.set_error(err);
+        }
+        self
+    }
+
+    fn set_error(&mut self, err: &Error) {
+        self.result = err.kind().into_static().to_string();
+        self.message = err.to_string();
+    }
+
+    /// Convert the recorded result back.
+    fn to_result(&self) -> Result<()> {
+        if self.result == "ok" {
+            return Ok(());
+        }
+
+        Err(Error::new(parse_error_kind(&self.result), "replayed error")
+            .with_context("operation", &self.op)
+            .with_context("path", &self.path)
+            .with_context("recorded", &self.message))
+    }
+}
+
+/// Parse the error kind from its static str, returns `Unexpected` if not
+/// known.
+fn parse_error_kind(s: &str) -> ErrorKind {
+    [
+        ErrorKind::Unsupported,
+        ErrorKind::ConfigInvalid,
+        ErrorKind::NotFound,
+        ErrorKind::PermissionDenied,
+        ErrorKind::IsADirectory,
+        ErrorKind::NotADirectory,
+        ErrorKind::AlreadyExists,
+        ErrorKind::RateLimited,
+        ErrorKind::IsSameFile,
+        ErrorKind::ConditionNotMatch,
+        ErrorKind::ContentTruncated,
+        ErrorKind::ContentIncomplete,
+        ErrorKind::InvalidInput,
+        ErrorKind::ChecksumMismatch,
+        ErrorKind::RangeNotSatisfied,
+        ErrorKind::Cancelled,
+    ]
+    .into_iter()
+    .find(|kind| kind.into_static() == s)
+    .unwrap_or(ErrorKind::Unexpected)
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct RecordMetadata {
+    mode: String,
+    metakey: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content_length: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    etag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_modified: Option<DateTime<Utc>>,
+}
+
+impl From<&Metadata> for RecordMetadata {
+    fn from(meta: &Metadata) -> Self {
+        Self {
+            mode: meta.mode().to_string(),
+            metakey: meta.metakey().bits(),
+            content_length: meta.content_length_raw(),
+            content_type: meta.content_type().map(|v| v.to_string()),
+            etag: meta.etag().map(|v| v.to_string()),
+            last_modified: meta.last_modified(),
+        }
+    }
+}
+
+impl RecordMetadata {
+    fn to_metadata(&self) -> Metadata {
+        let mode = match self.mode.as_str() {
+            "file" => EntryMode::FILE,
+            "dir" => EntryMode::DIR,
+            _ => EntryMode::Unknown,
+        };
+
+        let mut meta = Metadata::new(mode);
+        if let Some(v) = self.content_length {
+            meta.set_content_length(v);
+        }
+        if let Some(v) = &self.content_type {
+            meta.set_content_type(v);
+        }
+        if let Some(v) = &self.etag {
+            meta.set_etag(v);
+        }
+        if let Some(v) = self.last_modified {
+            meta.set_last_modified(v);
+        }
+        // Restore the recorded metakey so that lister won't stat again.
+        meta.with_metakey(FlagSet::<Metakey>::new_truncated(self.metakey))
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct RecordEntry {
+    path: String,
+    metadata: RecordMetadata,
+}
+
+pub struct RecordAccessor<A: Accessor> {
+    inner: A,
+    recorder: Arc<Recorder>,
+}
+
+impl<A: Accessor> Debug for RecordAccessor<A> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RecordAccessor")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<A: Accessor> RecordAccessor<A> {
+    fn record<T>(&self, record: Record, res: &Result<T>) {
+        self.recorder.record(&record.with_result(res));
+    }
+
+    fn record_stat(&self, mut record: Record, res: &Result<RpStat>) {
+        if let Ok(rp) = res {
+            record.metadata = Some(rp.metadata().into());
+        }
+        self.record(record, res)
+    }
+}
+
+#[async_trait]
+impl<A: Accessor> LayeredAccessor for RecordAccessor<A> {
+    type Inner = A;
+    type Reader = RecordWrapper<A::Reader>;
+    type BlockingReader = RecordWrapper<A::BlockingReader>;
+    type Writer = RecordWrapper<A::Writer>;
+    type BlockingWriter = RecordWrapper<A::BlockingWriter>;
+    type Pager = RecordWrapper<A::Pager>;
+    type BlockingPager = RecordWrapper<A::BlockingPager>;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        let record = Record::new(Operation::CreateDir, path, &args);
+        let res = self.inner.create_dir(path, args).await;
+        self.record(record, &res);
+        res
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        let mut record = Record::new(Operation::Read, path, &args);
+        match self.inner.read(path, args).await {
+            Ok((rp, r)) => {
+                record.metadata = Some(rp.metadata().into());
+                Ok((rp, RecordWrapper::new(self.recorder.clone(), record, r)))
+            }
+            Err(err) => {
+                record.set_error(&err);
+                self.recorder.record(&record);
+                Err(err)
+            }
+        }
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        let mut record = Record::new(Operation::Write, path, &args);
+        match self.inner.write(path, args).await {
+            Ok((rp, w)) => Ok((rp, RecordWrapper::new(self.recorder.clone(), record, w))),
+            Err(err) => {
+                record.set_error(&err);
+                self.recorder.record(&record);
+                Err(err)
+            }
+        }
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        let record = Record::new(Operation::Copy, from, &args).with_to(to);
+        let res = self.inner.copy(from, to, args).await;
+        self.record(record, &res);
+        res
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        let record = Record::new(Operation::Rename, from, &args).with_to(to);
+        let res = self.inner.rename(from, to, args).await;
+        self.record(record, &res);
+        res
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        let record = Record::new(Operation::Stat, path, &args);
+        let res = self.inner.stat(path, args).await;
+        self.record_stat(record, &res);
+        res
+    }
+
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        let record = Record::new(Operation::Delete, path, &args);
+        let res = self.inner.delete(path, args).await;
+        self.record(record, &res);
+        res
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        let mut record = Record::new(Operation::List, path, &args);
+        match self.inner.list(path, args).await {
+            Ok((rp, p)) => {
+                record.entries = Some(vec![]);
+                Ok((rp, RecordWrapper::new(self.recorder.clone(), record, p)))
+            }
+            Err(err) => {
+                record.set_error(&err);
+                self.recorder.record(&record);
+                Err(err)
+            }
+        }
+    }
+
+    fn blocking_create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        let record = Record::new(Operation::BlockingCreateDir, path, &args);
+        let res = self.inner.blocking_create_dir(path, args);
+        self.record(record, &res);
+        res
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        let mut record = Record::new(Operation::BlockingRead, path, &args);
+        match self.inner.blocking_read(path, args) {
+            Ok((rp, r)) => {
+                record.metadata = Some(rp.metadata().into());
+                Ok((rp, RecordWrapper::new(self.recorder.clone(), record, r)))
+            }
+            Err(err) => {
+                record.set_error(&err);
+                self.recorder.record(&record);
+                Err(err)
+            }
+        }
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        let mut record = Record::new(Operation::BlockingWrite, path, &args);
+        match self.inner.blocking_write(path, args) {
+            Ok((rp, w)) => Ok((rp, RecordWrapper::new(self.recorder.clone(), record, w))),
+            Err(err) => {
+                record.set_error(&err);
+                self.recorder.record(&record);
+                Err(err)
+            }
+        }
+    }
+
+    fn blocking_copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        let record = Record::new(Operation::BlockingCopy, from, &args).with_to(to);
+        let res = self.inner.blocking_copy(from, to, args);
+        self.record(record, &res);
+        res
+    }
+
+    fn blocking_rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        let record = Record::new(Operation::BlockingRename, from, &args).with_to(to);
+        let res = self.inner.blocking_rename(from, to, args);
+        self.record(record, &res);
+        res
+    }
+
+    fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        let record = Record::new(Operation::BlockingStat, path, &args);
+        let res = self.inner.blocking_stat(path, args);
+        self.record_stat(record, &res);
+        res
+    }
+
+    fn blocking_delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        let record = Record::new(Operation::BlockingDelete, path, &args);
+        let res = self.inner.blocking_delete(path, args);
+        self.record(record, &res);
+        res
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        let mut record = Record::new(Operation::BlockingList, path, &args);
+        match self.inner.blocking_list(path, args) {
+            Ok((rp, p)) => {
+                record.entries = Some(vec![]);
+                Ok((rp, RecordWrapper::new(self.recorder.clone(), record, p)))
+            }
+            Err(err) => {
+                record.set_error(&err);
+                self.recorder.record(&record);
+                Err(err)
+            }
+        }
+    }
+}
+
+/// RecordWrapper collects the content, bytes and entries passed through
+/// and write the record while dropping.
+pub struct RecordWrapper<R> {
+    inner: R,
+    recorder: Arc<Recorder>,
+    record: Record,
+    content: BytesMut,
+}
+
+impl<R> RecordWrapper<R> {
+    fn new(recorder: Arc<Recorder>, record: Record, inner: R) -> Self {
+        Self {
+            inner,
+            recorder,
+            record,
+            content: BytesMut::new(),
+        }
+    }
+
+    fn on_read(&mut self, res: std::result::Result<&[u8], &Error>) {
+        match res {
+            Ok(bs) => {
+                self.record.bytes += bs.len() as u64;
+                self.content.extend_from_slice(bs);
+            }
+            Err(err) => self.record.set_error(err),
+        }
+    }
+
+    fn on_write(&mut self, res: &Result<usize>) {
+        match res {
+            Ok(n) => self.record.bytes += *n as u64,
+            Err(err) => self.record.set_error(err),
+        }
+    }
+
+    fn on_page(&mut self, res: &Result<Option<Vec<oio::Entry>>>) {
+        match res {
+            Ok(Some(entries)) => {
+                let recorded = self.record.entries.get_or_insert_with(Vec::new);
+                recorded.extend(entries.iter().map(|e| RecordEntry {
+                    path: e.path().to_string(),
+                    metadata: e.metadata().into(),
+                }));
+            }
+            Ok(None) => {}
+            Err(err) => self.record.set_error(err),
+        }
+    }
+}
+
+impl<R> Drop for RecordWrapper<R> {
+    fn drop(&mut self) {
+        if !self.content.is_empty() {
+            self.record.content = Some(BASE64_STANDARD.encode(&self.content));
+        }
+        self.recorder.record(&self.record);
+    }
+}
+
+impl<R: oio::Read> oio::Read for RecordWrapper<R> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        let res = self.inner.poll_read(cx, buf);
+        if let Poll::Ready(res) = &res {
+            self.on_read(res.as_ref().map(|n| &buf[..*n]));
+        }
+        res
+    }
+
+    fn poll_seek(&mut self, cx: &mut Context<'_>, pos: io::SeekFrom) -> Poll<Result<u64>> {
+        self.inner.poll_seek(cx, pos)
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        let res = self.inner.poll_next(cx);
+        if let Poll::Ready(Some(res)) = &res {
+            self.on_read(res.as_ref().map(|bs| bs.as_ref()));
+        }
+        res
+    }
+}
+
+impl<R: oio::BlockingRead> oio::BlockingRead for RecordWrapper<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let res = self.inner.read(buf);
+        self.on_read(res.as_ref().map(|n| &buf[..*n]));
+        res
+    }
+
+    fn seek(&mut self, pos: io::SeekFrom) -> Result<u64> {
+        self.inner.seek(pos)
+    }
+
+    fn next(&mut self) -> Option<Result<Bytes>> {
+        let res = self.inner.next();
+        if let Some(res) = &res {
+            self.on_read(res.as_ref().map(|bs| bs.as_ref()));
+        }
+        res
+    }
+}
+
+impl<R: oio::Write> oio::Write for RecordWrapper<R> {
+    fn poll_write(&mut self, cx: &mut Context<'_>, bs: &dyn oio::WriteBuf) -> Poll<Result<usize>> {
+        let res = self.inner.poll_write(cx, bs);
+        if let Poll::Ready(res) = &res {
+            self.on_write(res);
+        }
+        res
+    }
+
+    fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.inner.poll_abort(cx)
+    }
+
+    fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let res = self.inner.poll_close(cx);
+        if let Poll::Ready(Err(err)) = &res {
+            self.record.set_error(err);
+        }
+        res
+    }
+
+    fn metadata(&self) -> Option<Metadata> {
+        self.inner.metadata()
+    }
+}
+
+impl<R: oio::BlockingWrite> oio::BlockingWrite for RecordWrapper<R> {
+    fn write(&mut self, bs: &dyn oio::WriteBuf) -> Result<usize> {
+        let res = self.inner.write(bs);
+        self.on_write(&res);
+        res
+    }
+
+    fn close(&mut self) -> Result<()> {
+        let res = self.inner.close();
+        if let Err(err) = &res {
+            self.record.set_error(err);
+        }
+        res
+    }
+}
+
+#[async_trait]
+impl<R: oio::Page> oio::Page for RecordWrapper<R> {
+    async fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        let res = self.inner.next().await;
+        self.on_page(&res);
+        res
+    }
+
+    fn continuation_token(&self) -> Option<String> {
+        self.inner.continuation_token()
+    }
+}
+
+impl<R: oio::BlockingPage> oio::BlockingPage for RecordWrapper<R> {
+    fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        let res = self.inner.next();
+        self.on_page(&res);
+        res
+    }
+
+    fn continuation_token(&self) -> Option<String> {
+        self.inner.continuation_token()
+    }
+}
+
+/// Replay the responses recorded by [`RecordLayer`] without touching the
+/// underlying service.
+///
+/// Records are matched by operation and path, and consumed in the order
+/// they are recorded. Blocking and non-blocking operations share the same
+/// records, so records taken by `read` could be replayed by `blocking_read`.
+///
+/// # Notes
+///
+/// - Args of operations are not compared, `read` with range will return
+///   the recorded content directly.
+/// - Recorded errors are returned when the operation starts, even if they
+///   happened while reading or listing.
+/// - Operations without a record will return `Unexpected`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use anyhow::Result;
+/// use opendal::layers::ReplayLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<()> {
+/// let op = Operator::new(services::Memory::default())?
+///     .layer(ReplayLayer::new("/tmp/opendal-record.jsonl")?)
+///     .finish();
+///
+/// let entries = op.list("path/to/dir/").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ReplayLayer {
+    records: Arc<Mutex<HashMap<(String, String), VecDeque<Record>>>>,
+}
+
+impl ReplayLayer {
+    /// Create a new replay layer which loads records from given file.
+    pub fn new(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let new_error = |msg: &'static str, err: io::Error| {
+            Error::new(ErrorKind::Unexpected, msg)
+                .with_context("path", path.display().to_string())
+                .set_source(err)
+        };
+
+        let file = File::open(path).map_err(|err| new_error("open record file failed", err))?;
+
+        let mut records: HashMap<_, VecDeque<_>> = HashMap::new();
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|err| new_error("read record file failed", err))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let record: Record = serde_json::from_str(&line).map_err(|err| {
+                Error::new(ErrorKind::Unexpected, "parse record failed")
+                    .with_context("path", path.display().to_string())
+                    .with_context("line", &line)
+                    .set_source(err)
+            })?;
+            records
+                .entry((replay_op(&record.op).to_string(), record.path.clone()))
+                .or_default()
+                .push_back(record);
+        }
+
+        Ok(Self {
+            records: Arc::new(Mutex::new(records)),
+        })
+    }
+}
+
+/// Blocking operations share the records with non-blocking ones.
+fn replay_op(op: &str) -> &str {
+    op.trim_start_matches("blocking_")
+}
+
+impl<A: Accessor> Layer<A> for ReplayLayer {
+    type LayeredAccessor = ReplayAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        ReplayAccessor {
+            inner,
+            records: self.records.clone(),
+        }
+    }
+}
+
+pub struct ReplayAccessor<A: Accessor> {
+    inner: A,
+    records: Arc<Mutex<HashMap<(String, String), VecDeque<Record>>>>,
+}
+
+impl<A: Accessor> Debug for ReplayAccessor<A> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReplayAccessor")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<A: Accessor> ReplayAccessor<A> {
+    /// Take the next record of given operation and path.
+    fn next_record(&self, op: Operation, path: &str) -> Result<Record> {
+        let key = (replay_op(op.into_static()).to_string(), path.to_string());
+        self.records
+            .lock()
+            .get_mut(&key)
+            .and_then(|records| records.pop_front())
+            .ok_or_else(|| {
+                Error::new(ErrorKind::Unexpected, "no record found for replay")
+                    .with_operation(op)
+                    .with_context("path", path)
+            })
+    }
+
+    fn replay(&self, op: Operation, path: &str) -> Result<()> {
+        self.next_record(op, path)?.to_result()
+    }
+
+    fn replay_read(&self, op: Operation, path: &str) -> Result<(RpRead, oio::Cursor)> {
+        let record = self.next_record(op, path)?;
+        record.to_result()?;
+
+        let content = match &record.content {
+            Some(content) => BASE64_STANDARD.decode(content).map_err(|err| {
+                Error::new(ErrorKind::Unexpected, "decode recorded content failed")
+                    .with_operation(op)
+                    .with_context("path", path)
+                    .set_source(err)
+            })?,
+            None => vec![],
+        };
+        let rp = match &record.metadata {
+            Some(meta) => RpRead::with_metadata(meta.to_metadata()),
+            None => RpRead::new(content.len() as u64),
+        };
+
+        Ok((rp, oio::Cursor::from(content)))
+    }
+
+    fn replay_write(&self, op: Operation, path: &str) -> Result<(RpWrite, ReplayWriter)> {
+        let record = self.next_record(op, path)?;
+        // Errors happened while writing or closing are returned by close.
+        let w = ReplayWriter {
+            result: Some(record.to_result()),
+        };
+
+        Ok((RpWrite::default(), w))
+    }
+
+    fn replay_stat(&self, op: Operation, path: &str) -> Result<RpStat> {
+        let record = self.next_record(op, path)?;
+        record.to_result()?;
+
+        let meta = record.metadata.unwrap_or_default().to_metadata();
+        Ok(RpStat::new(meta))
+    }
+
+    fn replay_list(&self, op: Operation, path: &str) -> Result<(RpList, ReplayPager)> {
+        let record = self.next_record(op, path)?;
+        record.to_result()?;
+
+        let entries = record
+            .entries
+            .unwrap_or_default()
+            .into_iter()
+            .map(|e| oio::Entry::with(e.path, e.metadata.to_metadata()))
+            .collect::<Vec<_>>();
+        let pager = ReplayPager {
+            entries: (!entries.is_empty()).then_some(entries),
+        };
+
+        Ok((RpList::default(), pager))
+    }
+}
+
+#[async_trait]
+impl<A: Accessor> LayeredAccessor for ReplayAccessor<A> {
+    type Inner = A;
+    type Reader = oio::Cursor;
+    type BlockingReader = oio::Cursor;
+    type Writer = ReplayWriter;
+    type BlockingWriter = ReplayWriter;
+    type Pager = ReplayPager;
+    type BlockingPager = ReplayPager;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    fn metadata(&self) -> AccessorInfo {
+        let mut meta = self.inner.info();
+
+        let cap = meta.full_capability_mut();
+        cap.stat = true;
+        cap.read = true;
+        cap.read_can_next = true;
+        cap.write = true;
+        cap.write_can_multi = true;
+        cap.create_dir = true;
+        cap.delete = true;
+        cap.copy = true;
+        cap.rename = true;
+        cap.list = true;
+        cap.list_with_delimiter_slash = true;
+        cap.list_without_delimiter = true;
+        cap.blocking = true;
+
+        meta
+    }
+
+    async fn create_dir(&self, path: &str, _: OpCreateDir) -> Result<RpCreateDir> {
+        self.replay(Operation::CreateDir, path)?;
+        Ok(RpCreateDir::default())
+    }
+
+    async fn read(&self, path: &str, _: OpRead) -> Result<(RpRead, Self::Reader)> {
+        self.replay_read(Operation::Read, path)
+    }
+
+    async fn write(&self, path: &str, _: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        self.replay_write(Operation::Write, path)
+    }
+
+    async fn copy(&self, from: &str, _: &str, _: OpCopy) -> Result<RpCopy> {
+        self.replay(Operation::Copy, from)?;
+        Ok(RpCopy::default())
+    }
+
+    async fn rename(&self, from: &str, _: &str, _: OpRename) -> Result<RpRename> {
+        self.replay(Operation::Rename, from)?;
+        Ok(RpRename::default())
+    }
+
+    async fn stat(&self, path: &str, _: OpStat) -> Result<RpStat> {
+        self.replay_stat(Operation::Stat, path)
+    }
+
+    async fn delete(&self, path: &str, _: OpDelete) -> Result<RpDelete> {
+        self.replay(Operation::Delete, path)?;
+        Ok(RpDelete::default())
+    }
+
+    async fn list(&self, path: &str, _: OpList) -> Result<(RpList, Self::Pager)> {
+        self.replay_list(Operation::List, path)
+    }
+
+    fn blocking_create_dir(&self, path: &str, _: OpCreateDir) -> Result<RpCreateDir> {
+        self.replay(Operation::BlockingCreateDir, path)?;
+        Ok(RpCreateDir::default())
+    }
+
+    fn blocking_read(&self, path: &str, _: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.replay_read(Operation::BlockingRead, path)
+    }
+
+    fn blocking_write(&self, path: &str, _: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        self.replay_write(Operation::BlockingWrite, path)
+    }
+
+    fn blocking_copy(&self, from: &str, _: &str, _: OpCopy) -> Result<RpCopy> {
+        self.replay(Operation::BlockingCopy, from)?;
+        Ok(RpCopy::default())
+    }
+
+    fn blocking_rename(&self, from: &str, _: &str, _: OpRename) -> Result<RpRename> {
+        self.replay(Operation::BlockingRename, from)?;
+        Ok(RpRename::default())
+    }
+
+    fn blocking_stat(&self, path: &str, _: OpStat) -> Result<RpStat> {
+        self.replay_stat(Operation::BlockingStat, path)
+    }
+
+    fn blocking_delete(&self, path: &str, _: OpDelete) -> Result<RpDelete> {
+        self.replay(Operation::BlockingDelete, path)?;
+        Ok(RpDelete::default())
+    }
+
+    fn blocking_list(&self, path: &str, _: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        self.replay_list(Operation::BlockingList, path)
+    }
+}
+
+/// ReplayWriter drops all written content and returns the recorded result
+/// while closing.
+pub struct ReplayWriter {
+    result: Option<Result<()>>,
+}
+
+impl ReplayWriter {
+    fn close_inner(&mut self) -> Result<()> {
+        self.result.take().unwrap_or(Ok(()))
+    }
+}
+
+impl oio::Write for ReplayWriter {
+    fn poll_write(&mut self, _: &mut Context<'_>, bs: &dyn oio::WriteBuf) -> Poll<Result<usize>> {
+        Poll::Ready(Ok(bs.remaining()))
+    }
+
+    fn poll_abort(&mut self, _: &mut Context<'_>) -> Poll<Result<()>> {
+        self.result = None;
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(&mut self, _: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(self.close_inner())
+    }
+}
+
+impl oio::BlockingWrite for ReplayWriter {
+    fn write(&mut self, bs: &dyn oio::WriteBuf) -> Result<usize> {
+        Ok(bs.remaining())
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.close_inner()
+    }
+}
+
+/// ReplayPager returns all recorded entries in one page.
+pub struct ReplayPager {
+    entries: Option<Vec<oio::Entry>>,
+}
+
+#[async_trait]
+impl oio::Page for ReplayPager {
+    async fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        Ok(self.entries.take())
+    }
+}
+
+impl oio::BlockingPage for ReplayPager {
+    fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
+        Ok(self.entries.take())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::TryStreamExt;
+
+    use super::*;
+    use crate::services;
+
+    #[tokio::test]
+    async fn test_record_and_replay() -> Result<()> {
+        let file =
+            std::env::temp_dir().join(format!("opendal-record-{}.jsonl", uuid::Uuid::new_v4()));
+
+        let op = Operator::new(services::Memory::default())?
+            .layer(RecordLayer::new(&file)?)
+            .finish();
+        op.write("dir/file", "Hello, World!").await?;
+        assert_eq!(op.read("dir/file").await?, b"Hello, World!");
+        let meta = op.stat("dir/file").await?;
+        let entries = op.list("dir/").await?;
+        let err = op.stat("not_exist").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        let op = Operator::new(services::Memory::default())?
+            .layer(ReplayLayer::new(&file)?)
+            .finish();
+        op.write("dir/file", "Hello, World!").await?;
+        assert_eq!(op.read("dir/file").await?, b"Hello, World!");
+        let replayed = op.stat("dir/file").await?;
+        assert_eq!(replayed.mode(), meta.mode());
+        assert_eq!(replayed.content_length(), meta.content_length());
+        let replayed: Vec<_> = op.lister("dir/").await?.try_collect().await?;
+        assert_eq!(
+            replayed.iter().map(|e| e.path()).collect::<Vec<_>>(),
+            entries.iter().map(|e| e.path()).collect::<Vec<_>>()
+        );
+        let err = op.stat("not_exist").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        // All records have been consumed.
+        let err = op.stat("dir/file").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+
+        std::fs::remove_file(&file).expect("remove record file must succeed");
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_error_kind() {
+        assert_eq!(parse_error_kind("NotFound"), ErrorKind::NotFound);
+        assert_eq!(
+            parse_error_kind(ErrorKind::ConditionNotMatch.into_static()),
+            ErrorKind::ConditionNotMatch
+        );
+        assert_eq!(parse_error_kind("Whatever"), ErrorKind::Unexpected);
+    }
+}
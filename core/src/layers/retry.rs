@@ -15,8 +15,12 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use std::collections::hash_map::RandomState;
 use std::fmt::Debug;
 use std::fmt::Formatter;
+use std::future::Future;
+use std::hash::BuildHasher;
+use std::hash::Hasher;
 use std::io;
 use std::pin::Pin;
 use std::sync::Arc;
@@ -27,10 +31,8 @@ use std::time::Duration;
 
 use async_trait::async_trait;
 use backon::BackoffBuilder;
-use backon::BlockingRetryable;
 use backon::ExponentialBackoff;
 use backon::ExponentialBuilder;
-use backon::Retryable;
 use bytes::Bytes;
 use futures::FutureExt;
 use log::warn;
@@ -49,6 +51,14 @@ use crate::*;
 /// returns true. If operation still failed, this layer will set error to
 /// `Persistent` which means error has been retried.
 ///
+/// For services that support `read_with_range`, a reader that failed in the
+/// middle will be resumed by sending a new range request for the remaining
+/// bytes, instead of reading the whole content again.
+///
+/// If the error carries a [`Error::retry_after`] hint (for example, parsed
+/// from the `Retry-After` header of a throttled response), the next delay
+/// will be at least that long, but never longer than the max delay.
+///
 /// `write` and `blocking_write` don't support retry so far, visit [this issue](https://github.com/apache/incubator-opendal/issues/1223) for more details.
 ///
 /// # Examples
@@ -96,7 +106,7 @@ use crate::*;
 ///     .finish();
 /// ```
 pub struct RetryLayer<I = DefaultRetryInterceptor> {
-    builder: ExponentialBuilder,
+    builder: RetryBackoffBuilder,
     notify: Arc<I>,
 }
 
@@ -112,7 +122,7 @@ impl<I> Clone for RetryLayer<I> {
 impl Default for RetryLayer {
     fn default() -> Self {
         Self {
-            builder: ExponentialBuilder::default(),
+            builder: RetryBackoffBuilder::default(),
             notify: Arc::new(DefaultRetryInterceptor),
         }
     }
@@ -172,10 +182,14 @@ impl RetryLayer {
 
     /// Set jitter of current backoff.
     ///
-    /// If jitter is enabled, ExponentialBackoff will add a random jitter in `[0, min_delay)
-    /// to current delay.
+    /// If jitter is enabled, every delay will be picked randomly in
+    /// `[0, delay]` where `delay` is the exponential delay without jitter
+    /// (also known as "full jitter"). This spreads retries from many clients
+    /// that failed at the same time.
+    ///
+    /// Jitter is disabled by default.
     pub fn with_jitter(mut self) -> Self {
-        self.builder = self.builder.with_jitter();
+        self.builder.jitter = true;
         self
     }
 
@@ -185,13 +199,13 @@ impl RetryLayer {
     ///
     /// This function will panic if input factor smaller than `1.0`.
     pub fn with_factor(mut self, factor: f32) -> Self {
-        self.builder = self.builder.with_factor(factor);
+        self.builder.inner = self.builder.inner.with_factor(factor);
         self
     }
 
     /// Set min_delay of current backoff.
     pub fn with_min_delay(mut self, min_delay: Duration) -> Self {
-        self.builder = self.builder.with_min_delay(min_delay);
+        self.builder.inner = self.builder.inner.with_min_delay(min_delay);
         self
     }
 
@@ -207,7 +221,7 @@ impl RetryLayer {
     ///
     /// Backoff will return `None` if max times is reaching.
     pub fn with_max_times(mut self, max_times: usize) -> Self {
-        self.builder = self.builder.with_max_times(max_times);
+        self.builder.inner = self.builder.inner.with_max_times(max_times);
         self
     }
 }
@@ -224,6 +238,127 @@ impl<A: Accessor, I: RetryInterceptor> Layer<A> for RetryLayer<I> {
     }
 }
 
+/// The max delay used if users don't set it, same as the default of backon.
+const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(60);
+
+/// Retry the future returned by `f` until it succeeds, the error is not
+/// temporary or the backoff is exhausted.
+///
+/// The error is passed to backoff explicitly, so that its retry-after hint
+/// could be honored.
+async fn retry<T, F, Fut, N>(builder: &RetryBackoffBuilder, mut f: F, mut notify: N) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+    N: FnMut(&Error, Duration),
+{
+    let mut backoff = builder.build();
+
+    loop {
+        match f().await {
+            Ok(v) => return Ok(v),
+            Err(err) if !err.is_temporary() => return Err(err),
+            Err(err) => match backoff.next_for(&err) {
+                None => return Err(err),
+                Some(dur) => {
+                    notify(&err, dur);
+                    tokio::time::sleep(dur).await;
+                }
+            },
+        }
+    }
+}
+
+/// Blocking version of [`retry`].
+fn blocking_retry<T, F, N>(builder: &RetryBackoffBuilder, mut f: F, mut notify: N) -> Result<T>
+where
+    F: FnMut() -> Result<T>,
+    N: FnMut(&Error, Duration),
+{
+    let mut backoff = builder.build();
+
+    loop {
+        match f() {
+            Ok(v) => return Ok(v),
+            Err(err) if !err.is_temporary() => return Err(err),
+            Err(err) => match backoff.next_for(&err) {
+                None => return Err(err),
+                Some(dur) => {
+                    notify(&err, dur);
+                    std::thread::sleep(dur);
+                }
+            },
+        }
+    }
+}
+
+/// RetryBackoffBuilder builds exponential backoff with optional full jitter.
+#[derive(Debug, Clone)]
+struct RetryBackoffBuilder {
+    inner: ExponentialBuilder,
+    jitter: bool,
+    max_delay: Duration,
+}
+
+impl Default for RetryBackoffBuilder {
+    fn default() -> Self {
+        Self {
+            inner: ExponentialBuilder::default().with_max_delay(DEFAULT_MAX_DELAY),
+            jitter: false,
+            max_delay: DEFAULT_MAX_DELAY,
+        }
+    }
+}
+
+impl RetryBackoffBuilder {
+    fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.inner = self.inner.with_max_delay(max_delay);
+        self.max_delay = max_delay;
+        self
+    }
+
+    fn build(&self) -> RetryBackoff {
+        RetryBackoff {
+            inner: self.inner.build(),
+            jitter: self.jitter,
+            max_delay: self.max_delay,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct RetryBackoff {
+    inner: ExponentialBackoff,
+    jitter: bool,
+    max_delay: Duration,
+}
+
+impl RetryBackoff {
+    /// Get the delay before retrying the given error.
+    ///
+    /// The delay will be at least the retry-after hint of the error, but
+    /// never longer than `max_delay`.
+    fn next_for(&mut self, err: &Error) -> Option<Duration> {
+        let dur = self.next_delay()?;
+        Some(match err.retry_after() {
+            Some(hint) => dur.max(hint).min(self.max_delay),
+            None => dur,
+        })
+    }
+
+    fn next_delay(&mut self) -> Option<Duration> {
+        let dur = self.inner.next()?;
+        if !self.jitter {
+            return Some(dur);
+        }
+
+        // `RandomState` is seeded randomly, which is good enough for jitter
+        // without adding a new dependency.
+        let random = RandomState::new().build_hasher().finish();
+        Some(dur.mul_f64(random as f64 / u64::MAX as f64))
+    }
+}
+
 /// RetryInterceptor is used to intercept while retry happened.
 pub trait RetryInterceptor: Send + Sync + 'static {
     /// Everytime RetryLayer is retrying, this function will be called.
@@ -265,7 +400,7 @@ impl RetryInterceptor for DefaultRetryInterceptor {
 
 pub struct RetryAccessor<A: Accessor, I: RetryInterceptor> {
     inner: A,
-    builder: ExponentialBuilder,
+    builder: RetryBackoffBuilder,
     notify: Arc<I>,
 }
 
@@ -292,10 +427,10 @@ impl<A: Accessor, I: RetryInterceptor> LayeredAccessor for RetryAccessor<A, I> {
     }
 
     async fn create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
-        { || self.inner.create_dir(path, args.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
-            .notify(|err, dur: Duration| {
+        blocking_retry(
+            &self.builder,
+            || self.inner.create_dir(path, args.clone()),
+            |err, dur: Duration| {
                 self.notify.intercept(
                     err,
                     dur,
@@ -304,42 +439,44 @@ impl<A: Accessor, I: RetryInterceptor> LayeredAccessor for RetryAccessor<A, I> {
                         ("path", path),
                     ],
                 )
-            })
-            .map(|v| v.map_err(|e| e.set_persistent()))
-            .await
+            },
+        )
+        .map(|v| v.map_err(|e| e.set_persistent()))
+        .await
     }
 
     async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
-        { || self.inner.read(path, args.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
-            .notify(|err, dur| {
+        retry(
+            &self.builder,
+            || self.inner.read(path, args.clone()),
+            |err, dur| {
                 self.notify.intercept(
                     err,
                     dur,
                     &[("operation", Operation::Read.into_static()), ("path", path)],
                 )
+            },
+        )
+        .map(|v| {
+            v.map(|(rp, r)| {
+                (
+                    rp,
+                    RetryWrapper::new(r, self.notify.clone(), path, self.builder.clone()),
+                )
             })
-            .map(|v| {
-                v.map(|(rp, r)| {
-                    (
-                        rp,
-                        RetryWrapper::new(r, self.notify.clone(), path, self.builder.clone()),
-                    )
-                })
-                .map_err(|e| e.set_persistent())
-            })
-            .await
+            .map_err(|e| e.set_persistent())
+        })
+        .await
     }
 
     /// Return `Interrupted` Error even after retry.
     ///
     /// Allowing users to retry the write request from upper logic.
     async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
-        { || self.inner.write(path, args.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
-            .notify(|err, dur| {
+        retry(
+            &self.builder,
+            || self.inner.write(path, args.clone()),
+            |err, dur| {
                 self.notify.intercept(
                     err,
                     dur,
@@ -348,39 +485,41 @@ impl<A: Accessor, I: RetryInterceptor> LayeredAccessor for RetryAccessor<A, I> {
                         ("path", path),
                     ],
                 )
+            },
+        )
+        .map(|v| {
+            v.map(|(rp, r)| {
+                (
+                    rp,
+                    RetryWrapper::new(r, self.notify.clone(), path, self.builder.clone()),
+                )
             })
-            .map(|v| {
-                v.map(|(rp, r)| {
-                    (
-                        rp,
-                        RetryWrapper::new(r, self.notify.clone(), path, self.builder.clone()),
-                    )
-                })
-                .map_err(|e| e.set_persistent())
-            })
-            .await
+            .map_err(|e| e.set_persistent())
+        })
+        .await
     }
 
     async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
-        { || self.inner.stat(path, args.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
-            .notify(|err, dur| {
+        retry(
+            &self.builder,
+            || self.inner.stat(path, args.clone()),
+            |err, dur| {
                 self.notify.intercept(
                     err,
                     dur,
                     &[("operation", Operation::Stat.into_static()), ("path", path)],
                 )
-            })
-            .map(|v| v.map_err(|e| e.set_persistent()))
-            .await
+            },
+        )
+        .map(|v| v.map_err(|e| e.set_persistent()))
+        .await
     }
 
     async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
-        { || self.inner.delete(path, args.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
-            .notify(|err, dur| {
+        retry(
+            &self.builder,
+            || self.inner.delete(path, args.clone()),
+            |err, dur| {
                 self.notify.intercept(
                     err,
                     dur,
@@ -389,16 +528,17 @@ impl<A: Accessor, I: RetryInterceptor> LayeredAccessor for RetryAccessor<A, I> {
                         ("path", path),
                     ],
                 )
-            })
-            .map(|v| v.map_err(|e| e.set_persistent()))
-            .await
+            },
+        )
+        .map(|v| v.map_err(|e| e.set_persistent()))
+        .await
     }
 
     async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
-        { || self.inner.copy(from, to, args.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
-            .notify(|err, dur| {
+        retry(
+            &self.builder,
+            || self.inner.copy(from, to, args.clone()),
+            |err, dur| {
                 self.notify.intercept(
                     err,
                     dur,
@@ -408,16 +548,17 @@ impl<A: Accessor, I: RetryInterceptor> LayeredAccessor for RetryAccessor<A, I> {
                         ("to", to),
                     ],
                 )
-            })
-            .map(|v| v.map_err(|e| e.set_persistent()))
-            .await
+            },
+        )
+        .map(|v| v.map_err(|e| e.set_persistent()))
+        .await
     }
 
     async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
-        { || self.inner.rename(from, to, args.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
-            .notify(|err, dur| {
+        retry(
+            &self.builder,
+            || self.inner.rename(from, to, args.clone()),
+            |err, dur| {
                 self.notify.intercept(
                     err,
                     dur,
@@ -427,35 +568,37 @@ impl<A: Accessor, I: RetryInterceptor> LayeredAccessor for RetryAccessor<A, I> {
                         ("to", to),
                     ],
                 )
-            })
-            .map(|v| v.map_err(|e| e.set_persistent()))
-            .await
+            },
+        )
+        .map(|v| v.map_err(|e| e.set_persistent()))
+        .await
     }
 
     async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
-        { || self.inner.list(path, args.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
-            .notify(|err, dur| {
+        retry(
+            &self.builder,
+            || self.inner.list(path, args.clone()),
+            |err, dur| {
                 self.notify.intercept(
                     err,
                     dur,
                     &[("operation", Operation::List.into_static()), ("path", path)],
                 )
+            },
+        )
+        .map(|v| {
+            v.map(|(l, p)| {
+                let pager = RetryWrapper::new(p, self.notify.clone(), path, self.builder.clone());
+                (l, pager)
             })
-            .map(|v| {
-                v.map(|(l, p)| {
-                    let pager =
-                        RetryWrapper::new(p, self.notify.clone(), path, self.builder.clone());
-                    (l, pager)
-                })
-                .map_err(|e| e.set_persistent())
-            })
-            .await
+            .map_err(|e| e.set_persistent())
+        })
+        .await
     }
 
     async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
-        {
+        retry(
+            &self.builder,
             || async {
                 let rp = self.inner.batch(args.clone()).await?;
                 let mut nrp = Vec::with_capacity(rp.results().len());
@@ -464,29 +607,27 @@ impl<A: Accessor, I: RetryInterceptor> LayeredAccessor for RetryAccessor<A, I> {
                     nrp.push((path, Ok(result)))
                 }
                 Ok(RpBatch::new(nrp))
-            }
-        }
-        .retry(&self.builder)
-        .when(|e: &Error| e.is_temporary())
-        .notify(|err, dur| {
-            self.notify.intercept(
-                err,
-                dur,
-                &[
-                    ("operation", Operation::Batch.into_static()),
-                    ("count", &args.operation().len().to_string()),
-                ],
-            )
-        })
+            },
+            |err, dur| {
+                self.notify.intercept(
+                    err,
+                    dur,
+                    &[
+                        ("operation", Operation::Batch.into_static()),
+                        ("count", &args.operation().len().to_string()),
+                    ],
+                )
+            },
+        )
         .await
         .map_err(|e| e.set_persistent())
     }
 
     fn blocking_create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
-        { || self.inner.blocking_create_dir(path, args.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
-            .notify(|err, dur| {
+        retry(
+            &self.builder,
+            || self.inner.blocking_create_dir(path, args.clone()),
+            |err, dur| {
                 self.notify.intercept(
                     err,
                     dur,
@@ -495,16 +636,16 @@ impl<A: Accessor, I: RetryInterceptor> LayeredAccessor for RetryAccessor<A, I> {
                         ("path", path),
                     ],
                 )
-            })
-            .call()
-            .map_err(|e| e.set_persistent())
+            },
+        )
+        .map_err(|e| e.set_persistent())
     }
 
     fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
-        { || self.inner.blocking_read(path, args.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
-            .notify(|err, dur| {
+        blocking_retry(
+            &self.builder,
+            || self.inner.blocking_read(path, args.clone()),
+            |err, dur| {
                 self.notify.intercept(
                     err,
                     dur,
@@ -513,22 +654,22 @@ impl<A: Accessor, I: RetryInterceptor> LayeredAccessor for RetryAccessor<A, I> {
                         ("path", path),
                     ],
                 )
-            })
-            .call()
-            .map(|(rp, r)| {
-                (
-                    rp,
-                    RetryWrapper::new(r, self.notify.clone(), path, self.builder.clone()),
-                )
-            })
-            .map_err(|e| e.set_persistent())
+            },
+        )
+        .map(|(rp, r)| {
+            (
+                rp,
+                RetryWrapper::new(r, self.notify.clone(), path, self.builder.clone()),
+            )
+        })
+        .map_err(|e| e.set_persistent())
     }
 
     fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
-        { || self.inner.blocking_write(path, args.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
-            .notify(|err, dur| {
+        blocking_retry(
+            &self.builder,
+            || self.inner.blocking_write(path, args.clone()),
+            |err, dur| {
                 self.notify.intercept(
                     err,
                     dur,
@@ -537,22 +678,22 @@ impl<A: Accessor, I: RetryInterceptor> LayeredAccessor for RetryAccessor<A, I> {
                         ("path", path),
                     ],
                 )
-            })
-            .call()
-            .map(|(rp, r)| {
-                (
-                    rp,
-                    RetryWrapper::new(r, self.notify.clone(), path, self.builder.clone()),
-                )
-            })
-            .map_err(|e| e.set_persistent())
+            },
+        )
+        .map(|(rp, r)| {
+            (
+                rp,
+                RetryWrapper::new(r, self.notify.clone(), path, self.builder.clone()),
+            )
+        })
+        .map_err(|e| e.set_persistent())
     }
 
     fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
-        { || self.inner.blocking_stat(path, args.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
-            .notify(|err, dur| {
+        blocking_retry(
+            &self.builder,
+            || self.inner.blocking_stat(path, args.clone()),
+            |err, dur| {
                 self.notify.intercept(
                     err,
                     dur,
@@ -561,16 +702,16 @@ impl<A: Accessor, I: RetryInterceptor> LayeredAccessor for RetryAccessor<A, I> {
                         ("path", path),
                     ],
                 )
-            })
-            .call()
-            .map_err(|e| e.set_persistent())
+            },
+        )
+        .map_err(|e| e.set_persistent())
     }
 
     fn blocking_delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
-        { || self.inner.blocking_delete(path, args.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
-            .notify(|err, dur| {
+        blocking_retry(
+            &self.builder,
+            || self.inner.blocking_delete(path, args.clone()),
+            |err, dur| {
                 self.notify.intercept(
                     err,
                     dur,
@@ -579,16 +720,16 @@ impl<A: Accessor, I: RetryInterceptor> LayeredAccessor for RetryAccessor<A, I> {
                         ("path", path),
                     ],
                 )
-            })
-            .call()
-            .map_err(|e| e.set_persistent())
+            },
+        )
+        .map_err(|e| e.set_persistent())
     }
 
     fn blocking_copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
-        { || self.inner.blocking_copy(from, to, args.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
-            .notify(|err, dur| {
+        blocking_retry(
+            &self.builder,
+            || self.inner.blocking_copy(from, to, args.clone()),
+            |err, dur| {
                 self.notify.intercept(
                     err,
                     dur,
@@ -598,16 +739,16 @@ impl<A: Accessor, I: RetryInterceptor> LayeredAccessor for RetryAccessor<A, I> {
                         ("to", to),
                     ],
                 )
-            })
-            .call()
-            .map_err(|e| e.set_persistent())
+            },
+        )
+        .map_err(|e| e.set_persistent())
     }
 
     fn blocking_rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
-        { || self.inner.blocking_rename(from, to, args.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
-            .notify(|err, dur| {
+        blocking_retry(
+            &self.builder,
+            || self.inner.blocking_rename(from, to, args.clone()),
+            |err, dur| {
                 self.notify.intercept(
                     err,
                     dur,
@@ -617,16 +758,16 @@ impl<A: Accessor, I: RetryInterceptor> LayeredAccessor for RetryAccessor<A, I> {
                         ("to", to),
                     ],
                 )
-            })
-            .call()
-            .map_err(|e| e.set_persistent())
+            },
+        )
+        .map_err(|e| e.set_persistent())
     }
 
     fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
-        { || self.inner.blocking_list(path, args.clone()) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
-            .notify(|err, dur| {
+        blocking_retry(
+            &self.builder,
+            || self.inner.blocking_list(path, args.clone()),
+            |err, dur| {
                 self.notify.intercept(
                     err,
                     dur,
@@ -635,13 +776,13 @@ impl<A: Accessor, I: RetryInterceptor> LayeredAccessor for RetryAccessor<A, I> {
                         ("path", path),
                     ],
                 )
-            })
-            .call()
-            .map(|(rp, p)| {
-                let p = RetryWrapper::new(p, self.notify.clone(), path, self.builder.clone());
-                (rp, p)
-            })
-            .map_err(|e| e.set_persistent())
+            },
+        )
+        .map(|(rp, p)| {
+            let p = RetryWrapper::new(p, self.notify.clone(), path, self.builder.clone());
+            (rp, p)
+        })
+        .map_err(|e| e.set_persistent())
     }
 }
 
@@ -650,13 +791,13 @@ pub struct RetryWrapper<R, I> {
     notify: Arc<I>,
 
     path: String,
-    builder: ExponentialBuilder,
-    current_backoff: Option<ExponentialBackoff>,
+    builder: RetryBackoffBuilder,
+    current_backoff: Option<RetryBackoff>,
     sleep: Option<Pin<Box<tokio::time::Sleep>>>,
 }
 
 impl<R, I> RetryWrapper<R, I> {
-    fn new(inner: R, notify: Arc<I>, path: &str, backoff: ExponentialBuilder) -> Self {
+    fn new(inner: R, notify: Arc<I>, path: &str, backoff: RetryBackoffBuilder) -> Self {
         Self {
             inner,
             notify,
@@ -694,7 +835,7 @@ impl<R: oio::Read, I: RetryInterceptor> oio::Read for RetryWrapper<R, I> {
                     }
                 };
 
-                match backoff.next() {
+                match backoff.next_for(&err) {
                     None => {
                         self.current_backoff = None;
                         Poll::Ready(Err(err))
@@ -740,7 +881,7 @@ impl<R: oio::Read, I: RetryInterceptor> oio::Read for RetryWrapper<R, I> {
                     }
                 };
 
-                match backoff.next() {
+                match backoff.next_for(&err) {
                     None => {
                         self.current_backoff = None;
                         Poll::Ready(Err(err))
@@ -790,7 +931,7 @@ impl<R: oio::Read, I: RetryInterceptor> oio::Read for RetryWrapper<R, I> {
                     }
                 };
 
-                match backoff.next() {
+                match backoff.next_for(&err) {
                     None => {
                         self.current_backoff = None;
                         Poll::Ready(Some(Err(err)))
@@ -815,10 +956,10 @@ impl<R: oio::Read, I: RetryInterceptor> oio::Read for RetryWrapper<R, I> {
 
 impl<R: oio::BlockingRead, I: RetryInterceptor> oio::BlockingRead for RetryWrapper<R, I> {
     fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
-        { || self.inner.read(buf) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
-            .notify(|err, dur| {
+        blocking_retry(
+            &self.builder,
+            || self.inner.read(buf),
+            |err, dur| {
                 self.notify.intercept(
                     err,
                     dur,
@@ -827,16 +968,16 @@ impl<R: oio::BlockingRead, I: RetryInterceptor> oio::BlockingRead for RetryWrapp
                         ("path", &self.path),
                     ],
                 );
-            })
-            .call()
-            .map_err(|e| e.set_persistent())
+            },
+        )
+        .map_err(|e| e.set_persistent())
     }
 
     fn seek(&mut self, pos: io::SeekFrom) -> Result<u64> {
-        { || self.inner.seek(pos) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
-            .notify(|err, dur| {
+        blocking_retry(
+            &self.builder,
+            || self.inner.seek(pos),
+            |err, dur| {
                 self.notify.intercept(
                     err,
                     dur,
@@ -845,16 +986,16 @@ impl<R: oio::BlockingRead, I: RetryInterceptor> oio::BlockingRead for RetryWrapp
                         ("path", &self.path),
                     ],
                 );
-            })
-            .call()
-            .map_err(|e| e.set_persistent())
+            },
+        )
+        .map_err(|e| e.set_persistent())
     }
 
     fn next(&mut self) -> Option<Result<Bytes>> {
-        { || self.inner.next().transpose() }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
-            .notify(|err, dur| {
+        blocking_retry(
+            &self.builder,
+            || self.inner.next().transpose(),
+            |err, dur| {
                 self.notify.intercept(
                     err,
                     dur,
@@ -863,10 +1004,10 @@ impl<R: oio::BlockingRead, I: RetryInterceptor> oio::BlockingRead for RetryWrapp
                         ("path", &self.path),
                     ],
                 );
-            })
-            .call()
-            .map_err(|e| e.set_persistent())
-            .transpose()
+            },
+        )
+        .map_err(|e| e.set_persistent())
+        .transpose()
     }
 }
 
@@ -896,7 +1037,7 @@ impl<R: oio::Write, I: RetryInterceptor> oio::Write for RetryWrapper<R, I> {
                     }
                 };
 
-                match backoff.next() {
+                match backoff.next_for(&err) {
                     None => {
                         self.current_backoff = None;
                         Poll::Ready(Err(err))
@@ -942,7 +1083,7 @@ impl<R: oio::Write, I: RetryInterceptor> oio::Write for RetryWrapper<R, I> {
                     }
                 };
 
-                match backoff.next() {
+                match backoff.next_for(&err) {
                     None => {
                         self.current_backoff = None;
                         Poll::Ready(Err(err))
@@ -988,7 +1129,7 @@ impl<R: oio::Write, I: RetryInterceptor> oio::Write for RetryWrapper<R, I> {
                     }
                 };
 
-                match backoff.next() {
+                match backoff.next_for(&err) {
                     None => {
                         self.current_backoff = None;
                         Poll::Ready(Err(err))
@@ -1009,14 +1150,18 @@ impl<R: oio::Write, I: RetryInterceptor> oio::Write for RetryWrapper<R, I> {
             }
         }
     }
+
+    fn metadata(&self) -> Option<Metadata> {
+        self.inner.metadata()
+    }
 }
 
 impl<R: oio::BlockingWrite, I: RetryInterceptor> oio::BlockingWrite for RetryWrapper<R, I> {
     fn write(&mut self, bs: &dyn oio::WriteBuf) -> Result<usize> {
-        { || self.inner.write(bs) }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
-            .notify(|err, dur| {
+        blocking_retry(
+            &self.builder,
+            || self.inner.write(bs),
+            |err, dur| {
                 self.notify.intercept(
                     err,
                     dur,
@@ -1025,16 +1170,16 @@ impl<R: oio::BlockingWrite, I: RetryInterceptor> oio::BlockingWrite for RetryWra
                         ("path", &self.path),
                     ],
                 );
-            })
-            .call()
-            .map_err(|e| e.set_persistent())
+            },
+        )
+        .map_err(|e| e.set_persistent())
     }
 
     fn close(&mut self) -> Result<()> {
-        { || self.inner.close() }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
-            .notify(|err, dur| {
+        blocking_retry(
+            &self.builder,
+            || self.inner.close(),
+            |err, dur| {
                 self.notify.intercept(
                     err,
                     dur,
@@ -1043,9 +1188,9 @@ impl<R: oio::BlockingWrite, I: RetryInterceptor> oio::BlockingWrite for RetryWra
                         ("path", &self.path),
                     ],
                 );
-            })
-            .call()
-            .map_err(|e| e.set_persistent())
+            },
+        )
+        .map_err(|e| e.set_persistent())
     }
 }
 
@@ -1058,7 +1203,7 @@ impl<P: oio::Page, I: RetryInterceptor> oio::Page for RetryWrapper<P, I> {
             match self.inner.next().await {
                 Ok(v) => return Ok(v),
                 Err(e) if !e.is_temporary() => return Err(e),
-                Err(e) => match backoff.next() {
+                Err(e) => match backoff.next_for(&e) {
                     None => return Err(e),
                     Some(dur) => {
                         self.notify.intercept(
@@ -1076,14 +1221,18 @@ impl<P: oio::Page, I: RetryInterceptor> oio::Page for RetryWrapper<P, I> {
             }
         }
     }
+
+    fn continuation_token(&self) -> Option<String> {
+        self.inner.continuation_token()
+    }
 }
 
 impl<P: oio::BlockingPage, I: RetryInterceptor> oio::BlockingPage for RetryWrapper<P, I> {
     fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
-        { || self.inner.next() }
-            .retry(&self.builder)
-            .when(|e| e.is_temporary())
-            .notify(|err, dur| {
+        blocking_retry(
+            &self.builder,
+            || self.inner.next(),
+            |err, dur| {
                 self.notify.intercept(
                     err,
                     dur,
@@ -1092,9 +1241,13 @@ impl<P: oio::BlockingPage, I: RetryInterceptor> oio::BlockingPage for RetryWrapp
                         ("path", &self.path),
                     ],
                 );
-            })
-            .call()
-            .map_err(|e| e.set_persistent())
+            },
+        )
+        .map_err(|e| e.set_persistent())
+    }
+
+    fn continuation_token(&self) -> Option<String> {
+        self.inner.continuation_token()
     }
 }
 
@@ -1352,6 +1505,133 @@ mod tests {
         assert_eq!(*builder.attempt.lock().unwrap(), 5);
     }
 
+    #[derive(Default, Clone)]
+    struct ResumeBuilder {
+        failed: Arc<Mutex<bool>>,
+        ranges: Arc<Mutex<Vec<BytesRange>>>,
+    }
+
+    impl Builder for ResumeBuilder {
+        const SCHEME: Scheme = Scheme::Custom("resume");
+        type Accessor = ResumeService;
+
+        fn from_map(_: HashMap<String, String>) -> Self {
+            Self::default()
+        }
+
+        fn build(&mut self) -> Result<Self::Accessor> {
+            Ok(ResumeService {
+                failed: self.failed.clone(),
+                ranges: self.ranges.clone(),
+            })
+        }
+    }
+
+    #[derive(Debug, Clone, Default)]
+    struct ResumeService {
+        failed: Arc<Mutex<bool>>,
+        ranges: Arc<Mutex<Vec<BytesRange>>>,
+    }
+
+    #[async_trait]
+    impl Accessor for ResumeService {
+        type Reader = ResumeReader;
+        type BlockingReader = ();
+        type Writer = ();
+        type BlockingWriter = ();
+        type Pager = ();
+        type BlockingPager = ();
+
+        fn info(&self) -> AccessorInfo {
+            let mut am = AccessorInfo::default();
+            am.set_native_capability(Capability {
+                read: true,
+                read_with_range: true,
+                ..Default::default()
+            });
+
+            am
+        }
+
+        async fn read(&self, _: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+            let range = args.range();
+            self.ranges.lock().unwrap().push(range);
+
+            let data = range.apply_on_bytes(Bytes::from("Hello, World!"));
+            // Only the first reader will fail after 7 bytes.
+            let mut failed = self.failed.lock().unwrap();
+            let fail_after = if *failed { None } else { Some(7) };
+            *failed = true;
+
+            Ok((
+                RpRead::new(data.len() as u64),
+                ResumeReader { data, fail_after },
+            ))
+        }
+    }
+
+    #[derive(Debug, Clone, Default)]
+    struct ResumeReader {
+        data: Bytes,
+        fail_after: Option<usize>,
+    }
+
+    impl oio::Read for ResumeReader {
+        fn poll_read(&mut self, _: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+            if self.fail_after == Some(0) {
+                return Poll::Ready(Err(Error::new(
+                    ErrorKind::Unexpected,
+                    "connection reset while reading",
+                )
+                .set_temporary()));
+            }
+
+            let mut n = buf.len().min(self.data.len());
+            if let Some(fail_after) = self.fail_after.as_mut() {
+                n = n.min(*fail_after);
+                *fail_after -= n;
+            }
+            buf[..n].copy_from_slice(&self.data.split_to(n));
+            Poll::Ready(Ok(n))
+        }
+
+        fn poll_seek(&mut self, _: &mut Context<'_>, _: io::SeekFrom) -> Poll<Result<u64>> {
+            unreachable!("resume test only reads sequentially and never seeks")
+        }
+
+        fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+            let mut bs = vec![0; 4];
+            match ready!(self.poll_read(cx, &mut bs)) {
+                Ok(0) => Poll::Ready(None),
+                Ok(v) => Poll::Ready(Some(Ok(Bytes::from(bs[..v].to_vec())))),
+                Err(err) => Poll::Ready(Some(Err(err))),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_read_resume_from_delivered_bytes() {
+        let _ = tracing_subscriber::fmt().with_test_writer().try_init();
+
+        let builder = ResumeBuilder::default();
+        let op = Operator::new(builder.clone())
+            .unwrap()
+            .layer(RetryLayer::new().with_min_delay(Duration::from_millis(10)))
+            .finish();
+
+        let mut r = op.reader("resume").await.unwrap();
+        let mut content = Vec::new();
+        r.read_to_end(&mut content)
+            .await
+            .expect("read must succeed");
+        assert_eq!(content, "Hello, World!".as_bytes());
+
+        // The second request should only read the remaining bytes.
+        let ranges = builder.ranges.lock().unwrap();
+        assert_eq!(ranges.len(), 2);
+        assert_eq!(ranges[1], BytesRange::new(Some(7), Some(6)));
+    }
+
     #[tokio::test]
     async fn test_retry_list() {
         let _ = tracing_subscriber::fmt().with_test_writer().try_init();
@@ -1400,4 +1680,54 @@ mod tests {
         op.remove(paths).await.expect("batch must succeed");
         assert_eq!(*builder.attempt.lock().unwrap(), 5);
     }
+
+    #[test]
+    fn test_retry_backoff_full_jitter() {
+        let mut builder = RetryBackoffBuilder::default().with_max_delay(Duration::from_secs(1));
+        builder.inner = builder
+            .inner
+            .with_min_delay(Duration::from_millis(100))
+            .with_max_times(6);
+        builder.jitter = true;
+
+        let expected: Vec<_> = [100, 200, 400, 800, 1000, 1000]
+            .into_iter()
+            .map(Duration::from_millis)
+            .collect();
+
+        let mut jittered = false;
+        for _ in 0..1000 {
+            let mut backoff = builder.build();
+            let actual: Vec<_> = std::iter::from_fn(|| backoff.next_delay()).collect();
+            assert_eq!(actual.len(), expected.len());
+            for (actual, expected) in actual.iter().zip(expected.iter()) {
+                assert!(actual <= expected, "{actual:?} > {expected:?}");
+                jittered |= actual != expected;
+            }
+        }
+        assert!(jittered, "delay must be jittered");
+    }
+
+    #[test]
+    fn test_retry_backoff_retry_after() {
+        let mut builder = RetryBackoffBuilder::default();
+        builder.inner = builder
+            .inner
+            .with_min_delay(Duration::from_millis(100))
+            .with_max_times(2);
+        builder.jitter = true;
+
+        let err = Error::new(ErrorKind::RateLimited, "rate limited")
+            .set_temporary()
+            .with_retry_after(Duration::from_secs(3));
+
+        let mut backoff = builder.build();
+        assert_eq!(backoff.next_for(&err), Some(Duration::from_secs(3)));
+        assert_eq!(backoff.next_for(&err), Some(Duration::from_secs(3)));
+        assert_eq!(backoff.next_for(&err), None);
+
+        // The hint should be clamped by max delay.
+        let mut backoff = builder.with_max_delay(Duration::from_secs(1)).build();
+        assert_eq!(backoff.next_for(&err), Some(Duration::from_secs(1)));
+    }
 }
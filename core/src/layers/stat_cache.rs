@@ -0,0 +1,551 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::sync::Arc;
+use std::task::ready;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use parking_lot::Mutex;
+
+use crate::raw::*;
+use crate::*;
+
+/// The max shards used by the cache.
+const MAX_SHARDS: usize = 16;
+
+/// Add an in-memory LRU cache for stat results.
+///
+/// # Notes
+///
+/// - Only stat without conditions (like `if_match` or `version`) will be
+///   cached.
+/// - Cached entries will be invalidated by `write`, `delete`, `copy`,
+///   `rename` and `create_dir` on the same path.
+/// - Changes made by others can't be detected, users may see stale
+///   metadata until the entry expired.
+/// - The cache is split into shards to reduce lock contention, the LRU
+///   policy is applied to each shard.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use anyhow::Result;
+/// use opendal::layers::StatCacheLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+/// use opendal::Scheme;
+///
+/// let _ = Operator::new(services::Memory::default())
+///     .expect("must init")
+///     .layer(
+///         StatCacheLayer::default()
+///             .with_capacity(4096)
+///             .with_ttl(Duration::from_secs(30)),
+///     )
+///     .finish();
+/// ```
+#[derive(Clone)]
+pub struct StatCacheLayer {
+    capacity: usize,
+    ttl: Duration,
+}
+
+impl Default for StatCacheLayer {
+    fn default() -> Self {
+        Self {
+            capacity: 1024,
+            ttl: Duration::from_secs(60),
+        }
+    }
+}
+
+impl StatCacheLayer {
+    /// Set the max entries of the cache.
+    ///
+    /// Default to 1024.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Set the time to live of cached entries.
+    ///
+    /// Default to 60s.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+}
+
+impl<A: Accessor> Layer<A> for StatCacheLayer {
+    type LayeredAccessor = StatCacheAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        StatCacheAccessor {
+            inner,
+            cache: Arc::new(StatCache::new(self.capacity, self.ttl)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StatCacheAccessor<A: Accessor> {
+    inner: A,
+    cache: Arc<StatCache>,
+}
+
+impl<A: Accessor> StatCacheAccessor<A> {
+    /// Only stat without any conditions can be served from cache.
+    fn is_cacheable(args: &OpStat) -> bool {
+        args.if_match().is_none() && args.if_none_match().is_none() && args.version().is_none()
+    }
+}
+
+#[async_trait]
+impl<A: Accessor> LayeredAccessor for StatCacheAccessor<A> {
+    type Inner = A;
+    type Reader = A::Reader;
+    type BlockingReader = A::BlockingReader;
+    type Writer = StatCacheWriter<A::Writer>;
+    type BlockingWriter = StatCacheWriter<A::BlockingWriter>;
+    type Pager = A::Pager;
+    type BlockingPager = A::BlockingPager;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        let res = self.inner.create_dir(path, args).await;
+        self.cache.invalidate(path);
+        res
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        self.inner.read(path, args).await
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        self.cache.invalidate(path);
+        self.inner.write(path, args).await.map(|(rp, w)| {
+            (
+                rp,
+                StatCacheWriter::new(w, self.cache.clone(), path.to_string()),
+            )
+        })
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        let res = self.inner.copy(from, to, args).await;
+        self.cache.invalidate(to);
+        res
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        let res = self.inner.rename(from, to, args).await;
+        self.cache.invalidate(from);
+        self.cache.invalidate(to);
+        res
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        if !Self::is_cacheable(&args) {
+            return self.inner.stat(path, args).await;
+        }
+
+        if let Some(meta) = self.cache.get(path) {
+            return Ok(RpStat::new(meta));
+        }
+
+        let rp = self.inner.stat(path, args).await?;
+        self.cache.insert(path, rp.clone().into_metadata());
+        Ok(rp)
+    }
+
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        let res = self.inner.delete(path, args).await;
+        self.cache.invalidate(path);
+        res
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        self.inner.list(path, args).await
+    }
+
+    async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
+        let paths: Vec<String> = args.operation().iter().map(|(p, _)| p.clone()).collect();
+        let res = self.inner.batch(args).await;
+        for path in paths {
+            self.cache.invalidate(&path);
+        }
+        res
+    }
+
+    fn blocking_create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        let res = self.inner.blocking_create_dir(path, args);
+        self.cache.invalidate(path);
+        res
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.inner.blocking_read(path, args)
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        self.cache.invalidate(path);
+        self.inner.blocking_write(path, args).map(|(rp, w)| {
+            (
+                rp,
+                StatCacheWriter::new(w, self.cache.clone(), path.to_string()),
+            )
+        })
+    }
+
+    fn blocking_copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        let res = self.inner.blocking_copy(from, to, args);
+        self.cache.invalidate(to);
+        res
+    }
+
+    fn blocking_rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        let res = self.inner.blocking_rename(from, to, args);
+        self.cache.invalidate(from);
+        self.cache.invalidate(to);
+        res
+    }
+
+    fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        if !Self::is_cacheable(&args) {
+            return self.inner.blocking_stat(path, args);
+        }
+
+        if let Some(meta) = self.cache.get(path) {
+            return Ok(RpStat::new(meta));
+        }
+
+        let rp = self.inner.blocking_stat(path, args)?;
+        self.cache.insert(path, rp.clone().into_metadata());
+        Ok(rp)
+    }
+
+    fn blocking_delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        let res = self.inner.blocking_delete(path, args);
+        self.cache.invalidate(path);
+        res
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        self.inner.blocking_list(path, args)
+    }
+}
+
+/// StatCache is a sharded LRU cache for metadata.
+#[derive(Debug)]
+struct StatCache {
+    shards: Vec<Mutex<StatCacheShard>>,
+    ttl: Duration,
+}
+
+impl StatCache {
+    fn new(capacity: usize, ttl: Duration) -> Self {
+        let shards = capacity.clamp(1, MAX_SHARDS);
+        let shard_capacity = (capacity + shards - 1) / shards;
+
+        Self {
+            shards: (0..shards)
+                .map(|_| Mutex::new(StatCacheShard::new(shard_capacity)))
+                .collect(),
+            ttl,
+        }
+    }
+
+    fn shard(&self, path: &str) -> &Mutex<StatCacheShard> {
+        let mut hasher = DefaultHasher::new();
+        path.hash(&mut hasher);
+        &self.shards[hasher.finish() as usize % self.shards.len()]
+    }
+
+    fn get(&self, path: &str) -> Option<Metadata> {
+        self.shard(path).lock().get(path, Instant::now())
+    }
+
+    fn insert(&self, path: &str, meta: Metadata) {
+        self.shard(path)
+            .lock()
+            .insert(path, meta, Instant::now() + self.ttl)
+    }
+
+    fn invalidate(&self, path: &str) {
+        self.shard(path).lock().remove(path);
+    }
+}
+
+#[derive(Debug)]
+struct StatCacheEntry {
+    meta: Metadata,
+    expire_at: Instant,
+    tick: u64,
+}
+
+/// StatCacheShard tracks the recently used order by an increasing tick.
+#[derive(Debug)]
+struct StatCacheShard {
+    capacity: usize,
+    tick: u64,
+    entries: HashMap<String, StatCacheEntry>,
+    order: BTreeMap<u64, String>,
+}
+
+impl StatCacheShard {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            tick: 0,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+        }
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn get(&mut self, path: &str, now: Instant) -> Option<Metadata> {
+        let expired = self.entries.get(path)?.expire_at <= now;
+        if expired {
+            self.remove(path);
+            return None;
+        }
+
+        let tick = self.next_tick();
+        let entry = self.entries.get_mut(path).expect("entry must exist");
+        self.order.remove(&entry.tick);
+        entry.tick = tick;
+        self.order.insert(tick, path.to_string());
+
+        Some(entry.meta.clone())
+    }
+
+    fn insert(&mut self, path: &str, meta: Metadata, expire_at: Instant) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        self.remove(path);
+        while self.entries.len() >= self.capacity {
+            match self.order.pop_first() {
+                Some((_, p)) => {
+                    self.entries.remove(&p);
+                }
+                None => break,
+            }
+        }
+
+        let tick = self.next_tick();
+        self.order.insert(tick, path.to_string());
+        self.entries.insert(
+            path.to_string(),
+            StatCacheEntry {
+                meta,
+                expire_at,
+                tick,
+            },
+        );
+    }
+
+    fn remove(&mut self, path: &str) {
+        if let Some(entry) = self.entries.remove(path) {
+            self.order.remove(&entry.tick);
+        }
+    }
+}
+
+/// StatCacheWriter will invalidate the cached entry after the write has
+/// been finished.
+pub struct StatCacheWriter<W> {
+    inner: W,
+    cache: Arc<StatCache>,
+    path: String,
+}
+
+impl<W> StatCacheWriter<W> {
+    fn new(inner: W, cache: Arc<StatCache>, path: String) -> Self {
+        Self { inner, cache, path }
+    }
+}
+
+#[async_trait]
+impl<W: oio::Write> oio::Write for StatCacheWriter<W> {
+    fn poll_write(&mut self, cx: &mut Context<'_>, bs: &dyn oio::WriteBuf) -> Poll<Result<usize>> {
+        self.inner.poll_write(cx, bs)
+    }
+
+    fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let res = ready!(self.inner.poll_close(cx));
+        self.cache.invalidate(&self.path);
+        Poll::Ready(res)
+    }
+
+    fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let res = ready!(self.inner.poll_abort(cx));
+        self.cache.invalidate(&self.path);
+        Poll::Ready(res)
+    }
+
+    fn metadata(&self) -> Option<Metadata> {
+        self.inner.metadata()
+    }
+}
+
+impl<W: oio::BlockingWrite> oio::BlockingWrite for StatCacheWriter<W> {
+    fn write(&mut self, bs: &dyn oio::WriteBuf) -> Result<usize> {
+        self.inner.write(bs)
+    }
+
+    fn close(&mut self) -> Result<()> {
+        let res = self.inner.close();
+        self.cache.invalidate(&self.path);
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+
+    use super::*;
+
+    #[derive(Default, Clone)]
+    struct MockBuilder {
+        stats: Arc<AtomicUsize>,
+    }
+
+    impl Builder for MockBuilder {
+        const SCHEME: Scheme = Scheme::Custom("mock");
+        type Accessor = MockService;
+
+        fn from_map(_: HashMap<String, String>) -> Self {
+            Self::default()
+        }
+
+        fn build(&mut self) -> Result<Self::Accessor> {
+            Ok(MockService {
+                stats: self.stats.clone(),
+            })
+        }
+    }
+
+    #[derive(Debug, Clone, Default)]
+    struct MockService {
+        stats: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Accessor for MockService {
+        type Reader = ();
+        type BlockingReader = ();
+        type Writer = ();
+        type BlockingWriter = ();
+        type Pager = ();
+        type BlockingPager = ();
+
+        fn info(&self) -> AccessorInfo {
+            let mut am = AccessorInfo::default();
+            am.set_native_capability(Capability {
+                stat: true,
+                delete: true,
+                ..Default::default()
+            });
+
+            am
+        }
+
+        async fn stat(&self, _: &str, _: OpStat) -> Result<RpStat> {
+            self.stats.fetch_add(1, Ordering::SeqCst);
+            Ok(RpStat::new(Metadata::new(EntryMode::FILE)))
+        }
+
+        async fn delete(&self, _: &str, _: OpDelete) -> Result<RpDelete> {
+            Ok(RpDelete::default())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stat_cached() -> Result<()> {
+        let builder = MockBuilder::default();
+        let op = Operator::new(builder.clone())?
+            .layer(StatCacheLayer::default())
+            .finish();
+
+        for _ in 0..10 {
+            op.stat("hello").await?;
+        }
+        assert_eq!(builder.stats.load(Ordering::SeqCst), 1);
+
+        op.stat_with("hello").version("v1").await?;
+        assert_eq!(builder.stats.load(Ordering::SeqCst), 2);
+
+        op.delete("hello").await?;
+        op.stat("hello").await?;
+        assert_eq!(builder.stats.load(Ordering::SeqCst), 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stat_cache_expire() {
+        let mut shard = StatCacheShard::new(2);
+        let now = Instant::now();
+
+        shard.insert("a", Metadata::new(EntryMode::FILE), now);
+        assert!(shard.get("a", now).is_none());
+        assert!(shard.entries.is_empty());
+        assert!(shard.order.is_empty());
+    }
+
+    #[test]
+    fn test_stat_cache_lru() {
+        let mut shard = StatCacheShard::new(2);
+        let now = Instant::now();
+        let expire_at = now + Duration::from_secs(60);
+
+        shard.insert("a", Metadata::new(EntryMode::FILE), expire_at);
+        shard.insert("b", Metadata::new(EntryMode::FILE), expire_at);
+        // Touch `a` so that `b` becomes the least recently used one.
+        assert!(shard.get("a", now).is_some());
+        shard.insert("c", Metadata::new(EntryMode::FILE), expire_at);
+
+        assert!(shard.get("a", now).is_some());
+        assert!(shard.get("b", now).is_none());
+        assert!(shard.get("c", now).is_some());
+        assert_eq!(shard.entries.len(), shard.order.len());
+    }
+}
@@ -15,14 +15,17 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use std::future::Future;
 use std::io::SeekFrom;
+use std::pin::Pin;
+use std::task::ready;
 use std::task::Context;
 use std::task::Poll;
 use std::time::Duration;
-use std::time::Instant;
 
 use async_trait::async_trait;
 use bytes::Bytes;
+use tokio::time::Sleep;
 
 use crate::raw::oio::PageOperation;
 use crate::raw::oio::ReadOperation;
@@ -35,10 +38,20 @@ use crate::*;
 /// # Notes
 ///
 /// - For IO operations like `read`, `write`, we will set a timeout
-///   for each single IO operation.
+///   for each single IO operation, so a hung connection will be detected
+///   in the whole streaming read or write, not only while waiting for the
+///   initial response.
 /// - For other operations like `stat`, and `delete`, the timeout is for the whole
 ///   operation.
 ///
+/// The timeout of `read`, `write`, `list` and `stat` could be set separately,
+/// for example lists could take longer than others. Operations without a
+/// specific timeout will use the default one.
+///
+/// On expiry, the underlying future will be dropped to cancel the operation,
+/// and an `Unexpected` error marked as temporary will be returned, so
+/// [`RetryLayer`](super::RetryLayer) could retry it.
+///
 /// Besides, we will also set a slow speed for each IO operation. If the IO
 /// operation's speed is lower than the slow speed, we will return a timeout error
 /// instead of kept waiting for it.
@@ -59,6 +72,8 @@ use crate::*;
 /// # Examples
 ///
 /// ```
+/// use std::time::Duration;
+///
 /// use anyhow::Result;
 /// use opendal::layers::TimeoutLayer;
 /// use opendal::services;
@@ -67,12 +82,20 @@ use crate::*;
 ///
 /// let _ = Operator::new(services::Memory::default())
 ///     .expect("must init")
-///     .layer(TimeoutLayer::default())
+///     .layer(
+///         TimeoutLayer::default()
+///             .with_timeout(Duration::from_secs(10))
+///             .with_list_timeout(Duration::from_secs(60)),
+///     )
 ///     .finish();
 /// ```
 #[derive(Clone)]
 pub struct TimeoutLayer {
     timeout: Duration,
+    read_timeout: Option<Duration>,
+    write_timeout: Option<Duration>,
+    list_timeout: Option<Duration>,
+    stat_timeout: Option<Duration>,
     speed: u64,
 }
 
@@ -80,6 +103,10 @@ impl Default for TimeoutLayer {
     fn default() -> Self {
         Self {
             timeout: Duration::from_secs(60),
+            read_timeout: None,
+            write_timeout: None,
+            list_timeout: None,
+            stat_timeout: None,
             speed: 1024,
         }
     }
@@ -95,11 +122,38 @@ impl TimeoutLayer {
     }
 
     /// Set timeout for TimeoutLayer with given value.
+    ///
+    /// This timeout will be used by operations that don't have a specific
+    /// timeout.
     pub fn with_timeout(mut self, timeout: Duration) -> Self {
         self.timeout = timeout;
         self
     }
 
+    /// Set timeout for `read` and every IO operation of the returned reader.
+    pub fn with_read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = Some(timeout);
+        self
+    }
+
+    /// Set timeout for `write` and every IO operation of the returned writer.
+    pub fn with_write_timeout(mut self, timeout: Duration) -> Self {
+        self.write_timeout = Some(timeout);
+        self
+    }
+
+    /// Set timeout for `list` and every page of the returned pager.
+    pub fn with_list_timeout(mut self, timeout: Duration) -> Self {
+        self.list_timeout = Some(timeout);
+        self
+    }
+
+    /// Set timeout for `stat`.
+    pub fn with_stat_timeout(mut self, timeout: Duration) -> Self {
+        self.stat_timeout = Some(timeout);
+        self
+    }
+
     /// Set speed for TimeoutLayer with given value.
     ///
     /// # Notes
@@ -126,6 +180,10 @@ impl<A: Accessor> Layer<A> for TimeoutLayer {
             inner,
 
             timeout: self.timeout,
+            read_timeout: self.read_timeout.unwrap_or(self.timeout),
+            write_timeout: self.write_timeout.unwrap_or(self.timeout),
+            list_timeout: self.list_timeout.unwrap_or(self.timeout),
+            stat_timeout: self.stat_timeout.unwrap_or(self.timeout),
             speed: self.speed,
         }
     }
@@ -136,9 +194,26 @@ pub struct TimeoutAccessor<A: Accessor> {
     inner: A,
 
     timeout: Duration,
+    read_timeout: Duration,
+    write_timeout: Duration,
+    list_timeout: Duration,
+    stat_timeout: Duration,
     speed: u64,
 }
 
+impl<A: Accessor> TimeoutAccessor<A> {
+    async fn timeout<F: Future<Output = Result<T>>, T>(
+        &self,
+        timeout: Duration,
+        op: Operation,
+        fut: F,
+    ) -> Result<T> {
+        tokio::time::timeout(timeout, fut)
+            .await
+            .map_err(|_| new_timeout_error(op, timeout))?
+    }
+}
+
 #[async_trait]
 impl<A: Accessor> LayeredAccessor for TimeoutAccessor<A> {
     type Inner = A;
@@ -153,40 +228,93 @@ impl<A: Accessor> LayeredAccessor for TimeoutAccessor<A> {
         &self.inner
     }
 
+    async fn create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        self.timeout(
+            self.timeout,
+            Operation::CreateDir,
+            self.inner.create_dir(path, args),
+        )
+        .await
+    }
+
     async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
-        tokio::time::timeout(self.timeout, self.inner.read(path, args))
-            .await
-            .map_err(|_| {
-                Error::new(ErrorKind::Unexpected, "operation timeout")
-                    .with_operation(Operation::Read)
-                    .with_context("timeout", self.timeout.as_secs_f64().to_string())
-                    .set_temporary()
-            })?
-            .map(|(rp, r)| (rp, TimeoutWrapper::new(r, self.timeout, self.speed)))
+        self.timeout(
+            self.read_timeout,
+            Operation::Read,
+            self.inner.read(path, args),
+        )
+        .await
+        .map(|(rp, r)| (rp, TimeoutWrapper::new(r, self.read_timeout, self.speed)))
     }
 
     async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
-        tokio::time::timeout(self.timeout, self.inner.write(path, args))
-            .await
-            .map_err(|_| {
-                Error::new(ErrorKind::Unexpected, "operation timeout")
-                    .with_operation(Operation::Write)
-                    .with_context("timeout", self.timeout.as_secs_f64().to_string())
-                    .set_temporary()
-            })?
-            .map(|(rp, r)| (rp, TimeoutWrapper::new(r, self.timeout, self.speed)))
+        self.timeout(
+            self.write_timeout,
+            Operation::Write,
+            self.inner.write(path, args),
+        )
+        .await
+        .map(|(rp, r)| (rp, TimeoutWrapper::new(r, self.write_timeout, self.speed)))
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        self.timeout(
+            self.timeout,
+            Operation::Copy,
+            self.inner.copy(from, to, args),
+        )
+        .await
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        self.timeout(
+            self.timeout,
+            Operation::Rename,
+            self.inner.rename(from, to, args),
+        )
+        .await
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        self.timeout(
+            self.stat_timeout,
+            Operation::Stat,
+            self.inner.stat(path, args),
+        )
+        .await
+    }
+
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        self.timeout(
+            self.timeout,
+            Operation::Delete,
+            self.inner.delete(path, args),
+        )
+        .await
     }
 
     async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
-        tokio::time::timeout(self.timeout, self.inner.list(path, args))
+        self.timeout(
+            self.list_timeout,
+            Operation::List,
+            self.inner.list(path, args),
+        )
+        .await
+        .map(|(rp, r)| (rp, TimeoutWrapper::new(r, self.list_timeout, self.speed)))
+    }
+
+    async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
+        self.timeout(self.timeout, Operation::Batch, self.inner.batch(args))
             .await
-            .map_err(|_| {
-                Error::new(ErrorKind::Unexpected, "operation timeout")
-                    .with_operation(Operation::List)
-                    .with_context("timeout", self.timeout.as_secs_f64().to_string())
-                    .set_temporary()
-            })?
-            .map(|(rp, r)| (rp, TimeoutWrapper::new(r, self.timeout, self.speed)))
+    }
+
+    async fn presign(&self, path: &str, args: OpPresign) -> Result<RpPresign> {
+        self.timeout(
+            self.timeout,
+            Operation::Presign,
+            self.inner.presign(path, args),
+        )
+        .await
     }
 
     fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
@@ -202,6 +330,13 @@ impl<A: Accessor> LayeredAccessor for TimeoutAccessor<A> {
     }
 }
 
+fn new_timeout_error(op: impl Into<&'static str>, timeout: Duration) -> Error {
+    Error::new(ErrorKind::Unexpected, "operation timeout")
+        .with_operation(op)
+        .with_context("timeout", timeout.as_secs_f64().to_string())
+        .set_temporary()
+}
+
 pub struct TimeoutWrapper<R> {
     inner: R,
 
@@ -209,7 +344,9 @@ pub struct TimeoutWrapper<R> {
     #[allow(dead_code)]
     speed: u64,
 
-    start: Option<Instant>,
+    /// The timer of current IO operation, it will be reset once the
+    /// operation returns ready.
+    sleep: Option<Pin<Box<Sleep>>>,
 }
 
 impl<R> TimeoutWrapper<R> {
@@ -218,7 +355,7 @@ impl<R> TimeoutWrapper<R> {
             inner,
             timeout,
             speed,
-            start: None,
+            sleep: None,
         }
     }
 
@@ -228,190 +365,104 @@ impl<R> TimeoutWrapper<R> {
 
         timeout.max(self.timeout)
     }
-}
 
-impl<R: oio::Read> oio::Read for TimeoutWrapper<R> {
-    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
-        match self.start {
-            Some(start) => {
-                if start.elapsed() > self.timeout {
-                    // Clean up the start time before return ready.
-                    self.start = None;
-
-                    return Poll::Ready(Err(Error::new(
-                        ErrorKind::Unexpected,
-                        "operation timeout",
-                    )
-                    .with_operation(ReadOperation::Read)
-                    .with_context("timeout", self.timeout.as_secs_f64().to_string())
-                    .set_temporary()));
-                }
-            }
-            None => {
-                self.start = Some(Instant::now());
-            }
-        }
+    /// Poll the timer of current IO operation.
+    ///
+    /// The timer will be started at the first call, and it will register
+    /// the waker so that we will be woken up even if the inner IO hangs.
+    fn poll_timeout(&mut self, cx: &mut Context<'_>, op: &'static str) -> Poll<Error> {
+        let timeout = self.timeout;
+        let sleep = self
+            .sleep
+            .get_or_insert_with(|| Box::pin(tokio::time::sleep(timeout)));
+
+        ready!(sleep.as_mut().poll(cx));
+        // Clean up the timer before return ready.
+        self.sleep = None;
+        Poll::Ready(new_timeout_error(op, timeout))
+    }
 
-        match self.inner.poll_read(cx, buf) {
-            Poll::Pending => Poll::Pending,
+    /// Poll the inner IO operation with the timer.
+    fn poll_with_timeout<T>(
+        &mut self,
+        cx: &mut Context<'_>,
+        op: &'static str,
+        f: impl FnOnce(&mut R, &mut Context<'_>) -> Poll<T>,
+        on_timeout: impl FnOnce(Error) -> T,
+    ) -> Poll<T> {
+        match f(&mut self.inner, cx) {
             Poll::Ready(v) => {
-                self.start = None;
+                self.sleep = None;
                 Poll::Ready(v)
             }
+            Poll::Pending => {
+                let err = ready!(self.poll_timeout(cx, op));
+                Poll::Ready(on_timeout(err))
+            }
         }
     }
+}
 
-    fn poll_seek(&mut self, cx: &mut Context<'_>, pos: SeekFrom) -> Poll<Result<u64>> {
-        match self.start {
-            Some(start) => {
-                if start.elapsed() > self.timeout {
-                    // Clean up the start time before return ready.
-                    self.start = None;
-
-                    return Poll::Ready(Err(Error::new(
-                        ErrorKind::Unexpected,
-                        "operation timeout",
-                    )
-                    .with_operation(ReadOperation::Seek)
-                    .with_context("timeout", self.timeout.as_secs_f64().to_string())
-                    .set_temporary()));
-                }
-            }
-            None => {
-                self.start = Some(Instant::now());
-            }
-        }
+impl<R: oio::Read> oio::Read for TimeoutWrapper<R> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        self.poll_with_timeout(
+            cx,
+            ReadOperation::Read.into_static(),
+            |r, cx| r.poll_read(cx, buf),
+            Err,
+        )
+    }
 
-        match self.inner.poll_seek(cx, pos) {
-            Poll::Pending => Poll::Pending,
-            Poll::Ready(v) => {
-                self.start = None;
-                Poll::Ready(v)
-            }
-        }
+    fn poll_seek(&mut self, cx: &mut Context<'_>, pos: SeekFrom) -> Poll<Result<u64>> {
+        self.poll_with_timeout(
+            cx,
+            ReadOperation::Seek.into_static(),
+            |r, cx| r.poll_seek(cx, pos),
+            Err,
+        )
     }
 
     fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
-        match self.start {
-            Some(start) => {
-                if start.elapsed() > self.timeout {
-                    // Clean up the start time before return ready.
-                    self.start = None;
-
-                    return Poll::Ready(Some(Err(Error::new(
-                        ErrorKind::Unexpected,
-                        "operation timeout",
-                    )
-                    .with_operation(ReadOperation::Next)
-                    .with_context("timeout", self.timeout.as_secs_f64().to_string())
-                    .set_temporary())));
-                }
-            }
-            None => {
-                self.start = Some(Instant::now());
-            }
-        }
-
-        match self.inner.poll_next(cx) {
-            Poll::Pending => Poll::Pending,
-            Poll::Ready(v) => {
-                self.start = None;
-                Poll::Ready(v)
-            }
-        }
+        self.poll_with_timeout(
+            cx,
+            ReadOperation::Next.into_static(),
+            |r, cx| r.poll_next(cx),
+            |err| Some(Err(err)),
+        )
     }
 }
 
 #[async_trait]
 impl<R: oio::Write> oio::Write for TimeoutWrapper<R> {
     fn poll_write(&mut self, cx: &mut Context<'_>, bs: &dyn oio::WriteBuf) -> Poll<Result<usize>> {
-        match self.start {
-            Some(start) => {
-                if start.elapsed() > self.timeout {
-                    // Clean up the start time before return ready.
-                    self.start = None;
-
-                    return Poll::Ready(Err(Error::new(
-                        ErrorKind::Unexpected,
-                        "operation timeout",
-                    )
-                    .with_operation(WriteOperation::Write)
-                    .with_context("timeout", self.timeout.as_secs_f64().to_string())
-                    .set_temporary()));
-                }
-            }
-            None => {
-                self.start = Some(Instant::now());
-            }
-        }
-
-        match self.inner.poll_write(cx, bs) {
-            Poll::Pending => Poll::Pending,
-            Poll::Ready(v) => {
-                self.start = None;
-                Poll::Ready(v)
-            }
-        }
+        self.poll_with_timeout(
+            cx,
+            WriteOperation::Write.into_static(),
+            |w, cx| w.poll_write(cx, bs),
+            Err,
+        )
     }
 
     fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
-        match self.start {
-            Some(start) => {
-                if start.elapsed() > self.timeout {
-                    // Clean up the start time before return ready.
-                    self.start = None;
-
-                    return Poll::Ready(Err(Error::new(
-                        ErrorKind::Unexpected,
-                        "operation timeout",
-                    )
-                    .with_operation(WriteOperation::Abort)
-                    .with_context("timeout", self.timeout.as_secs_f64().to_string())
-                    .set_temporary()));
-                }
-            }
-            None => {
-                self.start = Some(Instant::now());
-            }
-        }
-
-        match self.inner.poll_abort(cx) {
-            Poll::Pending => Poll::Pending,
-            Poll::Ready(v) => {
-                self.start = None;
-                Poll::Ready(v)
-            }
-        }
+        self.poll_with_timeout(
+            cx,
+            WriteOperation::Abort.into_static(),
+            |w, cx| w.poll_abort(cx),
+            Err,
+        )
     }
 
     fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
-        match self.start {
-            Some(start) => {
-                if start.elapsed() > self.timeout {
-                    // Clean up the start time before return ready.
-                    self.start = None;
-
-                    return Poll::Ready(Err(Error::new(
-                        ErrorKind::Unexpected,
-                        "operation timeout",
-                    )
-                    .with_operation(WriteOperation::Close)
-                    .with_context("timeout", self.timeout.as_secs_f64().to_string())
-                    .set_temporary()));
-                }
-            }
-            None => {
-                self.start = Some(Instant::now());
-            }
-        }
+        self.poll_with_timeout(
+            cx,
+            WriteOperation::Close.into_static(),
+            |w, cx| w.poll_close(cx),
+            Err,
+        )
+    }
 
-        match self.inner.poll_close(cx) {
-            Poll::Pending => Poll::Pending,
-            Poll::Ready(v) => {
-                self.start = None;
-                Poll::Ready(v)
-            }
-        }
+    fn metadata(&self) -> Option<Metadata> {
+        self.inner.metadata()
     }
 }
 
@@ -420,11 +471,53 @@ impl<R: oio::Page> oio::Page for TimeoutWrapper<R> {
     async fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
         tokio::time::timeout(self.timeout, self.inner.next())
             .await
-            .map_err(|_| {
-                Error::new(ErrorKind::Unexpected, "operation timeout")
-                    .with_operation(PageOperation::Next)
-                    .with_context("timeout", self.timeout.as_secs_f64().to_string())
-                    .set_temporary()
-            })?
+            .map_err(|_| new_timeout_error(PageOperation::Next, self.timeout))?
+    }
+
+    fn continuation_token(&self) -> Option<String> {
+        self.inner.continuation_token()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::raw::oio::ReadExt;
+
+    /// A reader that never become ready and never wake up the task.
+    struct HangingReader;
+
+    impl oio::Read for HangingReader {
+        fn poll_read(&mut self, _: &mut Context<'_>, _: &mut [u8]) -> Poll<Result<usize>> {
+            Poll::Pending
+        }
+
+        fn poll_seek(&mut self, _: &mut Context<'_>, _: SeekFrom) -> Poll<Result<u64>> {
+            Poll::Pending
+        }
+
+        fn poll_next(&mut self, _: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+            Poll::Pending
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_timeout() {
+        let mut r = TimeoutWrapper::new(HangingReader, Duration::from_millis(10), 1024);
+
+        let err = r.read(&mut [0; 8]).await.expect_err("read must timeout");
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+        assert!(err.is_temporary());
+
+        // Timer should be reset for the next IO operation.
+        let err = r
+            .next()
+            .await
+            .expect("next must return")
+            .expect_err("next must timeout");
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+        assert!(err.is_temporary());
     }
 }
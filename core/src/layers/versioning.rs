@@ -0,0 +1,467 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::future::Future;
+use std::sync::atomic::AtomicI64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::task::ready;
+use std::task::Context;
+use std::task::Poll;
+
+use async_trait::async_trait;
+use bytes::Buf;
+use bytes::Bytes;
+use chrono::Utc;
+use futures::future::BoxFuture;
+
+use crate::raw::oio::BlockingRead;
+use crate::raw::oio::BlockingWrite;
+use crate::raw::oio::ReadExt;
+use crate::raw::oio::WriteExt;
+use crate::raw::*;
+use crate::*;
+
+/// The content prefix of pointer files.
+const POINTER_PREFIX: &str = "opendal-versioning:";
+/// Pointer files are always smaller than this size, larger files will not
+/// be read to check.
+const POINTER_MAX_SIZE: u64 = 64;
+
+/// Add versioning for services without native object versioning.
+///
+/// # Notes
+///
+/// Writing `path` will write the content to `path.v{version}` instead, and
+/// then update the pointer file at `path` to the new version after the
+/// content has been closed successfully. Reading or stating `path` will
+/// follow the pointer to the latest version. `version` is the microseconds
+/// since unix epoch padded to 20 digits, so that versions sort in order.
+///
+/// Older versions could be listed by [`VersioningLayer::list_versions`] and
+/// read by [`VersioningLayer::read_version`].
+///
+/// This layer is implemented on top of normal files, so:
+///
+/// - It's not consistent under concurrent writes. Updating the pointer is
+///   not atomic with writing the content, the pointer of the writer closed
+///   last wins even if its version is older.
+/// - Every `read` and `stat` costs an extra `stat` on the pointer, and an
+///   extra `read` if the pointer file is small enough.
+/// - `list_versions` lists the whole parent dir of `path`, which could be
+///   expensive for dirs with many files.
+/// - Version files are visible to `list`, and `delete` only deletes the
+///   pointer, older versions should be deleted by their own paths.
+/// - Files that are not written via this layer will be read as is.
+/// - Append is not supported.
+///
+/// # Examples
+///
+/// ```
+/// use anyhow::Result;
+/// use opendal::layers::VersioningLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// let _ = Operator::new(services::Memory::default())
+///     .expect("must init")
+///     .layer(VersioningLayer::default())
+///     .finish();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct VersioningLayer {
+    last_version: Arc<AtomicI64>,
+}
+
+impl VersioningLayer {
+    /// List all versions of given path in ascending order, the last one is
+    /// the latest.
+    ///
+    /// The parent dir of path will be listed to find versions.
+    pub async fn list_versions(op: &Operator, path: &str) -> Result<Vec<String>> {
+        let path = normalize_path(path);
+        let parent = get_parent(&path);
+        let name = get_basename(&path);
+
+        let mut versions: Vec<String> = op
+            .list(parent)
+            .await?
+            .into_iter()
+            .filter_map(|entry| parse_version_name(entry.name(), name).map(|v| v.to_string()))
+            .collect();
+        versions.sort();
+
+        Ok(versions)
+    }
+
+    /// Read the content of given version of path.
+    pub async fn read_version(op: &Operator, path: &str, version: &str) -> Result<Vec<u8>> {
+        op.read(&version_path(path, version)).await
+    }
+}
+
+impl<A: Accessor> Layer<A> for VersioningLayer {
+    type LayeredAccessor = VersioningAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        VersioningAccessor {
+            inner: Arc::new(inner),
+            last_version: self.last_version.clone(),
+        }
+    }
+}
+
+fn version_path(path: &str, version: &str) -> String {
+    format!("{path}.v{version}")
+}
+
+/// Parse version from the name of version file of `name`.
+fn parse_version_name<'a>(entry: &'a str, name: &str) -> Option<&'a str> {
+    let version = entry.strip_prefix(name)?.strip_prefix(".v")?;
+    if version.is_empty() || !version.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some(version)
+}
+
+fn parse_pointer(bs: &[u8]) -> Option<String> {
+    let version = std::str::from_utf8(bs).ok()?.strip_prefix(POINTER_PREFIX)?;
+    if version.is_empty() || !version.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some(version.to_string())
+}
+
+#[derive(Debug, Clone)]
+pub struct VersioningAccessor<A: Accessor> {
+    inner: Arc<A>,
+    last_version: Arc<AtomicI64>,
+}
+
+impl<A: Accessor> VersioningAccessor<A> {
+    /// Generate a new version which is always larger than the previous one.
+    fn next_version(&self) -> String {
+        let now = Utc::now().timestamp_micros();
+        let prev = self
+            .last_version
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |v| Some(now.max(v + 1)))
+            .expect("closure always returns some");
+        format!("{:020}", now.max(prev + 1))
+    }
+
+    fn check_write_args(path: &str, args: &OpWrite) -> Result<()> {
+        if args.append() {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "append is not supported by versioning layer",
+            )
+            .with_operation(Operation::Write)
+            .with_context("path", path));
+        }
+        Ok(())
+    }
+
+    /// Resolve the version that pointer file of path points to.
+    ///
+    /// Returns `None` if path is not a pointer.
+    async fn resolve(&self, path: &str) -> Result<Option<String>> {
+        if path.ends_with('/') {
+            return Ok(None);
+        }
+
+        let meta = self.inner.stat(path, OpStat::new()).await?.into_metadata();
+        if !meta.mode().is_file() || meta.content_length() > POINTER_MAX_SIZE {
+            return Ok(None);
+        }
+
+        let (_, mut r) = self.inner.read(path, OpRead::new()).await?;
+        let mut bs = Vec::with_capacity(POINTER_MAX_SIZE as usize);
+        let mut buf = [0; POINTER_MAX_SIZE as usize];
+        loop {
+            let n = r.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+            bs.extend_from_slice(&buf[..n]);
+            if bs.len() as u64 > POINTER_MAX_SIZE {
+                return Ok(None);
+            }
+        }
+
+        Ok(parse_pointer(&bs))
+    }
+
+    fn blocking_resolve(&self, path: &str) -> Result<Option<String>> {
+        if path.ends_with('/') {
+            return Ok(None);
+        }
+
+        let meta = self
+            .inner
+            .blocking_stat(path, OpStat::new())?
+            .into_metadata();
+        if !meta.mode().is_file() || meta.content_length() > POINTER_MAX_SIZE {
+            return Ok(None);
+        }
+
+        let (_, mut r) = self.inner.blocking_read(path, OpRead::new())?;
+        let mut bs = Vec::with_capacity(POINTER_MAX_SIZE as usize);
+        let mut buf = [0; POINTER_MAX_SIZE as usize];
+        loop {
+            let n = r.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            bs.extend_from_slice(&buf[..n]);
+            if bs.len() as u64 > POINTER_MAX_SIZE {
+                return Ok(None);
+            }
+        }
+
+        Ok(parse_pointer(&bs))
+    }
+}
+
+#[async_trait]
+impl<A: Accessor> LayeredAccessor for VersioningAccessor<A> {
+    type Inner = A;
+    type Reader = A::Reader;
+    type BlockingReader = A::BlockingReader;
+    type Writer = VersioningWriter<A, A::Writer>;
+    type BlockingWriter = VersioningWriter<A, A::BlockingWriter>;
+    type Pager = A::Pager;
+    type BlockingPager = A::BlockingPager;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        match self.resolve(path).await? {
+            Some(version) => self.inner.read(&version_path(path, &version), args).await,
+            None => self.inner.read(path, args).await,
+        }
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        Self::check_write_args(path, &args)?;
+
+        let version = self.next_version();
+        let (rp, w) = self
+            .inner
+            .write(&version_path(path, &version), args)
+            .await?;
+
+        Ok((
+            rp,
+            VersioningWriter::new(w, self.inner.clone(), path, version),
+        ))
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        match self.resolve(path).await? {
+            Some(version) => {
+                let rp = self.inner.stat(&version_path(path, &version), args).await?;
+                Ok(rp.map_metadata(|meta| meta.with_version(version)))
+            }
+            None => self.inner.stat(path, args).await,
+        }
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        self.inner.list(path, args).await
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        match self.blocking_resolve(path)? {
+            Some(version) => self
+                .inner
+                .blocking_read(&version_path(path, &version), args),
+            None => self.inner.blocking_read(path, args),
+        }
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        Self::check_write_args(path, &args)?;
+
+        let version = self.next_version();
+        let (rp, w) = self
+            .inner
+            .blocking_write(&version_path(path, &version), args)?;
+
+        Ok((
+            rp,
+            VersioningWriter::new(w, self.inner.clone(), path, version),
+        ))
+    }
+
+    fn blocking_stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        match self.blocking_resolve(path)? {
+            Some(version) => {
+                let rp = self
+                    .inner
+                    .blocking_stat(&version_path(path, &version), args)?;
+                Ok(rp.map_metadata(|meta| meta.with_version(version)))
+            }
+            None => self.inner.blocking_stat(path, args),
+        }
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        self.inner.blocking_list(path, args)
+    }
+}
+
+/// VersioningWriter writes the content into version file, and updates the
+/// pointer after content has been closed.
+pub struct VersioningWriter<A: Accessor, W> {
+    inner: W,
+    acc: Arc<A>,
+    path: String,
+    version: String,
+    pointer: Option<BoxFuture<'static, Result<()>>>,
+}
+
+/// # Safety
+///
+/// We will only take `&mut Self` reference for VersioningWriter.
+unsafe impl<A: Accessor, W: Send> Sync for VersioningWriter<A, W> {}
+
+impl<A: Accessor, W> VersioningWriter<A, W> {
+    fn new(inner: W, acc: Arc<A>, path: &str, version: String) -> Self {
+        Self {
+            inner,
+            acc,
+            path: path.to_string(),
+            version,
+            pointer: None,
+        }
+    }
+
+    fn pointer_content(&self) -> Bytes {
+        Bytes::from(format!("{POINTER_PREFIX}{}", self.version))
+    }
+}
+
+#[async_trait]
+impl<A: Accessor> oio::Write for VersioningWriter<A, A::Writer> {
+    fn poll_write(&mut self, cx: &mut Context<'_>, bs: &dyn oio::WriteBuf) -> Poll<Result<usize>> {
+        self.inner.poll_write(cx, bs)
+    }
+
+    fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        if self.pointer.is_none() {
+            ready!(self.inner.poll_close(cx))?;
+
+            let acc = self.acc.clone();
+            let path = self.path.clone();
+            let mut bs = self.pointer_content();
+            self.pointer = Some(Box::pin(async move {
+                let (_, mut w) = acc.write(&path, OpWrite::new()).await?;
+                while bs.has_remaining() {
+                    let n = w.write(&bs).await?;
+                    bs.advance(n);
+                }
+                w.close().await
+            }));
+        }
+
+        let fut = self.pointer.as_mut().expect("pointer future must be valid");
+        let res = ready!(fut.as_mut().poll(cx));
+        self.pointer = None;
+        Poll::Ready(res)
+    }
+
+    fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.inner.poll_abort(cx)
+    }
+
+    fn metadata(&self) -> Option<Metadata> {
+        self.inner
+            .metadata()
+            .map(|meta| meta.with_version(self.version.clone()))
+    }
+}
+
+impl<A: Accessor> oio::BlockingWrite for VersioningWriter<A, A::BlockingWriter> {
+    fn write(&mut self, bs: &dyn oio::WriteBuf) -> Result<usize> {
+        self.inner.write(bs)
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.inner.close()?;
+
+        let (_, mut w) = self.acc.blocking_write(&self.path, OpWrite::new())?;
+        let mut bs = self.pointer_content();
+        while bs.has_remaining() {
+            let n = w.write(&bs)?;
+            bs.advance(n);
+        }
+        w.close()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::Memory;
+
+    #[test]
+    fn test_parse_version_name() {
+        let cases = vec![
+            (
+                "version file",
+                "a.txt.v00000000000000000001",
+                Some("00000000000000000001"),
+            ),
+            ("file itself", "a.txt", None),
+            ("other file", "b.txt.v1", None),
+            ("not digits", "a.txt.vabc", None),
+            ("empty version", "a.txt.v", None),
+        ];
+
+        for (desc, entry, expected) in cases {
+            assert_eq!(parse_version_name(entry, "a.txt"), expected, "{desc}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_versioning() -> Result<()> {
+        let op = Operator::new(Memory::default())?
+            .layer(VersioningLayer::default())
+            .finish();
+
+        op.write("dir/file", "v1").await?;
+        op.write("dir/file", "v2").await?;
+
+        assert_eq!(op.read("dir/file").await?, b"v2");
+        assert_eq!(op.stat("dir/file").await?.content_length(), 2);
+
+        let versions = VersioningLayer::list_versions(&op, "dir/file").await?;
+        assert_eq!(versions.len(), 2);
+        assert_eq!(
+            VersioningLayer::read_version(&op, "dir/file", &versions[0]).await?,
+            b"v1"
+        );
+        assert_eq!(
+            op.stat("dir/file").await?.version(),
+            Some(versions[1].as_str())
+        );
+
+        Ok(())
+    }
+}
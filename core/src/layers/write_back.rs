@@ -0,0 +1,686 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::sync::Arc;
+use std::task::ready;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use backon::ExponentialBuilder;
+use backon::Retryable;
+use bytes::Buf;
+use bytes::Bytes;
+use futures::channel::mpsc;
+use futures::channel::oneshot;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use futures::StreamExt;
+use log::warn;
+use parking_lot::Mutex;
+use tokio::runtime::Handle;
+
+use crate::raw::oio::ReadExt;
+use crate::raw::oio::WriteBuf;
+use crate::raw::oio::WriteExt;
+use crate::raw::*;
+use crate::services::Fs;
+use crate::*;
+
+/// Callback that will be called while a write failed to flush.
+type FlushErrorCallback = Arc<dyn Fn(&str, &Error) + Send + Sync>;
+
+/// Add a write-back cache on local disk.
+///
+/// Writes will be persisted into files under `root` first and return
+/// once the local file has been closed. The cached files will be flushed
+/// into the inner service by a background task in the order they have
+/// been closed.
+///
+/// # Durability
+///
+/// A successful `close` only means the content has been written into
+/// local disk, it doesn't mean the content has been written into the
+/// inner service.
+///
+/// - Failed flushes will be retried for temporary errors, errors that are
+///   still failing will be passed to the callback set by
+///   [`WriteBackLayer::with_error_callback`] and returned by
+///   [`WriteBackLayer::sync`]. The cached file will be kept on disk, but
+///   the path will no longer be served from it, reads and stats will go to
+///   the inner service again.
+/// - Pending writes will be lost if the process exits before they have
+///   been flushed. Please call [`WriteBackLayer::sync`] before exit.
+///
+/// # Notes
+///
+/// - The background task will be spawned on current tokio runtime, so
+///   writes must be closed inside a tokio runtime.
+/// - Reads and stats of paths that haven't been flushed will be served
+///   from the local cache. `list` will only return flushed entries.
+/// - `delete`, `copy` and `rename` of pending paths will wait for all
+///   pending writes to be flushed first.
+/// - Appends, writes with `if_not_exists` and blocking writes will
+///   bypass the cache.
+/// - Blocking reads and stats bypass the cache too, they will not see
+///   writes that haven't been flushed.
+/// - The cache index lives in memory, existing files under `root` will
+///   not be reused. Please use a dedicated dir.
+///
+/// # Examples
+///
+/// ```no_run
+/// use anyhow::Result;
+/// use opendal::layers::WriteBackLayer;
+/// use opendal::services;
+/// use opendal::Operator;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<()> {
+/// let layer = WriteBackLayer::new("/tmp/opendal-write-back")?.with_max_retries(5);
+/// let op = Operator::new(services::Memory::default())?
+///     .layer(layer.clone())
+///     .finish();
+///
+/// op.write("test", "Hello, World!").await?;
+/// // Wait for all pending writes to be flushed.
+/// layer.sync().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct WriteBackLayer {
+    max_retries: usize,
+    callback: Option<FlushErrorCallback>,
+    state: Arc<WriteBackState>,
+}
+
+impl WriteBackLayer {
+    /// Create a new write back layer which stores pending writes under `root`.
+    pub fn new(root: &str) -> Result<Self> {
+        let mut builder = Fs::default();
+        builder.root(root);
+        let op = Operator::new(builder)?.finish();
+
+        Ok(Self {
+            max_retries: 3,
+            callback: None,
+            state: Arc::new(WriteBackState::new(op)),
+        })
+    }
+
+    /// Set the max retry times of flushing a write.
+    ///
+    /// Default to 3.
+    pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Set the callback that will be called with path and error while a
+    /// write failed to flush after all retries.
+    pub fn with_error_callback(mut self, f: impl Fn(&str, &Error) + Send + Sync + 'static) -> Self {
+        self.callback = Some(Arc::new(f));
+        self
+    }
+
+    /// Wait for all pending writes to be flushed.
+    ///
+    /// Returns error if any write failed to flush since last `sync`.
+    pub async fn sync(&self) -> Result<()> {
+        self.state.wait().await;
+
+        let errors = std::mem::take(&mut *self.state.errors.lock());
+        let mut errors = errors.into_iter();
+        match errors.next() {
+            None => Ok(()),
+            Some((path, err)) => {
+                let mut paths = vec![path];
+                paths.extend(errors.map(|(path, _)| path));
+
+                Err(
+                    Error::new(ErrorKind::Unexpected, "some writes failed to flush")
+                        .with_operation("WriteBackLayer::sync")
+                        .with_context("failed", paths.len().to_string())
+                        .with_context("paths", paths.join(", "))
+                        .set_source(err),
+                )
+            }
+        }
+    }
+}
+
+impl<A: Accessor> Layer<A> for WriteBackLayer {
+    type LayeredAccessor = WriteBackAccessor<A>;
+
+    fn layer(&self, inner: A) -> Self::LayeredAccessor {
+        WriteBackAccessor {
+            inner: Arc::new(inner),
+            max_retries: self.max_retries,
+            callback: self.callback.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+enum FlushJob {
+    Flush(BoxFuture<'static, ()>),
+    Barrier(oneshot::Sender<()>),
+}
+
+/// WriteBackState is shared by all accessors of the same layer.
+struct WriteBackState {
+    op: Operator,
+    /// Map from path to its latest cached file that hasn't been flushed.
+    pending: Mutex<HashMap<String, String>>,
+    errors: Mutex<Vec<(String, Error)>>,
+    sender: Mutex<Option<mpsc::UnboundedSender<FlushJob>>>,
+}
+
+impl WriteBackState {
+    fn new(op: Operator) -> Self {
+        Self {
+            op,
+            pending: Mutex::default(),
+            errors: Mutex::default(),
+            sender: Mutex::default(),
+        }
+    }
+
+    fn get(&self, path: &str) -> Option<String> {
+        self.pending.lock().get(path).cloned()
+    }
+
+    fn is_pending(&self, path: &str) -> bool {
+        self.pending.lock().contains_key(path)
+    }
+
+    /// Stop serving given paths from cache since they have been changed
+    /// in inner service.
+    fn forget(&self, paths: &[&str]) {
+        let mut pending = self.pending.lock();
+        for path in paths {
+            pending.remove(*path);
+        }
+    }
+
+    /// Remove the pending entry if it still points to `file`.
+    fn remove_if_match(&self, path: &str, file: &str) {
+        let mut pending = self.pending.lock();
+        if pending.get(path).map(|v| v.as_str()) == Some(file) {
+            pending.remove(path);
+        }
+    }
+
+    /// Send job to the flush worker, the worker will be spawned on current
+    /// tokio runtime if it's not started yet.
+    fn send(&self, job: FlushJob) -> Result<()> {
+        let mut sender = self.sender.lock();
+        if sender.is_none() {
+            let handle = Handle::try_current().map_err(|err| {
+                Error::new(
+                    ErrorKind::Unexpected,
+                    "write back layer requires a tokio runtime to flush writes",
+                )
+                .set_source(err)
+            })?;
+
+            let (tx, rx) = mpsc::unbounded();
+            handle.spawn(run_flush_worker(rx));
+            *sender = Some(tx);
+        }
+
+        let tx = sender.as_ref().expect("flush worker must be started");
+        tx.unbounded_send(job)
+            .map_err(|_| Error::new(ErrorKind::Unexpected, "write back flush worker has stopped"))
+    }
+
+    /// Mark path as pending and schedule the flush of it.
+    fn enqueue(&self, path: &str, file: &str, fut: BoxFuture<'static, ()>) -> Result<()> {
+        self.pending
+            .lock()
+            .insert(path.to_string(), file.to_string());
+        self.send(FlushJob::Flush(fut))
+    }
+
+    /// Wait for all scheduled flushes to be finished.
+    async fn wait(&self) {
+        if self.sender.lock().is_none() {
+            return;
+        }
+
+        let (tx, rx) = oneshot::channel();
+        if self.send(FlushJob::Barrier(tx)).is_ok() {
+            let _ = rx.await;
+        }
+    }
+
+    /// Clean up the flushed file, the pending entry will only be removed
+    /// if it hasn't been overwritten by a later write.
+    ///
+    /// The pending entry of a write that failed permanently is removed too,
+    /// so that the path won't be served from a cache file that will never
+    /// be flushed. The cache file is kept on disk for recovery.
+    async fn finish(
+        &self,
+        path: &str,
+        file: &str,
+        res: Result<()>,
+        callback: Option<&FlushErrorCallback>,
+    ) {
+        match res {
+            Ok(()) => {
+                self.remove_if_match(path, file);
+
+                if let Err(err) = self.op.delete(file).await {
+                    warn!(
+                        target: "opendal::layers::write_back",
+                        "failed to remove flushed cache file {file}: {err}"
+                    );
+                }
+            }
+            Err(err) => {
+                self.remove_if_match(path, file);
+                warn!(
+                    target: "opendal::layers::write_back",
+                    "failed to flush {path}, content is kept in cache file {file}: {err}"
+                );
+
+                if let Some(f) = callback {
+                    f(path, &err);
+                }
+                self.errors.lock().push((path.to_string(), err));
+            }
+        }
+    }
+}
+
+/// Run flush jobs one by one so that writes of the same path will be
+/// flushed in order.
+async fn run_flush_worker(mut rx: mpsc::UnboundedReceiver<FlushJob>) {
+    while let Some(job) = rx.next().await {
+        match job {
+            FlushJob::Flush(fut) => fut.await,
+            FlushJob::Barrier(tx) => {
+                let _ = tx.send(());
+            }
+        }
+    }
+}
+
+/// Copy the content of cached file into inner service.
+async fn flush_once<A: Accessor>(
+    inner: &A,
+    op: &Operator,
+    path: &str,
+    file: &str,
+    args: OpWrite,
+) -> Result<()> {
+    let (_, mut r) = op.inner().read(file, OpRead::new()).await?;
+    let (_, mut w) = inner.write(path, args).await?;
+
+    let res = async {
+        while let Some(bs) = r.next().await {
+            let mut bs = bs?;
+            while bs.has_remaining() {
+                let n = w.write(&bs).await?;
+                bs.advance(n);
+            }
+        }
+        w.close().await
+    }
+    .await;
+
+    if res.is_err() {
+        let _ = w.abort().await;
+    }
+    res
+}
+
+pub struct WriteBackAccessor<A: Accessor> {
+    inner: Arc<A>,
+    max_retries: usize,
+    callback: Option<FlushErrorCallback>,
+    state: Arc<WriteBackState>,
+}
+
+impl<A: Accessor> Debug for WriteBackAccessor<A> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WriteBackAccessor")
+            .field("inner", &self.inner)
+            .field("max_retries", &self.max_retries)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<A: Accessor> WriteBackAccessor<A> {
+    /// Only plain writes can be cached, appends and conditional writes
+    /// depend on the state of inner service.
+    fn is_cacheable(args: &OpWrite) -> bool {
+        !args.append() && !args.if_not_exists()
+    }
+
+    /// Build the future that flushes `file` into `path` and cleans it up.
+    fn flush_future(&self, path: &str, file: &str, args: OpWrite) -> BoxFuture<'static, ()> {
+        let inner = self.inner.clone();
+        let state = self.state.clone();
+        let callback = self.callback.clone();
+        let backoff = ExponentialBuilder::default()
+            .with_min_delay(Duration::from_millis(100))
+            .with_max_times(self.max_retries);
+        let path = path.to_string();
+        let file = file.to_string();
+
+        async move {
+            let res = { || flush_once(inner.as_ref(), &state.op, &path, &file, args.clone()) }
+                .retry(&backoff)
+                .when(|e| e.is_temporary())
+                .await;
+
+            state.finish(&path, &file, res, callback.as_ref()).await;
+        }
+        .boxed()
+    }
+
+    /// Wait for pending writes if any of given paths is still pending.
+    async fn wait_if_pending(&self, paths: &[&str]) {
+        if paths.iter().any(|p| self.state.is_pending(p)) {
+            self.state.wait().await;
+        }
+    }
+}
+
+#[async_trait]
+impl<A: Accessor> LayeredAccessor for WriteBackAccessor<A> {
+    type Inner = A;
+    type Reader = WriteBackReader<A::Reader>;
+    type BlockingReader = A::BlockingReader;
+    type Writer = WriteBackWriter<A::Writer>;
+    type BlockingWriter = A::BlockingWriter;
+    type Pager = A::Pager;
+    type BlockingPager = A::BlockingPager;
+
+    fn inner(&self) -> &Self::Inner {
+        &self.inner
+    }
+
+    async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
+        if let Some(file) = self.state.get(path) {
+            let (rp, r) = self.state.op.inner().read(&file, args).await?;
+            return Ok((rp, WriteBackReader::Cached(r)));
+        }
+
+        self.inner
+            .read(path, args)
+            .await
+            .map(|(rp, r)| (rp, WriteBackReader::Direct(r)))
+    }
+
+    async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        if !Self::is_cacheable(&args) {
+            self.wait_if_pending(&[path]).await;
+            return self
+                .inner
+                .write(path, args)
+                .await
+                .map(|(rp, w)| (rp, WriteBackWriter::Direct(w)));
+        }
+
+        let file = uuid::Uuid::new_v4().to_string();
+        let (rp, w) = self.state.op.inner().write(&file, OpWrite::new()).await?;
+        let fut = self.flush_future(path, &file, args);
+
+        Ok((
+            rp,
+            WriteBackWriter::Cached(CachedWriter {
+                inner: w,
+                state: self.state.clone(),
+                path: path.to_string(),
+                file,
+                fut: Some(fut),
+            }),
+        ))
+    }
+
+    async fn copy(&self, from: &str, to: &str, args: OpCopy) -> Result<RpCopy> {
+        self.wait_if_pending(&[from, to]).await;
+        let rp = self.inner.copy(from, to, args).await?;
+        self.state.forget(&[to]);
+        Ok(rp)
+    }
+
+    async fn rename(&self, from: &str, to: &str, args: OpRename) -> Result<RpRename> {
+        self.wait_if_pending(&[from, to]).await;
+        let rp = self.inner.rename(from, to, args).await?;
+        self.state.forget(&[from, to]);
+        Ok(rp)
+    }
+
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
+        if let Some(file) = self.state.get(path) {
+            return self.state.op.inner().stat(&file, args).await;
+        }
+
+        self.inner.stat(path, args).await
+    }
+
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        self.wait_if_pending(&[path]).await;
+        let rp = self.inner.delete(path, args).await?;
+        self.state.forget(&[path]);
+        Ok(rp)
+    }
+
+    async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
+        self.inner.list(path, args).await
+    }
+
+    async fn batch(&self, args: OpBatch) -> Result<RpBatch> {
+        let paths: Vec<String> = args.operation().iter().map(|(p, _)| p.clone()).collect();
+        let paths: Vec<&str> = paths.iter().map(|p| p.as_str()).collect();
+        self.wait_if_pending(&paths).await;
+        let rp = self.inner.batch(args).await;
+        // Batch only contains deletes, serve none of them from cache.
+        self.state.forget(&paths);
+        rp
+    }
+
+    fn blocking_read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::BlockingReader)> {
+        self.inner.blocking_read(path, args)
+    }
+
+    fn blocking_write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::BlockingWriter)> {
+        self.inner.blocking_write(path, args)
+    }
+
+    fn blocking_list(&self, path: &str, args: OpList) -> Result<(RpList, Self::BlockingPager)> {
+        self.inner.blocking_list(path, args)
+    }
+}
+
+pub enum WriteBackReader<R> {
+    Direct(R),
+    Cached(oio::Reader),
+}
+
+impl<R: oio::Read> oio::Read for WriteBackReader<R> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        match self {
+            Self::Direct(r) => r.poll_read(cx, buf),
+            Self::Cached(r) => r.poll_read(cx, buf),
+        }
+    }
+
+    fn poll_seek(&mut self, cx: &mut Context<'_>, pos: std::io::SeekFrom) -> Poll<Result<u64>> {
+        match self {
+            Self::Direct(r) => r.poll_seek(cx, pos),
+            Self::Cached(r) => r.poll_seek(cx, pos),
+        }
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        match self {
+            Self::Direct(r) => r.poll_next(cx),
+            Self::Cached(r) => r.poll_next(cx),
+        }
+    }
+}
+
+pub enum WriteBackWriter<W> {
+    Direct(W),
+    Cached(CachedWriter),
+}
+
+impl<W: oio::Write> oio::Write for WriteBackWriter<W> {
+    fn poll_write(&mut self, cx: &mut Context<'_>, bs: &dyn WriteBuf) -> Poll<Result<usize>> {
+        match self {
+            Self::Direct(w) => w.poll_write(cx, bs),
+            Self::Cached(w) => w.poll_write(cx, bs),
+        }
+    }
+
+    fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        match self {
+            Self::Direct(w) => w.poll_close(cx),
+            Self::Cached(w) => w.poll_close(cx),
+        }
+    }
+
+    fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        match self {
+            Self::Direct(w) => w.poll_abort(cx),
+            Self::Cached(w) => w.poll_abort(cx),
+        }
+    }
+
+    fn metadata(&self) -> Option<Metadata> {
+        match self {
+            Self::Direct(w) => w.metadata(),
+            Self::Cached(_) => None,
+        }
+    }
+}
+
+/// CachedWriter writes content into local cached file, and schedules the
+/// flush after the file has been closed.
+pub struct CachedWriter {
+    inner: oio::Writer,
+    state: Arc<WriteBackState>,
+    path: String,
+    file: String,
+    fut: Option<BoxFuture<'static, ()>>,
+}
+
+/// # Safety
+///
+/// The flush future will only be accessed via `&mut self`.
+unsafe impl Sync for CachedWriter {}
+
+impl oio::Write for CachedWriter {
+    fn poll_write(&mut self, cx: &mut Context<'_>, bs: &dyn WriteBuf) -> Poll<Result<usize>> {
+        self.inner.poll_write(cx, bs)
+    }
+
+    fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        ready!(self.inner.poll_close(cx))?;
+
+        if let Some(fut) = self.fut.take() {
+            self.state.enqueue(&self.path, &self.file, fut)?;
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.fut = None;
+        self.inner.poll_abort(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::Memory;
+
+    #[tokio::test]
+    async fn test_write_back() -> Result<()> {
+        let root = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let layer = WriteBackLayer::new(root.to_str().unwrap())?;
+
+        let inner = Operator::new(Memory::default())?.finish();
+        let op = inner.clone().layer(layer.clone());
+
+        op.write("test", "Hello, World!").await?;
+        // Reads should always see the latest content.
+        assert_eq!(op.read("test").await?, b"Hello, World!");
+        assert_eq!(op.stat("test").await?.content_length(), 13);
+
+        op.write("test", "Hello, OpenDAL!").await?;
+        layer.sync().await?;
+
+        assert_eq!(inner.read("test").await?, b"Hello, OpenDAL!");
+        assert!(layer.state.pending.lock().is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_back_delete() -> Result<()> {
+        let root = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let layer = WriteBackLayer::new(root.to_str().unwrap())?;
+
+        let inner = Operator::new(Memory::default())?.finish();
+        let op = inner.clone().layer(layer.clone());
+
+        op.write("test", "Hello, World!").await?;
+        op.delete("test").await?;
+
+        // Deleted path must not be served from the cache.
+        assert_eq!(
+            op.stat("test").await.unwrap_err().kind(),
+            ErrorKind::NotFound
+        );
+        assert!(layer.state.pending.lock().is_empty());
+
+        layer.sync().await?;
+        assert!(!inner.is_exist("test").await?);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_back_failed_flush() -> Result<()> {
+        let root = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let layer = WriteBackLayer::new(root.to_str().unwrap())?;
+
+        let file = "cache-file".to_string();
+        layer
+            .state
+            .pending
+            .lock()
+            .insert("test".to_string(), file.clone());
+
+        let err = Error::new(ErrorKind::PermissionDenied, "flush failed");
+        layer.state.finish("test", &file, Err(err), None).await;
+
+        // Path of failed flush must not be served from the cache any more.
+        assert!(!layer.state.is_pending("test"));
+        assert!(layer.sync().await.is_err());
+        Ok(())
+    }
+}
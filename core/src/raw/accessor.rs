@@ -483,6 +483,7 @@ pub struct AccessorInfo {
     scheme: Scheme,
     root: String,
     name: String,
+    endpoint: String,
 
     native_capability: Capability,
     full_capability: Capability,
@@ -519,6 +520,7 @@ impl AccessorInfo {
     ///
     /// - name for `s3` => bucket name
     /// - name for `azblob` => container name
+    /// - name for `azfile` => share name
     pub fn name(&self) -> &str {
         &self.name
     }
@@ -529,6 +531,18 @@ impl AccessorInfo {
         self
     }
 
+    /// Endpoint of backend, could be empty if underlying backend doesn't
+    /// expose it.
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Set endpoint of this backend.
+    pub fn set_endpoint(&mut self, endpoint: &str) -> &mut Self {
+        self.endpoint = endpoint.to_string();
+        self
+    }
+
     /// Get backend's native capabilities.
     pub fn native_capability(&self) -> Capability {
         self.native_capability
@@ -0,0 +1,175 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use crate::*;
+
+/// Glob is a compiled glob pattern used to filter paths.
+///
+/// # Syntax
+///
+/// - `?` matches any single character except `/`.
+/// - `*` matches any sequence of characters except `/`.
+/// - `**` matches any sequence of characters including `/`.
+/// - `**/` matches zero or more directories.
+/// - `\` escapes the next character.
+///
+/// All other characters are matched literally.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Glob {
+    pattern: String,
+    tokens: Vec<Token>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Char(char),
+    Any,
+    Star,
+    Globstar,
+    GlobstarSlash,
+}
+
+impl Glob {
+    /// Compile a new glob pattern.
+    pub fn new(pattern: &str) -> Result<Self> {
+        let mut tokens = Vec::new();
+        let mut chars = pattern.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            let token = match c {
+                '?' => Token::Any,
+                '*' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    if chars.peek() == Some(&'/') {
+                        chars.next();
+                        Token::GlobstarSlash
+                    } else {
+                        Token::Globstar
+                    }
+                }
+                '*' => Token::Star,
+                '\\' => match chars.next() {
+                    Some(c) => Token::Char(c),
+                    None => {
+                        return Err(Error::new(
+                            ErrorKind::InvalidInput,
+                            "glob pattern must not end with an escape character",
+                        )
+                        .with_operation("Glob::new")
+                        .with_context("pattern", pattern))
+                    }
+                },
+                c => Token::Char(c),
+            };
+            tokens.push(token);
+        }
+
+        Ok(Self {
+            pattern: pattern.to_string(),
+            tokens,
+        })
+    }
+
+    /// Get the pattern of this glob.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Check if given input matches this glob.
+    pub fn is_match(&self, input: &str) -> bool {
+        let s: Vec<char> = input.chars().collect();
+        let n = s.len();
+
+        // `next[j]` is true if `tokens[i+1..]` matches `s[j..]`, we compute
+        // them from the last token to the first.
+        let mut next = vec![false; n + 1];
+        next[n] = true;
+
+        for token in self.tokens.iter().rev() {
+            let mut cur = vec![false; n + 1];
+            // For `**/`, true if `s[j..k]` ends with `/` and `next[k]` is
+            // true for some `k > j`.
+            let mut slash_then_next = false;
+            for j in (0..=n).rev() {
+                cur[j] = match token {
+                    Token::Char(c) => j < n && s[j] == *c && next[j + 1],
+                    Token::Any => j < n && s[j] != '/' && next[j + 1],
+                    Token::Star => next[j] || (j < n && s[j] != '/' && cur[j + 1]),
+                    Token::Globstar => next[j] || (j < n && cur[j + 1]),
+                    // Either match nothing, or match any chars ending with `/`.
+                    Token::GlobstarSlash => {
+                        slash_then_next =
+                            j < n && ((s[j] == '/' && next[j + 1]) || slash_then_next);
+                        next[j] || slash_then_next
+                    }
+                };
+            }
+            next = cur;
+        }
+
+        next[0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_is_match() {
+        let cases = vec![
+            ("*.log", "a.log", true),
+            ("*.log", ".log", true),
+            ("*.log", "a.txt", false),
+            ("*.log", "dir/a.log", false),
+            ("a?c", "abc", true),
+            ("a?c", "a/c", false),
+            ("a?c", "ac", false),
+            ("**", "a/b/c", true),
+            ("**/*.log", "a.log", true),
+            ("**/*.log", "a/b/c.log", true),
+            ("**/*.log", "a/b/c.txt", false),
+            ("dir/**/c", "dir/c", true),
+            ("dir/**/c", "dir/a/b/c", true),
+            ("dir/**/c", "dirc", false),
+            ("dir/**/c", "dir/abc", false),
+            ("**/c", "abc", false),
+            ("**/c", "a/b/c", true),
+            ("dir/**", "dir/a/b", true),
+            ("\\*.log", "*.log", true),
+            ("\\*.log", "a.log", false),
+            ("文件*", "文件.txt", true),
+            ("", "", true),
+            ("", "a", false),
+        ];
+
+        for (pattern, input, expected) in cases {
+            let glob = Glob::new(pattern).expect("pattern must be valid");
+            assert_eq!(
+                glob.is_match(input),
+                expected,
+                "pattern: {pattern}, input: {input}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_glob_invalid() {
+        let err = Glob::new("abc\\").unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+}
@@ -72,6 +72,46 @@ impl BytesRange {
         BytesRange(offset, size)
     }
 
+    /// Build a `BytesRange` from rust range, returns `InvalidInput` if range
+    /// start is larger than end.
+    pub(crate) fn try_from_range(range: impl RangeBounds<u64>) -> Result<Self> {
+        let start = match range.start_bound().cloned() {
+            Bound::Included(n) => Some(n),
+            Bound::Excluded(n) => Some(n.saturating_add(1)),
+            Bound::Unbounded => None,
+        };
+        let end = match range.end_bound().cloned() {
+            Bound::Included(n) => Some(n.saturating_add(1)),
+            Bound::Excluded(n) => Some(n),
+            Bound::Unbounded => None,
+        };
+
+        if let (Some(start), Some(end)) = (start, end) {
+            if start > end {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "range start must not be larger than end",
+                )
+                .with_context("range", format!("{start}..{end}")));
+            }
+        }
+
+        Ok(range.into())
+    }
+
+    /// Build a suffix `BytesRange` for the last `n` bytes, returns
+    /// `InvalidInput` if `n` is 0 since `bytes=-0` is not satisfiable.
+    pub(crate) fn try_from_tail(n: u64) -> Result<Self> {
+        if n == 0 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "tail size must be larger than 0",
+            ));
+        }
+
+        Ok(BytesRange::new(None, Some(n)))
+    }
+
     /// Get offset of BytesRange.
     pub fn offset(&self) -> Option<u64> {
         self.0
@@ -267,6 +307,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_bytes_range_try_from_range() {
+        assert_eq!(
+            BytesRange::try_from_range(1024..2048).unwrap(),
+            BytesRange::new(Some(1024), Some(1024))
+        );
+        assert_eq!(
+            BytesRange::try_from_range(1024..1024).unwrap(),
+            BytesRange::new(Some(1024), Some(0))
+        );
+        assert_eq!(
+            BytesRange::try_from_range(1024..=1023).unwrap(),
+            BytesRange::new(Some(1024), Some(0))
+        );
+
+        let err = BytesRange::try_from_range(2048..1024).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_bytes_range_try_from_tail() {
+        assert_eq!(
+            BytesRange::try_from_tail(1024).unwrap(),
+            BytesRange::new(None, Some(1024))
+        );
+
+        let err = BytesRange::try_from_tail(0).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
     #[test]
     fn test_bytes_range_from_str() -> Result<()> {
         let cases = vec![
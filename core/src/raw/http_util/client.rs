@@ -55,7 +55,18 @@ impl HttpClient {
     }
 
     /// Build a new http client in async context.
-    pub fn build(mut builder: reqwest::ClientBuilder) -> Result<Self> {
+    pub fn build(builder: reqwest::ClientBuilder) -> Result<Self> {
+        Self::build_with_connect_timeout(builder, None)
+    }
+
+    /// Build a new http client with given connect timeout.
+    ///
+    /// The connect timeout covers both DNS resolving, TCP connecting and
+    /// TLS handshaking. Default connect timeout will be used if not set.
+    pub(crate) fn build_with_connect_timeout(
+        mut builder: reqwest::ClientBuilder,
+        connect_timeout: Option<Duration>,
+    ) -> Result<Self> {
         // Make sure we don't enable auto gzip decompress.
         builder = builder.no_gzip();
         // Make sure we don't enable auto brotli decompress.
@@ -63,7 +74,7 @@ impl HttpClient {
         // Make sure we don't enable auto deflate decompress.
         builder = builder.no_deflate();
         // Make sure we don't wait a connection establishment forever.
-        builder = builder.connect_timeout(DEFAULT_CONNECT_TIMEOUT);
+        builder = builder.connect_timeout(connect_timeout.unwrap_or(DEFAULT_CONNECT_TIMEOUT));
 
         #[cfg(feature = "trust-dns")]
         let builder = builder.trust_dns(true);
@@ -75,6 +86,20 @@ impl HttpClient {
         })
     }
 
+    /// Construct `Self` with given [`reqwest::Client`].
+    ///
+    /// This is useful to share a pre-configured client (like proxy, custom
+    /// CA or connection pool settings) across operators.
+    ///
+    /// # Notes
+    ///
+    /// Users should make sure auto decompression like gzip is disabled on
+    /// given client, otherwise the content length returned by services will
+    /// not match the content we read.
+    pub fn with(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+
     /// Get the async client from http client.
     pub fn client(&self) -> reqwest::Client {
         self.client.clone()
@@ -87,6 +112,13 @@ impl HttpClient {
         let uri = req.uri().clone();
         let is_head = req.method() == http::Method::HEAD;
 
+        #[cfg(feature = "layers-otel")]
+        let req = {
+            let mut req = req;
+            inject_trace_context(req.headers_mut());
+            req
+        };
+
         let (parts, body) = req.into_parts();
 
         let mut req_builder = self
@@ -163,3 +195,34 @@ impl HttpClient {
         Ok(resp)
     }
 }
+
+/// Attach current trace context as W3C `traceparent` header so that
+/// requests can be correlated with the span started by `OtelLayer`.
+///
+/// Existing `traceparent` set by services or users will be kept.
+#[cfg(feature = "layers-otel")]
+fn inject_trace_context(headers: &mut http::HeaderMap) {
+    use opentelemetry::trace::TraceContextExt;
+    use opentelemetry::trace::TraceFlags;
+
+    if headers.contains_key("traceparent") {
+        return;
+    }
+
+    let cx = opentelemetry::Context::current();
+    let span = cx.span();
+    let sc = span.span_context();
+    if !sc.is_valid() {
+        return;
+    }
+
+    let value = format!(
+        "00-{:032x}-{:016x}-{:02x}",
+        sc.trace_id(),
+        sc.span_id(),
+        sc.trace_flags() & TraceFlags::SAMPLED
+    );
+    if let Ok(value) = http::HeaderValue::from_str(&value) {
+        headers.insert("traceparent", value);
+    }
+}
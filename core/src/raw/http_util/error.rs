@@ -46,6 +46,16 @@ pub fn new_request_sign_error(err: anyhow::Error) -> Error {
         .set_source(err)
 }
 
+/// Create a new error happened during writing with `if_not_exists` while
+/// the file already exists.
+///
+/// `operation` is the write operation that found the conflict.
+pub fn new_already_exists_error(operation: &'static str, err: Error) -> Error {
+    Error::new(ErrorKind::AlreadyExists, "file already exists")
+        .with_operation(operation)
+        .set_source(err)
+}
+
 /// Add response context to error.
 ///
 /// This helper function will:
@@ -15,6 +15,8 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use std::collections::HashMap;
+
 use base64::engine::general_purpose;
 use base64::Engine;
 use chrono::DateTime;
@@ -22,6 +24,7 @@ use chrono::Utc;
 use http::header::HeaderName;
 use http::header::CACHE_CONTROL;
 use http::header::CONTENT_DISPOSITION;
+use http::header::CONTENT_ENCODING;
 use http::header::CONTENT_LENGTH;
 use http::header::CONTENT_RANGE;
 use http::header::CONTENT_TYPE;
@@ -199,6 +202,51 @@ pub fn parse_content_disposition(headers: &HeaderMap) -> Result<Option<&str>> {
     }
 }
 
+/// Parse content encoding from header map.
+pub fn parse_content_encoding(headers: &HeaderMap) -> Result<Option<&str>> {
+    match headers.get(CONTENT_ENCODING) {
+        None => Ok(None),
+        Some(v) => Ok(Some(v.to_str().map_err(|e| {
+            Error::new(
+                ErrorKind::Unexpected,
+                "header value has to be valid utf-8 string",
+            )
+            .with_operation("http_util::parse_content_encoding")
+            .set_source(e)
+        })?)),
+    }
+}
+
+/// Parse headers with given prefix into a map, the prefix will be trimmed.
+///
+/// Header names are always lowercase, so `prefix` should be lowercase too.
+/// This is useful to parse user defined metadata like `x-ms-meta-*`.
+pub fn parse_prefixed_headers(
+    headers: &HeaderMap,
+    prefix: &str,
+) -> Result<HashMap<String, String>> {
+    let mut m = HashMap::new();
+
+    for (k, v) in headers {
+        let Some(key) = k.as_str().strip_prefix(prefix) else {
+            continue;
+        };
+
+        let value = v.to_str().map_err(|e| {
+            Error::new(
+                ErrorKind::Unexpected,
+                "header value has to be valid utf-8 string",
+            )
+            .with_operation("http_util::parse_prefixed_headers")
+            .with_context("header", k.as_str())
+            .set_source(e)
+        })?;
+        m.insert(key.to_string(), value.to_string());
+    }
+
+    Ok(m)
+}
+
 /// parse_into_metadata will parse standards http headers into Metadata.
 ///
 /// # Notes
@@ -242,6 +290,10 @@ pub fn parse_into_metadata(path: &str, headers: &HeaderMap) -> Result<Metadata>
         m.set_last_modified(v);
     }
 
+    if let Some(v) = parse_content_encoding(headers)? {
+        m.set_content_encoding(v);
+    }
+
     if let Some(v) = parse_content_disposition(headers)? {
         m.set_content_disposition(v);
     }
@@ -367,4 +419,21 @@ mod tests {
             assert_eq!(actual, expected)
         }
     }
+
+    #[test]
+    fn test_parse_prefixed_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ms-meta-owner", HeaderValue::from_static("alice"));
+        headers.insert("x-ms-meta-Project", HeaderValue::from_static("opendal"));
+        headers.insert("x-ms-version", HeaderValue::from_static("2022-11-02"));
+
+        let actual = parse_prefixed_headers(&headers, "x-ms-meta-").expect("parse must success");
+        assert_eq!(
+            actual,
+            HashMap::from([
+                ("owner".to_string(), "alice".to_string()),
+                ("project".to_string(), "opendal".to_string()),
+            ])
+        );
+    }
 }
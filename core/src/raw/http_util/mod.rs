@@ -35,6 +35,7 @@ pub use header::format_authorization_by_basic;
 pub use header::format_authorization_by_bearer;
 pub use header::format_content_md5;
 pub use header::parse_content_disposition;
+pub use header::parse_content_encoding;
 pub use header::parse_content_length;
 pub use header::parse_content_md5;
 pub use header::parse_content_range;
@@ -43,12 +44,14 @@ pub use header::parse_etag;
 pub use header::parse_into_metadata;
 pub use header::parse_last_modified;
 pub use header::parse_location;
+pub use header::parse_prefixed_headers;
 
 mod uri;
 pub use uri::percent_decode_path;
 pub use uri::percent_encode_path;
 
 mod error;
+pub use error::new_already_exists_error;
 pub use error::new_request_build_error;
 pub use error::new_request_credential_error;
 pub use error::new_request_sign_error;
@@ -35,6 +35,9 @@ pub use layer::*;
 mod path;
 pub use path::*;
 
+mod glob;
+pub use glob::Glob;
+
 mod operation;
 pub use operation::*;
 
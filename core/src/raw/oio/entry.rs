@@ -75,6 +75,11 @@ impl Entry {
         self.meta.mode()
     }
 
+    /// Get entry's metadata.
+    pub(crate) fn metadata(&self) -> &Metadata {
+        &self.meta
+    }
+
     /// Consume self to convert into an Entry.
     ///
     /// NOTE: implement this by hand to avoid leaking raw entry to end-users.
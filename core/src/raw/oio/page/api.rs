@@ -66,6 +66,15 @@ pub trait Page: Send + Sync + 'static {
     /// `Ok(None)` means all pages have been returned. Any following call
     /// to `next` will always get the same result.
     async fn next(&mut self) -> Result<Option<Vec<Entry>>>;
+
+    /// Get the opaque continuation token that can be used to resume listing
+    /// after the last page returned by `next`.
+    ///
+    /// Returns `None` if service doesn't support resuming or there are no
+    /// more pages.
+    fn continuation_token(&self) -> Option<String> {
+        None
+    }
 }
 
 /// The boxed version of [`Page`]
@@ -76,6 +85,10 @@ impl<P: Page + ?Sized> Page for Box<P> {
     async fn next(&mut self) -> Result<Option<Vec<Entry>>> {
         (**self).next().await
     }
+
+    fn continuation_token(&self) -> Option<String> {
+        (**self).continuation_token()
+    }
 }
 
 #[async_trait]
@@ -93,6 +106,10 @@ impl<P: Page> Page for Option<P> {
             None => Ok(None),
         }
     }
+
+    fn continuation_token(&self) -> Option<String> {
+        self.as_ref().and_then(|p| p.continuation_token())
+    }
 }
 
 /// BlockingPage is the blocking version of [`Page`].
@@ -102,6 +119,15 @@ pub trait BlockingPage: Send + 'static {
     /// `Ok(None)` means all pages have been returned. Any following call
     /// to `next` will always get the same result.
     fn next(&mut self) -> Result<Option<Vec<Entry>>>;
+
+    /// Get the opaque continuation token that can be used to resume listing
+    /// after the last page returned by `next`.
+    ///
+    /// Returns `None` if service doesn't support resuming or there are no
+    /// more pages.
+    fn continuation_token(&self) -> Option<String> {
+        None
+    }
 }
 
 /// BlockingPager is a boxed [`BlockingPage`]
@@ -111,6 +137,10 @@ impl<P: BlockingPage + ?Sized> BlockingPage for Box<P> {
     fn next(&mut self) -> Result<Option<Vec<Entry>>> {
         (**self).next()
     }
+
+    fn continuation_token(&self) -> Option<String> {
+        (**self).continuation_token()
+    }
 }
 
 impl BlockingPage for () {
@@ -126,4 +156,8 @@ impl<P: BlockingPage> BlockingPage for Option<P> {
             None => Ok(None),
         }
     }
+
+    fn continuation_token(&self) -> Option<String> {
+        self.as_ref().and_then(|p| p.continuation_token())
+    }
 }
@@ -43,6 +43,11 @@ use crate::*;
 ///
 /// This operation is not zero cost. If the accessor already returns a
 /// seekable reader, please don't use this.
+///
+/// If the underlying reader returns an error, the reader will be reset and
+/// the next read will send a new range request starting from the last byte
+/// that has been delivered. Layers like `RetryLayer` can resume a failed
+/// read in this way without restarting from the beginning.
 pub fn into_seekable_read_by_range<A: Accessor, R>(
     acc: Arc<A>,
     path: &str,
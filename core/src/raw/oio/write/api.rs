@@ -93,6 +93,16 @@ pub trait Write: Unpin + Send + Sync {
 
     /// Abort the pending writer.
     fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>>;
+
+    /// Fetch the metadata of written file returned by service while closing,
+    /// like `etag` and `content_length`.
+    ///
+    /// It's only valid after `poll_close` returns `Ready(Ok(()))`. Writers
+    /// that don't know the metadata should return `None`, which is the
+    /// default implementation.
+    fn metadata(&self) -> Option<Metadata> {
+        None
+    }
 }
 
 #[async_trait]
@@ -132,6 +142,10 @@ impl<T: Write + ?Sized> Write for Box<T> {
     fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
         (**self).poll_abort(cx)
     }
+
+    fn metadata(&self) -> Option<Metadata> {
+        (**self).metadata()
+    }
 }
 
 /// Impl WriteExt for all T: Write
@@ -0,0 +1,209 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::task::ready;
+use std::task::Context;
+use std::task::Poll;
+
+use async_trait::async_trait;
+
+use crate::raw::oio::WriteBuf;
+use crate::raw::*;
+use crate::*;
+
+/// BufferedWriter is used to coalesce small writes: data will be buffered until the buffered
+/// size exceeds the buffer size or the writer is closed.
+///
+/// Unlike [`oio::ExactBufWriter`], BufferedWriter doesn't split the buffered data, all of them
+/// will be flushed to the underlying storage together.
+///
+/// ## Notes
+///
+/// Use [`BufferedWriter::one_shot`] for services that can only accept one write, all data
+/// will be buffered until close and writes beyond the buffer size will be rejected.
+pub struct BufferedWriter<W: oio::Write> {
+    inner: W,
+
+    /// The size for buffer, we will flush the underlying storage once buffered data exceeds it.
+    buffer_size: usize,
+    /// Whether the inner writer can only accept one write.
+    one_shot: bool,
+    buffer: oio::ChunkedBytes,
+}
+
+impl<W: oio::Write> BufferedWriter<W> {
+    /// Create a new buffered writer.
+    pub fn new(inner: W, buffer_size: usize) -> Self {
+        Self {
+            inner,
+            buffer_size,
+            one_shot: false,
+            buffer: oio::ChunkedBytes::default(),
+        }
+    }
+
+    /// Create a new buffered writer for inner writer that can only accept one write.
+    ///
+    /// All data will be written at once while closing, `Unsupported` will be returned if
+    /// the written data exceeds the buffer size.
+    pub fn one_shot(inner: W, buffer_size: usize) -> Self {
+        Self {
+            one_shot: true,
+            ..Self::new(inner, buffer_size)
+        }
+    }
+}
+
+#[async_trait]
+impl<W: oio::Write> oio::Write for BufferedWriter<W> {
+    fn poll_write(&mut self, cx: &mut Context<'_>, bs: &dyn WriteBuf) -> Poll<Result<usize>> {
+        if self.one_shot {
+            let size = bs.remaining().min(self.buffer_size - self.buffer.len());
+            if size == 0 && bs.remaining() > 0 {
+                return Poll::Ready(Err(Error::new(
+                    ErrorKind::Unsupported,
+                    "written data exceeds buffer size but service can't accept multiple writes",
+                )
+                .with_context("buffer_size", self.buffer_size.to_string())));
+            }
+            let written = self.buffer.extend_from_write_buf(size, bs);
+            return Poll::Ready(Ok(written));
+        }
+
+        while self.buffer.len() >= self.buffer_size {
+            let written = ready!(self.inner.poll_write(cx, &self.buffer)?);
+            self.buffer.advance(written);
+        }
+
+        let written = self.buffer.extend_from_write_buf(bs.remaining(), bs);
+        Poll::Ready(Ok(written))
+    }
+
+    fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.buffer.clear();
+        self.inner.poll_abort(cx)
+    }
+
+    fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        // Always flush the remaining data even if it's smaller than buffer size.
+        while !self.buffer.is_empty() {
+            let n = ready!(self.inner.poll_write(cx, &self.buffer))?;
+            self.buffer.advance(n);
+        }
+
+        self.inner.poll_close(cx)
+    }
+
+    fn metadata(&self) -> Option<Metadata> {
+        self.inner.metadata()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::raw::oio::Write;
+    use crate::raw::oio::WriteExt;
+
+    #[derive(Default)]
+    struct MockWriter {
+        buf: Vec<u8>,
+        writes: usize,
+    }
+
+    #[async_trait]
+    impl Write for MockWriter {
+        fn poll_write(&mut self, _: &mut Context<'_>, bs: &dyn WriteBuf) -> Poll<Result<usize>> {
+            self.writes += 1;
+
+            let size = bs.remaining();
+            self.buf.extend_from_slice(&bs.bytes(size));
+            Poll::Ready(Ok(size))
+        }
+
+        fn poll_abort(&mut self, _: &mut Context<'_>) -> Poll<Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(&mut self, _: &mut Context<'_>) -> Poll<Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_buffered_writer_coalesce() -> Result<()> {
+        let mut w = BufferedWriter::new(MockWriter::default(), 10);
+
+        let mut expected = vec![];
+        for i in 0..20u8 {
+            let bs = Bytes::from(vec![i; 3]);
+            expected.extend_from_slice(&bs);
+            w.write(&bs).await?;
+        }
+        w.close().await?;
+
+        // 60 bytes are flushed every 12 bytes.
+        assert_eq!(w.inner.writes, 5);
+        assert_eq!(w.inner.buf, expected);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_buffered_writer_close_flush_remaining() -> Result<()> {
+        let mut w = BufferedWriter::new(MockWriter::default(), 1024);
+
+        w.write(&Bytes::from("hello")).await?;
+        w.write(&Bytes::from(", world")).await?;
+        assert_eq!(w.inner.writes, 0);
+
+        w.close().await?;
+        assert_eq!(w.inner.writes, 1);
+        assert_eq!(w.inner.buf, b"hello, world");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_buffered_writer_one_shot() -> Result<()> {
+        let mut w = BufferedWriter::one_shot(MockWriter::default(), 100);
+
+        for _ in 0..100 {
+            w.write(&Bytes::from("a")).await?;
+        }
+        w.close().await?;
+
+        assert_eq!(w.inner.writes, 1);
+        assert_eq!(w.inner.buf.len(), 100);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_buffered_writer_one_shot_exceeds_buffer() -> Result<()> {
+        let mut w = BufferedWriter::one_shot(MockWriter::default(), 4);
+
+        w.write(&Bytes::from("abc")).await?;
+        let err = w
+            .write(&Bytes::from("def"))
+            .await
+            .expect_err("write beyond buffer size must fail");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+        assert_eq!(w.inner.writes, 0);
+        Ok(())
+    }
+}
@@ -78,6 +78,13 @@ impl<ONE: oio::Write, TWO: oio::Write> oio::Write for TwoWaysWriter<ONE, TWO> {
             Self::Two(two) => two.poll_abort(cx),
         }
     }
+
+    fn metadata(&self) -> Option<Metadata> {
+        match self {
+            Self::One(one) => one.metadata(),
+            Self::Two(two) => two.metadata(),
+        }
+    }
 }
 
 /// ThreeWaysWriter is used to implement [`Write`] based on three ways.
@@ -119,4 +126,12 @@ impl<ONE: oio::Write, TWO: oio::Write, THREE: oio::Write> oio::Write
             Self::Three(three) => three.poll_abort(cx),
         }
     }
+
+    fn metadata(&self) -> Option<Metadata> {
+        match self {
+            Self::One(one) => one.metadata(),
+            Self::Two(two) => two.metadata(),
+            Self::Three(three) => three.metadata(),
+        }
+    }
 }
@@ -81,6 +81,10 @@ impl<W: oio::Write> oio::Write for ExactBufWriter<W> {
 
         self.inner.poll_close(cx)
     }
+
+    fn metadata(&self) -> Option<Metadata> {
+        self.inner.metadata()
+    }
 }
 
 #[cfg(test)]
@@ -43,6 +43,9 @@ pub use one_shot_write::OneShotWriter;
 mod exact_buf_write;
 pub use exact_buf_write::ExactBufWriter;
 
+mod buffered_write;
+pub use buffered_write::BufferedWriter;
+
 mod range_write;
 pub use range_write::RangeWrite;
 pub use range_write::RangeWriter;
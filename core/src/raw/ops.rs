@@ -19,24 +19,45 @@
 //!
 //! By using ops, users can add more context for operation.
 
+use std::collections::HashMap;
 use std::time::Duration;
 
 use flagset::FlagSet;
 
 use crate::raw::*;
+use crate::CancellationToken;
+use crate::Checksum;
+use crate::EntryMode;
 use crate::Metakey;
+use crate::ServerSideEncryption;
 
 /// Args for `create` operation.
 ///
 /// The path must be normalized.
 #[derive(Debug, Clone, Default)]
-pub struct OpCreateDir {}
+pub struct OpCreateDir {
+    user_metadata: Option<HashMap<String, String>>,
+}
 
 impl OpCreateDir {
     /// Create a new `OpCreateDir`.
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Get the user defined metadata from option
+    pub fn user_metadata(&self) -> Option<&HashMap<String, String>> {
+        self.user_metadata.as_ref()
+    }
+
+    /// Set the user defined metadata of option
+    ///
+    /// Service will store them along with the dir, and return them in
+    /// [`Metadata::user_metadata`](crate::Metadata::user_metadata).
+    pub fn with_user_metadata(mut self, user_metadata: HashMap<String, String>) -> Self {
+        self.user_metadata = Some(user_metadata);
+        self
+    }
 }
 
 /// Args for `delete` operation.
@@ -45,6 +66,7 @@ impl OpCreateDir {
 #[derive(Debug, Clone, Default)]
 pub struct OpDelete {
     version: Option<String>,
+    if_match: Option<String>,
 }
 
 impl OpDelete {
@@ -65,6 +87,17 @@ impl OpDelete {
     pub fn version(&self) -> Option<&str> {
         self.version.as_deref()
     }
+
+    /// Set the If-Match of this delete operation.
+    pub fn with_if_match(mut self, if_match: &str) -> Self {
+        self.if_match = Some(if_match.to_string());
+        self
+    }
+
+    /// Get If-Match of this delete operation.
+    pub fn if_match(&self) -> Option<&str> {
+        self.if_match.as_deref()
+    }
 }
 
 /// Args for `list` operation.
@@ -74,24 +107,52 @@ pub struct OpList {
     /// that could return.
     limit: Option<usize>,
 
+    /// The max number of entries that could be returned by one request
+    /// to the underlying service, services will use their own default if
+    /// not set.
+    page_size: Option<usize>,
+
     /// The start_after passes to underlying service to specify the specified key
     /// to start listing from.
     start_after: Option<String>,
 
+    /// The start_after_token passes to underlying service to resume listing
+    /// from the position recorded by a previous list.
+    start_after_token: Option<String>,
+
     /// The delimiter used to for the list operation. Default to be `/`
     delimiter: String,
 
     metakey: FlagSet<Metakey>,
+
+    /// The glob pattern used to filter entries.
+    pattern: Option<String>,
+
+    /// The mode of entries to return, all entries will be returned if not set.
+    mode: Option<EntryMode>,
+
+    /// The max number of entries that could be stated at the same time
+    /// to fill the required metakey.
+    concurrent: usize,
+
+    /// Whether to return an entry for the listed path itself.
+    include_self: bool,
 }
 
 impl Default for OpList {
     fn default() -> Self {
         OpList {
             limit: None,
+            page_size: None,
             start_after: None,
+            start_after_token: None,
             delimiter: "/".to_string(),
             // By default, we want to know what's the mode of this entry.
             metakey: Metakey::Mode.into(),
+            pattern: None,
+            mode: None,
+            concurrent: 1,
+            include_self: false,
         }
     }
 }
@@ -113,6 +174,17 @@ impl OpList {
         self.limit
     }
 
+    /// Change the page size of this list operation.
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        self.page_size = Some(page_size.max(1));
+        self
+    }
+
+    /// Get the page size of list operation.
+    pub fn page_size(&self) -> Option<usize> {
+        self.page_size
+    }
+
     /// Change the start_after of this list operation.
     pub fn with_start_after(mut self, start_after: &str) -> Self {
         self.start_after = Some(start_after.into());
@@ -124,6 +196,28 @@ impl OpList {
         self.start_after.as_deref()
     }
 
+    /// Change the start_after_token of this list operation.
+    pub fn with_start_after_token(mut self, token: &str) -> Self {
+        self.start_after_token = Some(token.into());
+        self
+    }
+
+    /// Get the start_after_token of list operation.
+    pub fn start_after_token(&self) -> Option<&str> {
+        self.start_after_token.as_deref()
+    }
+
+    /// Change whether to return an entry for the listed path itself.
+    pub fn with_include_self(mut self, include_self: bool) -> Self {
+        self.include_self = include_self;
+        self
+    }
+
+    /// Get whether to return an entry for the listed path itself.
+    pub fn include_self(&self) -> bool {
+        self.include_self
+    }
+
     /// Change the delimiter. The default delimiter is "/"
     pub fn with_delimiter(mut self, delimiter: &str) -> Self {
         self.delimiter = delimiter.to_string();
@@ -147,6 +241,42 @@ impl OpList {
     pub fn metakey(&self) -> FlagSet<Metakey> {
         self.metakey
     }
+
+    /// Change the glob pattern used to filter entries.
+    pub fn with_pattern(mut self, pattern: &str) -> Self {
+        self.pattern = Some(pattern.to_string());
+        self
+    }
+
+    /// Get the glob pattern used to filter entries.
+    pub fn pattern(&self) -> Option<&str> {
+        self.pattern.as_deref()
+    }
+
+    /// Change the mode of entries to return.
+    pub fn with_mode(mut self, mode: EntryMode) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    /// Get the mode of entries to return.
+    pub fn mode(&self) -> Option<EntryMode> {
+        self.mode
+    }
+
+    /// Change the concurrent of this list operation.
+    ///
+    /// The concurrent is the max number of stats that could be sent at the
+    /// same time to fill metakey not returned by list.
+    pub fn with_concurrent(mut self, concurrent: usize) -> Self {
+        self.concurrent = concurrent;
+        self
+    }
+
+    /// Get the concurrent of this list operation.
+    pub fn concurrent(&self) -> usize {
+        self.concurrent.max(1)
+    }
 }
 
 /// Args for `presign` operation.
@@ -266,6 +396,11 @@ pub struct OpRead {
     override_cache_control: Option<String>,
     override_content_disposition: Option<String>,
     version: Option<String>,
+    verify_checksum: bool,
+    encryption: Option<ServerSideEncryption>,
+    decompress: bool,
+    max_bytes: Option<u64>,
+    cancel_token: Option<CancellationToken>,
 }
 
 impl OpRead {
@@ -351,6 +486,61 @@ impl OpRead {
     pub fn version(&self) -> Option<&str> {
         self.version.as_deref()
     }
+
+    /// Set whether to verify content with the checksum stored by service.
+    pub fn with_verify_checksum(mut self, verify: bool) -> Self {
+        self.verify_checksum = verify;
+        self
+    }
+
+    /// Get whether to verify content with the checksum stored by service.
+    pub fn verify_checksum(&self) -> bool {
+        self.verify_checksum
+    }
+
+    /// Set the server side encryption of the option
+    pub fn with_encryption(mut self, encryption: ServerSideEncryption) -> Self {
+        self.encryption = Some(encryption);
+        self
+    }
+
+    /// Get the server side encryption from option
+    pub fn encryption(&self) -> Option<&ServerSideEncryption> {
+        self.encryption.as_ref()
+    }
+
+    /// Set whether content should be decompressed based on its content encoding
+    pub fn with_decompress(mut self, decompress: bool) -> Self {
+        self.decompress = decompress;
+        self
+    }
+
+    /// Get whether content should be decompressed from option
+    pub fn decompress(&self) -> bool {
+        self.decompress
+    }
+
+    /// Set the max bytes that could be delivered by this read.
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Get the max bytes that could be delivered by this read.
+    pub fn max_bytes(&self) -> Option<u64> {
+        self.max_bytes
+    }
+
+    /// Set the token that could cancel this read.
+    pub fn with_cancel_token(mut self, token: CancellationToken) -> Self {
+        self.cancel_token = Some(token);
+        self
+    }
+
+    /// Get the token that could cancel this read.
+    pub fn cancel_token(&self) -> Option<&CancellationToken> {
+        self.cancel_token.as_ref()
+    }
 }
 
 /// Args for `stat` operation.
@@ -359,6 +549,7 @@ pub struct OpStat {
     if_match: Option<String>,
     if_none_match: Option<String>,
     version: Option<String>,
+    raw_headers: bool,
 }
 
 impl OpStat {
@@ -399,6 +590,17 @@ impl OpStat {
     pub fn version(&self) -> Option<&str> {
         self.version.as_deref()
     }
+
+    /// Set whether the raw headers of the response should be returned.
+    pub fn with_raw_headers(mut self, raw_headers: bool) -> Self {
+        self.raw_headers = raw_headers;
+        self
+    }
+
+    /// Get whether the raw headers of the response should be returned.
+    pub fn raw_headers(&self) -> bool {
+        self.raw_headers
+    }
 }
 
 /// Args for `write` operation.
@@ -406,10 +608,16 @@ impl OpStat {
 pub struct OpWrite {
     append: bool,
     buffer: Option<usize>,
+    concurrent: usize,
 
     content_type: Option<String>,
     content_disposition: Option<String>,
     cache_control: Option<String>,
+    checksum: Option<Checksum>,
+    encryption: Option<ServerSideEncryption>,
+    user_metadata: Option<HashMap<String, String>>,
+    if_not_exists: bool,
+    atomic: bool,
 }
 
 impl OpWrite {
@@ -491,6 +699,90 @@ impl OpWrite {
         self.cache_control = Some(cache_control.to_string());
         self
     }
+
+    /// Get the checksum from option
+    pub fn checksum(&self) -> Option<Checksum> {
+        self.checksum
+    }
+
+    /// Set the checksum of option
+    ///
+    /// Service will reject the write if the content doesn't match.
+    pub fn with_checksum(mut self, checksum: Checksum) -> Self {
+        self.checksum = Some(checksum);
+        self
+    }
+
+    /// Get the server side encryption from option
+    pub fn encryption(&self) -> Option<&ServerSideEncryption> {
+        self.encryption.as_ref()
+    }
+
+    /// Set the server side encryption of option
+    ///
+    /// Service will encrypt the content with given key before storing it.
+    pub fn with_encryption(mut self, encryption: ServerSideEncryption) -> Self {
+        self.encryption = Some(encryption);
+        self
+    }
+
+    /// Get the user defined metadata from option
+    pub fn user_metadata(&self) -> Option<&HashMap<String, String>> {
+        self.user_metadata.as_ref()
+    }
+
+    /// Set the user defined metadata of option
+    ///
+    /// Service will store them along with the content, and return them in
+    /// [`Metadata::user_metadata`].
+    pub fn with_user_metadata(mut self, user_metadata: HashMap<String, String>) -> Self {
+        self.user_metadata = Some(user_metadata);
+        self
+    }
+
+    /// Get the if not exists flag from option
+    pub fn if_not_exists(&self) -> bool {
+        self.if_not_exists
+    }
+
+    /// Set the if not exists flag of option
+    ///
+    /// Service will reject the write with `AlreadyExists` if the file
+    /// already exists.
+    pub fn with_if_not_exists(mut self, if_not_exists: bool) -> Self {
+        self.if_not_exists = if_not_exists;
+        self
+    }
+
+    /// Get the atomic flag from option
+    pub fn atomic(&self) -> bool {
+        self.atomic
+    }
+
+    /// Set the atomic flag of option
+    ///
+    /// Data will be written into a temp file first and renamed to the
+    /// target path while closing.
+    pub fn with_atomic(mut self, atomic: bool) -> Self {
+        self.atomic = atomic;
+        self
+    }
+
+    /// Get the concurrent from op.
+    ///
+    /// The concurrent is the max number of chunks that could be uploaded at
+    /// the same time, `1` means chunks will be uploaded one by one.
+    pub fn concurrent(&self) -> usize {
+        self.concurrent.max(1)
+    }
+
+    /// Set the concurrent of op.
+    ///
+    /// Services that don't support concurrent upload will ignore this value.
+    pub fn with_concurrent(mut self, concurrent: usize) -> Self {
+        self.concurrent = concurrent;
+        self
+    }
 }
 
 /// Args for `copy` operation.
@@ -54,6 +54,7 @@ pub struct AtomicserverBuilder {
     private_key: Option<String>,
     public_key: Option<String>,
     parent_resource_id: Option<String>,
+    http_client: Option<HttpClient>,
 }
 
 impl AtomicserverBuilder {
@@ -89,6 +90,17 @@ impl AtomicserverBuilder {
         self.parent_resource_id = Some(parent_resource_id.into());
         self
     }
+
+    /// Specify the http client that used by this service.
+    ///
+    /// # Notes
+    ///
+    /// This API is part of OpenDAL's Raw API. `HttpClient` could be changed
+    /// during minor updates.
+    pub fn http_client(&mut self, client: HttpClient) -> &mut Self {
+        self.http_client = Some(client);
+        self
+    }
 }
 
 impl Builder for AtomicserverBuilder {
@@ -127,14 +139,20 @@ impl Builder for AtomicserverBuilder {
             name: Some("agent".to_string()),
         };
 
+        let client = if let Some(client) = self.http_client.take() {
+            client
+        } else {
+            HttpClient::new().map_err(|err| {
+                err.with_operation("Builder::build")
+                    .with_context("service", Scheme::Atomicserver)
+            })?
+        };
+
         Ok(AtomicserverBackend::new(Adapter {
             parent_resource_id,
             endpoint,
             agent,
-            client: HttpClient::new().map_err(|err| {
-                err.with_operation("Builder::build")
-                    .with_context("service", Scheme::Atomicserver)
-            })?,
+            client,
         })
         .with_root(&root))
     }
@@ -532,9 +532,11 @@ impl Accessor for AzblobBackend {
                 write_can_empty: true,
                 write_can_append: true,
                 write_with_cache_control: true,
+                write_with_checksum: true,
                 write_with_content_type: true,
 
                 delete: true,
+                delete_with_if_match: true,
                 create_dir: true,
                 copy: true,
 
@@ -639,8 +641,8 @@ impl Accessor for AzblobBackend {
         }
     }
 
-    async fn delete(&self, path: &str, _: OpDelete) -> Result<RpDelete> {
-        let resp = self.core.azblob_delete_blob(path).await?;
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        let resp = self.core.azblob_delete_blob(path, &args).await?;
 
         let status = resp.status();
 
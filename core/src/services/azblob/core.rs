@@ -43,6 +43,8 @@ mod constants {
     pub const X_MS_COPY_SOURCE: &str = "x-ms-copy-source";
     pub const X_MS_BLOB_CACHE_CONTROL: &str = "x-ms-blob-cache-control";
     pub const X_MS_BLOB_CONDITION_APPENDPOS: &str = "x-ms-blob-condition-appendpos";
+    pub const X_MS_CONTENT_CRC64: &str = "x-ms-content-crc64";
+    pub const CONTENT_MD5: &str = "content-md5";
 
     // Server-side encryption with customer-provided headers
     pub const X_MS_ENCRYPTION_KEY: &str = "x-ms-encryption-key";
@@ -258,6 +260,21 @@ impl AzblobCore {
             req = req.header(CONTENT_TYPE, ty)
         }
 
+        if let Some(checksum) = args.checksum() {
+            let name = match checksum {
+                Checksum::Crc64(_) => constants::X_MS_CONTENT_CRC64,
+                Checksum::Md5(_) => constants::CONTENT_MD5,
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::Unsupported,
+                        "azblob doesn't support write with this checksum",
+                    )
+                    .with_context("checksum", checksum.name()))
+                }
+            };
+            req = req.header(HeaderName::from_static(name), checksum.to_base64());
+        }
+
         req = req.header(
             HeaderName::from_static(constants::X_MS_BLOB_TYPE),
             "BlockBlob",
@@ -414,7 +431,11 @@ impl AzblobCore {
         self.send(req).await
     }
 
-    pub fn azblob_delete_blob_request(&self, path: &str) -> Result<Request<AsyncBody>> {
+    pub fn azblob_delete_blob_request(
+        &self,
+        path: &str,
+        args: &OpDelete,
+    ) -> Result<Request<AsyncBody>> {
         let p = build_abs_path(&self.root, path);
 
         let url = format!(
@@ -424,15 +445,23 @@ impl AzblobCore {
             percent_encode_path(&p)
         );
 
-        let req = Request::delete(&url);
+        let mut req = Request::delete(&url);
+
+        if let Some(if_match) = args.if_match() {
+            req = req.header(IF_MATCH, if_match);
+        }
 
         req.header(CONTENT_LENGTH, 0)
             .body(AsyncBody::Empty)
             .map_err(new_request_build_error)
     }
 
-    pub async fn azblob_delete_blob(&self, path: &str) -> Result<Response<IncomingAsyncBody>> {
-        let mut req = self.azblob_delete_blob_request(path)?;
+    pub async fn azblob_delete_blob(
+        &self,
+        path: &str,
+        args: &OpDelete,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let mut req = self.azblob_delete_blob_request(path, args)?;
 
         self.sign(&mut req).await?;
         self.send(req).await
@@ -516,7 +545,7 @@ impl AzblobCore {
         let mut multipart = Multipart::new();
 
         for (idx, path) in paths.iter().enumerate() {
-            let mut req = self.azblob_delete_blob_request(path)?;
+            let mut req = self.azblob_delete_blob_request(path, &OpDelete::default())?;
             self.batch_sign(&mut req).await?;
 
             multipart = multipart.part(
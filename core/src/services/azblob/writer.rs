@@ -73,6 +73,15 @@ impl oio::OneShotWrite for AzblobWriter {
 #[async_trait]
 impl oio::AppendObjectWrite for AzblobWriter {
     async fn offset(&self) -> Result<u64> {
+        // Checksum of the whole content can't be carried by append.
+        if let Some(checksum) = self.op.checksum() {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "azblob doesn't support append with checksum",
+            )
+            .with_context("checksum", checksum.name()));
+        }
+
         let resp = self
             .core
             .azblob_get_blob_properties(&self.path, &OpStat::default())
@@ -250,6 +250,8 @@ impl Accessor for AzdlsBackend {
 
                 write: true,
                 write_can_append: true,
+                write_with_content_type: true,
+                write_with_content_disposition: true,
                 create_dir: true,
                 delete: true,
                 rename: true,
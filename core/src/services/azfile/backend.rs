@@ -19,26 +19,50 @@ use std::collections::HashMap;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
 
 use async_trait::async_trait;
+use http::HeaderMap;
+use http::HeaderValue;
 use http::StatusCode;
 use log::debug;
+use log::warn;
 use reqsign::AzureStorageConfig;
 use reqsign::AzureStorageLoader;
 use reqsign::AzureStorageSigner;
+use uuid::Uuid;
 
 use crate::raw::*;
 use crate::services::azfile::pager::AzfilePager;
 use crate::*;
 
+use super::core::check_encryption_headers;
+use super::core::validate_user_metadata;
 use super::core::AzfileCore;
+use super::core::AZFILE_MAX_CLIENT_REQUEST_ID_LENGTH;
+use super::core::AZFILE_VERSION;
+use super::core::X_MS_CONTENT_MD5;
+use super::core::X_MS_COPY_STATUS;
+use super::core::X_MS_COPY_STATUS_DESCRIPTION;
+use super::core::X_MS_FILE_CREATION_TIME;
+use super::core::X_MS_META_PREFIX;
 use super::error::parse_error;
+use super::lease::AzfileLease;
+use super::lease::AzfileLeaser;
+use super::writer::AzfileRangeWriter;
 use super::writer::AzfileWriter;
 use super::writer::AzfileWriters;
+use super::writer::AZFILE_MAX_RANGE_SIZE;
 
 /// Default endpoint of Azure File services.
 const DEFAULT_AZFILE_ENDPOINT_SUFFIX: &str = "file.core.windows.net";
 
+/// The interval between two polls of a pending copy.
+const COPY_POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// The default max duration to wait for a pending copy.
+const DEFAULT_COPY_TIMEOUT: Duration = Duration::from_secs(3600);
+
 /// Azure File services support.
 #[doc = include_str!("docs.md")]
 #[derive(Default, Clone)]
@@ -49,6 +73,14 @@ pub struct AzfileBuilder {
     share_name: String,
     account_key: Option<String>,
     sas_token: Option<String>,
+    rename_replace_if_exists: Option<bool>,
+    snapshot: Option<String>,
+    api_version: Option<String>,
+    extra_query: Vec<(String, String)>,
+    client_request_id_prefix: Option<String>,
+    connect_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    copy_timeout: Option<Duration>,
     http_client: Option<HttpClient>,
 }
 
@@ -59,12 +91,22 @@ impl Debug for AzfileBuilder {
         ds.field("root", &self.root);
         ds.field("endpoint", &self.endpoint);
         ds.field("share_name", &self.share_name);
+        ds.field("snapshot", &self.snapshot);
+        ds.field("api_version", &self.api_version);
+        ds.field("extra_query", &self.extra_query);
+        ds.field("client_request_id_prefix", &self.client_request_id_prefix);
+        ds.field("connect_timeout", &self.connect_timeout);
+        ds.field("timeout", &self.timeout);
+        ds.field("copy_timeout", &self.copy_timeout);
         if self.account_name.is_some() {
             ds.field("account_name", &"<redacted>");
         }
         if self.account_key.is_some() {
             ds.field("account_key", &"<redacted>");
         }
+        if self.sas_token.is_some() {
+            ds.field("sas_token", &"<redacted>");
+        }
 
         ds.finish()
     }
@@ -128,6 +170,132 @@ impl AzfileBuilder {
         self
     }
 
+    /// Set sas_token of this backend.
+    ///
+    /// - If sas_token is set, requests will be authorized by appending the token
+    ///   to the url instead of signing with account_key.
+    /// - The leading `?` is optional, both `?sv=...` and `sv=...` are accepted.
+    ///
+    /// See [Grant limited access to Azure Storage resources using shared access signatures (SAS)](https://learn.microsoft.com/en-us/azure/storage/common/storage-sas-overview)
+    /// for more info.
+    pub fn sas_token(&mut self, sas_token: &str) -> &mut Self {
+        let sas_token = sas_token.trim_start_matches('?');
+        if !sas_token.is_empty() {
+            self.sas_token = Some(sas_token.to_string());
+        }
+
+        self
+    }
+
+    /// Set whether rename should replace the existing target.
+    ///
+    /// - Default to `true` which follows the semantics of [`Operator::rename`].
+    /// - If set to `false`, rename to an existing path will return an error
+    ///   with [`ErrorKind::AlreadyExists`].
+    pub fn rename_replace_if_exists(&mut self, replace: bool) -> &mut Self {
+        self.rename_replace_if_exists = Some(replace);
+        self
+    }
+
+    /// Set the share snapshot of this backend.
+    ///
+    /// If snapshot is set, list, stat and read will be performed against the
+    /// point-in-time view of the share, like `2023-10-15T12:03:40.0000000Z`.
+    ///
+    /// Snapshots are read-only, so write capabilities will not be advertised.
+    pub fn snapshot(&mut self, snapshot: &str) -> &mut Self {
+        if !snapshot.is_empty() {
+            self.snapshot = Some(snapshot.to_string());
+        }
+
+        self
+    }
+
+    /// Set the api version of this backend, like `2022-11-02`.
+    ///
+    /// It will be sent as `x-ms-version` header of every request. Default to
+    /// `2022-11-02` if not set.
+    ///
+    /// Some features require a minimum api version, refer to service docs
+    /// for more information.
+    pub fn api_version(&mut self, api_version: &str) -> &mut Self {
+        if !api_version.is_empty() {
+            self.api_version = Some(api_version.to_string());
+        }
+
+        self
+    }
+
+    /// Set extra query parameters that will be appended to every request.
+    ///
+    /// This is useful to try preview features of Azure that are toggled by
+    /// query parameters before OpenDAL supports them natively, for example
+    /// `extra_query([("comp", "lease")])`. Parameters will be percent
+    /// encoded and signed along with the request.
+    ///
+    /// # Notes
+    ///
+    /// This API is unstable and could be changed or removed at any time.
+    /// OpenDAL doesn't check these parameters, they could conflict with
+    /// the ones generated by OpenDAL and break the request.
+    pub fn extra_query<K: Into<String>, V: Into<String>>(
+        &mut self,
+        query: impl IntoIterator<Item = (K, V)>,
+    ) -> &mut Self {
+        self.extra_query
+            .extend(query.into_iter().map(|(k, v)| (k.into(), v.into())));
+        self
+    }
+
+    /// Set the prefix of `x-ms-client-request-id` of every request.
+    ///
+    /// Every request carries a generated `x-ms-client-request-id` like
+    /// `{prefix}{uuid}` which will be recorded in Azure's logs. Errors
+    /// returned by this service carry it as `client_request_id` along with
+    /// `request_id` returned by Azure, which could be given to Azure support
+    /// for correlation. Use a prefix like `my-app-` to find requests sent by
+    /// your app.
+    pub fn client_request_id_prefix(&mut self, prefix: &str) -> &mut Self {
+        if !prefix.is_empty() {
+            self.client_request_id_prefix = Some(prefix.to_string());
+        }
+
+        self
+    }
+
+    /// Set the connect timeout of the http client, default to 60s.
+    ///
+    /// The timeout covers DNS resolving, TCP connecting and TLS handshaking,
+    /// so that a dead network path won't hang the connect phase forever.
+    ///
+    /// Ignored if [`AzfileBuilder::http_client`] is set.
+    pub fn connect_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the timeout of every request sent by the http client, no timeout
+    /// by default.
+    ///
+    /// The timeout is applied from connecting until the response body has
+    /// been read, please make sure it's large enough for reading or writing
+    /// large ranges.
+    ///
+    /// Ignored if [`AzfileBuilder::http_client`] is set.
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the max duration to wait for a pending copy, default to 1 hour.
+    ///
+    /// Azure could copy files asynchronously, copy will poll the copy status
+    /// until it's finished or this timeout is reached.
+    pub fn copy_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.copy_timeout = Some(timeout);
+        self
+    }
+
     /// Specify the http client that used by this service.
     ///
     /// # Notes
@@ -138,6 +306,15 @@ impl AzfileBuilder {
         self.http_client = Some(client);
         self
     }
+
+    /// Build an [`AzfileLeaser`] to manage file leases with the same config.
+    ///
+    /// Like `build`, this will take the config out of builder, please clone
+    /// the builder first if it's also used to build an operator.
+    pub fn build_leaser(&mut self) -> Result<AzfileLeaser> {
+        let backend = self.build()?;
+        Ok(AzfileLeaser::new(backend.core))
+    }
 }
 
 impl Builder for AzfileBuilder {
@@ -152,6 +329,40 @@ impl Builder for AzfileBuilder {
         map.get("account_name").map(|v| builder.account_name(v));
         map.get("account_key").map(|v| builder.account_key(v));
         map.get("share_name").map(|v| builder.share_name(v));
+        map.get("sas_token").map(|v| builder.sas_token(v));
+        map.get("snapshot").map(|v| builder.snapshot(v));
+        map.get("api_version").map(|v| builder.api_version(v));
+        map.get("extra_query").map(|v| {
+            builder.extra_query(
+                v.split('&')
+                    .filter(|kv| !kv.is_empty())
+                    .map(|kv| kv.split_once('=').unwrap_or((kv, ""))),
+            )
+        });
+        map.get("client_request_id_prefix")
+            .map(|v| builder.client_request_id_prefix(v));
+        map.get("connect_timeout").map(|v| {
+            v.parse::<u64>()
+                .map(|v| builder.connect_timeout(Duration::from_secs(v)))
+        });
+        map.get("timeout").map(|v| {
+            v.parse::<u64>()
+                .map(|v| builder.timeout(Duration::from_secs(v)))
+        });
+        map.get("copy_timeout").map(|v| {
+            v.parse::<u64>()
+                .map(|v| builder.copy_timeout(Duration::from_secs(v)))
+        });
+        if let Some(v) = map.get("rename_replace_if_exists") {
+            match v.parse::<bool>() {
+                Ok(v) => {
+                    builder.rename_replace_if_exists(v);
+                }
+                Err(_) => warn!(
+                    "rename_replace_if_exists {v:?} is not a bool for azfile and will be ignored"
+                ),
+            }
+        }
 
         builder
     }
@@ -170,14 +381,76 @@ impl Builder for AzfileBuilder {
         }?;
         debug!("backend use endpoint {}", &endpoint);
 
+        let api_version = match &self.api_version {
+            Some(v) if is_valid_api_version(v) => HeaderValue::from_str(v).map_err(|err| {
+                Error::new(ErrorKind::ConfigInvalid, "api_version is invalid")
+                    .with_operation("Builder::build")
+                    .with_context("service", Scheme::Azfile)
+                    .set_source(err)
+            })?,
+            Some(v) => {
+                return Err(Error::new(
+                    ErrorKind::ConfigInvalid,
+                    "api_version must be a date like 2022-11-02",
+                )
+                .with_operation("Builder::build")
+                .with_context("service", Scheme::Azfile)
+                .with_context("api_version", v))
+            }
+            None => HeaderValue::from_static(AZFILE_VERSION),
+        };
+        debug!("backend use api_version {:?}", &api_version);
+
+        let extra_query = if self.extra_query.is_empty() {
+            None
+        } else {
+            Some(
+                self.extra_query
+                    .iter()
+                    .map(|(k, v)| format!("{}={}", percent_encode_path(k), percent_encode_path(v)))
+                    .collect::<Vec<_>>()
+                    .join("&"),
+            )
+        };
+        debug!("backend use extra_query {:?}", &extra_query);
+
+        let client_request_id_prefix = self.client_request_id_prefix.clone().unwrap_or_default();
+        // The generated id is the prefix followed by an uuid in 36 chars.
+        if client_request_id_prefix.len() + 36 > AZFILE_MAX_CLIENT_REQUEST_ID_LENGTH
+            || !client_request_id_prefix
+                .bytes()
+                .all(|b| b.is_ascii_graphic())
+        {
+            return Err(Error::new(
+                ErrorKind::ConfigInvalid,
+                "client_request_id_prefix must be visible ascii chars within 988 bytes",
+            )
+            .with_operation("Builder::build")
+            .with_context("service", Scheme::Azfile)
+            .with_context("client_request_id_prefix", client_request_id_prefix));
+        }
+
         let client = if let Some(client) = self.http_client.take() {
+            if self.connect_timeout.is_some() || self.timeout.is_some() {
+                warn!("http_client is set for azfile, connect_timeout and timeout will be ignored");
+            }
             client
         } else {
-            HttpClient::new().map_err(|err| {
-                err.with_operation("Builder::build")
-                    .with_context("service", Scheme::Azfile)
-            })?
+            let mut builder = reqwest::ClientBuilder::new();
+            if let Some(timeout) = self.timeout {
+                builder = builder.timeout(timeout);
+            }
+            HttpClient::build_with_connect_timeout(builder, self.connect_timeout).map_err(
+                |err| {
+                    err.with_operation("Builder::build")
+                        .with_context("service", Scheme::Azfile)
+                },
+            )?
         };
+        debug!(
+            "backend use connect_timeout {:?} and timeout {:?}",
+            self.connect_timeout, self.timeout
+        );
 
         let account_name_option = self
             .account_name
@@ -193,8 +466,13 @@ impl Builder for AzfileBuilder {
             ),
         }?;
 
+        if self.sas_token.is_some() && self.account_key.is_some() {
+            warn!("both sas_token and account_key are set for azfile, sas_token will be used");
+        }
+
         let config_loader = AzureStorageConfig {
-            account_name: Some(account_name),
+            account_name: Some(account_name.clone()),
+            account_key: self.account_key.clone(),
             sas_token: self.sas_token.clone(),
             ..Default::default()
         };
@@ -212,11 +490,111 @@ impl Builder for AzfileBuilder {
                 client,
                 signer,
                 share_name: self.share_name.clone(),
+                account_name,
+                account_key: self.account_key.clone(),
+                sas_token: self.sas_token.clone(),
+                rename_replace_if_exists: self.rename_replace_if_exists.unwrap_or(true),
+                snapshot: self.snapshot.clone(),
+                api_version,
+                extra_query,
+                client_request_id_prefix,
+                copy_timeout: self.copy_timeout.unwrap_or(DEFAULT_COPY_TIMEOUT),
             }),
         })
     }
 }
 
+/// Check if the api version looks like a date: `YYYY-MM-DD`.
+///
+/// We don't maintain the list of versions, Azure will reject unknown ones.
+fn is_valid_api_version(v: &str) -> bool {
+    let bs = v.as_bytes();
+    bs.len() == 10
+        && bs.iter().enumerate().all(|(i, b)| match i {
+            4 | 7 => *b == b'-',
+            _ => b.is_ascii_digit(),
+        })
+}
+
+/// Parse `x-ms-copy-status` from headers, returns `true` if the copy is still pending.
+///
+/// `aborted` copies will be returned as `Cancelled` and `failed` copies will be
+/// returned as `ContentIncomplete`, with the status description attached.
+fn parse_copy_pending(headers: &HeaderMap) -> Result<bool> {
+    let status = headers
+        .get(X_MS_COPY_STATUS)
+        .map(|v| v.to_str().unwrap_or_default())
+        // Azure doesn't return copy status for completed copies sometimes.
+        .unwrap_or("success");
+
+    match status {
+        "success" => Ok(false),
+        "pending" => Ok(true),
+        _ => {
+            let description = headers
+                .get(X_MS_COPY_STATUS_DESCRIPTION)
+                .map(|v| v.to_str().unwrap_or_default())
+                .unwrap_or_default();
+            let (kind, message) = match status {
+                "aborted" => (ErrorKind::Cancelled, "copy file has been aborted"),
+                "failed" => (ErrorKind::ContentIncomplete, "copy file failed"),
+                _ => (ErrorKind::Unexpected, "copy file returns unknown status"),
+            };
+            Err(Error::new(kind, message)
+                .with_operation(Operation::Copy)
+                .with_context("copy_status", status)
+                .with_context("copy_status_description", description))
+        }
+    }
+}
+
+/// Check the etag of a read response against `If-None-Match`.
+///
+/// Azure File's Get File doesn't support conditional headers, so we compare
+/// the returned etag by ourselves and report `ConditionNotMatch` for an
+/// unchanged file instead of returning the content.
+fn check_if_none_match(headers: &HeaderMap, if_none_match: &str) -> Result<()> {
+    let etag = match parse_etag(headers)? {
+        Some(etag) => etag,
+        None => return Ok(()),
+    };
+
+    let matched = if_none_match.split(',').map(|v| v.trim()).any(|v| {
+        v == "*" || v.trim_start_matches("W/").trim_matches('"') == etag.trim_matches('"')
+    });
+    if matched {
+        return Err(
+            Error::new(ErrorKind::ConditionNotMatch, "file has not been modified")
+                .with_operation("Backend::azfile_read")
+                .with_context("etag", etag),
+        );
+    }
+
+    Ok(())
+}
+
+/// Check the etag of a file against `If-Match`.
+///
+/// Azure File's Delete File doesn't support conditional headers, so we
+/// compare the etag by ourselves and report `ConditionNotMatch` for a
+/// changed file.
+fn check_if_match(headers: &HeaderMap, if_match: &str) -> Result<()> {
+    let etag = parse_etag(headers)?.unwrap_or_default();
+
+    let matched = if_match.split(',').map(|v| v.trim()).any(|v| {
+        v == "*" || (!v.starts_with("W/") && v.trim_matches('"') == etag.trim_matches('"'))
+    });
+    if !matched {
+        return Err(
+            Error::new(ErrorKind::ConditionNotMatch, "file has been modified")
+                .with_operation("Backend::azfile_delete")
+                .with_context("etag", etag),
+        );
+    }
+
+    Ok(())
+}
+
 fn infer_account_name_from_endpoint(endpoint: &str) -> Option<String> {
     let endpoint: &str = endpoint
         .strip_prefix("http://")
@@ -244,6 +622,74 @@ pub struct AzfileBackend {
     core: Arc<AzfileCore>,
 }
 
+impl AzfileBackend {
+    /// Delete file only if its etag matches `if_match`.
+    ///
+    /// An infinite lease is acquired before checking the etag, so that
+    /// the file can't be changed by others between the check and the
+    /// delete. The lease will be released if the etag is not matched or
+    /// the delete failed, and also on drop if this future is dropped
+    /// before finished.
+    async fn delete_if_match(&self, path: &str, if_match: &str) -> Result<RpDelete> {
+        if path.ends_with('/') {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "delete directory with if_match is not supported",
+            )
+            .with_operation(Operation::Delete)
+            .with_context("service", Scheme::Azfile)
+            .with_context("path", path));
+        }
+
+        let lease_id = Uuid::new_v4().to_string();
+        let resp = self
+            .core
+            .azfile_lease_file(path, "acquire", &lease_id)
+            .await?;
+        match resp.status() {
+            StatusCode::CREATED => resp.into_body().consume().await?,
+            _ => return Err(parse_error(resp).await?),
+        }
+        // Azure File only supports infinite leases, the file will be kept
+        // unwritable if we leave the lease behind.
+        let lease = AzfileLease::new(self.core.clone(), path.to_string(), lease_id);
+
+        let res: Result<RpDelete> = async {
+            let resp = self.core.azfile_get_file_properties(path).await?;
+            match resp.status() {
+                StatusCode::OK => {
+                    let res = check_if_match(resp.headers(), if_match);
+                    resp.into_body().consume().await?;
+                    res?;
+                }
+                _ => return Err(parse_error(resp).await?),
+            }
+
+            // The lease will be broken along with the deleted file.
+            let resp = self.core.azfile_delete_file(path, Some(lease.id())).await?;
+            match resp.status() {
+                StatusCode::ACCEPTED => {
+                    resp.into_body().consume().await?;
+                    Ok(RpDelete::default())
+                }
+                _ => Err(parse_error(resp).await?),
+            }
+        }
+        .await;
+
+        match res {
+            Ok(rp) => {
+                lease.forget();
+                Ok(rp)
+            }
+            Err(err) => {
+                lease.release_quietly().await;
+                Err(err.with_context("path", path))
+            }
+        }
+    }
+}
+
 #[async_trait]
 impl Accessor for AzfileBackend {
     type Reader = IncomingAsyncBody;
@@ -254,64 +700,118 @@ impl Accessor for AzfileBackend {
     type BlockingPager = ();
 
     fn info(&self) -> AccessorInfo {
-        let mut am = AccessorInfo::default();
-        am.set_scheme(Scheme::Azfile)
-            .set_root(&self.core.root)
-            .set_native_capability(Capability {
-                stat: true,
+        let mut cap = Capability {
+            stat: true,
+            stat_with_raw_headers: true,
 
-                read: true,
-                read_can_next: true,
-                read_with_range: true,
+            read: true,
+            read_can_next: true,
+            read_with_range: true,
+            read_with_if_none_match: true,
+            read_with_encryption: true,
 
-                write: true,
-                create_dir: true,
-                delete: true,
-                rename: true,
+            write: true,
+            write_can_multi: true,
+            write_can_append: true,
+            write_with_content_type: true,
+            // Only md5 is supported.
+            write_with_checksum: true,
+            write_with_content_disposition: true,
+            write_with_cache_control: true,
+            write_with_user_metadata: true,
+            write_with_encryption: true,
+            create_dir_with_user_metadata: true,
+            write_multi_max_size: Some(AZFILE_MAX_RANGE_SIZE),
+            create_dir: true,
+            delete: true,
+            delete_with_if_match: true,
+            copy: true,
+            rename: true,
+
+            list: true,
+            list_with_delimiter_slash: true,
+            list_with_limit: true,
+            list_with_page_size: true,
+            list_without_delimiter: true,
+            list_with_start_after_token: true,
+            list_with_include_self: true,
+
+            presign: true,
+            presign_stat: true,
+            presign_read: true,
+            presign_write: true,
+
+            path_case_insensitive: true,
+
+            ..Default::default()
+        };
 
-                list: true,
-                list_with_delimiter_slash: true,
+        // Snapshots are read-only, don't advertise write operations.
+        if self.core.snapshot.is_some() {
+            cap.write = false;
+            cap.write_can_multi = false;
+            cap.write_can_append = false;
+            cap.write_with_content_type = false;
+            cap.write_with_checksum = false;
+            cap.write_with_content_disposition = false;
+            cap.write_with_cache_control = false;
+            cap.write_with_user_metadata = false;
+            cap.write_with_encryption = false;
+            cap.write_multi_max_size = None;
+            cap.create_dir = false;
+            cap.create_dir_with_user_metadata = false;
+            cap.delete = false;
+            cap.delete_with_if_match = false;
+            cap.copy = false;
+            cap.rename = false;
+            cap.presign_write = false;
+        }
 
-                ..Default::default()
-            });
+        let mut am = AccessorInfo::default();
+        am.set_scheme(Scheme::Azfile)
+            .set_root(&self.core.root)
+            .set_name(&self.core.share_name)
+            .set_endpoint(&self.core.endpoint)
+            .set_native_capability(cap);
 
         am
     }
 
-    async fn create_dir(&self, path: &str, _: OpCreateDir) -> Result<RpCreateDir> {
-        self.core.ensure_parent_dir_exists(path).await?;
-        let resp = self.core.azfile_create_dir(path).await?;
-        let status = resp.status();
+    async fn create_dir(&self, path: &str, args: OpCreateDir) -> Result<RpCreateDir> {
+        self.core.check_path(path)?;
 
-        match status {
-            StatusCode::CREATED => {
-                resp.into_body().consume().await?;
-                Ok(RpCreateDir::default())
-            }
-            _ => {
-                if resp
-                    .headers()
-                    .get("x-ms-error-code")
-                    .map(|value| value.to_str().unwrap_or(""))
-                    .unwrap_or_else(|| "")
-                    == "ResourceAlreadyExists"
-                {
-                    Ok(RpCreateDir::default())
-                } else {
-                    Err(parse_error(resp).await?)
-                }
-            }
+        if let Some(user_metadata) = args.user_metadata() {
+            validate_user_metadata(user_metadata)?;
         }
+
+        self.core.ensure_parent_dir_exists(path).await?;
+        self.core
+            .azfile_ensure_dir(path, args.user_metadata())
+            .await?;
+
+        Ok(RpCreateDir::default())
     }
 
     async fn read(&self, path: &str, args: OpRead) -> Result<(RpRead, Self::Reader)> {
-        let resp = self.core.azfile_read(path, args.range()).await?;
+        self.core.check_path(path)?;
+
+        let resp = self
+            .core
+            .azfile_read(path, args.range(), args.encryption())
+            .await?;
 
         let status = resp.status();
 
         match status {
             StatusCode::OK | StatusCode::PARTIAL_CONTENT => {
-                let meta = parse_into_metadata(path, resp.headers())?;
+                if let Some(if_none_match) = args.if_none_match() {
+                    // Drop the response directly without consuming the body.
+                    check_if_none_match(resp.headers(), if_none_match)?;
+                }
+                if let Some(encryption) = args.encryption() {
+                    check_encryption_headers(resp.headers(), encryption)?;
+                }
+                let meta = parse_file_metadata(path, resp.status(), resp.headers())?;
                 Ok((RpRead::with_metadata(meta), resp.into_body()))
             }
             _ => Err(parse_error(resp).await?),
@@ -319,28 +819,50 @@ impl Accessor for AzfileBackend {
     }
 
     async fn write(&self, path: &str, args: OpWrite) -> Result<(RpWrite, Self::Writer)> {
+        self.core.check_path(path)?;
+
+        // Validate before creating parent dirs to avoid leaving them behind.
+        if let Some(user_metadata) = args.user_metadata() {
+            validate_user_metadata(user_metadata)?;
+        }
+        // Azure File only verifies content with `Content-MD5`.
+        if let Some(checksum) = args.checksum() {
+            if !matches!(checksum, Checksum::Md5(_)) {
+                return Err(Error::new(
+                    ErrorKind::Unsupported,
+                    "azfile only supports md5 checksum",
+                )
+                .with_context("checksum", checksum.name()));
+            }
+        }
+
         self.core.ensure_parent_dir_exists(path).await?;
-        let w = AzfileWriter::new(self.core.clone(), args.clone(), path.to_string());
         let w = if args.append() {
+            let w = AzfileWriter::new(self.core.clone(), args, path.to_string());
             AzfileWriters::Two(oio::AppendObjectWriter::new(w))
         } else {
-            AzfileWriters::One(oio::OneShotWriter::new(w))
+            let w = AzfileRangeWriter::new(self.core.clone(), args, path.to_string());
+            AzfileWriters::One(w)
         };
         return Ok((RpWrite::default(), w));
     }
 
-    async fn stat(&self, path: &str, _: OpStat) -> Result<RpStat> {
+    async fn stat(&self, path: &str, args: OpStat) -> Result<RpStat> {
         // Stat root always returns a DIR.
         if path == "/" {
             return Ok(RpStat::new(Metadata::new(EntryMode::DIR)));
         }
 
+        self.core.check_path(path)?;
+
         if path.ends_with('/') {
             let resp = self.core.azfile_get_path_properties(path).await?;
             let status = resp.status();
             match status {
                 StatusCode::OK => {
-                    let meta = parse_into_metadata(path, resp.headers())?;
+                    let meta = parse_dir_metadata(resp.headers())?;
+                    let meta = with_raw_headers(meta, resp.headers(), &args);
+                    resp.into_body().consume().await?;
                     Ok(RpStat::new(meta))
                 }
                 _ => Err(parse_error(resp).await?),
@@ -350,15 +872,87 @@ impl Accessor for AzfileBackend {
             let status = resp.status();
             match status {
                 StatusCode::OK => {
-                    let meta = parse_into_metadata(path, resp.headers())?;
+                    let meta = parse_file_metadata(path, status, resp.headers())?;
+                    let meta = with_raw_headers(meta, resp.headers(), &args);
                     Ok(RpStat::new(meta))
                 }
+                // The path could be a directory given without the trailing
+                // slash, fallback to the directory properties before giving up.
+                StatusCode::NOT_FOUND => {
+                    let err = parse_error(resp).await?;
+
+                    let resp = self.core.azfile_get_path_properties(path).await?;
+                    match resp.status() {
+                        StatusCode::OK => {
+                            let meta = parse_dir_metadata(resp.headers())?;
+                            let meta = with_raw_headers(meta, resp.headers(), &args);
+                            resp.into_body().consume().await?;
+                            Ok(RpStat::new(meta))
+                        }
+                        _ => {
+                            resp.into_body().consume().await?;
+                            Err(err)
+                        }
+                    }
+                }
                 _ => Err(parse_error(resp).await?),
             }
         }
     }
 
+    async fn copy(&self, from: &str, to: &str, _: OpCopy) -> Result<RpCopy> {
+        self.core.check_path(from)?;
+        self.core.check_path(to)?;
+
+        self.core.ensure_parent_dir_exists(to).await?;
+        let resp = self.core.azfile_copy(from, to).await?;
+        let status = resp.status();
+        let mut pending = match status {
+            StatusCode::ACCEPTED => {
+                let pending = parse_copy_pending(resp.headers())
+                    .map_err(|err| err.with_context("from", from).with_context("to", to))?;
+                resp.into_body().consume().await?;
+                pending
+            }
+            _ => return Err(parse_error(resp).await?),
+        };
+
+        // Azure could copy the file asynchronously, we need to poll the copy
+        // status until it's finished.
+        let deadline = Instant::now() + self.core.copy_timeout;
+        while pending {
+            if Instant::now() >= deadline {
+                return Err(Error::new(
+                    ErrorKind::Unexpected,
+                    "copy file is still pending after copy timeout",
+                )
+                .with_operation(Operation::Copy)
+                .with_context("from", from)
+                .with_context("to", to)
+                .with_context("copy_timeout", format!("{:?}", self.core.copy_timeout)));
+            }
+            tokio::time::sleep(COPY_POLL_INTERVAL).await;
+
+            let resp = self.core.azfile_get_file_properties(to).await?;
+            let status = resp.status();
+            pending = match status {
+                StatusCode::OK => {
+                    let pending = parse_copy_pending(resp.headers())
+                        .map_err(|err| err.with_context("from", from).with_context("to", to))?;
+                    resp.into_body().consume().await?;
+                    pending
+                }
+                _ => return Err(parse_error(resp).await?),
+            };
+        }
+
+        Ok(RpCopy::default())
+    }
+
     async fn rename(&self, from: &str, to: &str, _: OpRename) -> Result<RpRename> {
+        self.core.check_path(from)?;
+        self.core.check_path(to)?;
+
         self.core.ensure_parent_dir_exists(to).await?;
         let resp = self.core.azfile_rename(from, to).await?;
         let status = resp.status();
@@ -367,15 +961,32 @@ impl Accessor for AzfileBackend {
                 resp.into_body().consume().await?;
                 Ok(RpRename::default())
             }
+            // Azure returns `409 Conflict` if target exists and replacement is disabled.
+            StatusCode::CONFLICT if !self.core.rename_replace_if_exists => {
+                let err = parse_error(resp).await?;
+                Err(
+                    Error::new(ErrorKind::AlreadyExists, "rename target already exists")
+                        .with_operation(Operation::Rename)
+                        .with_context("from", from)
+                        .with_context("to", to)
+                        .set_source(err),
+                )
+            }
             _ => Err(parse_error(resp).await?),
         }
     }
 
-    async fn delete(&self, path: &str, _: OpDelete) -> Result<RpDelete> {
+    async fn delete(&self, path: &str, args: OpDelete) -> Result<RpDelete> {
+        self.core.check_path(path)?;
+
+        if let Some(if_match) = args.if_match() {
+            return self.delete_if_match(path, if_match).await;
+        }
+
         let resp = if path.ends_with('/') {
             self.core.azfile_delete_dir(path).await?
         } else {
-            self.core.azfile_delete_file(path).await?
+            self.core.azfile_delete_file(path, None).await?
         };
 
         let status = resp.status();
@@ -388,18 +999,154 @@ impl Accessor for AzfileBackend {
         }
     }
 
+    async fn presign(&self, path: &str, args: OpPresign) -> Result<RpPresign> {
+        self.core.check_path(path)?;
+
+        let (mut req, permission) = match args.operation() {
+            PresignOperation::Stat(_) => (self.core.azfile_get_file_properties_request(path)?, "r"),
+            PresignOperation::Read(v) => {
+                (self.core.azfile_read_request(path, v.range(), None)?, "r")
+            }
+            // Azure file doesn't support upload content in one request, so we presign
+            // the create file request here. Callers should set `x-ms-content-length`
+            // and then upload content via `comp=range` with the same query.
+            PresignOperation::Write(v) => {
+                (self.core.azfile_create_file_request(path, None, v)?, "cw")
+            }
+        };
+
+        self.core
+            .sign_query(&mut req, path, permission, args.expire())?;
+
+        let (parts, _) = req.into_parts();
+
+        Ok(RpPresign::new(PresignedRequest::new(
+            parts.method,
+            parts.uri,
+            parts.headers,
+        )))
+    }
+
     async fn list(&self, path: &str, args: OpList) -> Result<(RpList, Self::Pager)> {
-        let op = AzfilePager::new(self.core.clone(), path.to_string(), args.limit());
+        self.core.check_path(path)?;
+
+        // Recursive listing walks through multiple directories which can't
+        // be resumed by a single marker.
+        if args.start_after_token().is_some() && args.delimiter().is_empty() {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "azfile doesn't support recursive list with start after token",
+            )
+            .with_operation(Operation::List)
+            .with_context("path", path));
+        }
+
+        let op = AzfilePager::new(
+            self.core.clone(),
+            path.to_string(),
+            args.delimiter(),
+            // `limit` is only a hint of page size, `page_size` takes precedence.
+            args.page_size().or(args.limit()),
+            args.metakey(),
+            args.mode(),
+            args.include_self(),
+            args.start_after_token(),
+        );
 
         Ok((RpList::default(), op))
     }
 }
 
+/// Parse metadata from the response of stat or read.
+///
+/// For ranged reads, `Content-MD5` (if any) is the MD5 of the range, the MD5
+/// of the whole file is returned by `x-ms-content-md5` instead.
+fn parse_file_metadata(path: &str, status: StatusCode, headers: &HeaderMap) -> Result<Metadata> {
+    let mut meta = parse_into_metadata(path, headers)?;
+
+    let user_metadata = parse_prefixed_headers(headers, X_MS_META_PREFIX)?;
+    if !user_metadata.is_empty() {
+        meta.set_user_metadata(user_metadata);
+    }
+
+    if status == StatusCode::PARTIAL_CONTENT {
+        let md5 = headers
+            .get(X_MS_CONTENT_MD5)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string());
+        if let Some(md5) = md5 {
+            meta.set_content_md5(&md5);
+        }
+    }
+
+    Ok(meta)
+}
+
+/// Parse the response of get directory properties into a DIR metadata.
+/// Attach the raw headers of response to metadata if users asked for them.
+fn with_raw_headers(mut meta: Metadata, headers: &HeaderMap, args: &OpStat) -> Metadata {
+    if args.raw_headers() {
+        meta.set_raw_headers(headers.clone());
+    }
+    meta
+}
+
+fn parse_dir_metadata(headers: &HeaderMap) -> Result<Metadata> {
+    let mut meta = Metadata::new(EntryMode::DIR);
+
+    if let Some(v) = parse_etag(headers)? {
+        meta.set_etag(v);
+    }
+    if let Some(v) = parse_last_modified(headers)? {
+        meta.set_last_modified(v);
+    }
+    if let Some(v) = headers
+        .get(X_MS_FILE_CREATION_TIME)
+        .and_then(|v| v.to_str().ok())
+    {
+        meta.set_created(parse_datetime_from_rfc3339(v)?);
+    }
+
+    let user_metadata = parse_prefixed_headers(headers, X_MS_META_PREFIX)?;
+    if !user_metadata.is_empty() {
+        meta.set_user_metadata(user_metadata);
+    }
+
+    Ok(meta)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::Builder;
 
+    #[test]
+    fn test_parse_dir_metadata() {
+        let mut headers = HeaderMap::new();
+        headers.insert(http::header::ETAG, HeaderValue::from_static("\"0x8D\""));
+        headers.insert(
+            http::header::LAST_MODIFIED,
+            HeaderValue::from_static("Thu, 01 Jun 2023 08:00:00 GMT"),
+        );
+        headers.insert(
+            X_MS_FILE_CREATION_TIME,
+            HeaderValue::from_static("2023-06-01T07:00:00.0000000Z"),
+        );
+        headers.insert("x-ms-meta-owner", HeaderValue::from_static("alice"));
+
+        let meta = parse_dir_metadata(&headers).expect("parse must succeed");
+        assert_eq!(meta.mode(), EntryMode::DIR);
+        assert_eq!(meta.etag(), Some("\"0x8D\""));
+        assert!(meta.last_modified().is_some());
+        assert!(meta.created().is_some());
+        assert_eq!(
+            meta.user_metadata()
+                .and_then(|m| m.get("owner"))
+                .map(|v| v.as_str()),
+            Some("alice")
+        );
+    }
+
     #[test]
     fn test_infer_storage_name_from_endpoint() {
         let cases = vec![
@@ -439,4 +1186,316 @@ mod tests {
             "account-key".to_string()
         );
     }
+
+    #[test]
+    fn test_info_share_name_and_endpoint() {
+        let mut azfile_builder = AzfileBuilder::default();
+        azfile_builder.endpoint("https://account.file.core.windows.net/");
+        azfile_builder.share_name("myshare");
+        azfile_builder.account_key("account-key");
+        let azfile = azfile_builder
+            .build()
+            .expect("build Azfile should be succeeded.");
+
+        let info = azfile.info();
+        assert_eq!(info.name(), "myshare");
+        assert_eq!(info.endpoint(), "https://account.file.core.windows.net");
+    }
+
+    #[test]
+    fn test_info_snapshot_read_only() {
+        let mut azfile_builder = AzfileBuilder::default();
+        azfile_builder.endpoint("https://account.file.core.windows.net/");
+        azfile_builder.share_name("myshare");
+        azfile_builder.account_key("account-key");
+        azfile_builder.snapshot("2023-10-15T12:03:40.0000000Z");
+        let azfile = azfile_builder
+            .build()
+            .expect("build Azfile should be succeeded.");
+
+        let cap = azfile.info().native_capability();
+        assert!(cap.read && cap.stat && cap.list);
+        assert!(!cap.write && !cap.create_dir && !cap.delete);
+        assert!(!cap.copy && !cap.rename && !cap.presign_write);
+    }
+
+    #[test]
+    fn test_builder_api_version() {
+        let mut azfile_builder = AzfileBuilder::default();
+        azfile_builder.endpoint("https://account.file.core.windows.net/");
+        azfile_builder.account_key("account-key");
+        let azfile = azfile_builder.build().expect("build must succeed");
+        assert_eq!(azfile.core.api_version, AZFILE_VERSION);
+
+        azfile_builder.api_version("2023-01-03");
+        let azfile = azfile_builder.build().expect("build must succeed");
+        assert_eq!(azfile.core.api_version, "2023-01-03");
+
+        for v in ["latest", "2023-1-3", "2023/01/03", "2023-01-03T00"] {
+            azfile_builder.api_version(v);
+            let err = azfile_builder.build().expect_err("build must fail");
+            assert_eq!(err.kind(), ErrorKind::ConfigInvalid, "{v}");
+        }
+    }
+
+    #[test]
+    fn test_builder_client_request_id_prefix() {
+        let mut azfile_builder = AzfileBuilder::default();
+        azfile_builder.endpoint("https://account.file.core.windows.net/");
+        azfile_builder.account_key("account-key");
+        let azfile = azfile_builder.build().expect("build must succeed");
+        assert_eq!(azfile.core.client_request_id_prefix, "");
+
+        azfile_builder.client_request_id_prefix("my-app-");
+        let azfile = azfile_builder.build().expect("build must succeed");
+        assert_eq!(azfile.core.client_request_id_prefix, "my-app-");
+
+        let too_long = "a".repeat(989);
+        for v in ["my app", "应用-", too_long.as_str()] {
+            azfile_builder.client_request_id_prefix(v);
+            let err = azfile_builder.build().expect_err("build must fail");
+            assert_eq!(err.kind(), ErrorKind::ConfigInvalid, "{v}");
+        }
+    }
+
+    #[test]
+    fn test_builder_extra_query() {
+        let mut azfile_builder = AzfileBuilder::default();
+        azfile_builder.endpoint("https://account.file.core.windows.net/");
+        azfile_builder.account_key("account-key");
+        let azfile = azfile_builder.build().expect("build must succeed");
+        assert_eq!(azfile.core.extra_query, None);
+
+        azfile_builder.extra_query([("comp", "lease"), ("preview", "a b")]);
+        let azfile = azfile_builder.build().expect("build must succeed");
+        assert_eq!(
+            azfile.core.extra_query.as_deref(),
+            Some("comp=lease&preview=a%20b")
+        );
+
+        let azfile_builder = AzfileBuilder::from_map(HashMap::from([
+            (
+                "endpoint".to_string(),
+                "https://account.file.core.windows.net/".to_string(),
+            ),
+            ("account_key".to_string(), "account-key".to_string()),
+            ("extra_query".to_string(), "comp=lease&flag".to_string()),
+        ]));
+        assert_eq!(
+            azfile_builder.extra_query,
+            vec![
+                ("comp".to_string(), "lease".to_string()),
+                ("flag".to_string(), "".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_builder_rename_replace_if_exists() {
+        let azfile_builder = AzfileBuilder::from_map(HashMap::from([(
+            "rename_replace_if_exists".to_string(),
+            "false".to_string(),
+        )]));
+        assert_eq!(azfile_builder.rename_replace_if_exists, Some(false));
+
+        let azfile_builder = AzfileBuilder::from_map(HashMap::from([(
+            "rename_replace_if_exists".to_string(),
+            "no".to_string(),
+        )]));
+        assert_eq!(azfile_builder.rename_replace_if_exists, None);
+    }
+
+    #[test]
+    fn test_builder_timeout() {
+        let azfile_builder = AzfileBuilder::from_map(HashMap::from([
+            (
+                "endpoint".to_string(),
+                "https://account.file.core.windows.net/".to_string(),
+            ),
+            ("account_key".to_string(), "account-key".to_string()),
+            ("connect_timeout".to_string(), "5".to_string()),
+            ("timeout".to_string(), "30".to_string()),
+        ]));
+        assert_eq!(azfile_builder.connect_timeout, Some(Duration::from_secs(5)));
+        assert_eq!(azfile_builder.timeout, Some(Duration::from_secs(30)));
+
+        let mut azfile_builder = azfile_builder;
+        azfile_builder.build().expect("build must succeed");
+    }
+
+    #[test]
+    fn test_parse_copy_pending() {
+        let cases = vec![
+            ("success", Some("success"), Some(false)),
+            ("pending", Some("pending"), Some(true)),
+            ("missing", None, Some(false)),
+            ("failed", Some("failed"), None),
+            ("aborted", Some("aborted"), None),
+        ];
+
+        for (desc, input, expected) in cases {
+            let mut headers = HeaderMap::new();
+            if let Some(v) = input {
+                headers.insert(X_MS_COPY_STATUS, v.parse().unwrap());
+            }
+            let actual = parse_copy_pending(&headers).ok();
+            assert_eq!(actual, expected, "{}", desc);
+        }
+
+        let cases = vec![
+            ("failed", ErrorKind::ContentIncomplete),
+            ("aborted", ErrorKind::Cancelled),
+        ];
+
+        for (status, expected) in cases {
+            let mut headers = HeaderMap::new();
+            headers.insert(X_MS_COPY_STATUS, status.parse().unwrap());
+            headers.insert(
+                X_MS_COPY_STATUS_DESCRIPTION,
+                "500 InternalError".parse().unwrap(),
+            );
+            let err = parse_copy_pending(&headers).expect_err("must fail");
+            assert_eq!(err.kind(), expected, "{}", status);
+            assert!(err.to_string().contains("500 InternalError"), "{}", status);
+        }
+    }
+
+    #[test]
+    fn test_check_if_none_match() {
+        let cases = vec![
+            ("matched etag", "\"0x8D\"", false),
+            ("matched etag without quotes", "0x8D", false),
+            ("matched weak etag", "W/\"0x8D\"", false),
+            ("matched one of etags", "\"0x7D\", \"0x8D\"", false),
+            ("matched any", "*", false),
+            ("not matched etag", "\"0x7D\"", true),
+        ];
+
+        let mut headers = HeaderMap::new();
+        headers.insert(http::header::ETAG, "\"0x8D\"".parse().unwrap());
+        for (desc, input, expected) in cases {
+            let res = check_if_none_match(&headers, input);
+            assert_eq!(res.is_ok(), expected, "{}", desc);
+            if let Err(err) = res {
+                assert_eq!(err.kind(), ErrorKind::ConditionNotMatch, "{}", desc);
+            }
+        }
+    }
+
+    #[test]
+    fn test_check_if_match() {
+        let cases = vec![
+            ("matched etag", "\"0x8D\"", true),
+            ("matched etag without quotes", "0x8D", true),
+            ("matched one of etags", "\"0x7D\", \"0x8D\"", true),
+            ("matched any", "*", true),
+            ("weak etag never matched", "W/\"0x8D\"", false),
+            ("not matched etag", "\"0x7D\"", false),
+        ];
+
+        let mut headers = HeaderMap::new();
+        headers.insert(http::header::ETAG, "\"0x8D\"".parse().unwrap());
+        for (desc, input, expected) in cases {
+            let res = check_if_match(&headers, input);
+            assert_eq!(res.is_ok(), expected, "{}", desc);
+            if let Err(err) = res {
+                assert_eq!(err.kind(), ErrorKind::ConditionNotMatch, "{}", desc);
+            }
+        }
+    }
+
+    #[test]
+    fn test_builder_normalize_sas_token() {
+        let cases = vec![
+            (
+                "sas token without leading question mark",
+                "sv=2022-11-02&sig=abc",
+            ),
+            (
+                "sas token with leading question mark",
+                "?sv=2022-11-02&sig=abc",
+            ),
+        ];
+        for (desc, input) in cases {
+            let mut azfile_builder = AzfileBuilder::default();
+            azfile_builder.sas_token(input);
+            assert_eq!(
+                azfile_builder.sas_token,
+                Some("sv=2022-11-02&sig=abc".to_string()),
+                "{}",
+                desc
+            );
+        }
+
+        let mut azfile_builder = AzfileBuilder::default();
+        azfile_builder.sas_token("?");
+        assert_eq!(azfile_builder.sas_token, None);
+    }
+
+    #[test]
+    fn test_parse_file_metadata_content_md5() {
+        use base64::prelude::BASE64_STANDARD;
+        use base64::Engine;
+        use md5::Digest;
+        use md5::Md5;
+
+        let payload = b"Hello, World!";
+        let digest: [u8; 16] = Md5::digest(payload).into();
+        let encoded = BASE64_STANDARD.encode(digest);
+
+        // Full read returns the stored MD5 as `Content-MD5`.
+        let mut headers = HeaderMap::new();
+        headers.insert("content-md5", encoded.parse().unwrap());
+        let meta = parse_file_metadata("file", StatusCode::OK, &headers).unwrap();
+        let md5 = BASE64_STANDARD
+            .decode(meta.content_md5().expect("content md5 must exist"))
+            .unwrap();
+        assert_eq!(md5, digest);
+
+        // Ranged read returns the stored MD5 as `x-ms-content-md5`.
+        let mut headers = HeaderMap::new();
+        headers.insert(X_MS_CONTENT_MD5, encoded.parse().unwrap());
+        let meta = parse_file_metadata("file", StatusCode::PARTIAL_CONTENT, &headers).unwrap();
+        assert_eq!(meta.content_md5(), Some(encoded.as_str()));
+
+        // Files written without MD5 have no content md5.
+        let meta = parse_file_metadata("file", StatusCode::OK, &HeaderMap::new())
+            .unwrap()
+            .with_metakey(Metakey::Complete);
+        assert_eq!(meta.content_md5(), None);
+    }
+
+    #[test]
+    fn test_parse_file_metadata_user_metadata() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ms-meta-owner", "alice".parse().unwrap());
+        let meta = parse_file_metadata("file", StatusCode::OK, &headers).unwrap();
+        assert_eq!(
+            meta.user_metadata(),
+            Some(&HashMap::from([("owner".to_string(), "alice".to_string())]))
+        );
+
+        let meta = parse_file_metadata("file", StatusCode::OK, &HeaderMap::new())
+            .unwrap()
+            .with_metakey(Metakey::Complete);
+        assert_eq!(meta.user_metadata(), None);
+    }
+
+    #[test]
+    fn test_with_raw_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ms-server-encrypted", "true".parse().unwrap());
+        let meta = parse_file_metadata("file", StatusCode::OK, &headers).unwrap();
+
+        let meta = with_raw_headers(meta, &headers, &OpStat::default());
+        assert!(meta.raw_headers().is_none());
+
+        let meta = with_raw_headers(meta, &headers, &OpStat::default().with_raw_headers(true));
+        assert_eq!(
+            meta.raw_headers()
+                .and_then(|v| v.get("x-ms-server-encrypted"))
+                .map(|v| v.to_str().unwrap()),
+            Some("true")
+        );
+    }
 }
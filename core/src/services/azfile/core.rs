@@ -15,15 +15,28 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use std::collections::HashMap;
 use std::collections::VecDeque;
 use std::fmt::Debug;
 use std::fmt::Formatter;
 use std::fmt::Write;
+use std::time::Duration;
 
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use chrono::DateTime;
+use chrono::Utc;
+use hmac::Hmac;
+use hmac::Mac;
+
+use http::header::CACHE_CONTROL;
 use http::header::CONTENT_DISPOSITION;
+use http::header::CONTENT_ENCODING;
+use http::header::CONTENT_LANGUAGE;
 use http::header::CONTENT_LENGTH;
 use http::header::CONTENT_TYPE;
 use http::header::RANGE;
+use http::HeaderMap;
 use http::HeaderName;
 use http::HeaderValue;
 use http::Request;
@@ -32,20 +45,71 @@ use http::StatusCode;
 use reqsign::AzureStorageCredential;
 use reqsign::AzureStorageLoader;
 use reqsign::AzureStorageSigner;
+use sha2::Digest;
+use sha2::Sha256;
 
 use crate::raw::*;
 use crate::*;
 
+use super::error::parse_error;
+use super::error::parse_error_code;
+
 const X_MS_VERSION: &str = "x-ms-version";
 const X_MS_WRITE: &str = "x-ms-write";
-const X_MS_RENAME_SOURCE: &str = "x-ms-rename-source";
+const X_MS_FILE_RENAME_SOURCE: &str = "x-ms-file-rename-source";
+const X_MS_FILE_RENAME_REPLACE_IF_EXISTS: &str = "x-ms-file-rename-replace-if-exists";
 const X_MS_CONTENT_LENGTH: &str = "x-ms-content-length";
 const X_MS_TYPE: &str = "x-ms-type";
+const X_MS_CONTENT_TYPE: &str = "x-ms-content-type";
+const X_MS_CONTENT_DISPOSITION: &str = "x-ms-content-disposition";
+const X_MS_CONTENT_ENCODING: &str = "x-ms-content-encoding";
+const X_MS_CONTENT_LANGUAGE: &str = "x-ms-content-language";
+const X_MS_CACHE_CONTROL: &str = "x-ms-cache-control";
+const CONTENT_MD5: &str = "content-md5";
+pub const X_MS_CONTENT_MD5: &str = "x-ms-content-md5";
+const X_MS_COPY_SOURCE: &str = "x-ms-copy-source";
+const X_MS_LEASE_ACTION: &str = "x-ms-lease-action";
+const X_MS_LEASE_DURATION: &str = "x-ms-lease-duration";
+const X_MS_PROPOSED_LEASE_ID: &str = "x-ms-proposed-lease-id";
+pub const X_MS_LEASE_ID: &str = "x-ms-lease-id";
+pub const X_MS_COPY_STATUS: &str = "x-ms-copy-status";
+pub const X_MS_COPY_STATUS_DESCRIPTION: &str = "x-ms-copy-status-description";
+pub const X_MS_META_PREFIX: &str = "x-ms-meta-";
+pub const X_MS_FILE_CREATION_TIME: &str = "x-ms-file-creation-time";
+pub const X_MS_CLIENT_REQUEST_ID: &str = "x-ms-client-request-id";
+pub const X_MS_REQUEST_ID: &str = "x-ms-request-id";
+const X_MS_ENCRYPTION_KEY: &str = "x-ms-encryption-key";
+const X_MS_ENCRYPTION_KEY_SHA256: &str = "x-ms-encryption-key-sha256";
+const X_MS_ENCRYPTION_ALGORITHM: &str = "x-ms-encryption-algorithm";
+const X_MS_ENCRYPTION_SCOPE: &str = "x-ms-encryption-scope";
+
+/// The max length of `x-ms-client-request-id` accepted by azure.
+pub const AZFILE_MAX_CLIENT_REQUEST_ID_LENGTH: usize = 1024;
+
+/// The max length of a full path in azure file service.
+const AZFILE_MAX_PATH_LENGTH: usize = 2048;
+/// The max length of a single path segment in azure file service.
+const AZFILE_MAX_PATH_SEGMENT_LENGTH: usize = 255;
+
+/// The default api version used in requests and SAS, could be overridden by `api_version` in builder.
+pub const AZFILE_VERSION: &str = "2022-11-02";
 
 pub struct AzfileCore {
     pub root: String,
     pub endpoint: String,
     pub share_name: String,
+    pub account_name: String,
+    pub account_key: Option<String>,
+    pub sas_token: Option<String>,
+    pub rename_replace_if_exists: bool,
+    pub snapshot: Option<String>,
+    pub api_version: HeaderValue,
+    /// Percent encoded extra query like `k1=v1&k2=v2`, appended to every request.
+    pub extra_query: Option<String>,
+    /// Prefix of the generated `x-ms-client-request-id`, empty by default.
+    pub client_request_id_prefix: String,
+    /// The max duration to wait for a pending copy.
+    pub copy_timeout: Duration,
     pub client: HttpClient,
     pub loader: AzureStorageLoader,
     pub signer: AzureStorageSigner,
@@ -81,85 +145,320 @@ impl AzfileCore {
 
     pub async fn sign<T>(&self, req: &mut Request<T>) -> Result<()> {
         let cred = self.load_credential().await?;
+        // Extra query must be appended before signing, so that it could be
+        // covered by the shared key signature.
+        if let Some(extra_query) = &self.extra_query {
+            append_query(req, extra_query)?;
+        }
         // Insert x-ms-version header for normal requests.
         req.headers_mut().insert(
             HeaderName::from_static(X_MS_VERSION),
-            // consistent with azdls and azblob
-            HeaderValue::from_static("2022-11-02"),
+            self.api_version.clone(),
         );
+        // Azure records the client request id in its logs, so that failed
+        // requests could be found by support with it.
+        if !req.headers().contains_key(X_MS_CLIENT_REQUEST_ID) {
+            let id = HeaderValue::from_str(&self.new_client_request_id())
+                .map_err(new_request_build_error)?;
+            req.headers_mut()
+                .insert(HeaderName::from_static(X_MS_CLIENT_REQUEST_ID), id);
+        }
+        // If the credential is a sas token, signer will append it to the
+        // query instead of computing the shared key signature.
         self.signer.sign(req, &cred).map_err(new_request_sign_error)
     }
 
-    #[inline]
-    pub async fn send(&self, req: Request<AsyncBody>) -> Result<Response<IncomingAsyncBody>> {
-        self.client.send(req).await
-    }
-
-    pub async fn azfile_read(
+    /// Sign request by appending a shared access signature into query.
+    ///
+    /// The user provided sas_token will be used first, otherwise we will
+    /// generate a file service SAS via account_key.
+    pub fn sign_query<T>(
         &self,
+        req: &mut Request<T>,
         path: &str,
-        range: BytesRange,
-    ) -> Result<Response<IncomingAsyncBody>> {
+        permission: &str,
+        expire: Duration,
+    ) -> Result<()> {
+        let query = match &self.sas_token {
+            Some(token) => token.clone(),
+            None => {
+                let account_key = self.account_key.as_deref().ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::ConfigInvalid,
+                        "presign requires either sas_token or account_key",
+                    )
+                })?;
+                let expire = chrono::Duration::from_std(expire).map_err(|err| {
+                    Error::new(ErrorKind::InvalidInput, "presign expire is too large")
+                        .set_source(err)
+                })?;
+
+                let p = build_abs_path(&self.root, path)
+                    .trim_start_matches('/')
+                    .to_string();
+                build_file_sas(
+                    &self.account_name,
+                    account_key,
+                    &self.share_name,
+                    &p,
+                    permission,
+                    Utc::now() + expire,
+                )?
+            }
+        };
+
+        if let Some(extra_query) = &self.extra_query {
+            append_query(req, extra_query)?;
+        }
+        append_query(req, &query)
+    }
+
+    /// Build the url of given path under the share.
+    ///
+    /// Path will be percent encoded, so that names with spaces, `+`, `#`,
+    /// `?` or non-ASCII characters are addressed correctly. All requests
+    /// should build their url from here instead of concatenating paths.
+    pub fn build_path_url(&self, path: &str) -> String {
         let p = build_abs_path(&self.root, path);
 
-        let url = format!(
+        format!(
             "{}/{}/{}",
             self.endpoint,
             self.share_name,
-            percent_encode_path(&p)
+            percent_encode_path(p.trim_start_matches('/'))
+        )
+    }
+
+    /// Check if given path could be accepted by azure file service.
+    ///
+    /// Root is included since azure sees the full path.
+    pub fn check_path(&self, path: &str) -> Result<()> {
+        validate_path(&build_abs_path(&self.root, path))
+    }
+
+    /// Generate a new `x-ms-client-request-id` for request.
+    pub fn new_client_request_id(&self) -> String {
+        format!("{}{}", self.client_request_id_prefix, uuid::Uuid::new_v4())
+    }
+
+    /// Send request, errors without response will carry the client request
+    /// id so that they could still be correlated.
+    pub async fn send(&self, req: Request<AsyncBody>) -> Result<Response<IncomingAsyncBody>> {
+        let client_request_id = req
+            .headers()
+            .get(X_MS_CLIENT_REQUEST_ID)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string());
+
+        self.client
+            .send(req)
+            .await
+            .map_err(|err| match client_request_id {
+                Some(id) => err.with_context("client_request_id", id),
+                None => err,
+            })
+    }
+
+    /// Send request within a tracing span named `azfile`.
+    ///
+    /// The span records `operation`, `path`, `status`, `bytes` and
+    /// `duration_ms` of this REST call. `bytes` is the content length of
+    /// the request if present, otherwise the content length of the response.
+    ///
+    /// The duration only covers the round trip until the response headers
+    /// have been received, reading the body is not included.
+    #[cfg(feature = "layers-tracing")]
+    pub async fn send_with_span(
+        &self,
+        operation: &'static str,
+        path: &str,
+        req: Request<AsyncBody>,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        use std::time::Instant;
+
+        use tracing::field::Empty;
+        use tracing::Instrument;
+
+        let span = tracing::debug_span!(
+            "azfile",
+            operation,
+            path,
+            status = Empty,
+            bytes = Empty,
+            duration_ms = Empty
         );
+        let req_bytes = parse_content_length(req.headers())?;
+
+        let start = Instant::now();
+        let resp = self.send(req).instrument(span.clone()).await?;
+
+        span.record("duration_ms", start.elapsed().as_millis() as u64);
+        span.record("status", resp.status().as_u16());
+        if let Some(bytes) = req_bytes.or(parse_content_length(resp.headers())?) {
+            span.record("bytes", bytes);
+        }
+
+        Ok(resp)
+    }
+
+    /// Send request directly since tracing is not enabled.
+    #[cfg(not(feature = "layers-tracing"))]
+    #[inline]
+    pub async fn send_with_span(
+        &self,
+        _: &'static str,
+        _: &str,
+        req: Request<AsyncBody>,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        self.send(req).await
+    }
+
+    pub fn azfile_read_request(
+        &self,
+        path: &str,
+        range: BytesRange,
+        encryption: Option<&ServerSideEncryption>,
+    ) -> Result<Request<AsyncBody>> {
+        let url = self.build_path_url(path);
+
+        let url = with_snapshot(url, self.snapshot.as_deref());
 
         let mut req = Request::get(&url);
 
-        req = req.header(RANGE, range.to_header());
+        if let Some(encryption) = encryption {
+            req = insert_encryption_headers(req, encryption, false)?;
+        }
 
-        let mut req = req
-            .body(AsyncBody::Empty)
-            .map_err(new_request_build_error)?;
+        if !range.is_full() {
+            req = req.header(RANGE, range.to_header());
+        }
+
+        req.body(AsyncBody::Empty).map_err(new_request_build_error)
+    }
+
+    pub async fn azfile_read(
+        &self,
+        path: &str,
+        range: BytesRange,
+        encryption: Option<&ServerSideEncryption>,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        // Azure Files doesn't support suffix range like `bytes=-1024`, we
+        // need to resolve it with the content length first.
+        //
+        // ref: https://learn.microsoft.com/en-us/rest/api/storageservices/specifying-the-range-header-for-file-service-operations
+        let range = match (range.offset(), range.size()) {
+            (None, Some(_)) => {
+                let resp = self.azfile_get_file_properties(path).await?;
+                if resp.status() != StatusCode::OK {
+                    return Err(parse_error(resp).await?);
+                }
+                let content_length = parse_content_length(resp.headers())?.ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::Unexpected,
+                        "content length is required to read with suffix range",
+                    )
+                })?;
+                resolve_suffix_range(range, content_length)?
+            }
+            _ => range,
+        };
+
+        let mut req = self.azfile_read_request(path, range, encryption)?;
         self.sign(&mut req).await?;
-        self.send(req).await
+        let resp = self.send_with_span("read", path, req).await?;
+
+        if resp.status() != StatusCode::RANGE_NOT_SATISFIABLE {
+            return Ok(resp);
+        }
+
+        // Carry the requested range and the actual file length so that users
+        // could know why the range is not satisfiable.
+        let content_length = match parse_content_range(resp.headers())?.and_then(|v| v.size()) {
+            Some(size) => Some(size),
+            None => self.azfile_content_length(path).await,
+        };
+        let mut err = parse_error(resp)
+            .await?
+            .with_context("range", range.to_string());
+        if let Some(size) = content_length {
+            err = err.with_context("content_length", size.to_string());
+        }
+        Err(err)
     }
 
-    pub async fn azfile_create_file(
+    /// Get the content length of given file, returns `None` if failed.
+    async fn azfile_content_length(&self, path: &str) -> Option<u64> {
+        let resp = self.azfile_get_file_properties(path).await.ok()?;
+        if resp.status() != StatusCode::OK {
+            return None;
+        }
+        parse_content_length(resp.headers()).ok()?
+    }
+
+    /// Build the create file request.
+    ///
+    /// `x-ms-content-length` will not be set if size is `None`, callers
+    /// should set it by themselves.
+    pub fn azfile_create_file_request(
         &self,
         path: &str,
-        size: usize,
+        size: Option<usize>,
         args: &OpWrite,
-    ) -> Result<Response<IncomingAsyncBody>> {
-        let p = build_abs_path(&self.root, path)
-            .trim_start_matches('/')
-            .to_string();
-        let url = format!(
-            "{}/{}/{}",
-            self.endpoint,
-            self.share_name,
-            percent_encode_path(&p)
-        );
+    ) -> Result<Request<AsyncBody>> {
+        let url = self.build_path_url(path);
 
         let mut req = Request::put(&url);
 
         // x-ms-content-length specifies the maximum size for the file, up to 4 tebibytes (TiB)
         // https://learn.microsoft.com/en-us/rest/api/storageservices/create-file
-        req = req.header(X_MS_CONTENT_LENGTH, size);
+        if let Some(size) = size {
+            req = req.header(X_MS_CONTENT_LENGTH, size);
+        }
 
         req = req.header(X_MS_TYPE, "file");
 
         // Content length must be 0 for create request.
         req = req.header(CONTENT_LENGTH, 0);
 
+        // File properties must be set via `x-ms-content-*` at create time,
+        // they will be returned as `Content-*` headers while stat or read.
         if let Some(ty) = args.content_type() {
-            req = req.header(CONTENT_TYPE, ty);
+            req = req.header(X_MS_CONTENT_TYPE, ty);
         }
 
         if let Some(pos) = args.content_disposition() {
-            req = req.header(CONTENT_DISPOSITION, pos);
+            req = req.header(X_MS_CONTENT_DISPOSITION, pos);
         }
 
-        let mut req = req
-            .body(AsyncBody::Empty)
-            .map_err(new_request_build_error)?;
+        // Store the MD5 of the whole file, so that it could be returned as
+        // `Content-MD5` while stat or read.
+        if let Some(checksum @ Checksum::Md5(_)) = args.checksum() {
+            req = req.header(X_MS_CONTENT_MD5, checksum.to_base64());
+        }
+
+        if let Some(encryption) = args.encryption() {
+            req = insert_encryption_headers(req, encryption, true)?;
+        }
+
+        if let Some(user_metadata) = args.user_metadata() {
+            validate_user_metadata(user_metadata)?;
+            for (k, v) in user_metadata {
+                req = req.header(format!("{X_MS_META_PREFIX}{k}"), v);
+            }
+        }
+
+        req.body(AsyncBody::Empty).map_err(new_request_build_error)
+    }
+
+    pub async fn azfile_create_file(
+        &self,
+        path: &str,
+        size: usize,
+        args: &OpWrite,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let mut req = self.azfile_create_file_request(path, Some(size), args)?;
         self.sign(&mut req).await?;
-        self.send(req).await
+        self.send_with_span("create_file", path, req).await
     }
 
     pub async fn azfile_update(
@@ -167,18 +466,11 @@ impl AzfileCore {
         path: &str,
         size: u64,
         position: u64,
+        checksum: Option<Checksum>,
+        encryption: Option<&ServerSideEncryption>,
         body: AsyncBody,
     ) -> Result<Response<IncomingAsyncBody>> {
-        let p = build_abs_path(&self.root, path)
-            .trim_start_matches('/')
-            .to_string();
-
-        let url = format!(
-            "{}/{}/{}?comp=range",
-            self.endpoint,
-            self.share_name,
-            percent_encode_path(&p)
-        );
+        let url = format!("{}?comp=range", self.build_path_url(path));
 
         let mut req = Request::put(&url);
 
@@ -191,32 +483,87 @@ impl AzfileCore {
             BytesRange::from(position..position + size).to_header(),
         );
 
+        // Azure file only validates MD5 of the range been put.
+        if let Some(checksum) = checksum {
+            match checksum {
+                Checksum::Md5(_) => req = req.header(CONTENT_MD5, checksum.to_base64()),
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::Unsupported,
+                        "azfile doesn't support write with this checksum",
+                    )
+                    .with_context("checksum", checksum.name()))
+                }
+            }
+        }
+
+        // Every range must be put with the same key used at create time.
+        if let Some(encryption) = encryption {
+            req = insert_encryption_headers(req, encryption, false)?;
+        }
+
         let mut req = req.body(body).map_err(new_request_build_error)?;
         self.sign(&mut req).await?;
-        self.send(req).await
+        self.send_with_span("write", path, req).await
     }
 
-    pub async fn azfile_get_file_properties(
+    /// Resize the file to given size via `Set File Properties`.
+    ///
+    /// Azure clears the content properties that not specified in this
+    /// request, so the properties returned by a previous stat should be
+    /// passed in via `headers` to keep them.
+    ///
+    /// # Reference
+    ///
+    /// https://learn.microsoft.com/en-us/rest/api/storageservices/set-file-properties
+    pub async fn azfile_resize(
         &self,
         path: &str,
+        size: u64,
+        headers: &HeaderMap,
     ) -> Result<Response<IncomingAsyncBody>> {
-        let p = build_abs_path(&self.root, path)
-            .trim_start_matches('/')
-            .to_string();
+        let url = format!("{}?comp=properties", self.build_path_url(path));
 
-        let url = format!(
-            "{}/{}/{}",
-            self.endpoint,
-            self.share_name,
-            percent_encode_path(&p)
-        );
+        let mut req = Request::put(&url);
 
-        let req = Request::head(&url);
+        req = req.header(X_MS_CONTENT_LENGTH, size);
+        req = req.header(CONTENT_LENGTH, 0);
+
+        for (from, to) in [
+            (CONTENT_TYPE, X_MS_CONTENT_TYPE),
+            (CONTENT_DISPOSITION, X_MS_CONTENT_DISPOSITION),
+            (CONTENT_ENCODING, X_MS_CONTENT_ENCODING),
+            (CONTENT_LANGUAGE, X_MS_CONTENT_LANGUAGE),
+            (CACHE_CONTROL, X_MS_CACHE_CONTROL),
+        ] {
+            if let Some(v) = headers.get(from) {
+                req = req.header(to, v);
+            }
+        }
 
         let mut req = req
             .body(AsyncBody::Empty)
             .map_err(new_request_build_error)?;
         self.sign(&mut req).await?;
+        self.send_with_span("resize", path, req).await
+    }
+
+    pub fn azfile_get_file_properties_request(&self, path: &str) -> Result<Request<AsyncBody>> {
+        let url = self.build_path_url(path);
+
+        let url = with_snapshot(url, self.snapshot.as_deref());
+
+        let req = Request::head(&url);
+
+        req.body(AsyncBody::Empty).map_err(new_request_build_error)
+    }
+
+    pub async fn azfile_get_file_properties(
+        &self,
+        path: &str,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let mut req = self.azfile_get_file_properties_request(path)?;
+        self.sign(&mut req).await?;
         self.send(req).await
     }
 
@@ -224,16 +571,8 @@ impl AzfileCore {
         &self,
         path: &str,
     ) -> Result<Response<IncomingAsyncBody>> {
-        let p = build_abs_path(&self.root, path)
-            .trim_start_matches('/')
-            .to_string();
-
-        let url = format!(
-            "{}/{}/{}?restype=directory",
-            self.endpoint,
-            self.share_name,
-            percent_encode_path(&p)
-        );
+        let url = format!("{}?restype=directory", self.build_path_url(path));
+        let url = with_snapshot(url, self.snapshot.as_deref());
 
         let req = Request::head(&url);
 
@@ -244,40 +583,62 @@ impl AzfileCore {
         self.send(req).await
     }
 
-    pub async fn azfile_rename(
+    /// Rename file or directory via the native rename API.
+    ///
+    /// The request is sent to the target path with the source url in
+    /// `x-ms-file-rename-source`.
+    pub async fn azfile_rename(&self, from: &str, to: &str) -> Result<Response<IncomingAsyncBody>> {
+        let source = self.build_path_url(from);
+        let url = if to.ends_with('/') {
+            format!("{}?restype=directory&comp=rename", self.build_path_url(to))
+        } else {
+            format!("{}?comp=rename", self.build_path_url(to))
+        };
+
+        let mut req = Request::put(&url)
+            .header(CONTENT_LENGTH, 0)
+            .header(X_MS_FILE_RENAME_SOURCE, source)
+            .header(
+                X_MS_FILE_RENAME_REPLACE_IF_EXISTS,
+                self.rename_replace_if_exists.to_string(),
+            )
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.sign(&mut req).await?;
+        self.send(req).await
+    }
+
+    pub async fn azfile_copy(&self, from: &str, to: &str) -> Result<Response<IncomingAsyncBody>> {
+        let source = self.build_path_url(from);
+        let target = self.build_path_url(to);
+
+        let mut req = Request::put(&target)
+            .header(X_MS_COPY_SOURCE, source)
+            .header(CONTENT_LENGTH, 0)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+
+        self.sign(&mut req).await?;
+        self.send(req).await
+    }
+
+    pub async fn azfile_create_dir(
         &self,
         path: &str,
-        new_path: &str,
+        user_metadata: Option<&HashMap<String, String>>,
     ) -> Result<Response<IncomingAsyncBody>> {
-        let p = build_abs_path(&self.root, path)
-            .trim_start_matches('/')
-            .to_string();
-
-        let new_p = build_abs_path(&self.root, new_path)
-            .trim_start_matches('/')
-            .to_string();
-
-        let url = if path.ends_with('/') {
-            format!(
-                "{}/{}/{}?restype=directory&comp=rename",
-                self.endpoint,
-                self.share_name,
-                percent_encode_path(&p)
-            )
-        } else {
-            format!(
-                "{}/{}/{}?comp=rename",
-                self.endpoint,
-                self.share_name,
-                percent_encode_path(&p)
-            )
-        };
+        let url = format!("{}?restype=directory", self.build_path_url(path));
 
         let mut req = Request::put(&url);
 
         req = req.header(CONTENT_LENGTH, 0);
 
-        req = req.header(X_MS_RENAME_SOURCE, percent_encode_path(&new_p));
+        if let Some(user_metadata) = user_metadata {
+            for (k, v) in user_metadata {
+                req = req.header(format!("{X_MS_META_PREFIX}{k}"), v);
+            }
+        }
 
         let mut req = req
             .body(AsyncBody::Empty)
@@ -286,22 +647,25 @@ impl AzfileCore {
         self.send(req).await
     }
 
-    pub async fn azfile_create_dir(&self, path: &str) -> Result<Response<IncomingAsyncBody>> {
-        let p = build_abs_path(&self.root, path)
-            .trim_start_matches('/')
-            .to_string();
-
+    /// Replace the user defined metadata of an existing directory.
+    pub async fn azfile_set_dir_metadata(
+        &self,
+        path: &str,
+        user_metadata: &HashMap<String, String>,
+    ) -> Result<Response<IncomingAsyncBody>> {
         let url = format!(
-            "{}/{}/{}?restype=directory",
-            self.endpoint,
-            self.share_name,
-            percent_encode_path(&p)
+            "{}?restype=directory&comp=metadata",
+            self.build_path_url(path)
         );
 
         let mut req = Request::put(&url);
 
         req = req.header(CONTENT_LENGTH, 0);
 
+        for (k, v) in user_metadata {
+            req = req.header(format!("{X_MS_META_PREFIX}{k}"), v);
+        }
+
         let mut req = req
             .body(AsyncBody::Empty)
             .map_err(new_request_build_error)?;
@@ -309,19 +673,20 @@ impl AzfileCore {
         self.send(req).await
     }
 
-    pub async fn azfile_delete_file(&self, path: &str) -> Result<Response<IncomingAsyncBody>> {
-        let p = build_abs_path(&self.root, path)
-            .trim_start_matches('/')
-            .to_string();
+    /// Delete file, `lease_id` must be provided if the file has an active
+    /// lease.
+    pub async fn azfile_delete_file(
+        &self,
+        path: &str,
+        lease_id: Option<&str>,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let url = self.build_path_url(path);
 
-        let url = format!(
-            "{}/{}/{}",
-            self.endpoint,
-            self.share_name,
-            percent_encode_path(&p)
-        );
+        let mut req = Request::delete(&url);
 
-        let req = Request::delete(&url);
+        if let Some(lease_id) = lease_id {
+            req = req.header(X_MS_LEASE_ID, lease_id);
+        }
 
         let mut req = req
             .body(AsyncBody::Empty)
@@ -330,17 +695,45 @@ impl AzfileCore {
         self.send(req).await
     }
 
-    pub async fn azfile_delete_dir(&self, path: &str) -> Result<Response<IncomingAsyncBody>> {
-        let p = build_abs_path(&self.root, path)
-            .trim_start_matches('/')
-            .to_string();
+    /// Acquire or release an infinite lease of file.
+    ///
+    /// Files with an active lease can only be written or deleted by
+    /// requests carrying the same lease id.
+    ///
+    /// # Reference
+    ///
+    /// https://learn.microsoft.com/en-us/rest/api/storageservices/lease-file
+    pub async fn azfile_lease_file(
+        &self,
+        path: &str,
+        action: &str,
+        lease_id: &str,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let url = format!("{}?comp=lease", self.build_path_url(path));
+
+        let mut req = Request::put(&url)
+            .header(CONTENT_LENGTH, 0)
+            .header(X_MS_LEASE_ACTION, action);
+
+        req = match action {
+            "acquire" => req
+                .header(X_MS_LEASE_DURATION, "-1")
+                .header(X_MS_PROPOSED_LEASE_ID, lease_id),
+            // Break doesn't require the lease id, so that any client could
+            // break a lease left behind by others.
+            "break" => req,
+            _ => req.header(X_MS_LEASE_ID, lease_id),
+        };
 
-        let url = format!(
-            "{}/{}/{}?restype=directory",
-            self.endpoint,
-            self.share_name,
-            percent_encode_path(&p)
-        );
+        let mut req = req
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+        self.sign(&mut req).await?;
+        self.send(req).await
+    }
+
+    pub async fn azfile_delete_dir(&self, path: &str) -> Result<Response<IncomingAsyncBody>> {
+        let url = format!("{}?restype=directory", self.build_path_url(path));
 
         let req = Request::delete(&url);
 
@@ -354,27 +747,24 @@ impl AzfileCore {
     pub async fn azfile_list(
         &self,
         path: &str,
-        limit: &Option<usize>,
+        max_results: usize,
+        include: Option<&str>,
         continuation: &String,
     ) -> Result<Response<IncomingAsyncBody>> {
-        let p = build_abs_path(&self.root, path)
-            .trim_start_matches('/')
-            .to_string();
+        let mut url = format!("{}?restype=directory&comp=list", self.build_path_url(path));
 
-        let mut url = format!(
-            "{}/{}/{}?restype=directory&comp=list&include=Timestamps,ETag",
-            self.endpoint,
-            self.share_name,
-            percent_encode_path(&p),
-        );
+        if let Some(include) = include {
+            write!(url, "&include={}", include).expect("write into string must succeed");
+        }
 
         if !continuation.is_empty() {
-            write!(url, "&marker={}", &continuation).expect("write into string must succeed");
+            write!(url, "&marker={}", percent_encode_path(continuation))
+                .expect("write into string must succeed");
         }
 
-        if let Some(limit) = limit {
-            write!(url, "&maxresults={}", limit).expect("write into string must succeed");
-        }
+        write!(url, "&maxresults={}", max_results).expect("write into string must succeed");
+
+        let url = with_snapshot(url, self.snapshot.as_deref());
 
         let req = Request::get(&url);
 
@@ -382,7 +772,7 @@ impl AzfileCore {
             .body(AsyncBody::Empty)
             .map_err(new_request_build_error)?;
         self.sign(&mut req).await?;
-        self.send(req).await
+        self.send_with_span("list", path, req).await
     }
 
     pub async fn ensure_parent_dir_exists(&self, path: &str) -> Result<()> {
@@ -394,25 +784,585 @@ impl AzfileCore {
             dirs.push_front(p);
         }
         for dir in dirs {
-            let resp = self.azfile_create_dir(dir).await?;
-
-            if resp.status() != StatusCode::CREATED {
-                if resp
-                    .headers()
-                    .get("x-ms-error-code")
-                    .map(|value| value.to_str().unwrap_or(""))
-                    .unwrap_or_else(|| "")
-                    == "ResourceAlreadyExists"
-                {
-                    continue;
+            self.azfile_ensure_dir(dir, None)
+                .await
+                .map_err(|err| err.with_context("parent", dir))?;
+        }
+
+        Ok(())
+    }
+
+    /// Create the directory if not exists.
+    ///
+    /// Azure returns `409 Conflict` with `ResourceAlreadyExists` for the
+    /// existing directory, which will be treated as success. If
+    /// `user_metadata` is given, the metadata of the existing directory will
+    /// be replaced instead.
+    pub async fn azfile_ensure_dir(
+        &self,
+        path: &str,
+        user_metadata: Option<&HashMap<String, String>>,
+    ) -> Result<()> {
+        let resp = self.azfile_create_dir(path, user_metadata).await?;
+
+        match resp.status() {
+            StatusCode::CREATED => {
+                resp.into_body().consume().await?;
+                Ok(())
+            }
+            StatusCode::CONFLICT
+                if parse_error_code(resp.headers()) == Some("ResourceAlreadyExists") =>
+            {
+                resp.into_body().consume().await?;
+
+                let Some(user_metadata) = user_metadata else {
+                    return Ok(());
+                };
+
+                let resp = self.azfile_set_dir_metadata(path, user_metadata).await?;
+                match resp.status() {
+                    StatusCode::OK => {
+                        resp.into_body().consume().await?;
+                        Ok(())
+                    }
+                    _ => Err(parse_error(resp).await?),
                 }
-                return Err(Error::new(
-                    ErrorKind::Unexpected,
-                    format!("failed to create directory: {}", dir).as_str(),
-                ));
             }
+            _ => Err(parse_error(resp).await?),
         }
+    }
+}
 
-        Ok(())
+/// Append `sharesnapshot` into the url if snapshot is specified.
+fn with_snapshot(mut url: String, snapshot: Option<&str>) -> String {
+    if let Some(snapshot) = snapshot {
+        let sep = if url.contains('?') { '&' } else { '?' };
+        write!(url, "{sep}sharesnapshot={}", percent_encode_path(snapshot))
+            .expect("write into string must succeed");
+    }
+
+    url
+}
+
+/// Append the percent encoded query into the uri of request.
+fn append_query<T>(req: &mut Request<T>, query: &str) -> Result<()> {
+    let uri = req.uri().to_string();
+    let sep = if uri.contains('?') { '&' } else { '?' };
+    *req.uri_mut() = format!("{uri}{sep}{query}")
+        .parse()
+        .map_err(|err| Error::new(ErrorKind::Unexpected, "build uri with query").set_source(err))?;
+
+    Ok(())
+}
+
+/// Convert suffix range like `bytes=-1024` into absolute range with given
+/// content length.
+fn resolve_suffix_range(range: BytesRange, content_length: u64) -> Result<BytesRange> {
+    let range = match (range.offset(), range.size()) {
+        // `bytes=-0` can't be expressed as an absolute range.
+        (None, Some(0)) => {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "suffix range must not be empty",
+            ))
+        }
+        // Read the whole file if it's smaller than the suffix.
+        (None, Some(size)) if size >= content_length => BytesRange::from(..),
+        (None, Some(size)) => BytesRange::new(Some(content_length - size), Some(size)),
+        _ => range,
+    };
+
+    Ok(range)
+}
+
+/// Insert the headers of server side encryption specified by users.
+///
+/// Encryption scope can only be set while creating the file, files in scope
+/// will be decrypted automatically in later requests.
+fn insert_encryption_headers(
+    mut req: http::request::Builder,
+    encryption: &ServerSideEncryption,
+    is_create: bool,
+) -> Result<http::request::Builder> {
+    match encryption {
+        // Azure Files always encrypts data with Microsoft-managed keys,
+        // nothing need to be set.
+        ServerSideEncryption::ProviderManaged => {}
+        ServerSideEncryption::CustomerKey(key) => {
+            let mut v = HeaderValue::from_str(&BASE64_STANDARD.encode(key))
+                .map_err(new_request_build_error)?;
+            v.set_sensitive(true);
+            req = req.header(X_MS_ENCRYPTION_KEY, v);
+
+            let mut v = HeaderValue::from_str(&BASE64_STANDARD.encode(Sha256::digest(key)))
+                .map_err(new_request_build_error)?;
+            v.set_sensitive(true);
+            req = req.header(X_MS_ENCRYPTION_KEY_SHA256, v);
+
+            req = req.header(X_MS_ENCRYPTION_ALGORITHM, "AES256");
+        }
+        ServerSideEncryption::Scope(scope) if is_create => {
+            req = req.header(X_MS_ENCRYPTION_SCOPE, scope.as_str());
+        }
+        ServerSideEncryption::Scope(_) => {}
+    }
+
+    Ok(req)
+}
+
+/// Check the encryption returned by service matches the expected one.
+pub fn check_encryption_headers(
+    headers: &HeaderMap,
+    encryption: &ServerSideEncryption,
+) -> Result<()> {
+    let (name, expected) = match encryption {
+        ServerSideEncryption::CustomerKey(key) => (
+            X_MS_ENCRYPTION_KEY_SHA256,
+            BASE64_STANDARD.encode(Sha256::digest(key)),
+        ),
+        ServerSideEncryption::Scope(scope) => (X_MS_ENCRYPTION_SCOPE, scope.clone()),
+        _ => return Ok(()),
+    };
+
+    let actual = headers.get(name).and_then(|v| v.to_str().ok());
+    if actual == Some(expected.as_str()) {
+        return Ok(());
+    }
+
+    Err(Error::new(
+        ErrorKind::Unexpected,
+        "server side encryption of the file doesn't match",
+    )
+    .with_context("encryption", encryption.name())
+    .with_context("header", name))
+}
+
+/// Validate user defined metadata before sending them as `x-ms-meta-*`.
+///
+/// Azure requires metadata names to be valid C# identifiers, otherwise the
+/// request will be rejected by a plain `400 Bad Request`.
+///
+/// Reference: [Naming and Referencing Shares, Directories, Files, and Metadata](https://learn.microsoft.com/en-us/rest/api/storageservices/naming-and-referencing-shares--directories--files--and-metadata#metadata-names)
+pub fn validate_user_metadata(user_metadata: &HashMap<String, String>) -> Result<()> {
+    for key in user_metadata.keys() {
+        let mut chars = key.chars();
+        let valid = matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "user metadata key must be a valid C# identifier",
+            )
+            .with_context("service", Scheme::Azfile)
+            .with_context("key", key));
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate the length of absolute path before sending requests.
+///
+/// Azure rejects overly long paths with an opaque `400 InvalidHeaderValue`
+/// or `400 OutOfRangeInput`, so we report them early instead.
+///
+/// Reference: [Naming and Referencing Shares, Directories, Files, and Metadata](https://learn.microsoft.com/en-us/rest/api/storageservices/naming-and-referencing-shares--directories--files--and-metadata#directory-and-file-names)
+pub fn validate_path(abs_path: &str) -> Result<()> {
+    let p = abs_path.trim_end_matches('/');
+
+    if p.chars().count() > AZFILE_MAX_PATH_LENGTH {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            &format!("path too long for azfile (max {AZFILE_MAX_PATH_LENGTH} chars)"),
+        )
+        .with_context("service", Scheme::Azfile)
+        .with_context("path", abs_path));
+    }
+
+    if let Some(seg) = p
+        .split('/')
+        .find(|seg| seg.chars().count() > AZFILE_MAX_PATH_SEGMENT_LENGTH)
+    {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            &format!(
+                "path segment too long for azfile (max {AZFILE_MAX_PATH_SEGMENT_LENGTH} chars)"
+            ),
+        )
+        .with_context("service", Scheme::Azfile)
+        .with_context("path", abs_path)
+        .with_context("segment", seg));
+    }
+
+    Ok(())
+}
+
+/// Build a file service shared access signature for given file.
+///
+/// Returns the query string without leading `?`.
+///
+/// Reference: [Create a service SAS](https://learn.microsoft.com/en-us/rest/api/storageservices/create-service-sas)
+fn build_file_sas(
+    account_name: &str,
+    account_key: &str,
+    share_name: &str,
+    path: &str,
+    permission: &str,
+    expiry: DateTime<Utc>,
+) -> Result<String> {
+    let expiry = expiry.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    // The canonicalized resource of file service is different from blob's.
+    let resource = format!("/file/{account_name}/{share_name}/{path}");
+
+    let string_to_sign = [
+        permission,
+        // signedStart
+        "",
+        expiry.as_str(),
+        resource.as_str(),
+        // signedIdentifier
+        "",
+        // signedIP
+        "",
+        // signedProtocol
+        "",
+        AZFILE_VERSION,
+        // rscc, rscd, rsce, rscl, rsct
+        "",
+        "",
+        "",
+        "",
+        "",
+    ]
+    .join("\n");
+
+    let key = BASE64_STANDARD.decode(account_key).map_err(|err| {
+        Error::new(ErrorKind::ConfigInvalid, "account_key is not valid base64").set_source(err)
+    })?;
+    let mut mac = Hmac::<Sha256>::new_from_slice(&key).expect("hmac can take key of any size, qed");
+    mac.update(string_to_sign.as_bytes());
+    let signature = BASE64_STANDARD.encode(mac.finalize().into_bytes());
+
+    Ok(format!(
+        "sv={}&sr=f&sp={}&se={}&sig={}",
+        AZFILE_VERSION,
+        permission,
+        percent_encode_path(&expiry),
+        percent_encode_path(&signature)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn test_with_snapshot() {
+        let snapshot = Some("2023-10-15T12:03:40.0000000Z");
+        let cases = vec![
+            ("no snapshot", "https://a/s/p", None, "https://a/s/p"),
+            (
+                "url without query",
+                "https://a/s/p",
+                snapshot,
+                "https://a/s/p?sharesnapshot=2023-10-15T12%3A03%3A40.0000000Z",
+            ),
+            (
+                "url with query",
+                "https://a/s/p?restype=directory",
+                snapshot,
+                "https://a/s/p?restype=directory&sharesnapshot=2023-10-15T12%3A03%3A40.0000000Z",
+            ),
+        ];
+
+        for (desc, url, snapshot, expected) in cases {
+            assert_eq!(with_snapshot(url.to_string(), snapshot), expected, "{desc}");
+        }
+    }
+
+    fn new_test_core(root: &str) -> AzfileCore {
+        AzfileCore {
+            root: root.to_string(),
+            endpoint: "https://account.file.core.windows.net".to_string(),
+            share_name: "share".to_string(),
+            account_name: "account".to_string(),
+            account_key: None,
+            sas_token: None,
+            rename_replace_if_exists: false,
+            snapshot: None,
+            api_version: HeaderValue::from_static(AZFILE_VERSION),
+            extra_query: None,
+            client_request_id_prefix: String::new(),
+            copy_timeout: Duration::from_secs(3600),
+            client: HttpClient::new().expect("must init"),
+            loader: AzureStorageLoader::new(reqsign::AzureStorageConfig::default()),
+            signer: AzureStorageSigner::new(),
+        }
+    }
+
+    #[test]
+    fn test_new_client_request_id() {
+        let mut core = new_test_core("/");
+        let id = core.new_client_request_id();
+        assert!(uuid::Uuid::parse_str(&id).is_ok(), "{id}");
+        assert_ne!(id, core.new_client_request_id());
+
+        core.client_request_id_prefix = "my-app-".to_string();
+        let id = core.new_client_request_id();
+        let uuid = id.strip_prefix("my-app-").expect("prefix must exist");
+        assert!(uuid::Uuid::parse_str(uuid).is_ok(), "{id}");
+    }
+
+    #[test]
+    fn test_build_path_url() {
+        let cases = vec![
+            ("plain", "/", "dir/file", "dir/file"),
+            (
+                "space",
+                "/",
+                "Rust By Example.pdf",
+                "Rust%20By%20Example.pdf",
+            ),
+            ("plus", "/", "a+b", "a%2Bb"),
+            ("number sign", "/", "dir/#1", "dir/%231"),
+            ("question mark", "/", "what?.txt", "what%3F.txt"),
+            (
+                "non-ASCII",
+                "/",
+                "目录/文件",
+                "%E7%9B%AE%E5%BD%95/%E6%96%87%E4%BB%B6",
+            ),
+            ("dir", "/", "dir/sub dir/", "dir/sub%20dir/"),
+            ("root", "/my root/", "file", "my%20root/file"),
+        ];
+
+        for (desc, root, path, expected) in cases {
+            let core = new_test_core(root);
+            assert_eq!(
+                core.build_path_url(path),
+                format!("https://account.file.core.windows.net/share/{expected}"),
+                "{desc}"
+            );
+        }
+
+        // Requests should be sent to the encoded url.
+        let core = new_test_core("/");
+        let req = core
+            .azfile_get_file_properties_request("dir/Rust By Example.pdf")
+            .expect("build request must succeed");
+        assert_eq!(
+            req.uri().to_string(),
+            "https://account.file.core.windows.net/share/dir/Rust%20By%20Example.pdf"
+        );
+    }
+
+    #[test]
+    fn test_azfile_read_request_range() {
+        let core = new_test_core("/");
+
+        let cases = vec![
+            ("full", BytesRange::from(..), None),
+            (
+                "range",
+                BytesRange::from(1024..2048),
+                Some("bytes=1024-2047"),
+            ),
+            ("from offset", BytesRange::from(1024..), Some("bytes=1024-")),
+        ];
+
+        for (desc, range, expected) in cases {
+            let req = core
+                .azfile_read_request("dir/file", range, None)
+                .expect("build request must succeed");
+            assert_eq!(
+                req.uri().to_string(),
+                "https://account.file.core.windows.net/share/dir/file",
+                "{desc}"
+            );
+            assert_eq!(
+                req.headers().get(RANGE).map(|v| v.to_str().unwrap()),
+                expected,
+                "{desc}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_azfile_create_file_request_content_headers() {
+        let core = new_test_core("/");
+
+        let args = OpWrite::default()
+            .with_content_type("application/pdf")
+            .with_content_disposition("attachment; filename=\"a.pdf\"");
+        let req = core
+            .azfile_create_file_request("dir/a.pdf", Some(0), &args)
+            .expect("build request must succeed");
+        assert_eq!(req.headers()[X_MS_CONTENT_TYPE], "application/pdf");
+        assert_eq!(
+            req.headers()[X_MS_CONTENT_DISPOSITION],
+            "attachment; filename=\"a.pdf\""
+        );
+        // Plain `Content-*` headers will be ignored by Azure Files.
+        assert!(req.headers().get(CONTENT_TYPE).is_none());
+        assert!(req.headers().get(CONTENT_DISPOSITION).is_none());
+    }
+
+    #[test]
+    fn test_azfile_create_file_request_encryption() {
+        let core = new_test_core("/");
+
+        let key = [1; 32];
+        let args = OpWrite::default().with_encryption(ServerSideEncryption::CustomerKey(key));
+        let req = core
+            .azfile_create_file_request("dir/file", Some(0), &args)
+            .expect("build request must succeed");
+        let header = |name: &str| req.headers().get(name).map(|v| v.to_str().unwrap());
+        assert_eq!(
+            header(X_MS_ENCRYPTION_KEY),
+            Some(BASE64_STANDARD.encode(key).as_str())
+        );
+        assert_eq!(header(X_MS_ENCRYPTION_ALGORITHM), Some("AES256"));
+        assert!(req.headers()[X_MS_ENCRYPTION_KEY].is_sensitive());
+
+        // The key sha256 returned by service must match the given key.
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            X_MS_ENCRYPTION_KEY_SHA256,
+            req.headers()[X_MS_ENCRYPTION_KEY_SHA256].clone(),
+        );
+        assert!(
+            check_encryption_headers(&headers, &ServerSideEncryption::CustomerKey(key)).is_ok()
+        );
+        assert!(
+            check_encryption_headers(&headers, &ServerSideEncryption::CustomerKey([2; 32]))
+                .is_err()
+        );
+
+        // Scope will only be set while creating file.
+        let scope = ServerSideEncryption::Scope("scope".to_string());
+        let args = OpWrite::default().with_encryption(scope.clone());
+        let req = core
+            .azfile_create_file_request("dir/file", Some(0), &args)
+            .expect("build request must succeed");
+        assert_eq!(req.headers()[X_MS_ENCRYPTION_SCOPE], "scope");
+        let req = core
+            .azfile_read_request("dir/file", BytesRange::from(..), Some(&scope))
+            .expect("build request must succeed");
+        assert!(req.headers().get(X_MS_ENCRYPTION_SCOPE).is_none());
+    }
+
+    #[test]
+    fn test_build_file_sas() {
+        let expiry = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+
+        let sas = build_file_sas("account", "a2V5", "share", "dir/file.txt", "r", expiry)
+            .expect("build sas must succeed");
+
+        assert_eq!(
+            sas,
+            "sv=2022-11-02&sr=f&sp=r&se=2023-01-01T00%3A00%3A00Z&sig=x7qA61wC1moX15caUcO3p%2BhXK2BDzkI9Hdj4oGo/Nrc%3D"
+        );
+    }
+
+    #[test]
+    fn test_build_file_sas_invalid_key() {
+        let expiry = Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap();
+
+        let err = build_file_sas("account", "not base64!", "share", "file", "r", expiry)
+            .expect_err("invalid key must fail");
+        assert_eq!(err.kind(), ErrorKind::ConfigInvalid);
+    }
+
+    #[test]
+    fn test_resolve_suffix_range() {
+        let cases = vec![
+            (
+                "suffix",
+                BytesRange::new(None, Some(1024)),
+                4096,
+                "3072-4095",
+            ),
+            (
+                "larger than file",
+                BytesRange::new(None, Some(1024)),
+                512,
+                "0-",
+            ),
+            (
+                "not suffix",
+                BytesRange::from(1024..2048),
+                4096,
+                "1024-2047",
+            ),
+        ];
+
+        for (desc, range, content_length, expected) in cases {
+            let actual = resolve_suffix_range(range, content_length).expect("must succeed");
+            assert_eq!(actual.to_string(), expected, "{desc}");
+        }
+
+        let err = resolve_suffix_range(BytesRange::new(None, Some(0)), 4096)
+            .expect_err("empty suffix must fail");
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_validate_user_metadata() {
+        let cases = vec![
+            ("owner", true),
+            ("_owner_2", true),
+            ("Owner", true),
+            ("2owner", false),
+            ("owner-name", false),
+            ("owner name", false),
+            ("", false),
+        ];
+
+        for (key, valid) in cases {
+            let user_metadata = HashMap::from([(key.to_string(), "alice".to_string())]);
+            let res = validate_user_metadata(&user_metadata);
+            assert_eq!(res.is_ok(), valid, "{key}");
+            if let Err(err) = res {
+                assert_eq!(err.kind(), ErrorKind::InvalidInput);
+            }
+        }
+    }
+
+    #[test]
+    fn test_validate_path() {
+        let cases = vec![
+            ("normal path", "/dir/file".to_string(), true),
+            ("normal dir", "/dir/".to_string(), true),
+            (
+                "max segment",
+                format!("/dir/{}", "a".repeat(AZFILE_MAX_PATH_SEGMENT_LENGTH)),
+                true,
+            ),
+            (
+                "segment too long",
+                format!("/dir/{}", "a".repeat(AZFILE_MAX_PATH_SEGMENT_LENGTH + 1)),
+                false,
+            ),
+            (
+                "multi-byte segment counts chars",
+                format!("/{}", "文".repeat(AZFILE_MAX_PATH_SEGMENT_LENGTH)),
+                true,
+            ),
+            (
+                "path too long",
+                "/abc".repeat(AZFILE_MAX_PATH_LENGTH / 4 + 1),
+                false,
+            ),
+        ];
+
+        for (desc, path, valid) in cases {
+            let res = validate_path(&path);
+            assert_eq!(res.is_ok(), valid, "{desc}");
+            if let Err(err) = res {
+                assert_eq!(err.kind(), ErrorKind::InvalidInput, "{desc}");
+            }
+        }
     }
 }
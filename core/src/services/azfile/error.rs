@@ -16,8 +16,11 @@
 // under the License.
 
 use std::fmt::Debug;
+use std::time::Duration;
 
 use bytes::Buf;
+use http::header::RETRY_AFTER;
+use http::HeaderMap;
 use http::Response;
 use http::StatusCode;
 use quick_xml::de;
@@ -28,6 +31,9 @@ use crate::Error;
 use crate::ErrorKind;
 use crate::Result;
 
+use super::core::X_MS_CLIENT_REQUEST_ID;
+use super::core::X_MS_REQUEST_ID;
+
 /// AzfileError is the error returned by azure file service.
 #[derive(Default, Deserialize)]
 #[serde(default, rename_all = "PascalCase")]
@@ -37,6 +43,8 @@ struct AzfileError {
     query_parameter_name: String,
     query_parameter_value: String,
     reason: String,
+    header_name: String,
+    header_value: String,
 }
 
 impl Debug for AzfileError {
@@ -55,11 +63,22 @@ impl Debug for AzfileError {
         if !self.reason.is_empty() {
             de.field("reason", &self.reason);
         }
+        if !self.header_name.is_empty() {
+            de.field("header_name", &self.header_name);
+        }
+        if !self.header_value.is_empty() {
+            de.field("header_value", &self.header_value);
+        }
 
         de.finish()
     }
 }
 
+/// Parse the `x-ms-error-code` header from response headers.
+pub fn parse_error_code(headers: &HeaderMap) -> Option<&str> {
+    headers.get("x-ms-error-code").and_then(|v| v.to_str().ok())
+}
+
 /// Parse error response into Error.
 pub async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
     let (parts, body) = resp.into_parts();
@@ -71,6 +90,8 @@ pub async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
         StatusCode::PRECONDITION_FAILED | StatusCode::NOT_MODIFIED => {
             (ErrorKind::ConditionNotMatch, false)
         }
+        StatusCode::RANGE_NOT_SATISFIABLE => (ErrorKind::RangeNotSatisfied, false),
+        StatusCode::TOO_MANY_REQUESTS => (ErrorKind::RateLimited, true),
         StatusCode::INTERNAL_SERVER_ERROR
         | StatusCode::BAD_GATEWAY
         | StatusCode::SERVICE_UNAVAILABLE
@@ -78,9 +99,23 @@ pub async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
         _ => (ErrorKind::Unexpected, false),
     };
 
-    let mut message = match de::from_reader::<_, AzfileError>(bs.clone().reader()) {
-        Ok(azfile_err) => format!("{azfile_err:?}"),
-        Err(_) => String::from_utf8_lossy(&bs).into_owned(),
+    let azfile_err = de::from_reader::<_, AzfileError>(bs.clone().reader()).ok();
+
+    // The share itself doesn't exist, which means the config is invalid
+    // instead of the path is not found.
+    let code = parse_error_code(&parts.headers).or(azfile_err.as_ref().map(|v| v.code.as_str()));
+    let kind = match code {
+        Some("ShareNotFound") => ErrorKind::ConfigInvalid,
+        // Azure returns `InvalidHeaderValue` while the path carried in
+        // headers like `x-ms-file-rename-source` is too long or malformed.
+        // Retrying won't help, users need to change the input instead.
+        Some("InvalidHeaderValue") => ErrorKind::InvalidInput,
+        _ => kind,
+    };
+
+    let mut message = match &azfile_err {
+        Some(azfile_err) => format!("{azfile_err:?}"),
+        None => String::from_utf8_lossy(&bs).into_owned(),
     };
 
     // If there is no body here, fill with error code.
@@ -99,6 +134,27 @@ pub async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
     }
 
     let mut err = Error::new(kind, &message);
+    if let Some(code) = code.filter(|v| !v.is_empty()) {
+        err = err.with_service_code(code);
+    }
+
+    // Azure returns `Retry-After` while throttling, carry it so that callers
+    // could know how long to wait before next retry.
+    if retryable {
+        if let Some(dur) = parse_retry_after(&parts.headers) {
+            err = err.with_retry_after(dur);
+        }
+    }
+
+    // Azure support needs these ids to find the request in their logs.
+    for (key, header) in [
+        ("client_request_id", X_MS_CLIENT_REQUEST_ID),
+        ("request_id", X_MS_REQUEST_ID),
+    ] {
+        if let Some(v) = parts.headers.get(header).and_then(|v| v.to_str().ok()) {
+            err = err.with_context(key, v);
+        }
+    }
 
     err = with_error_response_context(err, parts);
 
@@ -108,3 +164,232 @@ pub async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
 
     Ok(err)
 }
+
+/// Parse `Retry-After` header in seconds.
+///
+/// Azure only returns delay seconds, so we don't handle http date here.
+fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::stream;
+    use http::HeaderValue;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_parse_error_range_not_satisfiable() -> Result<()> {
+        let bs = bytes::Bytes::from(
+            r#"<?xml version="1.0" encoding="utf-8"?><Error><Code>InvalidRange</Code><Message>The range specified is invalid for the current size of the resource.</Message></Error>"#,
+        );
+        let body =
+            IncomingAsyncBody::new(Box::new(oio::into_stream(stream::iter(vec![Ok(bs)]))), None);
+        let resp = Response::builder()
+            .status(StatusCode::RANGE_NOT_SATISFIABLE)
+            .body(body)
+            .unwrap();
+
+        let err = parse_error(resp).await?;
+        assert_eq!(err.kind(), ErrorKind::RangeNotSatisfied);
+        assert!(!err.is_temporary());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_parse_error_service_unavailable() -> Result<()> {
+        let body = IncomingAsyncBody::new(
+            Box::new(oio::into_stream(stream::iter(vec![
+                Ok(bytes::Bytes::new()),
+            ]))),
+            None,
+        );
+        let resp = Response::builder()
+            .status(StatusCode::SERVICE_UNAVAILABLE)
+            .header(RETRY_AFTER, "5")
+            .body(body)
+            .unwrap();
+
+        let err = parse_error(resp).await?;
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+        assert!(err.is_temporary());
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(5)));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_parse_error_request_id() -> Result<()> {
+        let body = IncomingAsyncBody::new(
+            Box::new(oio::into_stream(stream::iter(vec![
+                Ok(bytes::Bytes::new()),
+            ]))),
+            None,
+        );
+        let resp = Response::builder()
+            .status(StatusCode::FORBIDDEN)
+            .header(X_MS_CLIENT_REQUEST_ID, "my-app-1")
+            .header(X_MS_REQUEST_ID, "b3a0c5e2-001a-0047-29a5-6c4d7d000000")
+            .body(body)
+            .unwrap();
+
+        let err = parse_error(resp).await?;
+        let s = err.to_string();
+        assert!(s.contains("client_request_id: my-app-1"), "{s}");
+        assert!(
+            s.contains("request_id: b3a0c5e2-001a-0047-29a5-6c4d7d000000"),
+            "{s}"
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_parse_error_share_not_found() -> Result<()> {
+        let cases = vec![
+            ("share not found", "ShareNotFound", ErrorKind::ConfigInvalid),
+            (
+                "resource not found",
+                "ResourceNotFound",
+                ErrorKind::NotFound,
+            ),
+        ];
+
+        for (desc, code, expected) in cases {
+            let body = IncomingAsyncBody::new(
+                Box::new(oio::into_stream(stream::iter(vec![
+                    Ok(bytes::Bytes::new()),
+                ]))),
+                None,
+            );
+            let resp = Response::builder()
+                .status(StatusCode::NOT_FOUND)
+                .header("x-ms-error-code", code)
+                .body(body)
+                .unwrap();
+
+            let err = parse_error(resp).await?;
+            assert_eq!(err.kind(), expected, "{desc}");
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_parse_error_invalid_header_value() -> Result<()> {
+        let bs = bytes::Bytes::from(
+            r#"<?xml version="1.0" encoding="utf-8"?><Error><Code>InvalidHeaderValue</Code><Message>The value for one of the HTTP headers is not in the correct format.</Message><HeaderName>x-ms-file-rename-source</HeaderName><HeaderValue>https://account.file.core.windows.net/share/aaa</HeaderValue></Error>"#,
+        );
+        let body =
+            IncomingAsyncBody::new(Box::new(oio::into_stream(stream::iter(vec![Ok(bs)]))), None);
+        let resp = Response::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .body(body)
+            .unwrap();
+
+        let err = parse_error(resp).await?;
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        assert!(!err.is_temporary());
+        assert!(err.to_string().contains("x-ms-file-rename-source"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_parse_error_service_code() -> Result<()> {
+        let cases = vec![
+            (
+                "parent not found",
+                StatusCode::NOT_FOUND,
+                "ParentNotFound",
+                ErrorKind::NotFound,
+            ),
+            (
+                "resource not found",
+                StatusCode::NOT_FOUND,
+                "ResourceNotFound",
+                ErrorKind::NotFound,
+            ),
+            (
+                "share not found",
+                StatusCode::NOT_FOUND,
+                "ShareNotFound",
+                ErrorKind::ConfigInvalid,
+            ),
+            (
+                "authentication failed",
+                StatusCode::FORBIDDEN,
+                "AuthenticationFailed",
+                ErrorKind::PermissionDenied,
+            ),
+        ];
+
+        for (desc, status, code, kind) in cases {
+            let bs = bytes::Bytes::from(format!(
+                r#"<?xml version="1.0" encoding="utf-8"?><Error><Code>{code}</Code><Message>something wrong happened</Message></Error>"#
+            ));
+            let body = IncomingAsyncBody::new(
+                Box::new(oio::into_stream(stream::iter(vec![Ok(bs)]))),
+                None,
+            );
+            let resp = Response::builder().status(status).body(body).unwrap();
+
+            let err = parse_error(resp).await?;
+            assert_eq!(err.kind(), kind, "{desc}");
+            assert_eq!(err.service_code(), Some(code), "{desc}");
+        }
+
+        // No code returned means no service code.
+        let body = IncomingAsyncBody::new(
+            Box::new(oio::into_stream(stream::iter(vec![
+                Ok(bytes::Bytes::new()),
+            ]))),
+            None,
+        );
+        let resp = Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(body)
+            .unwrap();
+        let err = parse_error(resp).await?;
+        assert_eq!(err.service_code(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_error_code() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(parse_error_code(&headers), None);
+
+        headers.insert("x-ms-error-code", "ResourceTypeMismatch".parse().unwrap());
+        assert_eq!(parse_error_code(&headers), Some("ResourceTypeMismatch"));
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let cases = vec![
+            ("valid seconds", Some("5"), Some(Duration::from_secs(5))),
+            (
+                "with whitespace",
+                Some(" 10 "),
+                Some(Duration::from_secs(10)),
+            ),
+            ("http date", Some("Wed, 21 Oct 2015 07:28:00 GMT"), None),
+            ("missing", None, None),
+        ];
+
+        for (desc, input, expected) in cases {
+            let mut headers = HeaderMap::new();
+            if let Some(v) = input {
+                headers.insert(RETRY_AFTER, HeaderValue::from_static(v));
+            }
+            assert_eq!(parse_retry_after(&headers), expected, "{}", desc);
+        }
+    }
+}
@@ -0,0 +1,277 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::sync::Arc;
+
+use http::StatusCode;
+use log::warn;
+use uuid::Uuid;
+
+use super::core::AzfileCore;
+use super::error::parse_error;
+use crate::raw::*;
+use crate::*;
+
+/// AzfileLeaser manages [file leases](https://learn.microsoft.com/en-us/rest/api/storageservices/lease-file)
+/// of azure file service, which could be used as a distributed lock.
+///
+/// While a file is leased, writes and deletes without the lease id will be
+/// rejected by azure, and acquiring the lease again will fail with
+/// `ConditionNotMatch` until the lease is released or broken.
+///
+/// # Notes
+///
+/// - Azure File only supports infinite leases on files, so there is no
+///   duration or renew. Leases must be released or broken explicitly.
+/// - The file must exist before acquiring lease on it.
+///
+/// # Examples
+///
+/// ```no_run
+/// use anyhow::Result;
+/// use opendal::services::Azfile;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<()> {
+/// let mut builder = Azfile::default();
+/// builder.endpoint("https://account.file.core.windows.net");
+/// builder.share_name("share");
+///
+/// let leaser = builder.build_leaser()?;
+/// let lease = leaser.acquire_lease("locks/job").await?;
+/// // Do the work that requires mutual exclusion.
+/// lease.release().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct AzfileLeaser {
+    core: Arc<AzfileCore>,
+}
+
+impl Debug for AzfileLeaser {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AzfileLeaser")
+            .field("core", &self.core)
+            .finish()
+    }
+}
+
+impl AzfileLeaser {
+    pub(super) fn new(core: Arc<AzfileCore>) -> Self {
+        Self { core }
+    }
+
+    /// Acquire an infinite lease on given file.
+    ///
+    /// Returns `ConditionNotMatch` if the file has been leased by others.
+    /// The returned [`AzfileLease`] will release the lease on drop if it's
+    /// not released explicitly.
+    pub async fn acquire_lease(&self, path: &str) -> Result<AzfileLease> {
+        let path = check_lease_path(path)?;
+
+        let lease_id = Uuid::new_v4().to_string();
+        let resp = self
+            .core
+            .azfile_lease_file(&path, "acquire", &lease_id)
+            .await?;
+        match resp.status() {
+            StatusCode::CREATED => resp.into_body().consume().await?,
+            // Azure returns `409 LeaseAlreadyPresent` if the file has been leased.
+            StatusCode::CONFLICT => {
+                let err = parse_error(resp).await?;
+                return Err(Error::new(
+                    ErrorKind::ConditionNotMatch,
+                    "file has been leased by others",
+                )
+                .with_context("service", Scheme::Azfile)
+                .with_context("path", &path)
+                .set_source(err));
+            }
+            _ => return Err(parse_error(resp).await?),
+        }
+
+        Ok(AzfileLease::new(self.core.clone(), path, lease_id))
+    }
+
+    /// Release the lease of given file with lease id.
+    pub async fn release_lease(&self, path: &str, lease_id: &str) -> Result<()> {
+        let path = check_lease_path(path)?;
+        release_lease(&self.core, &path, lease_id).await
+    }
+
+    /// Break the lease of given file without knowing the lease id.
+    ///
+    /// This is useful to recover from leases left behind by crashed
+    /// holders.
+    pub async fn break_lease(&self, path: &str) -> Result<()> {
+        let path = check_lease_path(path)?;
+
+        let resp = self.core.azfile_lease_file(&path, "break", "").await?;
+        match resp.status() {
+            StatusCode::ACCEPTED => {
+                resp.into_body().consume().await?;
+                Ok(())
+            }
+            _ => Err(parse_error(resp).await?),
+        }
+    }
+}
+
+/// AzfileLease is an acquired lease on a file.
+///
+/// The lease will be released in background while dropping if it's not
+/// released explicitly, which requires a running tokio runtime. Prefer
+/// calling [`AzfileLease::release`] to get the result.
+pub struct AzfileLease {
+    core: Arc<AzfileCore>,
+    path: String,
+    id: String,
+    released: bool,
+}
+
+impl Debug for AzfileLease {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AzfileLease")
+            .field("path", &self.path)
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+impl AzfileLease {
+    /// Wrap an acquired lease, so that it will be released on drop.
+    pub(super) fn new(core: Arc<AzfileCore>, path: String, id: String) -> Self {
+        Self {
+            core,
+            path,
+            id,
+            released: false,
+        }
+    }
+
+    /// Get the path of leased file.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Get the lease id.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Release this lease.
+    pub async fn release(mut self) -> Result<()> {
+        self.released = true;
+        release_lease(&self.core, &self.path, &self.id).await
+    }
+
+    /// Mark this lease as gone without releasing, like the leased file has
+    /// been deleted.
+    pub(super) fn forget(mut self) {
+        self.released = true;
+    }
+
+    /// Release this lease, or break it if release failed.
+    ///
+    /// Failures will only be logged, so that callers could still get the
+    /// original error. The lease will still be released on drop if this
+    /// future is dropped before finished.
+    pub(super) async fn release_quietly(mut self) {
+        for action in ["release", "break"] {
+            let res = match self
+                .core
+                .azfile_lease_file(&self.path, action, &self.id)
+                .await
+            {
+                Ok(resp) if resp.status().is_success() => resp.into_body().consume().await,
+                Ok(resp) => Err(parse_error(resp).await.unwrap_or_else(|err| err)),
+                Err(err) => Err(err),
+            };
+            match res {
+                Ok(()) => break,
+                Err(err) => warn!("{action} lease {} of {} failed: {err}", self.id, self.path),
+            }
+        }
+        self.released = true;
+    }
+}
+
+impl Drop for AzfileLease {
+    fn drop(&mut self) {
+        if self.released {
+            return;
+        }
+
+        let core = self.core.clone();
+        let path = std::mem::take(&mut self.path);
+        let id = std::mem::take(&mut self.id);
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn(async move {
+                    if let Err(err) = release_lease(&core, &path, &id).await {
+                        warn!("release lease {id} of {path} on drop failed: {err}");
+                    }
+                });
+            }
+            Err(_) => {
+                warn!("lease {id} of {path} is not released since there is no tokio runtime")
+            }
+        }
+    }
+}
+
+async fn release_lease(core: &AzfileCore, path: &str, lease_id: &str) -> Result<()> {
+    let resp = core.azfile_lease_file(path, "release", lease_id).await?;
+    match resp.status() {
+        StatusCode::OK => {
+            resp.into_body().consume().await?;
+            Ok(())
+        }
+        _ => Err(parse_error(resp).await?),
+    }
+}
+
+/// Leases are only supported on files.
+fn check_lease_path(path: &str) -> Result<String> {
+    let path = normalize_path(path);
+    if path.ends_with('/') {
+        return Err(
+            Error::new(ErrorKind::IsADirectory, "lease is only supported on files")
+                .with_context("service", Scheme::Azfile)
+                .with_context("path", &path),
+        );
+    }
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_lease_path() {
+        assert_eq!(check_lease_path("/locks/job").unwrap(), "locks/job");
+        assert_eq!(
+            check_lease_path("locks/").unwrap_err().kind(),
+            ErrorKind::IsADirectory
+        );
+    }
+}
@@ -16,9 +16,12 @@
 // under the License.
 
 pub use backend::AzfileBuilder as Azfile;
+pub use lease::AzfileLease;
+pub use lease::AzfileLeaser;
 
 mod backend;
 mod core;
 mod error;
+mod lease;
 mod pager;
 mod writer;
@@ -15,120 +15,602 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use std::collections::VecDeque;
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use chrono::DateTime;
+use chrono::Utc;
+use flagset::FlagSet;
 use http::StatusCode;
+use log::debug;
 use quick_xml::de::from_str;
+use quick_xml::events::Event;
+use quick_xml::Reader;
 use serde::Deserialize;
 
+use crate::raw::oio::ReadExt;
 use crate::raw::*;
 use crate::*;
 
 use super::core::AzfileCore;
 use super::error::parse_error;
+use super::error::parse_error_code;
 
 pub struct AzfilePager {
     core: Arc<AzfileCore>,
     path: String,
-    limit: Option<usize>,
+    /// List all entries under path recursively if delimiter is empty.
+    recursive: bool,
+    /// Directories that waiting to be listed in recursive mode.
+    pending_dirs: VecDeque<String>,
+    /// The max entries to request per round trip.
+    page_size: usize,
+    /// The metakey required by users, properties not required will be skipped.
+    metakey: FlagSet<Metakey>,
+    /// The mode of entries required by users, entries of other mode will be
+    /// skipped without parsing their properties.
+    mode: Option<EntryMode>,
+    /// Whether an entry for the listed path itself still needs to be returned.
+    include_self: bool,
     done: bool,
+    /// Whether we have got any successful response.
+    started: bool,
+    /// The `NextMarker` returned by azure, used as continuation token.
     continuation: String,
 }
 
 impl AzfilePager {
-    pub fn new(core: Arc<AzfileCore>, path: String, limit: Option<usize>) -> Self {
+    pub fn new(
+        core: Arc<AzfileCore>,
+        path: String,
+        delimiter: &str,
+        page_size: Option<usize>,
+        metakey: FlagSet<Metakey>,
+        mode: Option<EntryMode>,
+        include_self: bool,
+        start_after_token: Option<&str>,
+    ) -> Self {
+        let recursive = delimiter.is_empty();
+
         Self {
             core,
-            path,
-            limit,
+            path: normalize_list_path(&path),
+            recursive,
+            pending_dirs: VecDeque::new(),
+            page_size: page_size.unwrap_or(AZFILE_MAX_LIST_PAGE_SIZE),
+            metakey,
+            mode,
+            // The entry has been returned before the token was got.
+            include_self: include_self && start_after_token.is_none(),
             done: false,
-            continuation: "".to_string(),
+            started: false,
+            continuation: start_after_token.unwrap_or_default().to_string(),
+        }
+    }
+
+    /// Check if users require entries of given mode.
+    fn require_mode(&self, mode: EntryMode) -> bool {
+        !matches!(self.mode, Some(m) if m != mode)
+    }
+
+    /// Check if users require given metakey.
+    fn require(&self, metakey: Metakey) -> bool {
+        self.metakey.contains(Metakey::Complete) || self.metakey.contains(metakey)
+    }
+
+    /// Build metadata from properties, only the required fields will be parsed.
+    fn parse_metadata(
+        &self,
+        mode: EntryMode,
+        file_id: &str,
+        properties: &Properties,
+    ) -> Result<Metadata> {
+        let mut meta = Metadata::new(mode);
+
+        if self.require(Metakey::Etag) {
+            if let Some(etag) = &properties.etag {
+                meta.set_etag(&normalize_etag(etag));
+            }
+        }
+        if self.require(Metakey::FileId) {
+            meta.set_file_id(file_id);
+        }
+        if mode.is_file() && self.require(Metakey::ContentLength) {
+            meta.set_content_length(properties.content_length.unwrap_or(0));
         }
+        if self.require(Metakey::LastModified) {
+            meta = with_last_modified(meta, properties);
+        }
+        if self.require(Metakey::Created) || self.require(Metakey::LastAccessed) {
+            meta = with_timestamps(meta, properties)?;
+        }
+
+        Ok(meta)
+    }
+
+    /// Build the entry of listed dir itself from the enumeration response.
+    fn self_entry(&self, directory_id: Option<&str>) -> oio::Entry {
+        let mut meta = Metadata::new(EntryMode::DIR);
+        if self.require(Metakey::FileId) {
+            if let Some(id) = directory_id {
+                meta.set_file_id(id);
+            }
+        }
+
+        let path = if self.path.is_empty() {
+            "/"
+        } else {
+            &self.path
+        };
+        oio::Entry::new(path, meta)
+    }
+
+    /// Check if given dir path is a file actually.
+    async fn is_file(&self, path: &str) -> Result<bool> {
+        let path = path.trim_end_matches('/');
+        if path.is_empty() {
+            return Ok(false);
+        }
+
+        let resp = self.core.azfile_get_file_properties(path).await?;
+        let status = resp.status();
+        resp.into_body().consume().await?;
+        Ok(status == StatusCode::OK)
+    }
+
+    /// Move to the next pending directory, mark as done if there is none.
+    fn advance_dir(&mut self) {
+        self.continuation.clear();
+        match self.pending_dirs.pop_front() {
+            Some(dir) => self.path = dir,
+            None => self.done = true,
+        }
+    }
+}
+
+/// Normalize the path to list into the form of `a/b/`.
+///
+/// The leading `/` will be trimmed and a trailing `/` will be added, so that
+/// `dir` and `dir/` list the same directory and entry paths can be joined
+/// with names directly. Root will be normalized into an empty string.
+fn normalize_list_path(path: &str) -> String {
+    let path = path.trim_matches('/');
+    if path.is_empty() {
+        return String::new();
+    }
+    format!("{path}/")
+}
+
+/// Build the `include` of list request, so that azure only returns the
+/// properties required by users.
+///
+/// `Content-Length` is always returned, while timestamps (including
+/// `Last-Modified`) and `Etag` are only returned when included.
+fn build_list_include(metakey: FlagSet<Metakey>) -> Option<String> {
+    let require = |key: Metakey| metakey.contains(Metakey::Complete) || metakey.contains(key);
+
+    let mut include = Vec::new();
+    if require(Metakey::LastModified) || require(Metakey::Created) || require(Metakey::LastAccessed)
+    {
+        include.push("Timestamps");
+    }
+    if require(Metakey::Etag) {
+        include.push("ETag");
+    }
+
+    if include.is_empty() {
+        None
+    } else {
+        Some(include.join(","))
     }
 }
 
+fn new_not_a_directory_error(path: &str) -> Error {
+    Error::new(
+        ErrorKind::NotADirectory,
+        "the path trying to list is not a directory",
+    )
+    .with_operation("AzfilePager::next")
+    .with_context("path", path)
+}
+
 #[async_trait]
 impl oio::Page for AzfilePager {
     async fn next(&mut self) -> Result<Option<Vec<oio::Entry>>> {
-        if self.done {
-            return Ok(None);
-        }
+        loop {
+            if self.done {
+                return Ok(None);
+            }
 
-        let resp = self
-            .core
-            .azfile_list(&self.path, &self.limit, &self.continuation)
-            .await?;
+            let max_results = self.page_size;
+            let include = build_list_include(self.metakey);
 
-        let status = resp.status();
+            let resp = self
+                .core
+                .azfile_list(
+                    &self.path,
+                    max_results,
+                    include.as_deref(),
+                    &self.continuation,
+                )
+                .await?;
 
-        if status != StatusCode::OK {
-            if status == StatusCode::NOT_FOUND {
-                return Ok(None);
+            let status = resp.status();
+
+            if status != StatusCode::OK {
+                if parse_error_code(resp.headers()) == Some("ResourceTypeMismatch") {
+                    return Err(new_not_a_directory_error(&self.path));
+                }
+                if status == StatusCode::NOT_FOUND {
+                    resp.into_body().consume().await?;
+                    // Directory could be removed during recursive listing, skip it.
+                    if self.recursive && !self.pending_dirs.is_empty() {
+                        self.advance_dir();
+                        continue;
+                    }
+                    // Azure returns 404 while listing a file, check it to return a
+                    // clear error instead of an empty list.
+                    if !self.started && self.is_file(&self.path).await? {
+                        return Err(new_not_a_directory_error(&self.path));
+                    }
+                    return Ok(None);
+                }
+                return Err(parse_error(resp).await?);
             }
-            return Err(parse_error(resp).await?);
+            self.started = true;
+
+            let mut body = resp.into_body();
+            let mut parser = EnumerationParser::default();
+            let mut entries = Vec::new();
+            let mut include_self = std::mem::take(&mut self.include_self);
+
+            let parent = self.path.clone();
+
+            // Parse the response incrementally so that we only hold at most one
+            // incomplete entry in memory instead of the whole body.
+            while let Some(bs) = body.next().await {
+                let items = parser.feed(&bs?)?;
+                // The entry of listed dir goes first, it's only known after
+                // the header has been parsed.
+                if include_self && parser.header_done {
+                    include_self = false;
+                    if self.require_mode(EntryMode::DIR) {
+                        entries.push(self.self_entry(parser.directory_id()));
+                    }
+                }
+                for item in items {
+                    match item {
+                        EnumerationEntry::File(_) if !self.require_mode(EntryMode::FILE) => {}
+                        EnumerationEntry::File(file) => {
+                            let meta = self.parse_metadata(
+                                EntryMode::FILE,
+                                &file.file_id,
+                                &file.properties,
+                            )?;
+                            let path = parent.clone() + &file.name;
+                            entries.push(oio::Entry::new(&path, meta));
+                        }
+                        EnumerationEntry::Directory(dir) => {
+                            let path = parent.clone() + &dir.name + "/";
+                            if self.recursive {
+                                self.pending_dirs.push_back(path.clone());
+                            }
+                            // Dirs are still walked in recursive mode even if
+                            // they are not required.
+                            if !self.require_mode(EntryMode::DIR) {
+                                continue;
+                            }
+                            let meta =
+                                self.parse_metadata(EntryMode::DIR, &dir.file_id, &dir.properties)?;
+                            entries.push(oio::Entry::new(&path, meta));
+                        }
+                    }
+                }
+            }
+
+            // Server could clamp `MaxResults` (to 5000 for now), log it to
+            // help diagnose why the page is smaller than expected.
+            debug!(
+                "azfile list {} requested max results {}, server applied {:?}",
+                self.path,
+                max_results,
+                parser.max_results()
+            );
+
+            let next_marker = parser.finish()?;
+            if next_marker.is_empty() {
+                if self.recursive {
+                    self.advance_dir();
+                } else {
+                    self.continuation.clear();
+                    self.done = true;
+                }
+            } else {
+                self.continuation = next_marker;
+            }
+
+            // Azure could return an empty page with a non-empty `NextMarker`, and
+            // an empty directory in recursive mode doesn't mean the end of listing
+            // either. Keep going until `done` has been set.
+            if entries.is_empty() {
+                continue;
+            }
+
+            return Ok(Some(entries));
         }
+    }
 
-        let bs = resp.into_body().bytes().await?;
+    fn continuation_token(&self) -> Option<String> {
+        // Marker only makes sense within one directory.
+        if self.recursive || self.continuation.is_empty() {
+            return None;
+        }
 
-        let text = String::from_utf8(bs.to_vec()).expect("response convert to string must success");
+        Some(self.continuation.clone())
+    }
+}
 
-        let results: EnumerationResults = from_str(&text).map_err(|e| {
-            Error::new(ErrorKind::Unexpected, "deserialize xml from response").set_source(e)
-        })?;
+/// The max results azure allows for one list request.
+const AZFILE_MAX_LIST_PAGE_SIZE: usize = 5000;
 
+/// Normalize etag returned in list response by removing the escaping.
+///
+/// Azure could return etag with escaped quotes like `\"0x8DBBDC4F8AC4AEF\"`,
+/// we store it as `"0x8DBBDC4F8AC4AEF"` to keep consistent with the `ETag`
+/// header returned by stat, so that it can be used in `If-Match` directly.
+/// Etags without quotes are returned as is.
+fn normalize_etag(etag: &str) -> String {
+    etag.trim().replace("\\\"", "\"")
+}
+
+/// Fill last modified into metadata.
+///
+/// Azure may omit `Last-Modified` for some entries, absent or unparseable
+/// values will be treated as `None` instead of failing the whole page. The
+/// key will be marked as set so that users will get `None` instead of panic.
+fn with_last_modified(mut meta: Metadata, properties: &Properties) -> Metadata {
+    match properties.last_modified.as_deref() {
+        None | Some("") => {}
+        Some(v) => match parse_datetime_from_rfc2822(v) {
+            Ok(v) => {
+                meta.set_last_modified(v);
+            }
+            Err(err) => debug!("ignore invalid last modified {v:?} of entry: {err}"),
+        },
+    }
+
+    let metakey = meta.metakey() | Metakey::LastModified;
+    meta.with_metakey(metakey)
+}
+
+/// Fill creation time and last access time into metadata.
+///
+/// Both keys will be marked as set even if azure doesn't return them, so
+/// that users will get `None` instead of panic.
+fn with_timestamps(mut meta: Metadata, properties: &Properties) -> Result<Metadata> {
+    if let Some(v) = parse_optional_datetime(&properties.creation_time)? {
+        meta.set_created(v);
+    }
+    if let Some(v) = parse_optional_datetime(&properties.last_access_time)? {
+        meta.set_last_accessed(v);
+    }
+
+    let metakey = meta.metakey() | Metakey::Created | Metakey::LastAccessed;
+    Ok(meta.with_metakey(metakey))
+}
+
+fn parse_optional_datetime(s: &Option<String>) -> Result<Option<DateTime<Utc>>> {
+    match s.as_deref() {
+        None | Some("") => Ok(None),
+        Some(v) => parse_datetime_from_rfc3339(v).map(Some),
+    }
+}
+
+const ENUMERATION_RESULTS: &[u8] = b"EnumerationResults";
+const ENTRIES: &[u8] = b"Entries";
+const FILE: &[u8] = b"File";
+const DIRECTORY: &[u8] = b"Directory";
+const MAX_RESULTS: &[u8] = b"MaxResults";
+const DIRECTORY_ID: &[u8] = b"DirectoryId";
+const NEXT_MARKER: &[u8] = b"NextMarker";
+
+/// EnumerationParser parses the `EnumerationResults` returned by azfile
+/// incrementally.
+///
+/// Bytes are only buffered until the xml event they belong to is complete,
+/// every `<File>` and `<Directory>` element will be parsed and dropped from
+/// buffer as soon as it's closed. So at most one incomplete entry will be
+/// kept in memory.
+#[derive(Default)]
+struct EnumerationParser {
+    buf: Vec<u8>,
+    /// Names of the elements that have been opened but not closed yet.
+    stack: Vec<Vec<u8>>,
+    /// Whether the root `<EnumerationResults>` has been closed.
+    root_closed: bool,
+    header_done: bool,
+    max_results: Option<usize>,
+    directory_id: Option<String>,
+    next_marker: String,
+}
+
+enum EnumerationEntry {
+    File(File),
+    Directory(Directory),
+}
+
+impl EnumerationParser {
+    /// Feed more bytes into parser, and return all complete entries.
+    fn feed(&mut self, bs: &[u8]) -> Result<Vec<EnumerationEntry>> {
+        self.buf.extend_from_slice(bs);
+
+        let mut buf = std::mem::take(&mut self.buf);
         let mut entries = Vec::new();
+        let consumed = self.parse(&buf, &mut entries)?;
+
+        // Keep the bytes of incomplete event for next feed.
+        buf.drain(..consumed);
+        self.buf = buf;
+        Ok(entries)
+    }
 
-        for file in results.entries.file {
-            let meta = Metadata::new(EntryMode::FILE)
-                .with_etag(file.properties.etag)
-                .with_content_length(file.properties.content_length.unwrap_or(0))
-                .with_last_modified(parse_datetime_from_rfc2822(&file.properties.last_modified)?);
-            let path = self.path.clone().trim_start_matches('/').to_string() + &file.name;
-            entries.push(oio::Entry::new(&path, meta));
+    /// Parse all complete events in `buf`, returns the size of consumed bytes.
+    fn parse(&mut self, buf: &[u8], entries: &mut Vec<EnumerationEntry>) -> Result<usize> {
+        let mut reader = Reader::from_reader(buf);
+        // Elements opened by previous feeds will be closed in this reader.
+        reader.trim_text(true).check_end_names(false);
+
+        let mut consumed = 0;
+        loop {
+            let event = match reader.read_event() {
+                Ok(event) => event,
+                // The event is split between chunks, wait for more bytes.
+                Err(quick_xml::Error::UnexpectedEof(_)) => return Ok(consumed),
+                Err(err) => return Err(new_xml_parse_error(err)),
+            };
+
+            match event {
+                Event::Eof => return Ok(buf.len()),
+                Event::Start(e) => {
+                    let name = e.name().as_ref().to_vec();
+                    self.check_root(&name)?;
+
+                    if self.is_entry(&name) {
+                        match reader.read_to_end(e.name()) {
+                            Ok(_) => {}
+                            Err(quick_xml::Error::UnexpectedEof(_)) => return Ok(consumed),
+                            Err(err) => return Err(new_xml_parse_error(err)),
+                        }
+                        let end = reader.buffer_position();
+                        entries.push(parse_entry(&name, &buf[consumed..end])?);
+                    } else {
+                        self.stack.push(name);
+                        self.check_header_done();
+                    }
+                }
+                Event::Empty(e) => {
+                    let name = e.name();
+                    let name = name.as_ref();
+                    self.check_root(name)?;
+
+                    if self.stack.is_empty() {
+                        self.root_closed = true;
+                        self.header_done = true;
+                    } else if self.stack.len() == 1 && (name == ENTRIES || name == NEXT_MARKER) {
+                        self.header_done = true;
+                    }
+                }
+                Event::End(_) => {
+                    self.stack.pop();
+                    if self.stack.is_empty() {
+                        self.root_closed = true;
+                        self.header_done = true;
+                    }
+                }
+                Event::Text(e) => {
+                    // Text could be split between chunks, it's only complete
+                    // once the next tag has been seen.
+                    if reader.buffer_position() == buf.len() {
+                        return Ok(consumed);
+                    }
+                    let text = e.unescape().map_err(new_xml_parse_error)?;
+                    self.on_text(&text)?;
+                }
+                _ => {}
+            }
+
+            consumed = reader.buffer_position();
         }
+    }
 
-        for dir in results.entries.directory {
-            let meta = Metadata::new(EntryMode::DIR)
-                .with_etag(dir.properties.etag)
-                .with_last_modified(parse_datetime_from_rfc2822(&dir.properties.last_modified)?);
-            let path = self.path.clone().trim_start_matches('/').to_string() + &dir.name + "/";
-            entries.push(oio::Entry::new(&path, meta));
+    /// Only `<EnumerationResults>` is allowed as the root element, so that
+    /// bodies like html error pages won't be treated as an empty listing.
+    fn check_root(&self, name: &[u8]) -> Result<()> {
+        if self.stack.is_empty() && (self.root_closed || name != ENUMERATION_RESULTS) {
+            return Err(new_invalid_enumeration_error()
+                .with_context("element", String::from_utf8_lossy(name)));
         }
+        Ok(())
+    }
 
-        if results.next_marker.is_empty() {
-            self.done = true;
-        } else {
-            self.continuation = results.next_marker;
+    /// Check if `name` is an entry under `<Entries>`.
+    fn is_entry(&self, name: &[u8]) -> bool {
+        self.stack.len() == 2 && self.stack[1] == ENTRIES && (name == FILE || name == DIRECTORY)
+    }
+
+    /// Header ends once `<Entries>` or `<NextMarker>` has been opened.
+    fn check_header_done(&mut self) {
+        if let [_, name] = self.stack.as_slice() {
+            if name.as_slice() == ENTRIES || name.as_slice() == NEXT_MARKER {
+                self.header_done = true;
+            }
         }
+    }
 
-        if entries.is_empty() {
-            Ok(None)
-        } else {
-            Ok(Some(entries))
+    fn on_text(&mut self, text: &str) -> Result<()> {
+        match self.stack.as_slice() {
+            [] => return Err(new_invalid_enumeration_error()),
+            [_, name] if name.as_slice() == MAX_RESULTS => self.max_results = text.parse().ok(),
+            [_, name] if name.as_slice() == DIRECTORY_ID => {
+                self.directory_id = Some(text.to_string())
+            }
+            [_, name] if name.as_slice() == NEXT_MARKER => self.next_marker = text.to_string(),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// The `MaxResults` applied by server, only valid after header has been
+    /// fed.
+    fn max_results(&self) -> Option<usize> {
+        self.max_results
+    }
+
+    /// The `DirectoryId` of listed dir, only valid after header has been
+    /// fed.
+    fn directory_id(&self) -> Option<&str> {
+        self.directory_id.as_deref()
+    }
+
+    /// Finish the parse and return the next marker.
+    ///
+    /// Empty string will be returned if there are no more entries.
+    fn finish(self) -> Result<String> {
+        if !self.root_closed {
+            return Err(new_invalid_enumeration_error());
         }
+
+        Ok(self.next_marker)
     }
 }
 
-#[derive(Debug, Deserialize, PartialEq)]
-#[serde(rename_all = "PascalCase")]
-struct EnumerationResults {
-    marker: Option<String>,
-    prefix: Option<String>,
-    max_results: Option<u32>,
-    directory_id: Option<String>,
-    entries: Entries,
-    #[serde(default)]
-    next_marker: String,
+/// Deserialize a complete `<File>` or `<Directory>` element.
+fn parse_entry(name: &[u8], bs: &[u8]) -> Result<EnumerationEntry> {
+    let element = std::str::from_utf8(bs)
+        .map_err(|e| Error::new(ErrorKind::Unexpected, "decode xml from response").set_source(e))?;
+
+    let entry = if name == FILE {
+        EnumerationEntry::File(from_str(element).map_err(new_xml_deserialize_error)?)
+    } else {
+        EnumerationEntry::Directory(from_str(element).map_err(new_xml_deserialize_error)?)
+    };
+    Ok(entry)
 }
 
-#[derive(Debug, Deserialize, PartialEq)]
-#[serde(rename_all = "PascalCase")]
-struct Entries {
-    #[serde(default)]
-    file: Vec<File>,
-    #[serde(default)]
-    directory: Vec<Directory>,
+fn new_invalid_enumeration_error() -> Error {
+    Error::new(
+        ErrorKind::Unexpected,
+        "list response is not a complete EnumerationResults",
+    )
+}
+
+fn new_xml_parse_error(e: quick_xml::Error) -> Error {
+    Error::new(ErrorKind::Unexpected, "parse xml from response").set_source(e)
+}
+
+fn new_xml_deserialize_error(e: quick_xml::DeError) -> Error {
+    Error::new(ErrorKind::Unexpected, "deserialize xml from response").set_source(e)
 }
 
 #[derive(Debug, Deserialize, PartialEq)]
@@ -154,24 +636,43 @@ struct Directory {
 struct Properties {
     #[serde(rename = "Content-Length")]
     content_length: Option<u64>,
-    #[serde(rename = "CreationTime")]
-    creation_time: String,
-    #[serde(rename = "LastAccessTime")]
-    last_access_time: String,
-    #[serde(rename = "LastWriteTime")]
-    last_write_time: String,
-    #[serde(rename = "ChangeTime")]
-    change_time: String,
-    #[serde(rename = "Last-Modified")]
-    last_modified: String,
-    #[serde(rename = "Etag")]
-    etag: String,
+    #[serde(rename = "CreationTime", default)]
+    creation_time: Option<String>,
+    #[serde(rename = "LastAccessTime", default)]
+    last_access_time: Option<String>,
+    #[serde(rename = "LastWriteTime", default)]
+    last_write_time: Option<String>,
+    #[serde(rename = "ChangeTime", default)]
+    change_time: Option<String>,
+    #[serde(rename = "Last-Modified", default)]
+    last_modified: Option<String>,
+    #[serde(rename = "Etag", default)]
+    etag: Option<String>,
 }
 
 #[cfg(test)]
 mod tests {
+    use rand::rngs::StdRng;
+    use rand::Rng;
+    use rand::RngCore;
+    use rand::SeedableRng;
+
     use super::*;
 
+    fn parse_in_chunks(xml: &[u8], size: usize) -> Result<(Vec<File>, Vec<Directory>, String)> {
+        let mut parser = EnumerationParser::default();
+        let (mut files, mut dirs) = (Vec::new(), Vec::new());
+        for chunk in xml.chunks(size) {
+            for entry in parser.feed(chunk)? {
+                match entry {
+                    EnumerationEntry::File(v) => files.push(v),
+                    EnumerationEntry::Directory(v) => dirs.push(v),
+                }
+            }
+        }
+        Ok((files, dirs, parser.finish()?))
+    }
+
     #[test]
     fn test_parse_list_result() {
         let xml = r#"
@@ -212,20 +713,421 @@ mod tests {
 </EnumerationResults>
         "#;
 
-        let results: EnumerationResults = from_str(xml).unwrap();
+        // Feed in different chunk size to make sure split tags are handled.
+        for size in [1, 7, 64, xml.len()] {
+            let (files, dirs, next_marker) = parse_in_chunks(xml.as_bytes(), size).unwrap();
+
+            assert_eq!(files.len(), 1);
+            assert_eq!(files[0].name, "Rust By Example.pdf");
+            assert_eq!(files[0].file_id, "13835093239654252544");
+            assert_eq!(
+                files[0].properties.etag.as_deref(),
+                Some("\\\"0x8DBBDC4F8AC4AEF\\\"")
+            );
+
+            assert_eq!(dirs.len(), 1);
+            assert_eq!(dirs[0].name, "test_list_rich_dir");
+            assert_eq!(dirs[0].file_id, "12105702186650959872");
+            assert_eq!(
+                dirs[0].properties.etag.as_deref(),
+                Some("\\\"0x8DBCD76C58C3E96\\\"")
+            );
 
-        assert_eq!(results.entries.file[0].name, "Rust By Example.pdf");
+            assert_eq!(next_marker, "");
+        }
 
+        let (files, _, _) = parse_in_chunks(xml.as_bytes(), xml.len()).unwrap();
+        let meta = with_timestamps(Metadata::new(EntryMode::FILE), &files[0].properties).unwrap();
+        assert_eq!(
+            meta.created(),
+            Some(parse_datetime_from_rfc3339("2023-09-25T12:43:05.8483527Z").unwrap())
+        );
         assert_eq!(
-            results.entries.file[0].properties.etag,
-            "\\\"0x8DBBDC4F8AC4AEF\\\""
+            meta.last_accessed(),
+            Some(parse_datetime_from_rfc3339("2023-09-25T12:43:05.8483527Z").unwrap())
         );
+    }
 
-        assert_eq!(results.entries.directory[0].name, "test_list_rich_dir");
+    #[test]
+    fn test_with_timestamps_missing() {
+        let xml = r#"
+<Properties>
+    <Last-Modified>Mon, 25 Sep 2023 12:43:08 GMT</Last-Modified>
+    <Etag>"0x8DBBDC4F8AC4AEF"</Etag>
+    <CreationTime></CreationTime>
+</Properties>
+        "#;
 
-        assert_eq!(
-            results.entries.directory[0].properties.etag,
-            "\\\"0x8DBCD76C58C3E96\\\""
+        let properties: Properties = from_str(xml).unwrap();
+        let meta = with_timestamps(Metadata::new(EntryMode::FILE), &properties).unwrap();
+        assert_eq!(meta.created(), None);
+        assert_eq!(meta.last_accessed(), None);
+    }
+
+    #[test]
+    fn test_with_last_modified() {
+        let cases = vec![
+            (
+                "valid",
+                "<Last-Modified>Mon, 25 Sep 2023 12:43:08 GMT</Last-Modified>",
+                true,
+            ),
+            ("missing", "", false),
+            ("empty", "<Last-Modified></Last-Modified>", false),
+            ("invalid", "<Last-Modified>yesterday</Last-Modified>", false),
+        ];
+
+        for (desc, last_modified, expected) in cases {
+            let xml = format!(
+                r#"<Properties><Content-Length>1</Content-Length>{last_modified}</Properties>"#
+            );
+            let properties: Properties = from_str(&xml).unwrap();
+            let meta = with_last_modified(Metadata::new(EntryMode::FILE), &properties);
+            assert_eq!(meta.last_modified().is_some(), expected, "{desc}");
+        }
+    }
+
+    #[test]
+    fn test_parse_list_result_with_next_marker() {
+        let xml = r#"
+<?xml version="1.0" encoding="utf-8"?>
+<EnumerationResults ServiceEndpoint="https://myaccount.file.core.windows.net/" ShareName="myshare" DirectoryPath="">
+  <MaxResults>1</MaxResults>
+  <Entries>
+    <Directory>
+        <Name>a</Name>
+        <FileId>12105702186650959872</FileId>
+        <Properties>
+            <Last-Modified>Sun, 15 Oct 2023 12:03:40 GMT</Last-Modified>
+            <Etag>"0x8DBCD76C58C3E96"</Etag>
+        </Properties>
+    </Directory>
+  </Entries>
+  <NextMarker>2!12!YiZhPQ--</NextMarker>
+</EnumerationResults>
+        "#;
+
+        for size in [1, 5, xml.len()] {
+            let (files, dirs, next_marker) = parse_in_chunks(xml.as_bytes(), size).unwrap();
+            assert!(files.is_empty());
+            assert_eq!(dirs.len(), 1);
+            assert_eq!(next_marker, "2!12!YiZhPQ--");
+        }
+    }
+
+    #[test]
+    fn test_normalize_list_path() {
+        let cases = vec![
+            ("root", "", ""),
+            ("root with slash", "/", ""),
+            ("dir", "dir", "dir/"),
+            ("dir with slash", "dir/", "dir/"),
+            ("dir with leading slash", "/dir", "dir/"),
+            ("nested dir", "a/b/", "a/b/"),
+            ("nested dir without slash", "a/b", "a/b/"),
+        ];
+
+        for (desc, input, expected) in cases {
+            let path = normalize_list_path(input);
+            assert_eq!(path, expected, "{desc}");
+            assert!(!(path + "file").contains("//"), "{desc}");
+        }
+    }
+
+    #[test]
+    fn test_parse_max_results() {
+        let xml = r#"
+<?xml version="1.0" encoding="utf-8"?>
+<EnumerationResults ServiceEndpoint="https://myaccount.file.core.windows.net/" ShareName="myshare" DirectoryPath="">
+  <MaxResults>5000</MaxResults>
+  <Entries />
+  <NextMarker />
+</EnumerationResults>
+        "#;
+
+        for size in [1, 5, xml.len()] {
+            let mut parser = EnumerationParser::default();
+            for chunk in xml.as_bytes().chunks(size) {
+                assert!(parser.feed(chunk).unwrap().is_empty());
+            }
+            assert_eq!(parser.max_results(), Some(5000));
+            assert_eq!(parser.finish().unwrap(), "");
+        }
+
+        let mut parser = EnumerationParser::default();
+        parser
+            .feed(b"<EnumerationResults><Entries></Entries><NextMarker /></EnumerationResults>")
+            .unwrap();
+        assert_eq!(parser.max_results(), None);
+    }
+
+    #[test]
+    fn test_parse_directory_id() {
+        let xml = r#"
+<?xml version="1.0" encoding="utf-8"?>
+<EnumerationResults ServiceEndpoint="https://myaccount.file.core.windows.net/" ShareName="myshare" DirectoryPath="dir">
+  <MaxResults>5000</MaxResults>
+  <DirectoryId>13835128424026341376</DirectoryId>
+  <Entries />
+  <NextMarker />
+</EnumerationResults>
+        "#;
+
+        for size in [1, 5, xml.len()] {
+            let mut parser = EnumerationParser::default();
+            for chunk in xml.as_bytes().chunks(size) {
+                assert!(parser.feed(chunk).unwrap().is_empty());
+            }
+            assert_eq!(parser.directory_id(), Some("13835128424026341376"));
+        }
+
+        let mut parser = EnumerationParser::default();
+        parser
+            .feed(b"<EnumerationResults><Entries></Entries><NextMarker /></EnumerationResults>")
+            .unwrap();
+        assert_eq!(parser.directory_id(), None);
+    }
+
+    #[test]
+    fn test_parse_list_result_empty_page_with_next_marker() {
+        let xml = r#"
+<?xml version="1.0" encoding="utf-8"?>
+<EnumerationResults ServiceEndpoint="https://myaccount.file.core.windows.net/" ShareName="myshare" DirectoryPath="">
+  <Prefix>b</Prefix>
+  <MaxResults>1</MaxResults>
+  <Entries />
+  <NextMarker>2!12!YiZhPQ--</NextMarker>
+</EnumerationResults>
+        "#;
+
+        for size in [1, 5, xml.len()] {
+            let (files, dirs, next_marker) = parse_in_chunks(xml.as_bytes(), size).unwrap();
+            assert!(files.is_empty());
+            assert!(dirs.is_empty());
+            assert_eq!(next_marker, "2!12!YiZhPQ--");
+        }
+    }
+
+    #[test]
+    fn test_build_list_include() {
+        let cases = vec![
+            ("mode only", FlagSet::from(Metakey::Mode), None),
+            (
+                "content length only",
+                Metakey::Mode | Metakey::ContentLength,
+                None,
+            ),
+            (
+                "last modified",
+                Metakey::Mode | Metakey::LastModified,
+                Some("Timestamps"),
+            ),
+            (
+                "created",
+                Metakey::Mode | Metakey::Created,
+                Some("Timestamps"),
+            ),
+            ("etag", Metakey::Mode | Metakey::Etag, Some("ETag")),
+            (
+                "complete",
+                FlagSet::from(Metakey::Complete),
+                Some("Timestamps,ETag"),
+            ),
+        ];
+
+        for (desc, metakey, expected) in cases {
+            assert_eq!(build_list_include(metakey).as_deref(), expected, "{desc}");
+        }
+    }
+
+    #[test]
+    fn test_normalize_etag() {
+        let cases = vec![
+            ("quoted", r#""0x8DBBDC4F8AC4AEF""#, r#""0x8DBBDC4F8AC4AEF""#),
+            ("unquoted", "0x8DBBDC4F8AC4AEF", "0x8DBBDC4F8AC4AEF"),
+            (
+                "escaped quoted",
+                r#"\"0x8DBBDC4F8AC4AEF\""#,
+                r#""0x8DBBDC4F8AC4AEF""#,
+            ),
+            ("weak", r#"W/\"0815\""#, r#"W/"0815""#),
+        ];
+
+        for (desc, input, expected) in cases {
+            assert_eq!(normalize_etag(input), expected, "{}", desc);
+        }
+    }
+
+    #[test]
+    fn test_parse_list_result_invalid_utf8() {
+        let xml = b"<EnumerationResults><Entries><File>\xff\xfe</File>";
+
+        assert!(parse_in_chunks(xml, xml.len()).is_err());
+    }
+
+    #[test]
+    fn test_parse_list_result_not_enumeration() {
+        let cases = vec![
+            ("empty", ""),
+            ("plain text", "service unavailable"),
+            ("html", "<html><body>service unavailable</body></html>"),
+            ("unclosed", "<EnumerationResults><Entries />"),
+        ];
+
+        for (desc, body) in cases {
+            for size in [1, body.len().max(1)] {
+                assert!(parse_in_chunks(body.as_bytes(), size).is_err(), "{desc}");
+            }
+        }
+    }
+
+    /// Seed corpus for fuzzing the enumeration parser.
+    const FUZZ_SEED_XML: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<EnumerationResults ServiceEndpoint="https://myaccount.file.core.windows.net/" ShareName="myshare" DirectoryPath="">
+  <MaxResults>2</MaxResults>
+  <Entries>
+    <File>
+      <Name>a &amp; b.txt</Name>
+      <FileId>13835093239654252544</FileId>
+      <Properties>
+        <Content-Length>5832374</Content-Length>
+        <CreationTime>2023-09-25T12:43:05.8483527Z</CreationTime>
+        <LastAccessTime>2023-09-25T12:43:05.8483527Z</LastAccessTime>
+        <Last-Modified>Mon, 25 Sep 2023 12:43:08 GMT</Last-Modified>
+        <Etag>\"0x8DBBDC4F8AC4AEF\"</Etag>
+      </Properties>
+    </File>
+    <Directory>
+      <Name>dir</Name>
+      <FileId>12105702186650959872</FileId>
+      <Properties>
+        <CreationTime>2023-10-15T12:03:40.7194774Z</CreationTime>
+        <LastAccessTime>2023-10-15T12:03:40.7194774Z</LastAccessTime>
+        <Last-Modified>Sun, 15 Oct 2023 12:03:40 GMT</Last-Modified>
+        <Etag>"0x8DBCD76C58C3E96"</Etag>
+      </Properties>
+    </Directory>
+  </Entries>
+  <NextMarker>2!12!YiZhPQ--</NextMarker>
+</EnumerationResults>"#;
+
+    /// Build the seed corpus from the seed xml with every `Properties`
+    /// field removed once, plus some adversarial inputs.
+    fn fuzz_seeds() -> Vec<Vec<u8>> {
+        let mut seeds = vec![FUZZ_SEED_XML.as_bytes().to_vec()];
+
+        let fields = [
+            "Name",
+            "FileId",
+            "Properties",
+            "Content-Length",
+            "CreationTime",
+            "LastAccessTime",
+            "Last-Modified",
+            "Etag",
+        ];
+        for field in fields {
+            let lines: Vec<&str> = FUZZ_SEED_XML
+                .lines()
+                .filter(|l| !l.trim_start().starts_with(&format!("<{field}>")))
+                .collect();
+            seeds.push(lines.join("\n").into_bytes());
+        }
+
+        seeds.extend(
+            [
+                // Reordered fields.
+                "<Entries><File><Properties><Etag>e</Etag><Last-Modified>Mon, 25 Sep 2023 12:43:08 GMT</Last-Modified></Properties><FileId>1</FileId><Name>a</Name></File></Entries>",
+                // Nested and unclosed entries.
+                "<Entries><File><Directory><Name>a</Name></Directory></File><File>",
+                "<Entries><Directory><File></File></Directory></Entries><NextMarker>",
+                // Broken next markers.
+                "<NextMarker>abc",
+                "<NextMarker/>",
+                "<NextMarker>&invalid;</NextMarker>",
+                "<NextMarker",
+                // Invalid values.
+                "<File><Name>a</Name><FileId>1</FileId><Properties><Content-Length>-1</Content-Length><CreationTime>invalid</CreationTime><Last-Modified>invalid</Last-Modified><Etag /></Properties></File>",
+                "",
+                "<",
+                "</File><File>",
+            ]
+            .iter()
+            .map(|v| v.as_bytes().to_vec()),
         );
+        seeds.push(b"<File>\xff\xfe</File><Directory>\x00</Directory>".to_vec());
+
+        seeds
+    }
+
+    /// Mutate the input by flipping, removing, inserting or duplicating bytes.
+    fn fuzz_mutate(rng: &mut StdRng, input: &[u8]) -> Vec<u8> {
+        let mut data = input.to_vec();
+        for _ in 0..rng.gen_range(1..8) {
+            if data.is_empty() {
+                data.push(rng.gen());
+                continue;
+            }
+
+            let idx = rng.gen_range(0..data.len());
+            match rng.gen_range(0..5) {
+                0 => data[idx] = rng.gen(),
+                1 => {
+                    let end = rng.gen_range(idx..=data.len());
+                    data.drain(idx..end);
+                }
+                2 => data.insert(idx, rng.gen()),
+                3 => {
+                    let end = rng.gen_range(idx..=data.len());
+                    let dup = data[idx..end].to_vec();
+                    data.splice(idx..idx, dup);
+                }
+                _ => data.truncate(idx),
+            }
+        }
+        data
+    }
+
+    /// Parse the input like what the pager does, all errors are fine but
+    /// it must never panic.
+    fn fuzz_parse(rng: &mut StdRng, input: &[u8]) {
+        let size = rng.gen_range(1..=input.len().max(1));
+        let (files, dirs) = match parse_in_chunks(input, size) {
+            Ok((files, dirs, _)) => (files, dirs),
+            Err(_) => return,
+        };
+
+        let properties = files
+            .iter()
+            .map(|v| &v.properties)
+            .chain(dirs.iter().map(|v| &v.properties));
+        for p in properties {
+            if let Some(etag) = &p.etag {
+                let _ = normalize_etag(etag);
+            }
+            let _ = with_last_modified(Metadata::new(EntryMode::FILE), p);
+            let _ = with_timestamps(Metadata::new(EntryMode::FILE), p);
+        }
+    }
+
+    #[test]
+    fn test_fuzz_enumeration_parser() {
+        // Use a fixed seed so that failures can be reproduced.
+        let mut rng = StdRng::seed_from_u64(0x617a_6669_6c65);
+
+        for seed in fuzz_seeds() {
+            fuzz_parse(&mut rng, &seed);
+            for _ in 0..200 {
+                let input = fuzz_mutate(&mut rng, &seed);
+                fuzz_parse(&mut rng, &input);
+            }
+        }
+
+        // Arbitrary bytes.
+        for _ in 0..200 {
+            let mut input = vec![0; rng.gen_range(0..256)];
+            rng.fill_bytes(&mut input);
+            fuzz_parse(&mut rng, &input);
+        }
     }
 }
@@ -15,19 +15,37 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use std::cmp;
 use std::sync::Arc;
+use std::task::ready;
+use std::task::Context;
+use std::task::Poll;
 
 use async_trait::async_trait;
+use futures::future::BoxFuture;
+use futures::lock::Mutex;
+use futures::stream::FuturesOrdered;
+use futures::FutureExt;
+use futures::StreamExt;
+use http::header::CACHE_CONTROL;
+use http::header::CONTENT_DISPOSITION;
+use http::header::CONTENT_TYPE;
+use http::HeaderMap;
+use http::HeaderValue;
 use http::StatusCode;
 
+use crate::raw::oio::WriteBuf;
 use crate::raw::*;
 use crate::*;
 
 use super::core::AzfileCore;
 use super::error::parse_error;
 
+/// The max size of a range that could be put in one `Put Range` call.
+pub const AZFILE_MAX_RANGE_SIZE: usize = 4 * 1024 * 1024;
+
 pub type AzfileWriters =
-    oio::TwoWaysWriter<oio::OneShotWriter<AzfileWriter>, oio::AppendObjectWriter<AzfileWriter>>;
+    oio::TwoWaysWriter<AzfileRangeWriter, oio::AppendObjectWriter<AzfileWriter>>;
 
 pub struct AzfileWriter {
     core: Arc<AzfileCore>,
@@ -41,62 +59,377 @@ impl AzfileWriter {
     }
 }
 
-#[async_trait]
-impl oio::OneShotWrite for AzfileWriter {
-    async fn write_once(&self, bs: &dyn oio::WriteBuf) -> Result<()> {
-        let bs = oio::ChunkedBytes::from_vec(bs.vectored_bytes(bs.remaining()));
+/// AzfileRangeWriter uploads content by `Put Range` in chunks.
+///
+/// - Content will be split into chunks of `buffer` size (at most 4 MiB), and
+///   at most `concurrent` chunks will be uploaded at the same time.
+/// - The file will be created or grown before putting a range, so that ranges
+///   could be put in any order. Ranges never overlap, so the content will be
+///   assembled in order.
+/// - The first failed range will be returned with its offset, in-flight ranges
+///   will be dropped and the file will be removed while aborting.
+/// - The etag returned by the last range will be returned as metadata after
+///   closing, unless ranges have been put concurrently, in which case the
+///   last finished range is unknown.
+pub struct AzfileRangeWriter {
+    core: Arc<AzfileCore>,
+    op: OpWrite,
+    path: String,
+    /// Properties that must be carried while resizing the file, otherwise
+    /// they will be reset.
+    properties: HeaderMap,
 
-        let resp = self
-            .core
-            .azfile_create_file(&self.path, bs.len(), &self.op)
-            .await?;
+    chunk_size: usize,
+    concurrent: usize,
+    buffer: oio::ChunkedBytes,
+    /// The offset of the next range to put.
+    written: u64,
+    /// The count of ranges that have been put.
+    ranges: usize,
+    /// The etag returned by the latest finished range.
+    etag: Option<String>,
+    /// Whether the last range has been put while closing.
+    finished: bool,
+    /// The size of the file on server, `None` means it's not created yet.
+    size: Arc<Mutex<Option<u64>>>,
+    futures: FuturesOrdered<BoxFuture<'static, Result<Option<String>>>>,
+    abort: Option<BoxFuture<'static, Result<()>>>,
+}
 
-        let status = resp.status();
-        match status {
-            StatusCode::OK | StatusCode::CREATED => {
-                resp.into_body().consume().await?;
+/// # Safety
+///
+/// We will only take `&mut Self` reference for futures.
+unsafe impl Sync for AzfileRangeWriter {}
+
+impl AzfileRangeWriter {
+    pub fn new(core: Arc<AzfileCore>, op: OpWrite, path: String) -> Self {
+        let mut properties = HeaderMap::new();
+        for (key, value) in [
+            (CONTENT_TYPE, op.content_type()),
+            (CONTENT_DISPOSITION, op.content_disposition()),
+            (CACHE_CONTROL, op.cache_control()),
+        ] {
+            if let Some(v) = value.and_then(|v| HeaderValue::from_str(v).ok()) {
+                properties.insert(key, v);
             }
-            _ => {
-                return Err(parse_error(resp)
+        }
+
+        let chunk_size = cmp::min(
+            op.buffer().unwrap_or(AZFILE_MAX_RANGE_SIZE),
+            AZFILE_MAX_RANGE_SIZE,
+        );
+        let concurrent = op.concurrent();
+
+        AzfileRangeWriter {
+            core,
+            op,
+            path,
+            properties,
+
+            chunk_size,
+            concurrent,
+            buffer: oio::ChunkedBytes::default(),
+            written: 0,
+            ranges: 0,
+            etag: None,
+            finished: false,
+            size: Arc::new(Mutex::new(None)),
+            futures: FuturesOrdered::new(),
+            abort: None,
+        }
+    }
+
+    /// Put a range at current offset in background.
+    fn put_range(&mut self, bs: oio::ChunkedBytes, checksum: Option<Checksum>) {
+        let offset = self.written;
+        let size = bs.len() as u64;
+        self.written += size;
+        self.ranges += 1;
+
+        let core = self.core.clone();
+        let op = self.op.clone();
+        let path = self.path.clone();
+        let properties = self.properties.clone();
+        let file_size = self.size.clone();
+
+        let fut = async move {
+            // Make sure the file is large enough before putting range.
+            let mut etag = None;
+            {
+                let mut file_size = file_size.lock().await;
+                let end = offset + size;
+                match *file_size {
+                    None => {
+                        let resp = core.azfile_create_file(&path, end as usize, &op).await?;
+                        match resp.status() {
+                            StatusCode::OK | StatusCode::CREATED => {
+                                etag = parse_etag(resp.headers())?.map(|v| v.to_string());
+                                resp.into_body().consume().await?
+                            }
+                            _ => {
+                                return Err(parse_error(resp)
+                                    .await?
+                                    .with_operation("Backend::azfile_create_file"))
+                            }
+                        }
+                        *file_size = Some(end);
+                    }
+                    Some(v) if v < end => {
+                        let resp = core.azfile_resize(&path, end, &properties).await?;
+                        match resp.status() {
+                            StatusCode::OK => resp.into_body().consume().await?,
+                            _ => {
+                                return Err(parse_error(resp)
+                                    .await?
+                                    .with_operation("Backend::azfile_resize"))
+                            }
+                        }
+                        *file_size = Some(end);
+                    }
+                    Some(_) => {}
+                }
+            }
+
+            if size == 0 {
+                return Ok(etag);
+            }
+
+            let resp = core
+                .azfile_update(
+                    &path,
+                    size,
+                    offset,
+                    checksum,
+                    op.encryption(),
+                    AsyncBody::ChunkedBytes(bs),
+                )
+                .await?;
+            match resp.status() {
+                StatusCode::OK | StatusCode::CREATED => {
+                    let etag = parse_etag(resp.headers())?.map(|v| v.to_string());
+                    resp.into_body().consume().await?;
+                    Ok(etag)
+                }
+                _ => Err(parse_error(resp)
                     .await?
-                    .with_operation("Backend::azfile_create_file"));
+                    .with_operation("Backend::azfile_update")),
             }
-        }
+        };
 
-        let resp = self
-            .core
-            .azfile_update(&self.path, bs.len() as u64, 0, AsyncBody::ChunkedBytes(bs))
-            .await?;
-        let status = resp.status();
-        return match status {
-            StatusCode::OK | StatusCode::CREATED => {
-                resp.into_body().consume().await?;
+        self.futures.push_back(Box::pin(fut.map(move |res| {
+            res.map_err(|err| err.with_context("offset", offset.to_string()))
+        })));
+    }
+
+    /// Poll the next finished range, in-flight ranges will be dropped if
+    /// any range failed.
+    fn poll_next_range(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<()>>> {
+        let res = ready!(self.futures.poll_next_unpin(cx));
+        let res = res.map(|res| match res {
+            Ok(etag) => {
+                self.etag = etag;
                 Ok(())
             }
-            _ => Err(parse_error(resp)
-                .await?
-                .with_operation("Backend::azfile_update")),
-        };
+            Err(err) => {
+                self.futures = FuturesOrdered::new();
+                Err(err)
+            }
+        });
+        Poll::Ready(res)
+    }
+}
+
+impl oio::Write for AzfileRangeWriter {
+    fn poll_write(&mut self, cx: &mut Context<'_>, bs: &dyn WriteBuf) -> Poll<Result<usize>> {
+        loop {
+            // Drive in-flight ranges and report errors as soon as possible.
+            if let Poll::Ready(Some(res)) = self.poll_next_range(cx) {
+                res?;
+                continue;
+            }
+            if self.futures.len() >= self.concurrent {
+                return Poll::Pending;
+            }
+
+            // Only put the range if there is more data, so that content smaller
+            // than the chunk size will be put in one range while closing.
+            if self.buffer.len() >= self.chunk_size && bs.remaining() > 0 {
+                // Checksum of the whole content can't be carried by ranges.
+                if let Some(checksum) = self.op.checksum() {
+                    return Poll::Ready(Err(Error::new(
+                        ErrorKind::Unsupported,
+                        "azfile doesn't support write with checksum for content larger than a range",
+                    )
+                    .with_context("checksum", checksum.name())
+                    .with_context("range_size", self.chunk_size.to_string())));
+                }
+
+                let chunk =
+                    oio::ChunkedBytes::from_vec(self.buffer.vectored_bytes(self.chunk_size));
+                self.buffer.advance(self.chunk_size);
+                self.put_range(chunk, None);
+                continue;
+            }
+
+            let remaining = self.chunk_size - self.buffer.len();
+            let written = self.buffer.extend_from_write_buf(remaining, bs);
+            return Poll::Ready(Ok(written));
+        }
+    }
+
+    fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        while !self.finished {
+            if self.futures.len() >= self.concurrent {
+                if let Some(res) = ready!(self.poll_next_range(cx)) {
+                    res?;
+                }
+                continue;
+            }
+
+            // Put the last range, or create an empty file if nothing written.
+            if !self.buffer.is_empty() || self.written == 0 {
+                let checksum = if self.written == 0 {
+                    self.op.checksum()
+                } else {
+                    None
+                };
+                let bs = std::mem::take(&mut self.buffer);
+                self.put_range(bs, checksum);
+            }
+            self.finished = true;
+        }
+
+        while let Some(res) = ready!(self.poll_next_range(cx)) {
+            res?;
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.futures = FuturesOrdered::new();
+        self.buffer.clear();
+
+        // Nothing has been sent, no need to clean up.
+        if self.written == 0 && !self.finished {
+            return Poll::Ready(Ok(()));
+        }
+
+        let fut = self.abort.get_or_insert_with(|| {
+            let core = self.core.clone();
+            let path = self.path.clone();
+            Box::pin(async move {
+                let resp = core.azfile_delete_file(&path, None).await?;
+                match resp.status() {
+                    StatusCode::ACCEPTED | StatusCode::NOT_FOUND => {
+                        resp.into_body().consume().await?;
+                        Ok(())
+                    }
+                    _ => Err(parse_error(resp)
+                        .await?
+                        .with_operation("Backend::azfile_delete_file")),
+                }
+            })
+        });
+
+        let res = ready!(fut.poll_unpin(cx));
+        self.abort = None;
+        self.written = 0;
+        self.ranges = 0;
+        self.etag = None;
+        self.finished = false;
+        Poll::Ready(res)
+    }
+
+    fn metadata(&self) -> Option<Metadata> {
+        if !self.finished || !self.futures.is_empty() {
+            return None;
+        }
+
+        let mut meta = Metadata::new(EntryMode::FILE);
+        meta.set_content_length(self.written);
+        // Ranges could finish in any order while put concurrently, the etag
+        // of the last yielded range may not be the final one.
+        if self.concurrent == 1 || self.ranges == 1 {
+            if let Some(etag) = &self.etag {
+                meta.set_etag(etag);
+            }
+        }
+        Some(meta)
     }
 }
 
 #[async_trait]
 impl oio::AppendObjectWrite for AzfileWriter {
     async fn offset(&self) -> Result<u64> {
+        // Checksum of the whole content can't be carried by append.
+        if let Some(checksum) = self.op.checksum() {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "azfile doesn't support append with checksum",
+            )
+            .with_context("checksum", checksum.name()));
+        }
+
         let resp = self.core.azfile_get_file_properties(&self.path).await?;
 
         let status = resp.status();
 
         match status {
-            StatusCode::OK => Ok(parse_content_length(resp.headers())?.unwrap_or_default()),
+            StatusCode::OK => {
+                let size = parse_content_length(resp.headers())?.unwrap_or_default();
+                resp.into_body().consume().await?;
+                Ok(size)
+            }
+            // Create an empty file so that the properties like content type
+            // could be set at create time.
+            StatusCode::NOT_FOUND => {
+                resp.into_body().consume().await?;
+
+                let resp = self
+                    .core
+                    .azfile_create_file(&self.path, 0, &self.op)
+                    .await?;
+                match resp.status() {
+                    StatusCode::OK | StatusCode::CREATED => {
+                        resp.into_body().consume().await?;
+                        Ok(0)
+                    }
+                    _ => Err(parse_error(resp)
+                        .await?
+                        .with_operation("Backend::azfile_create_file")),
+                }
+            }
             _ => Err(parse_error(resp).await?),
         }
     }
 
     async fn append(&self, offset: u64, size: u64, body: AsyncBody) -> Result<()> {
+        // Azure file must be resized before putting range beyond its length.
+        let resp = self.core.azfile_get_file_properties(&self.path).await?;
+        if resp.status() != StatusCode::OK {
+            return Err(parse_error(resp)
+                .await?
+                .with_operation("Backend::azfile_get_file_properties"));
+        }
+        let headers = resp.headers().clone();
+        resp.into_body().consume().await?;
+
+        let resp = self
+            .core
+            .azfile_resize(&self.path, offset + size, &headers)
+            .await?;
+        match resp.status() {
+            StatusCode::OK => resp.into_body().consume().await?,
+            _ => {
+                return Err(parse_error(resp)
+                    .await?
+                    .with_operation("Backend::azfile_resize"))
+            }
+        }
+
         let resp = self
             .core
-            .azfile_update(&self.path, size, offset, body)
+            .azfile_update(&self.path, size, offset, None, self.op.encryption(), body)
             .await?;
 
         let status = resp.status();
@@ -41,6 +41,7 @@ pub struct DbfsBuilder {
     root: Option<String>,
     endpoint: Option<String>,
     token: Option<String>,
+    http_client: Option<HttpClient>,
 }
 
 impl Debug for DbfsBuilder {
@@ -92,6 +93,17 @@ impl DbfsBuilder {
         }
         self
     }
+
+    /// Specify the http client that used by this service.
+    ///
+    /// # Notes
+    ///
+    /// This API is part of OpenDAL's Raw API. `HttpClient` could be changed
+    /// during minor updates.
+    pub fn http_client(&mut self, client: HttpClient) -> &mut Self {
+        self.http_client = Some(client);
+        self
+    }
 }
 
 impl Builder for DbfsBuilder {
@@ -132,7 +144,14 @@ impl Builder for DbfsBuilder {
             }
         };
 
-        let client = HttpClient::new()?;
+        let client = if let Some(client) = self.http_client.take() {
+            client
+        } else {
+            HttpClient::new().map_err(|err| {
+                err.with_operation("Builder::build")
+                    .with_context("service", Scheme::Dbfs)
+            })?
+        };
 
         debug!("backend build finished: {:?}", &self);
         Ok(DbfsBackend {
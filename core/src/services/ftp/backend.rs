@@ -337,6 +337,8 @@ impl Accessor for FtpBackend {
                 (Box::new(ds), meta.size() as u64 - offset)
             }
             (None, Some(size)) => {
+                // The whole file will be returned if it's smaller than size.
+                let size = min(size, meta.size() as u64);
                 ftp_stream
                     .resume_transfer((meta.size() as u64 - size) as usize)
                     .await?;
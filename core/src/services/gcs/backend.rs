@@ -337,6 +337,8 @@ impl Accessor for GcsBackend {
                 write_can_empty: true,
                 write_can_multi: true,
                 write_with_content_type: true,
+                write_with_if_not_exists: true,
+                write_with_cache_control: true,
                 // The buffer size should be a multiple of 256 KiB (256 x 1024 bytes), unless it's the last chunk that completes the upload.
                 // Larger chunk sizes typically make uploads faster, but note that there's a tradeoff between speed and memory usage.
                 // It's recommended that you use at least 8 MiB for the chunk size.
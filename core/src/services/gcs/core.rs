@@ -248,6 +248,11 @@ impl GcsCore {
             write!(&mut url, "&predefinedAcl={}", acl).unwrap();
         }
 
+        // Generation `0` only matches if there are no live versions of the object.
+        if op.if_not_exists() {
+            url.push_str("&ifGenerationMatch=0");
+        }
+
         let mut req = Request::post(&url);
 
         req = req.header(CONTENT_LENGTH, size.unwrap_or_default());
@@ -520,13 +525,19 @@ impl GcsCore {
     pub async fn gcs_initiate_resumable_upload(
         &self,
         path: &str,
+        op: &OpWrite,
     ) -> Result<Response<IncomingAsyncBody>> {
         let p = build_abs_path(&self.root, path);
-        let url = format!(
+        let mut url = format!(
             "{}/upload/storage/v1/b/{}/o?uploadType=resumable&name={}",
             self.endpoint, self.bucket, p
         );
 
+        // Generation `0` only matches if there are no live versions of the object.
+        if op.if_not_exists() {
+            url.push_str("&ifGenerationMatch=0");
+        }
+
         let mut req = Request::post(&url)
             .header(CONTENT_LENGTH, 0)
             .body(AsyncBody::Empty)
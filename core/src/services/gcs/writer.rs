@@ -64,12 +64,18 @@ impl oio::RangeWrite for GcsWriter {
                 resp.into_body().consume().await?;
                 Ok(())
             }
+            StatusCode::PRECONDITION_FAILED if self.op.if_not_exists() => Err(
+                new_already_exists_error("RangeWrite::write_once", parse_error(resp).await?),
+            ),
             _ => Err(parse_error(resp).await?),
         }
     }
 
     async fn initiate_range(&self) -> Result<String> {
-        let resp = self.core.gcs_initiate_resumable_upload(&self.path).await?;
+        let resp = self
+            .core
+            .gcs_initiate_resumable_upload(&self.path, &self.op)
+            .await?;
         let status = resp.status();
 
         match status {
@@ -84,6 +90,9 @@ impl oio::RangeWrite for GcsWriter {
                     ))
                 }
             }
+            StatusCode::PRECONDITION_FAILED if self.op.if_not_exists() => Err(
+                new_already_exists_error("RangeWrite::initiate_range", parse_error(resp).await?),
+            ),
             _ => Err(parse_error(resp).await?),
         }
     }
@@ -128,6 +137,9 @@ impl oio::RangeWrite for GcsWriter {
                 resp.into_body().consume().await?;
                 Ok(())
             }
+            StatusCode::PRECONDITION_FAILED if self.op.if_not_exists() => Err(
+                new_already_exists_error("RangeWrite::complete_range", parse_error(resp).await?),
+            ),
             _ => Err(parse_error(resp).await?),
         }
     }
@@ -227,7 +227,7 @@ impl Accessor for HdfsBackend {
             // Read from offset.
             (Some(offset), None) => (offset, meta.len()),
             // Read the last size bytes.
-            (None, Some(size)) => (meta.len() - size, meta.len()),
+            (None, Some(size)) => (meta.len().saturating_sub(size), meta.len()),
             // Read the whole file.
             (None, None) => (0, meta.len()),
         };
@@ -365,7 +365,7 @@ impl Accessor for HdfsBackend {
             // Read from offset.
             (Some(offset), None) => (offset, meta.len()),
             // Read the last size bytes.
-            (None, Some(size)) => (meta.len() - size, meta.len()),
+            (None, Some(size)) => (meta.len().saturating_sub(size), meta.len()),
             // Read the whole file.
             (None, None) => (0, meta.len()),
         };
@@ -45,6 +45,7 @@ pub struct LibsqlBuilder {
     key_field: Option<String>,
     value_field: Option<String>,
     root: Option<String>,
+    http_client: Option<HttpClient>,
 }
 
 impl Debug for LibsqlBuilder {
@@ -131,6 +132,17 @@ impl LibsqlBuilder {
         }
         self
     }
+
+    /// Specify the http client that used by this service.
+    ///
+    /// # Notes
+    ///
+    /// This API is part of OpenDAL's Raw API. `HttpClient` could be changed
+    /// during minor updates.
+    pub fn http_client(&mut self, client: HttpClient) -> &mut Self {
+        self.http_client = Some(client);
+        self
+    }
 }
 
 impl Builder for LibsqlBuilder {
@@ -174,10 +186,14 @@ impl Builder for LibsqlBuilder {
                 .as_str(),
         );
 
-        let client = HttpClient::new().map_err(|err| {
-            err.with_operation("Builder::build")
-                .with_context("service", Scheme::Libsql)
-        })?;
+        let client = if let Some(client) = self.http_client.take() {
+            client
+        } else {
+            HttpClient::new().map_err(|err| {
+                err.with_operation("Builder::build")
+                    .with_context("service", Scheme::Libsql)
+            })?
+        };
 
         Ok(LibsqlBackend::new(Adapter {
             client,
@@ -243,6 +243,10 @@ pub use self::d1::D1;
 mod azfile;
 #[cfg(feature = "services-azfile")]
 pub use self::azfile::Azfile;
+#[cfg(feature = "services-azfile")]
+pub use self::azfile::AzfileLease;
+#[cfg(feature = "services-azfile")]
+pub use self::azfile::AzfileLeaser;
 
 #[cfg(feature = "services-mongodb")]
 mod mongodb;
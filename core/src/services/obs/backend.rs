@@ -277,6 +277,7 @@ impl Accessor for ObsBackend {
                 write_can_multi: true,
                 write_with_content_type: true,
                 write_with_cache_control: true,
+                write_with_content_disposition: true,
                 // The min multipart size of OBS is 5 MiB.
                 //
                 // ref: <https://support.huaweicloud.com/intl/en-us/ugobs-obs/obs_41_0021.html>
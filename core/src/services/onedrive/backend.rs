@@ -76,6 +76,7 @@ impl Accessor for OnedriveBackend {
             .set_native_capability(Capability {
                 read: true,
                 write: true,
+                write_with_content_type: true,
                 stat: true,
                 delete: true,
                 create_dir: true,
@@ -902,7 +902,10 @@ impl Accessor for S3Backend {
                 write_can_empty: true,
                 write_can_multi: true,
                 write_with_cache_control: true,
+                write_with_checksum: true,
                 write_with_content_type: true,
+                write_with_content_disposition: true,
+                write_with_if_not_exists: true,
                 // The min multipart size of S3 is 5 MiB.
                 //
                 // ref: <https://docs.aws.amazon.com/AmazonS3/latest/userguide/qfacts.html>
@@ -55,6 +55,9 @@ mod constants {
     pub const X_AMZ_SERVER_SIDE_ENCRYPTION_AWS_KMS_KEY_ID: &str =
         "x-amz-server-side-encryption-aws-kms-key-id";
     pub const X_AMZ_STORAGE_CLASS: &str = "x-amz-storage-class";
+    pub const X_AMZ_CHECKSUM_CRC32C: &str = "x-amz-checksum-crc32c";
+    pub const X_AMZ_CHECKSUM_SHA256: &str = "x-amz-checksum-sha256";
+    pub const CONTENT_MD5: &str = "content-md5";
 
     pub const X_AMZ_COPY_SOURCE_SERVER_SIDE_ENCRYPTION_CUSTOMER_ALGORITHM: &str =
         "x-amz-copy-source-server-side-encryption-customer-algorithm";
@@ -360,11 +363,32 @@ impl S3Core {
             req = req.header(CACHE_CONTROL, cache_control)
         }
 
+        if args.if_not_exists() {
+            req = req.header(IF_NONE_MATCH, "*")
+        }
+
         // Set storage class header
         if let Some(v) = &self.default_storage_class {
             req = req.header(HeaderName::from_static(constants::X_AMZ_STORAGE_CLASS), v);
         }
 
+        // Set checksum header
+        if let Some(checksum) = args.checksum() {
+            let name = match checksum {
+                Checksum::Crc32c(_) => constants::X_AMZ_CHECKSUM_CRC32C,
+                Checksum::Sha256(_) => constants::X_AMZ_CHECKSUM_SHA256,
+                Checksum::Md5(_) => constants::CONTENT_MD5,
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::Unsupported,
+                        "s3 doesn't support write with this checksum",
+                    )
+                    .with_context("checksum", checksum.name()))
+                }
+            };
+            req = req.header(HeaderName::from_static(name), checksum.to_base64());
+        }
+
         // Set SSE headers.
         req = self.insert_sse_headers(req, true);
 
@@ -587,6 +611,7 @@ impl S3Core {
         path: &str,
         upload_id: &str,
         parts: Vec<CompleteMultipartUploadRequestPart>,
+        if_none_match: Option<&str>,
     ) -> Result<Response<IncomingAsyncBody>> {
         let p = build_abs_path(&self.root, path);
 
@@ -597,7 +622,11 @@ impl S3Core {
             percent_encode_path(upload_id)
         );
 
-        let req = Request::post(&url);
+        let mut req = Request::post(&url);
+
+        if let Some(if_none_match) = if_none_match {
+            req = req.header(IF_NONE_MATCH, if_none_match);
+        }
 
         // Set SSE headers.
         let req = self.insert_sse_headers(req, true);
@@ -84,6 +84,9 @@ pub fn parse_s3_error_code(code: &str) -> Option<(ErrorKind, bool)> {
         // > A conflicting conditional operation is currently in progress
         // > against this resource. Try again.
         "OperationAborted" => Some((ErrorKind::Unexpected, true)),
+        // > A conflicting operation occurred. If using PutObject you can
+        // > retry the request.
+        "ConditionalRequestConflict" => Some((ErrorKind::Unexpected, true)),
         // > Please reduce your request rate.
         //
         // It's Ok to retry since later on the request rate may get reduced.
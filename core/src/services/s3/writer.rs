@@ -62,11 +62,26 @@ impl oio::MultipartUploadWrite for S3Writer {
                 resp.into_body().consume().await?;
                 Ok(())
             }
+            StatusCode::PRECONDITION_FAILED if self.op.if_not_exists() => {
+                Err(new_already_exists_error(
+                    "MultipartUploadWrite::write_once",
+                    parse_error(resp).await?,
+                ))
+            }
             _ => Err(parse_error(resp).await?),
         }
     }
 
     async fn initiate_part(&self) -> Result<String> {
+        // Checksum of the whole content can't be carried by multipart upload.
+        if let Some(checksum) = self.op.checksum() {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "s3 doesn't support multipart write with checksum",
+            )
+            .with_context("checksum", checksum.name()));
+        }
+
         let resp = self
             .core
             .s3_initiate_multipart_upload(&self.path, &self.op)
@@ -142,7 +157,12 @@ impl oio::MultipartUploadWrite for S3Writer {
 
         let resp = self
             .core
-            .s3_complete_multipart_upload(&self.path, upload_id, parts)
+            .s3_complete_multipart_upload(
+                &self.path,
+                upload_id,
+                parts,
+                self.op.if_not_exists().then_some("*"),
+            )
             .await?;
 
         let status = resp.status();
@@ -153,6 +173,12 @@ impl oio::MultipartUploadWrite for S3Writer {
 
                 Ok(())
             }
+            StatusCode::PRECONDITION_FAILED if self.op.if_not_exists() => {
+                Err(new_already_exists_error(
+                    "MultipartUploadWrite::complete_part",
+                    parse_error(resp).await?,
+                ))
+            }
             _ => Err(parse_error(resp).await?),
         }
     }
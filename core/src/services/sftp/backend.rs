@@ -244,6 +244,7 @@ impl Accessor for SftpBackend {
 
                 write: true,
                 write_can_multi: true,
+                write_can_append: true,
 
                 create_dir: true,
                 delete: true,
@@ -175,6 +175,7 @@ impl Accessor for SupabaseBackend {
                 read: true,
 
                 write: true,
+                write_with_content_type: true,
                 create_dir: true,
                 delete: true,
 
@@ -690,6 +690,9 @@ impl Accessor for WasabiBackend {
                 read_with_range: true,
 
                 write: true,
+                write_with_content_type: true,
+                write_with_content_disposition: true,
+                write_with_cache_control: true,
                 create_dir: true,
                 delete: true,
                 copy: true,
@@ -239,6 +239,8 @@ impl Accessor for WebdavBackend {
 
                 write: true,
                 write_can_empty: true,
+                write_with_content_type: true,
+                write_with_content_disposition: true,
 
                 create_dir: true,
                 delete: true,
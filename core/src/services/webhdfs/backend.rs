@@ -48,6 +48,7 @@ pub struct WebhdfsBuilder {
     endpoint: Option<String>,
     delegation: Option<String>,
     disable_list_batch: bool,
+    http_client: Option<HttpClient>,
 }
 
 impl Debug for WebhdfsBuilder {
@@ -116,6 +117,17 @@ impl WebhdfsBuilder {
         self.disable_list_batch = true;
         self
     }
+
+    /// Specify the http client that used by this service.
+    ///
+    /// # Notes
+    ///
+    /// This API is part of OpenDAL's Raw API. `HttpClient` could be changed
+    /// during minor updates.
+    pub fn http_client(&mut self, client: HttpClient) -> &mut Self {
+        self.http_client = Some(client);
+        self
+    }
 }
 
 impl Builder for WebhdfsBuilder {
@@ -166,7 +178,14 @@ impl Builder for WebhdfsBuilder {
             .take()
             .map(|dt| format!("delegation_token={dt}"));
 
-        let client = HttpClient::new()?;
+        let client = if let Some(client) = self.http_client.take() {
+            client
+        } else {
+            HttpClient::new().map_err(|err| {
+                err.with_operation("Builder::build")
+                    .with_context("service", Scheme::Webhdfs)
+            })?
+        };
 
         let backend = WebhdfsBackend {
             root,
@@ -416,10 +435,13 @@ impl Accessor for WebhdfsBackend {
                 read_with_range: true,
 
                 write: true,
+                write_with_content_type: true,
                 create_dir: true,
                 delete: true,
 
                 list: true,
+                // `startAfter` is only supported by `LISTSTATUS_BATCH`.
+                list_with_start_after: !self.disable_list_batch,
                 list_with_delimiter_slash: true,
 
                 ..Default::default()
@@ -535,7 +557,24 @@ impl Accessor for WebhdfsBackend {
         let path = path.trim_end_matches('/');
 
         if !self.disable_list_batch {
-            let req = self.webhdfs_list_status_batch_request(path, &OpList::default())?;
+            // `startAfter` of WebHDFS is the name of a child in the listed directory.
+            let mut batch_args = OpList::default();
+            if let Some(start_after) = args.start_after() {
+                let child = if path.is_empty() {
+                    Some(start_after)
+                } else {
+                    start_after
+                        .strip_prefix(path)
+                        .and_then(|v| v.strip_prefix('/'))
+                };
+                if let Some(name) = child.and_then(|v| v.split('/').next()) {
+                    if !name.is_empty() {
+                        batch_args = batch_args.with_start_after(name);
+                    }
+                }
+            }
+
+            let req = self.webhdfs_list_status_batch_request(path, &batch_args)?;
             let resp = self.client.send(req).await?;
             match resp.status() {
                 StatusCode::OK => {
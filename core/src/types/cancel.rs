@@ -0,0 +1,245 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fmt::Formatter;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+use std::task::Waker;
+use std::time::Duration;
+use std::time::Instant;
+
+use parking_lot::Mutex;
+use tokio::time::Sleep;
+
+use crate::*;
+
+/// CancellationToken is used to cancel operations cooperatively.
+///
+/// Token can be cloned and shared by many operations, all of them will be
+/// cancelled once [`CancellationToken::cancel`] is called or the deadline
+/// is reached. Cancelled operations will drop their in-flight requests and
+/// return [`ErrorKind::Cancelled`].
+///
+/// # Notes
+///
+/// Deadline is driven by tokio's timer, so tokens with deadline must be
+/// used inside a tokio runtime with time enabled.
+///
+/// # Examples
+///
+/// ```
+/// # use std::time::Duration;
+/// # use anyhow::Result;
+/// # use opendal::CancellationToken;
+/// # use opendal::Operator;
+/// # #[tokio::main]
+/// # async fn test(op: Operator) -> Result<()> {
+/// let token = CancellationToken::with_timeout(Duration::from_secs(10));
+///
+/// // Call `token.cancel()` to cancel the read before the deadline, for
+/// // example, while the client has been disconnected.
+/// let bs = op.read_with("path/to/file").cancel_token(token.clone()).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    state: Arc<CancelState>,
+}
+
+#[derive(Default)]
+struct CancelState {
+    cancelled: AtomicBool,
+    deadline: Option<Instant>,
+    next_id: AtomicUsize,
+    wakers: Mutex<HashMap<usize, Waker>>,
+}
+
+impl Debug for CancellationToken {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("cancelled", &self.state.cancelled.load(Ordering::Acquire))
+            .field("deadline", &self.state.deadline)
+            .finish()
+    }
+}
+
+impl CancellationToken {
+    /// Create a new token that will only be cancelled by `cancel`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new token that will be cancelled at the deadline.
+    pub fn with_deadline(deadline: Instant) -> Self {
+        Self {
+            state: Arc::new(CancelState {
+                deadline: Some(deadline),
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Create a new token that will be cancelled after timeout.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self::with_deadline(Instant::now() + timeout)
+    }
+
+    /// Get the deadline of this token.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.state.deadline
+    }
+
+    /// Cancel all operations that use this token.
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::Release);
+
+        let wakers = std::mem::take(&mut *self.state.wakers.lock());
+        for (_, waker) in wakers {
+            waker.wake();
+        }
+    }
+
+    /// Check if this token has been cancelled or has reached the deadline.
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::Acquire)
+            || matches!(self.state.deadline, Some(deadline) if Instant::now() >= deadline)
+    }
+
+    /// Wait until this token has been cancelled.
+    pub async fn cancelled(&self) {
+        self.wait().await
+    }
+
+    pub(crate) fn wait(&self) -> CancelWait {
+        CancelWait {
+            token: self.clone(),
+            id: None,
+            sleep: None,
+        }
+    }
+
+    /// Run the future until it's ready or this token has been cancelled.
+    ///
+    /// Returns `None` if cancelled, the future will be dropped.
+    pub(crate) async fn run<F: Future>(&self, fut: F) -> Option<F::Output> {
+        if self.is_cancelled() {
+            return None;
+        }
+
+        futures::pin_mut!(fut);
+        match futures::future::select(fut, self.wait()).await {
+            futures::future::Either::Left((v, _)) => Some(v),
+            futures::future::Either::Right(_) => None,
+        }
+    }
+}
+
+/// CancelWait is the future that waits for the token to be cancelled.
+pub(crate) struct CancelWait {
+    token: CancellationToken,
+    /// The id of registered waker, `None` means not registered yet.
+    id: Option<usize>,
+    sleep: Option<Pin<Box<Sleep>>>,
+}
+
+impl CancelWait {
+    pub(crate) fn poll_cancelled(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        let state = &self.token.state;
+        if state.cancelled.load(Ordering::Acquire) {
+            return Poll::Ready(());
+        }
+
+        let id = *self
+            .id
+            .get_or_insert_with(|| state.next_id.fetch_add(1, Ordering::Relaxed));
+        state.wakers.lock().insert(id, cx.waker().clone());
+        // Check again in case `cancel` happened before the waker has been
+        // registered.
+        if state.cancelled.load(Ordering::Acquire) {
+            return Poll::Ready(());
+        }
+
+        match state.deadline {
+            Some(deadline) => self
+                .sleep
+                .get_or_insert_with(|| Box::pin(tokio::time::sleep_until(deadline.into())))
+                .as_mut()
+                .poll(cx),
+            None => Poll::Pending,
+        }
+    }
+}
+
+impl Future for CancelWait {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.get_mut().poll_cancelled(cx)
+    }
+}
+
+impl Drop for CancelWait {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            self.token.state.wakers.lock().remove(&id);
+        }
+    }
+}
+
+/// Build the error returned while operation has been cancelled.
+pub(crate) fn new_cancelled_error() -> Error {
+    Error::new(ErrorKind::Cancelled, "operation has been cancelled")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cancellation_token_cancel() {
+        let token = CancellationToken::new();
+        assert!(!token.is_cancelled());
+
+        let t = token.clone();
+        let handle = tokio::spawn(async move { t.run(futures::future::pending::<()>()).await });
+        tokio::task::yield_now().await;
+
+        token.cancel();
+        assert!(token.is_cancelled());
+        assert_eq!(handle.await.unwrap(), None);
+        assert!(token.state.wakers.lock().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cancellation_token_deadline() {
+        let token = CancellationToken::with_timeout(Duration::from_millis(10));
+        assert_eq!(token.run(async { 1 }).await, Some(1));
+
+        let res = token.run(futures::future::pending::<()>()).await;
+        assert_eq!(res, None);
+        assert!(token.is_cancelled());
+    }
+}
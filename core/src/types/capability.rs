@@ -16,6 +16,10 @@
 // under the License.
 
 use std::fmt::Debug;
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+use crate::raw::Operation;
 
 /// Capability is used to describe what operations are supported
 /// by current Operator.
@@ -52,6 +56,8 @@ pub struct Capability {
     pub stat_with_if_match: bool,
     /// If operator supports stat with if none match.
     pub stat_with_if_none_match: bool,
+    /// If operator supports stat with raw headers returned.
+    pub stat_with_raw_headers: bool,
 
     /// If operator supports read.
     pub read: bool,
@@ -71,6 +77,8 @@ pub struct Capability {
     pub read_with_override_content_disposition: bool,
     /// if operator supports read with override content type.
     pub read_with_override_content_type: bool,
+    /// If operator supports read with server side encryption.
+    pub read_with_encryption: bool,
 
     /// If operator supports write.
     pub write: bool,
@@ -86,6 +94,14 @@ pub struct Capability {
     pub write_with_content_disposition: bool,
     /// If operator supports write with cache control.
     pub write_with_cache_control: bool,
+    /// If operator supports write with checksum.
+    pub write_with_checksum: bool,
+    /// If operator supports write with server side encryption.
+    pub write_with_encryption: bool,
+    /// If operator supports write with user defined metadata.
+    pub write_with_user_metadata: bool,
+    /// If operator supports write with if not exists.
+    pub write_with_if_not_exists: bool,
     /// write_multi_max_size is the max size that services support in write_multi.
     ///
     /// For example, AWS S3 supports 5GiB as max in write_multi.
@@ -105,9 +121,18 @@ pub struct Capability {
 
     /// If operator supports create dir.
     pub create_dir: bool,
+    /// If operator supports create dir with user defined metadata.
+    pub create_dir_with_user_metadata: bool,
 
     /// If operator supports delete.
     pub delete: bool,
+    /// If operator supports delete with if match.
+    ///
+    /// Delete will fail with `ConditionNotMatch` if the etag of path is not
+    /// matched.
+    ///
+    /// For now, `azblob` and `azfile` honor conditional deletes.
+    pub delete_with_if_match: bool,
 
     /// If operator supports copy.
     pub copy: bool,
@@ -119,8 +144,14 @@ pub struct Capability {
     pub list: bool,
     /// If backend supports list with limit.
     pub list_with_limit: bool,
+    /// If backend supports list with page size.
+    pub list_with_page_size: bool,
     /// If backend supports list with start after.
     pub list_with_start_after: bool,
+    /// If backend supports list with start after token.
+    pub list_with_start_after_token: bool,
+    /// If backend supports list with include self.
+    pub list_with_include_self: bool,
     /// If backend support list with using slash as delimiter.
     pub list_with_delimiter_slash: bool,
     /// If backend supports list without delimiter.
@@ -144,10 +175,223 @@ pub struct Capability {
 
     /// If operator supports blocking.
     pub blocking: bool,
+
+    /// If paths of operator are case insensitive but case preserving, like
+    /// `Foo.txt` and `foo.txt` refer to the same file.
+    pub path_case_insensitive: bool,
+}
+
+impl Capability {
+    /// Check if given operation is supported.
+    ///
+    /// Blocking operations are supported only if both the operation and
+    /// `blocking` are supported.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use opendal::raw::Operation;
+    /// use opendal::services::Memory;
+    /// use opendal::Operator;
+    ///
+    /// let op = Operator::new(Memory::default()).unwrap().finish();
+    /// let cap = op.info().full_capability();
+    ///
+    /// assert!(cap.supports(Operation::Read));
+    /// assert!(!cap.supports(Operation::Presign));
+    /// ```
+    pub fn supports(&self, op: Operation) -> bool {
+        match op {
+            Operation::Info => true,
+            Operation::CreateDir => self.create_dir,
+            Operation::Read => self.read,
+            Operation::Write => self.write,
+            Operation::Copy => self.copy,
+            Operation::Rename => self.rename,
+            Operation::Stat => self.stat,
+            Operation::Delete => self.delete,
+            Operation::List => self.list,
+            Operation::Batch => self.batch,
+            Operation::Presign => self.presign,
+            Operation::BlockingCreateDir => self.blocking && self.create_dir,
+            Operation::BlockingRead => self.blocking && self.read,
+            Operation::BlockingWrite => self.blocking && self.write,
+            Operation::BlockingCopy => self.blocking && self.copy,
+            Operation::BlockingRename => self.blocking && self.rename,
+            Operation::BlockingStat => self.blocking && self.stat,
+            Operation::BlockingDelete => self.blocking && self.delete,
+            Operation::BlockingList => self.blocking && self.list,
+        }
+    }
+
+    /// Get all fields of capability as `(name, value)` pairs.
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("stat", self.stat.to_string()),
+            ("stat_with_if_match", self.stat_with_if_match.to_string()),
+            (
+                "stat_with_if_none_match",
+                self.stat_with_if_none_match.to_string(),
+            ),
+            (
+                "stat_with_raw_headers",
+                self.stat_with_raw_headers.to_string(),
+            ),
+            ("read", self.read.to_string()),
+            ("read_can_seek", self.read_can_seek.to_string()),
+            ("read_can_next", self.read_can_next.to_string()),
+            ("read_with_range", self.read_with_range.to_string()),
+            ("read_with_if_match", self.read_with_if_match.to_string()),
+            (
+                "read_with_if_none_match",
+                self.read_with_if_none_match.to_string(),
+            ),
+            (
+                "read_with_override_cache_control",
+                self.read_with_override_cache_control.to_string(),
+            ),
+            (
+                "read_with_override_content_disposition",
+                self.read_with_override_content_disposition.to_string(),
+            ),
+            (
+                "read_with_override_content_type",
+                self.read_with_override_content_type.to_string(),
+            ),
+            (
+                "read_with_encryption",
+                self.read_with_encryption.to_string(),
+            ),
+            ("write", self.write.to_string()),
+            ("write_can_multi", self.write_can_multi.to_string()),
+            ("write_can_empty", self.write_can_empty.to_string()),
+            ("write_can_append", self.write_can_append.to_string()),
+            (
+                "write_with_content_type",
+                self.write_with_content_type.to_string(),
+            ),
+            (
+                "write_with_content_disposition",
+                self.write_with_content_disposition.to_string(),
+            ),
+            (
+                "write_with_cache_control",
+                self.write_with_cache_control.to_string(),
+            ),
+            ("write_with_checksum", self.write_with_checksum.to_string()),
+            (
+                "write_with_encryption",
+                self.write_with_encryption.to_string(),
+            ),
+            (
+                "write_with_user_metadata",
+                self.write_with_user_metadata.to_string(),
+            ),
+            (
+                "write_with_if_not_exists",
+                self.write_with_if_not_exists.to_string(),
+            ),
+            (
+                "write_multi_max_size",
+                self.write_multi_max_size
+                    .map_or("-".to_string(), |v| v.to_string()),
+            ),
+            (
+                "write_multi_min_size",
+                self.write_multi_min_size
+                    .map_or("-".to_string(), |v| v.to_string()),
+            ),
+            (
+                "write_multi_align_size",
+                self.write_multi_align_size
+                    .map_or("-".to_string(), |v| v.to_string()),
+            ),
+            (
+                "write_total_max_size",
+                self.write_total_max_size
+                    .map_or("-".to_string(), |v| v.to_string()),
+            ),
+            ("create_dir", self.create_dir.to_string()),
+            (
+                "create_dir_with_user_metadata",
+                self.create_dir_with_user_metadata.to_string(),
+            ),
+            ("delete", self.delete.to_string()),
+            (
+                "delete_with_if_match",
+                self.delete_with_if_match.to_string(),
+            ),
+            ("copy", self.copy.to_string()),
+            ("rename", self.rename.to_string()),
+            ("list", self.list.to_string()),
+            ("list_with_limit", self.list_with_limit.to_string()),
+            ("list_with_page_size", self.list_with_page_size.to_string()),
+            (
+                "list_with_start_after",
+                self.list_with_start_after.to_string(),
+            ),
+            (
+                "list_with_start_after_token",
+                self.list_with_start_after_token.to_string(),
+            ),
+            (
+                "list_with_include_self",
+                self.list_with_include_self.to_string(),
+            ),
+            (
+                "list_with_delimiter_slash",
+                self.list_with_delimiter_slash.to_string(),
+            ),
+            (
+                "list_without_delimiter",
+                self.list_without_delimiter.to_string(),
+            ),
+            ("presign", self.presign.to_string()),
+            ("presign_read", self.presign_read.to_string()),
+            ("presign_stat", self.presign_stat.to_string()),
+            ("presign_write", self.presign_write.to_string()),
+            ("batch", self.batch.to_string()),
+            ("batch_delete", self.batch_delete.to_string()),
+            (
+                "batch_max_operations",
+                self.batch_max_operations
+                    .map_or("-".to_string(), |v| v.to_string()),
+            ),
+            ("blocking", self.blocking.to_string()),
+            (
+                "path_case_insensitive",
+                self.path_case_insensitive.to_string(),
+            ),
+        ]
+    }
+}
+
+/// Display capability as a table with one field per line, `-` means no limit.
+///
+/// ```text
+/// stat                                   true
+/// stat_with_if_match                     false
+/// ...
+/// batch_max_operations                   -
+/// ```
+impl Display for Capability {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let fields = self.fields();
+        let width = fields
+            .iter()
+            .map(|(k, _)| k.len())
+            .max()
+            .unwrap_or_default();
+
+        for (k, v) in fields {
+            writeln!(f, "{k:<width$} {v}")?;
+        }
+        Ok(())
+    }
 }
 
 impl Debug for Capability {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let mut s = vec![];
 
         if self.stat {
@@ -187,3 +431,48 @@ impl Debug for Capability {
         write!(f, "{{ {} }}", s.join(" | "))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capability_supports() {
+        let cap = Capability {
+            read: true,
+            write: true,
+            ..Default::default()
+        };
+
+        assert!(cap.supports(Operation::Info));
+        assert!(cap.supports(Operation::Read));
+        assert!(!cap.supports(Operation::List));
+        // Blocking operations require `blocking` too.
+        assert!(!cap.supports(Operation::BlockingRead));
+
+        let cap = Capability {
+            blocking: true,
+            ..cap
+        };
+        assert!(cap.supports(Operation::BlockingRead));
+        assert!(!cap.supports(Operation::BlockingList));
+    }
+
+    #[test]
+    fn test_capability_display() {
+        let cap = Capability {
+            stat: true,
+            batch_max_operations: Some(1000),
+            ..Default::default()
+        };
+        let s = cap.to_string();
+        let lines: Vec<_> = s.lines().collect();
+
+        assert_eq!(lines.len(), cap.fields().len());
+        assert!(lines[0].starts_with("stat ") && lines[0].ends_with(" true"));
+        assert!(s.contains("write_multi_max_size") && s.contains(" -\n"));
+        assert!(lines
+            .iter()
+            .any(|l| l.starts_with("batch_max_operations") && l.ends_with(" 1000")));
+    }
+}
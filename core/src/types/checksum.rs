@@ -0,0 +1,324 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use md5::Digest;
+use md5::Md5;
+use sha2::Sha256;
+
+use crate::raw::*;
+use crate::*;
+
+/// Checksum of the whole content of a file.
+///
+/// Checksum can be used in:
+///
+/// - `write_with(path).checksum(..)`: service will reject the upload if the
+///   content doesn't match.
+/// - `read_with(path).verify_checksum(true)`: checksum will be computed while
+///   reading and compared with the one stored by service at the end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Checksum {
+    /// CRC-32C (Castagnoli) checksum.
+    Crc32c(u32),
+    /// CRC-64 checksum, the variant used by Azure Storage (CRC-64/NVME).
+    Crc64(u64),
+    /// MD5 digest.
+    Md5([u8; 16]),
+    /// SHA-256 digest.
+    Sha256([u8; 32]),
+}
+
+impl Checksum {
+    /// Get the name of this checksum algorithm.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Checksum::Crc32c(_) => "crc32c",
+            Checksum::Crc64(_) => "crc64",
+            Checksum::Md5(_) => "md5",
+            Checksum::Sha256(_) => "sha256",
+        }
+    }
+
+    /// Convert checksum into bytes, crc values will be in big-endian.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Checksum::Crc32c(v) => v.to_be_bytes().to_vec(),
+            Checksum::Crc64(v) => v.to_be_bytes().to_vec(),
+            Checksum::Md5(v) => v.to_vec(),
+            Checksum::Sha256(v) => v.to_vec(),
+        }
+    }
+
+    /// Convert checksum into base64 encoded string which is widely used in
+    /// http headers like `Content-MD5`.
+    pub fn to_base64(&self) -> String {
+        BASE64_STANDARD.encode(self.to_bytes())
+    }
+}
+
+impl Display for Checksum {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.name(), self.to_base64())
+    }
+}
+
+/// ChecksumHasher computes checksum of streaming content and verifies it
+/// with the expected value.
+pub(crate) struct ChecksumHasher {
+    expected: Checksum,
+    state: HasherState,
+}
+
+enum HasherState {
+    Crc32c(u32),
+    Crc64(u64),
+    Md5(Md5),
+    Sha256(Sha256),
+}
+
+impl ChecksumHasher {
+    pub(crate) fn new(expected: Checksum) -> Self {
+        let state = match expected {
+            Checksum::Crc32c(_) => HasherState::Crc32c(!0),
+            Checksum::Crc64(_) => HasherState::Crc64(!0),
+            Checksum::Md5(_) => HasherState::Md5(Md5::new()),
+            Checksum::Sha256(_) => HasherState::Sha256(Sha256::new()),
+        };
+
+        Self { expected, state }
+    }
+
+    pub(crate) fn update(&mut self, bs: &[u8]) {
+        match &mut self.state {
+            HasherState::Crc32c(crc) => {
+                for b in bs {
+                    *crc = CRC32C_TABLE[((*crc ^ *b as u32) & 0xff) as usize] ^ (*crc >> 8);
+                }
+            }
+            HasherState::Crc64(crc) => {
+                for b in bs {
+                    *crc = CRC64_TABLE[((*crc ^ *b as u64) & 0xff) as usize] ^ (*crc >> 8);
+                }
+            }
+            HasherState::Md5(h) => h.update(bs),
+            HasherState::Sha256(h) => h.update(bs),
+        }
+    }
+
+    pub(crate) fn finish(self) -> Checksum {
+        match self.state {
+            HasherState::Crc32c(crc) => Checksum::Crc32c(!crc),
+            HasherState::Crc64(crc) => Checksum::Crc64(!crc),
+            HasherState::Md5(h) => Checksum::Md5(h.finalize().into()),
+            HasherState::Sha256(h) => Checksum::Sha256(h.finalize().into()),
+        }
+    }
+
+    /// Verify the computed checksum, returns `ChecksumMismatch` if not match.
+    pub(crate) fn verify(self) -> Result<()> {
+        let expected = self.expected;
+        let actual = self.finish();
+        if actual == expected {
+            return Ok(());
+        }
+
+        Err(
+            Error::new(ErrorKind::ChecksumMismatch, "checksum of content mismatch")
+                .with_context("expected", expected.to_string())
+                .with_context("actual", actual.to_string()),
+        )
+    }
+}
+
+/// Check whether content should be verified while reading.
+///
+/// Checksum is computed on the whole content, so it can't be used with range.
+pub(crate) fn read_verify_checksum(args: &OpRead) -> Result<bool> {
+    if !args.verify_checksum() {
+        return Ok(false);
+    }
+
+    if !args.range().is_full() {
+        return Err(
+            Error::new(ErrorKind::InvalidInput, "checksum can't be used with range")
+                .with_context("range", args.range().to_string()),
+        );
+    }
+
+    Ok(true)
+}
+
+/// Get the checksum stored by service from the metadata returned by read.
+///
+/// Only `Content-MD5` is supported for now, returns `Unsupported` if service
+/// doesn't return it.
+pub(crate) fn stored_checksum(meta: &Metadata) -> Result<Checksum> {
+    let md5 = meta.content_md5().ok_or_else(|| {
+        Error::new(
+            ErrorKind::Unsupported,
+            "service doesn't return the checksum of content",
+        )
+    })?;
+
+    let bs = BASE64_STANDARD.decode(md5).map_err(|err| {
+        Error::new(ErrorKind::Unexpected, "stored content md5 is invalid")
+            .with_context("content_md5", md5)
+            .set_source(err)
+    })?;
+    let digest: [u8; 16] = bs.try_into().map_err(|_| {
+        Error::new(ErrorKind::Unexpected, "stored content md5 is invalid")
+            .with_context("content_md5", md5)
+    })?;
+
+    Ok(Checksum::Md5(digest))
+}
+
+const CRC32C_TABLE: [u32; 256] = crc32_table(0x82F6_3B78);
+const CRC64_TABLE: [u64; 256] = crc64_table(0x9A6C_9329_AC4B_C9B5);
+
+/// Build the lookup table for reflected crc32 with given polynomial.
+const fn crc32_table(poly: u32) -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut j = 0;
+        while j < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ poly
+            } else {
+                crc >> 1
+            };
+            j += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// Build the lookup table for reflected crc64 with given polynomial.
+const fn crc64_table(poly: u64) -> [u64; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u64;
+        let mut j = 0;
+        while j < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ poly
+            } else {
+                crc >> 1
+            };
+            j += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compute(expected: Checksum, chunks: &[&[u8]]) -> Checksum {
+        let mut h = ChecksumHasher::new(expected);
+        for bs in chunks {
+            h.update(bs);
+        }
+        h.finish()
+    }
+
+    #[test]
+    fn test_checksum_hasher() {
+        let cases = vec![
+            ("crc32c", Checksum::Crc32c(0), Checksum::Crc32c(0xE306_9283)),
+            (
+                "crc64",
+                Checksum::Crc64(0),
+                Checksum::Crc64(0xAE8B_1486_0A79_9888),
+            ),
+            (
+                "md5",
+                Checksum::Md5([0; 16]),
+                Checksum::Md5([
+                    0x25, 0xf9, 0xe7, 0x94, 0x32, 0x3b, 0x45, 0x38, 0x85, 0xf5, 0x18, 0x1f, 0x1b,
+                    0x62, 0x4d, 0x0b,
+                ]),
+            ),
+            (
+                "sha256",
+                Checksum::Sha256([0; 32]),
+                Checksum::Sha256([
+                    0x15, 0xe2, 0xb0, 0xd3, 0xc3, 0x38, 0x91, 0xeb, 0xb0, 0xf1, 0xef, 0x60, 0x9e,
+                    0xc4, 0x19, 0x42, 0x0c, 0x20, 0xe3, 0x20, 0xce, 0x94, 0xc6, 0x5f, 0xbc, 0x8c,
+                    0x33, 0x12, 0x44, 0x8e, 0xb2, 0x25,
+                ]),
+            ),
+        ];
+
+        for (name, init, expected) in cases {
+            // Split input to make sure streaming update works.
+            let actual = compute(init, &[b"1234", b"", b"56789"]);
+            assert_eq!(actual, expected, "{name}");
+        }
+    }
+
+    #[test]
+    fn test_checksum_verify() {
+        let mut h = ChecksumHasher::new(Checksum::Crc32c(0xE306_9283));
+        h.update(b"123456789");
+        assert!(h.verify().is_ok());
+
+        let mut h = ChecksumHasher::new(Checksum::Crc32c(0));
+        h.update(b"123456789");
+        let err = h.verify().expect_err("must mismatch");
+        assert_eq!(err.kind(), ErrorKind::ChecksumMismatch);
+    }
+
+    #[test]
+    fn test_stored_checksum() {
+        let mut meta = Metadata::new(EntryMode::FILE);
+        let err = stored_checksum(&meta).expect_err("must fail without md5");
+        assert_eq!(err.kind(), ErrorKind::Unsupported);
+
+        meta.set_content_md5("JfnnlDI7RTiF9RgfG2JNCw==");
+        assert_eq!(
+            stored_checksum(&meta).expect("must succeed"),
+            Checksum::Md5([
+                0x25, 0xf9, 0xe7, 0x94, 0x32, 0x3b, 0x45, 0x38, 0x85, 0xf5, 0x18, 0x1f, 0x1b, 0x62,
+                0x4d, 0x0b,
+            ])
+        );
+
+        meta.set_content_md5("not-md5");
+        assert!(stored_checksum(&meta).is_err());
+    }
+
+    #[test]
+    fn test_checksum_to_base64() {
+        assert_eq!(Checksum::Crc32c(0xE306_9283).to_base64(), "4waSgw==");
+        assert_eq!(Checksum::Crc32c(0xE306_9283).to_string(), "crc32c:4waSgw==");
+    }
+}
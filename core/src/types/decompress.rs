@@ -0,0 +1,390 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+#[cfg(feature = "compress")]
+use std::io;
+#[cfg(feature = "compress")]
+use std::io::Write;
+#[cfg(feature = "compress")]
+use std::task::ready;
+#[cfg(feature = "compress")]
+use std::task::Context;
+#[cfg(feature = "compress")]
+use std::task::Poll;
+
+#[cfg(feature = "compress")]
+use bytes::Buf;
+#[cfg(feature = "compress")]
+use bytes::Bytes;
+
+use crate::raw::*;
+use crate::*;
+
+/// Content encodings that could be decompressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Encoding {
+    Gzip,
+    Deflate,
+    Zstd,
+}
+
+/// Parse the content encoding returned by services.
+///
+/// Returns `None` if content is not encoded.
+pub(crate) fn parse_encoding(v: &str) -> Result<Option<Encoding>> {
+    match v.trim().to_ascii_lowercase().as_str() {
+        "" | "identity" => Ok(None),
+        "gzip" | "x-gzip" => Ok(Some(Encoding::Gzip)),
+        "deflate" => Ok(Some(Encoding::Deflate)),
+        "zstd" => Ok(Some(Encoding::Zstd)),
+        _ => Err(Error::new(
+            ErrorKind::Unsupported,
+            "content encoding is not supported to decompress",
+        )
+        .with_context("content_encoding", v)),
+    }
+}
+
+/// Check if decompress is required by read args.
+///
+/// Compressed content can't be read by range, so it can't be used with range.
+pub(crate) fn read_decompress(args: &OpRead) -> Result<bool> {
+    if !args.decompress() {
+        return Ok(false);
+    }
+
+    if !args.range().is_full() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "decompress can't be used with range",
+        )
+        .with_context("range", args.range().to_string()));
+    }
+
+    Ok(true)
+}
+
+/// Get the encoding that needs to be decompressed from read response.
+fn response_encoding(meta: &Metadata) -> Result<Option<Encoding>> {
+    if !meta.metakey().contains(Metakey::ContentEncoding) {
+        return Ok(None);
+    }
+
+    match meta.content_encoding() {
+        Some(v) => parse_encoding(v),
+        None => Ok(None),
+    }
+}
+
+/// Wrap reader with decompressing based on the content encoding of response.
+pub(crate) fn decompress_reader(r: oio::Reader, meta: &Metadata) -> Result<oio::Reader> {
+    match response_encoding(meta)? {
+        None => Ok(r),
+        #[cfg(feature = "compress")]
+        Some(encoding) => Ok(Box::new(DecompressReader::new(r, encoding)?)),
+        #[cfg(not(feature = "compress"))]
+        Some(encoding) => Err(new_compress_disabled_error(encoding)),
+    }
+}
+
+/// Wrap blocking reader with decompressing based on the content encoding of
+/// response.
+pub(crate) fn decompress_blocking_reader(
+    r: oio::BlockingReader,
+    meta: &Metadata,
+) -> Result<oio::BlockingReader> {
+    match response_encoding(meta)? {
+        None => Ok(r),
+        #[cfg(feature = "compress")]
+        Some(encoding) => Ok(Box::new(DecompressReader::new(r, encoding)?)),
+        #[cfg(not(feature = "compress"))]
+        Some(encoding) => Err(new_compress_disabled_error(encoding)),
+    }
+}
+
+#[cfg(not(feature = "compress"))]
+fn new_compress_disabled_error(encoding: Encoding) -> Error {
+    Error::new(
+        ErrorKind::Unsupported,
+        "decompress requires feature `compress` to be enabled",
+    )
+    .with_context("content_encoding", format!("{encoding:?}"))
+}
+
+#[cfg(feature = "compress")]
+fn new_decompress_error(err: io::Error) -> Error {
+    Error::new(ErrorKind::Unexpected, "decompress content failed").set_source(err)
+}
+
+/// Decoder accepts compressed content and collects decompressed output.
+#[cfg(feature = "compress")]
+enum Decoder {
+    Gzip(flate2::write::GzDecoder<Vec<u8>>),
+    Deflate(flate2::write::ZlibDecoder<Vec<u8>>),
+    Zstd(zstd::stream::write::Decoder<'static, Vec<u8>>),
+}
+
+#[cfg(feature = "compress")]
+impl Decoder {
+    fn new(encoding: Encoding) -> Result<Self> {
+        let decoder = match encoding {
+            Encoding::Gzip => Decoder::Gzip(flate2::write::GzDecoder::new(Vec::new())),
+            Encoding::Deflate => Decoder::Deflate(flate2::write::ZlibDecoder::new(Vec::new())),
+            Encoding::Zstd => Decoder::Zstd(
+                zstd::stream::write::Decoder::new(Vec::new()).map_err(new_decompress_error)?,
+            ),
+        };
+        Ok(decoder)
+    }
+
+    fn write(&mut self, bs: &[u8]) -> Result<()> {
+        let res = match self {
+            Decoder::Gzip(d) => d.write_all(bs),
+            Decoder::Deflate(d) => d.write_all(bs),
+            Decoder::Zstd(d) => d.write_all(bs),
+        };
+        res.map_err(new_decompress_error)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let res = match self {
+            Decoder::Gzip(d) => d.try_finish(),
+            Decoder::Deflate(d) => d.try_finish(),
+            Decoder::Zstd(d) => d.flush(),
+        };
+        res.map_err(new_decompress_error)
+    }
+
+    /// Take all decompressed output so far.
+    fn take(&mut self) -> Vec<u8> {
+        let buf = match self {
+            Decoder::Gzip(d) => d.get_mut(),
+            Decoder::Deflate(d) => d.get_mut(),
+            Decoder::Zstd(d) => d.get_mut(),
+        };
+        std::mem::take(buf)
+    }
+}
+
+/// DecompressReader decompresses content read from inner reader.
+///
+/// Decompressed content can't be seeked since we don't know the mapping of
+/// offsets.
+#[cfg(feature = "compress")]
+pub struct DecompressReader<R> {
+    inner: R,
+    decoder: Decoder,
+    buf: Vec<u8>,
+    output: Bytes,
+    done: bool,
+}
+
+/// # Safety
+///
+/// Decoder will only be accessed via `&mut self`.
+#[cfg(feature = "compress")]
+unsafe impl<R: Sync> Sync for DecompressReader<R> {}
+
+#[cfg(feature = "compress")]
+impl<R> DecompressReader<R> {
+    pub(crate) fn new(inner: R, encoding: Encoding) -> Result<Self> {
+        Ok(Self {
+            inner,
+            decoder: Decoder::new(encoding)?,
+            buf: vec![0; 64 * 1024],
+            output: Bytes::new(),
+            done: false,
+        })
+    }
+
+    /// Feed compressed content into decoder, empty content means the end.
+    fn feed(&mut self, bs: &[u8]) -> Result<()> {
+        if bs.is_empty() {
+            self.decoder.finish()?;
+            self.done = true;
+        } else {
+            self.decoder.write(bs)?;
+        }
+        self.output = Bytes::from(self.decoder.take());
+        Ok(())
+    }
+
+    /// Feed the first `n` bytes of internal buffer into decoder.
+    fn feed_buf(&mut self, n: usize) -> Result<()> {
+        let buf = std::mem::take(&mut self.buf);
+        let res = self.feed(&buf[..n]);
+        self.buf = buf;
+        res
+    }
+
+    /// Copy decompressed output into buf.
+    fn copy_output(&mut self, buf: &mut [u8]) -> usize {
+        let n = self.output.len().min(buf.len());
+        buf[..n].copy_from_slice(&self.output[..n]);
+        self.output.advance(n);
+        n
+    }
+}
+
+#[cfg(feature = "compress")]
+impl<R: oio::Read> oio::Read for DecompressReader<R> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        loop {
+            if !self.output.is_empty() {
+                return Poll::Ready(Ok(self.copy_output(buf)));
+            }
+            if self.done {
+                return Poll::Ready(Ok(0));
+            }
+
+            let n = ready!(self.inner.poll_read(cx, &mut self.buf))?;
+            self.feed_buf(n)?;
+        }
+    }
+
+    fn poll_seek(&mut self, _: &mut Context<'_>, _: io::SeekFrom) -> Poll<Result<u64>> {
+        Poll::Ready(Err(Error::new(
+            ErrorKind::Unsupported,
+            "decompressed reader doesn't support seeking",
+        )))
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        loop {
+            if !self.output.is_empty() {
+                return Poll::Ready(Some(Ok(std::mem::take(&mut self.output))));
+            }
+            if self.done {
+                return Poll::Ready(None);
+            }
+
+            let res = match ready!(self.inner.poll_next(cx)) {
+                Some(Ok(bs)) if bs.is_empty() => continue,
+                Some(Ok(bs)) => self.feed(&bs),
+                Some(Err(err)) => Err(err),
+                None => self.feed(&[]),
+            };
+            if let Err(err) = res {
+                return Poll::Ready(Some(Err(err)));
+            }
+        }
+    }
+}
+
+#[cfg(feature = "compress")]
+impl<R: oio::BlockingRead> oio::BlockingRead for DecompressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            if !self.output.is_empty() {
+                return Ok(self.copy_output(buf));
+            }
+            if self.done {
+                return Ok(0);
+            }
+
+            let n = self.inner.read(&mut self.buf)?;
+            self.feed_buf(n)?;
+        }
+    }
+
+    fn seek(&mut self, _: io::SeekFrom) -> Result<u64> {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "decompressed reader doesn't support seeking",
+        ))
+    }
+
+    fn next(&mut self) -> Option<Result<Bytes>> {
+        loop {
+            if !self.output.is_empty() {
+                return Some(Ok(std::mem::take(&mut self.output)));
+            }
+            if self.done {
+                return None;
+            }
+
+            let res = match self.inner.next() {
+                Some(Ok(bs)) if bs.is_empty() => continue,
+                Some(Ok(bs)) => self.feed(&bs),
+                Some(Err(err)) => Err(err),
+                None => self.feed(&[]),
+            };
+            if let Err(err) = res {
+                return Some(Err(err));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_encoding() {
+        assert_eq!(parse_encoding("identity").unwrap(), None);
+        assert_eq!(parse_encoding(" GZIP ").unwrap(), Some(Encoding::Gzip));
+        assert_eq!(parse_encoding("zstd").unwrap(), Some(Encoding::Zstd));
+        assert_eq!(
+            parse_encoding("br").unwrap_err().kind(),
+            ErrorKind::Unsupported
+        );
+    }
+
+    #[test]
+    fn test_read_decompress_with_range() {
+        let args = OpRead::new().with_decompress(true);
+        assert!(read_decompress(&args).unwrap());
+
+        let args = args.with_range(BytesRange::new(Some(0), Some(10)));
+        let err = read_decompress(&args).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[cfg(feature = "compress")]
+    #[test]
+    fn test_decompress_reader() {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+
+        let content = "hello, world! ".repeat(1024);
+
+        let mut gzip = GzEncoder::new(Vec::new(), Compression::default());
+        gzip.write_all(content.as_bytes()).unwrap();
+        let gzip = gzip.finish().unwrap();
+        let zstd = zstd::encode_all(content.as_bytes(), 0).unwrap();
+
+        for (encoding, compressed) in [(Encoding::Gzip, gzip), (Encoding::Zstd, zstd)] {
+            let r: oio::BlockingReader = Box::new(oio::Cursor::from(compressed));
+            let mut r = DecompressReader::new(r, encoding).unwrap();
+
+            let mut actual = Vec::new();
+            while let Some(bs) = oio::BlockingRead::next(&mut r) {
+                actual.extend_from_slice(&bs.unwrap());
+            }
+            assert_eq!(actual, content.as_bytes(), "{encoding:?}");
+        }
+    }
+}
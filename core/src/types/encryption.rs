@@ -0,0 +1,66 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::fmt::Debug;
+use std::fmt::Formatter;
+
+/// Server side encryption of the content of a file.
+///
+/// ServerSideEncryption can be used in:
+///
+/// - `write_with(path).encryption(..)`: service will encrypt the content with
+///   given key before storing it.
+/// - `read_with(path).encryption(..)`: key to read the content which is
+///   encrypted by [`ServerSideEncryption::CustomerKey`].
+///
+/// Services that don't support given encryption will return `Unsupported`.
+#[derive(Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ServerSideEncryption {
+    /// Encrypt with keys managed by the service provider.
+    ProviderManaged,
+    /// Encrypt with the AES-256 key provided by customer.
+    ///
+    /// Service will not store the key, so the same key must be provided
+    /// while reading the content again.
+    CustomerKey([u8; 32]),
+    /// Encrypt with the keys of the named encryption scope that have been
+    /// configured in the service.
+    Scope(String),
+}
+
+impl ServerSideEncryption {
+    /// Get the name of this encryption.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ServerSideEncryption::ProviderManaged => "provider_managed",
+            ServerSideEncryption::CustomerKey(_) => "customer_key",
+            ServerSideEncryption::Scope(_) => "scope",
+        }
+    }
+}
+
+impl Debug for ServerSideEncryption {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ServerSideEncryption::ProviderManaged => write!(f, "ProviderManaged"),
+            // Never leak the key.
+            ServerSideEncryption::CustomerKey(_) => write!(f, "CustomerKey(<redacted>)"),
+            ServerSideEncryption::Scope(v) => f.debug_tuple("Scope").field(v).finish(),
+        }
+    }
+}
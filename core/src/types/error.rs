@@ -42,6 +42,7 @@ use std::fmt::Debug;
 use std::fmt::Display;
 use std::fmt::Formatter;
 use std::io;
+use std::time::Duration;
 
 /// Result that is a wrapper of `Result<T, opendal::Error>`
 pub type Result<T> = std::result::Result<T, Error>;
@@ -65,6 +66,11 @@ pub enum ErrorKind {
     /// The given path is a directory.
     IsADirectory,
     /// The given path is not a directory.
+    ///
+    /// For example:
+    ///
+    /// - `Operator::list` is called with a path that doesn't end with `/`.
+    /// - `azfile` is asked to list a path that is actually a file.
     NotADirectory,
     /// The given path already exists thus we failed to the specified operation on it.
     AlreadyExists,
@@ -105,6 +111,21 @@ pub enum ErrorKind {
     ///
     /// For example, user try to seek to a negative position
     InvalidInput,
+    /// The checksum of content is not match.
+    ///
+    /// For example, checksum computed while reading is different from the
+    /// checksum provided by users.
+    ChecksumMismatch,
+    /// The requested range is not satisfiable.
+    ///
+    /// For example, user try to read a range that starts after the end of
+    /// the file.
+    RangeNotSatisfied,
+    /// The operation has been cancelled.
+    ///
+    /// For example, the [`CancellationToken`](crate::CancellationToken)
+    /// passed to read has been cancelled or reached its deadline.
+    Cancelled,
 }
 
 impl ErrorKind {
@@ -137,6 +158,9 @@ impl From<ErrorKind> for &'static str {
             ErrorKind::ContentTruncated => "ContentTruncated",
             ErrorKind::ContentIncomplete => "ContentIncomplete",
             ErrorKind::InvalidInput => "InvalidInput",
+            ErrorKind::ChecksumMismatch => "ChecksumMismatch",
+            ErrorKind::RangeNotSatisfied => "RangeNotSatisfied",
+            ErrorKind::Cancelled => "Cancelled",
         }
     }
 }
@@ -241,6 +265,8 @@ pub struct Error {
     operation: &'static str,
     context: Vec<(&'static str, String)>,
     source: Option<anyhow::Error>,
+    retry_after: Option<Duration>,
+    service_code: Option<String>,
     backtrace: Backtrace,
 }
 
@@ -333,6 +359,8 @@ impl Error {
             operation: "",
             context: Vec::default(),
             source: None,
+            retry_after: None,
+            service_code: None,
             // `Backtrace::capture()` will check if backtrace has been enabled
             // internally. It's zero cost if backtrace is disabled.
             backtrace: Backtrace::capture(),
@@ -372,6 +400,24 @@ impl Error {
         self
     }
 
+    /// Set the delay suggested by services before retrying, like the
+    /// `Retry-After` header returned while throttling.
+    ///
+    /// The delay will also be added into context as `retry_after`.
+    pub fn with_retry_after(mut self, dur: Duration) -> Self {
+        self.retry_after = Some(dur);
+        self.with_context("retry_after", format!("{}s", dur.as_secs_f64()))
+    }
+
+    /// Set the error code returned by services, like `ParentNotFound` of
+    /// azfile.
+    ///
+    /// The code will also be added into context as `service_code`.
+    pub fn with_service_code(mut self, code: &str) -> Self {
+        self.service_code = Some(code.to_string());
+        self.with_context("service_code", code)
+    }
+
     /// Operate on error with map.
     pub fn map<F>(self, f: F) -> Self
     where
@@ -408,9 +454,38 @@ impl Error {
     }
 
     /// Check if this error is temporary.
+    ///
+    /// Temporary errors are expected to succeed after retry, like rate
+    /// limited or service unavailable. Errors that still failed after
+    /// [`RetryLayer`](crate::layers::RetryLayer) exhausted its attempts are
+    /// persistent and will return `false`.
     pub fn is_temporary(&self) -> bool {
         self.status == ErrorStatus::Temporary
     }
+
+    /// Check if this error's kind is [`ErrorKind::NotFound`].
+    pub fn is_not_found(&self) -> bool {
+        self.kind == ErrorKind::NotFound
+    }
+
+    /// Check if this error's kind is [`ErrorKind::Unsupported`].
+    pub fn is_unsupported(&self) -> bool {
+        self.kind == ErrorKind::Unsupported
+    }
+
+    /// Return the delay suggested by services before retrying.
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.retry_after
+    }
+
+    /// Return the error code returned by services.
+    ///
+    /// Codes are service specific, they could be used to distinguish errors
+    /// of the same kind, like `ParentNotFound` and `ResourceNotFound` of
+    /// azfile which are both [`ErrorKind::NotFound`].
+    pub fn service_code(&self) -> Option<&str> {
+        self.service_code.as_deref()
+    }
 }
 
 impl From<Error> for io::Error {
@@ -444,9 +519,26 @@ mod tests {
             ("called", "send_async".to_string()),
         ],
         source: Some(anyhow!("networking error")),
+        retry_after: None,
+        service_code: None,
         backtrace: Backtrace::disabled(),
     });
 
+    #[test]
+    fn test_error_status_helpers() {
+        let err = Error::new(ErrorKind::NotFound, "not found");
+        assert!(err.is_not_found());
+        assert!(!err.is_unsupported());
+        assert!(!err.is_temporary());
+
+        let err = Error::new(ErrorKind::Unsupported, "unsupported").set_temporary();
+        assert!(err.is_unsupported());
+        assert!(err.is_temporary());
+
+        let err = err.set_persistent();
+        assert!(!err.is_temporary());
+    }
+
     #[test]
     fn test_error_display() {
         let s = format!("{}", Lazy::force(&TEST_ERROR));
@@ -23,32 +23,88 @@ use std::task::Poll;
 
 use flagset::FlagSet;
 use futures::future::BoxFuture;
+use futures::stream::FuturesOrdered;
 use futures::FutureExt;
 use futures::Stream;
+use futures::StreamExt;
 
 use crate::raw::*;
 use crate::*;
 
 /// Future constructed by listing.
 type ListFuture = BoxFuture<'static, (oio::Pager, Result<Option<Vec<oio::Entry>>>)>;
-/// Future constructed by stating.
-type StatFuture = BoxFuture<'static, (String, Result<RpStat>)>;
+/// Future constructed by stating, entries don't need stat will be wrapped
+/// as a ready future so that the order of entries is kept.
+type StatFuture = BoxFuture<'static, Result<Entry>>;
 
 /// Lister is designed to list entries at given path in an asynchronous
 /// manner.
 ///
 /// Users can construct Lister by [`Operator::lister`].
 ///
-/// User can use lister as `Stream<Item = Result<Entry>>`.
+/// User can use lister as `Stream<Item = Result<Entry>>`, entries of every
+/// page returned by services will be yielded one by one.
+///
+/// # Cancellation Safety
+///
+/// Lister keeps all in-flight states (the page being fetched, the entry
+/// being stated and the buffered entries of current page) inside itself.
+/// Dropping a pending `next()` future will not lose any entries, the
+/// following `next()` will continue from where it left off.
+///
+/// If fetching a page failed, the error will be returned and the next poll
+/// will try to fetch the same page again.
+///
+/// # Metakey
+///
+/// Entries missing the metakey required by `list_with(path).metakey(..)`
+/// will be stated to fill the metadata, which costs one extra request per
+/// entry. Services like azfile return size, etag and last modified inline
+/// so requiring them is free, while `Metakey::Complete` or services that
+/// only return names will trigger stats. Use `list_with(path).concurrent(n)`
+/// to send at most `n` stats at the same time, entries are still returned
+/// in listing order.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use anyhow::Result;
+/// use futures::StreamExt;
+/// use futures::TryStreamExt;
+/// use opendal::EntryMode;
+/// use opendal::Operator;
+/// # #[tokio::main]
+/// # async fn test(op: Operator) -> Result<()> {
+/// let files: Vec<_> = op
+///     .lister("path/to/dir/")
+///     .await?
+///     .try_filter(|e| futures::future::ready(e.metadata().mode() == EntryMode::FILE))
+///     .take(10)
+///     .try_collect()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
 pub struct Lister {
     acc: FusedAccessor,
     /// required_metakey is the metakey required by users.
     required_metakey: FlagSet<Metakey>,
+    /// filter is used to drop entries that don't match the pattern.
+    filter: Option<PatternFilter>,
+    /// mode is used to drop entries that don't match the mode.
+    mode: Option<EntryMode>,
 
     buf: VecDeque<oio::Entry>,
     pager: Option<oio::Pager>,
     listing: Option<ListFuture>,
-    stating: Option<StatFuture>,
+    stating: FuturesOrdered<StatFuture>,
+    /// concurrent is the max number of entries in `stating`.
+    concurrent: usize,
+
+    /// page_token is the token to fetch the page that in `buf` now.
+    page_token: Option<String>,
+    /// next_token is the token to fetch the next page.
+    next_token: Option<String>,
 }
 
 /// # Safety
@@ -60,49 +116,84 @@ impl Lister {
     /// Create a new lister.
     pub(crate) async fn create(acc: FusedAccessor, path: &str, args: OpList) -> Result<Self> {
         let required_metakey = args.metakey();
+        let filter = PatternFilter::new(path, args.pattern())?;
+        let mode = args.mode();
+        let concurrent = args.concurrent();
+        let token = args.start_after_token().map(|v| v.to_string());
         let (_, pager) = acc.list(path, args).await?;
 
         Ok(Self {
             acc,
             required_metakey,
+            filter,
+            mode,
 
             buf: VecDeque::new(),
             pager: Some(pager),
             listing: None,
-            stating: None,
+            stating: FuturesOrdered::new(),
+            concurrent,
+
+            page_token: token.clone(),
+            next_token: token,
         })
     }
+
+    /// Get the opaque continuation token of this lister.
+    ///
+    /// The token can be persisted and passed to `start_after_token` of a
+    /// later list on the same path to resume listing. Entries of the page
+    /// that has not been fully consumed could be returned again after
+    /// resuming.
+    ///
+    /// Returns `None` if the service doesn't support resuming, or there is
+    /// nothing left to list.
+    pub fn continuation_token(&self) -> Option<String> {
+        if self.buf.is_empty() && self.stating.is_empty() {
+            self.next_token.clone()
+        } else {
+            self.page_token.clone()
+        }
+    }
 }
 
 impl Stream for Lister {
     type Item = Result<Entry>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        if let Some(fut) = self.stating.as_mut() {
-            let (path, rp) = ready!(fut.poll_unpin(cx));
-
-            // Make sure we will not poll this future again.
-            self.stating = None;
-            let metadata = rp?.into_metadata();
-
-            return Poll::Ready(Some(Ok(Entry::new(path, metadata))));
-        }
-
-        if let Some(oe) = self.buf.pop_front() {
+        while self.stating.len() < self.concurrent {
+            let oe = match self.buf.pop_front() {
+                Some(oe) => oe,
+                None => break,
+            };
             let (path, metadata) = oe.into_entry().into_parts();
+            if let Some(filter) = &self.filter {
+                if !filter.is_match(&path) {
+                    continue;
+                }
+            }
+            if matches!(self.mode, Some(mode) if metadata.mode() != mode) {
+                continue;
+            }
             // TODO: we can optimize this by checking the provided metakey provided by services.
             if metadata.contains_metakey(self.required_metakey) {
-                return Poll::Ready(Some(Ok(Entry::new(path, metadata))));
+                let entry = Entry::new(path, metadata);
+                self.stating
+                    .push_back(Box::pin(futures::future::ready(Ok(entry))));
+                continue;
             }
 
             let acc = self.acc.clone();
             let fut = async move {
-                let res = acc.stat(&path, OpStat::default()).await;
+                let rp = acc.stat(&path, OpStat::default()).await?;
 
-                (path, res)
+                Ok(Entry::new(path, rp.into_metadata()))
             };
-            self.stating = Some(Box::pin(fut));
-            return self.poll_next(cx);
+            self.stating.push_back(Box::pin(fut));
+        }
+
+        if !self.stating.is_empty() {
+            return self.stating.poll_next_unpin(cx);
         }
 
         if let Some(fut) = self.listing.as_mut() {
@@ -110,14 +201,25 @@ impl Stream for Lister {
 
             // Make sure we will not poll this future again.
             self.listing = None;
+            // Keep the pager so that we can fetch the page again after error.
+            self.pager = Some(op);
 
             return match res? {
                 Some(oes) => {
-                    self.pager = Some(op);
+                    self.page_token = self.next_token.take();
+                    self.next_token = self
+                        .pager
+                        .as_ref()
+                        .and_then(|pager| pager.continuation_token());
                     self.buf = oes.into();
                     self.poll_next(cx)
                 }
-                None => Poll::Ready(None),
+                None => {
+                    self.pager = None;
+                    self.page_token = None;
+                    self.next_token = None;
+                    Poll::Ready(None)
+                }
             };
         }
 
@@ -144,9 +246,18 @@ pub struct BlockingLister {
     acc: FusedAccessor,
     /// required_metakey is the metakey required by users.
     required_metakey: FlagSet<Metakey>,
+    /// filter is used to drop entries that don't match the pattern.
+    filter: Option<PatternFilter>,
+    /// mode is used to drop entries that don't match the mode.
+    mode: Option<EntryMode>,
 
     pager: Option<oio::BlockingPager>,
     buf: VecDeque<oio::Entry>,
+
+    /// page_token is the token to fetch the page that in `buf` now.
+    page_token: Option<String>,
+    /// next_token is the token to fetch the next page.
+    next_token: Option<String>,
 }
 
 /// # Safety
@@ -158,16 +269,35 @@ impl BlockingLister {
     /// Create a new lister.
     pub(crate) fn create(acc: FusedAccessor, path: &str, args: OpList) -> Result<Self> {
         let required_metakey = args.metakey();
+        let filter = PatternFilter::new(path, args.pattern())?;
+        let mode = args.mode();
+        let token = args.start_after_token().map(|v| v.to_string());
         let (_, pager) = acc.blocking_list(path, args)?;
 
         Ok(Self {
             acc,
             required_metakey,
+            filter,
+            mode,
 
             buf: VecDeque::new(),
             pager: Some(pager),
+
+            page_token: token.clone(),
+            next_token: token,
         })
     }
+
+    /// Get the opaque continuation token of this lister.
+    ///
+    /// Same as [`Lister::continuation_token`].
+    pub fn continuation_token(&self) -> Option<String> {
+        if self.buf.is_empty() {
+            self.next_token.clone()
+        } else {
+            self.page_token.clone()
+        }
+    }
 }
 
 /// TODO: we can implement next_chunk.
@@ -175,8 +305,16 @@ impl Iterator for BlockingLister {
     type Item = Result<Entry>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if let Some(oe) = self.buf.pop_front() {
+        while let Some(oe) = self.buf.pop_front() {
             let (path, metadata) = oe.into_entry().into_parts();
+            if let Some(filter) = &self.filter {
+                if !filter.is_match(&path) {
+                    continue;
+                }
+            }
+            if matches!(self.mode, Some(mode) if metadata.mode() != mode) {
+                continue;
+            }
             // TODO: we can optimize this by checking the provided metakey provided by services.
             if metadata.contains_metakey(self.required_metakey) {
                 return Some(Ok(Entry::new(path, metadata)));
@@ -198,9 +336,15 @@ impl Iterator for BlockingLister {
             // Ideally, the convert from `Vec` to `VecDeque` will not do reallocation.
             //
             // However, this could be changed as described in [impl<T, A> From<Vec<T, A>> for VecDeque<T, A>](https://doc.rust-lang.org/std/collections/struct.VecDeque.html#impl-From%3CVec%3CT%2C%20A%3E%3E-for-VecDeque%3CT%2C%20A%3E)
-            Ok(Some(entries)) => entries.into(),
+            Ok(Some(entries)) => {
+                self.page_token = self.next_token.take();
+                self.next_token = pager.continuation_token();
+                entries.into()
+            }
             Ok(None) => {
                 self.pager = None;
+                self.page_token = None;
+                self.next_token = None;
                 return None;
             }
             Err(err) => return Some(Err(err)),
@@ -210,13 +354,51 @@ impl Iterator for BlockingLister {
     }
 }
 
+/// PatternFilter matches entries against the glob pattern provided by users.
+///
+/// The pattern applies to the portion of the path after the listed path,
+/// and the trailing `/` of dirs will be ignored.
+struct PatternFilter {
+    path: String,
+    glob: Glob,
+}
+
+impl PatternFilter {
+    fn new(path: &str, pattern: Option<&str>) -> Result<Option<Self>> {
+        let pattern = match pattern {
+            Some(pattern) => pattern,
+            None => return Ok(None),
+        };
+
+        Ok(Some(Self {
+            path: path.to_string(),
+            glob: Glob::new(pattern)?,
+        }))
+    }
+
+    fn is_match(&self, path: &str) -> bool {
+        let rel = if self.path == "/" {
+            path
+        } else {
+            match path.strip_prefix(self.path.as_str()) {
+                Some(rel) => rel,
+                None => return false,
+            }
+        };
+
+        self.glob.is_match(rel.trim_end_matches('/'))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use futures::future;
     use futures::StreamExt;
+    use futures::TryStreamExt;
 
     use super::*;
     use crate::services::Azblob;
+    use crate::services::Memory;
 
     /// Inspired by <https://gist.github.com/kyle-mccarthy/1e6ae89cc34495d731b91ebf5eb5a3d9>
     ///
@@ -248,4 +430,102 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_list_with_mode() -> Result<()> {
+        let op = Operator::new(Memory::default())?.finish();
+        for path in ["dir/a", "dir/sub/b"] {
+            op.write(path, "data").await?;
+        }
+
+        let paths: Vec<String> = op
+            .list_with("dir/")
+            .mode(EntryMode::DIR)
+            .await?
+            .into_iter()
+            .map(|e| e.path().to_string())
+            .collect();
+        assert!(paths.iter().all(|p| p.ends_with('/')), "{paths:?}");
+        assert!(paths.contains(&"dir/sub/".to_string()), "{paths:?}");
+
+        let paths: Vec<String> = op
+            .list_with("dir/")
+            .mode(EntryMode::FILE)
+            .await?
+            .into_iter()
+            .map(|e| e.path().to_string())
+            .collect();
+        assert_eq!(paths, vec!["dir/a"]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_with_pattern() -> Result<()> {
+        let op = Operator::new(Memory::default())?.finish();
+        for path in ["dir/a.log", "dir/b.txt", "dir/logs/c.log", "dir/logs/d.txt"] {
+            op.write(path, "data").await?;
+        }
+
+        let mut paths: Vec<String> = op
+            .list_with("dir/")
+            .pattern("*.log")
+            .await?
+            .into_iter()
+            .map(|e| e.path().to_string())
+            .collect();
+        paths.sort();
+        assert_eq!(paths, vec!["dir/a.log"]);
+
+        let mut paths: Vec<String> = op
+            .list_with("dir/")
+            .delimiter("")
+            .pattern("**/*.log")
+            .await?
+            .into_iter()
+            .map(|e| e.path().to_string())
+            .collect();
+        paths.sort();
+        assert_eq!(paths, vec!["dir/a.log", "dir/logs/c.log"]);
+
+        let err = op.list_with("dir/").pattern("a\\").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lister_stream_not_lose_entries() -> Result<()> {
+        let op = Operator::new(Memory::default())?.finish();
+        let mut expected = Vec::new();
+        for i in 0..10 {
+            let path = format!("dir/file-{i}");
+            op.write(&path, "data").await?;
+            expected.push(path);
+        }
+
+        // Require content length so that every entry needs an extra stat.
+        let mut lister = op
+            .lister_with("dir/")
+            .metakey(Metakey::ContentLength)
+            .await?;
+
+        // Consume part of the lister by reference, the rest entries should
+        // still be returned later.
+        let mut paths: Vec<String> = (&mut lister)
+            .take(3)
+            .map_ok(|e| e.path().to_string())
+            .try_collect()
+            .await?;
+        let rest: Vec<String> = lister
+            .map_ok(|e| e.path().to_string())
+            .try_collect()
+            .await?;
+        paths.extend(rest);
+        paths.sort();
+        expected.sort();
+        assert_eq!(paths, expected);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,109 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use crate::*;
+
+/// ListResult splits entries returned by `list` into common prefixes and
+/// objects, like `CommonPrefixes` and `Contents` in S3's `ListObjectsV2`.
+///
+/// # Notes
+///
+/// While listing with a delimiter (the default `/`), dirs returned by
+/// services are the common prefixes of the objects under them, no matter
+/// whether the service has real dirs (like `fs` and `azfile`) or not (like
+/// `s3`). ListResult classifies entries by [`EntryMode`] which is always
+/// fetched, so callers don't need to derive dirs by path suffix.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use anyhow::Result;
+/// use opendal::ListResult;
+/// use opendal::Operator;
+/// # #[tokio::main]
+/// # async fn test(op: Operator) -> Result<()> {
+/// let result = ListResult::from(op.list("dir/").await?);
+/// for prefix in result.common_prefixes() {
+///     println!("Rendering dir {}", prefix.name())
+/// }
+/// for entry in result.entries() {
+///     println!("Rendering file {}", entry.name())
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ListResult {
+    common_prefixes: Vec<Entry>,
+    entries: Vec<Entry>,
+}
+
+impl ListResult {
+    /// Common prefixes of this result, all of them are dirs that end with `/`.
+    pub fn common_prefixes(&self) -> &[Entry] {
+        &self.common_prefixes
+    }
+
+    /// Entries of this result, all of them are objects that not end with `/`.
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Consume this result to get it's common prefixes and entries.
+    pub fn into_parts(self) -> (Vec<Entry>, Vec<Entry>) {
+        (self.common_prefixes, self.entries)
+    }
+}
+
+impl From<Vec<Entry>> for ListResult {
+    fn from(entries: Vec<Entry>) -> Self {
+        let (common_prefixes, entries) = entries
+            .into_iter()
+            .partition(|e| e.metadata().mode().is_dir());
+
+        Self {
+            common_prefixes,
+            entries,
+        }
+    }
+}
+
+impl FromIterator<Entry> for ListResult {
+    fn from_iter<T: IntoIterator<Item = Entry>>(iter: T) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_result_from_entries() {
+        let entries = vec![
+            Entry::new("dir/a".to_string(), Metadata::new(EntryMode::FILE)),
+            Entry::new("dir/sub/".to_string(), Metadata::new(EntryMode::DIR)),
+            Entry::new("dir/b".to_string(), Metadata::new(EntryMode::FILE)),
+        ];
+
+        let result = ListResult::from(entries);
+        let prefixes: Vec<_> = result.common_prefixes().iter().map(|e| e.path()).collect();
+        let entries: Vec<_> = result.entries().iter().map(|e| e.path()).collect();
+        assert_eq!(prefixes, vec!["dir/sub/"]);
+        assert_eq!(entries, vec!["dir/a", "dir/b"]);
+    }
+}
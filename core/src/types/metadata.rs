@@ -15,9 +15,12 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use std::collections::HashMap;
+
 use chrono::prelude::*;
 use flagset::flags;
 use flagset::FlagSet;
+use http::HeaderMap;
 
 use crate::raw::*;
 use crate::*;
@@ -38,13 +41,19 @@ pub struct Metadata {
 
     cache_control: Option<String>,
     content_disposition: Option<String>,
+    content_encoding: Option<String>,
     content_length: Option<u64>,
     content_md5: Option<String>,
     content_range: Option<BytesContentRange>,
     content_type: Option<String>,
     etag: Option<String>,
     last_modified: Option<DateTime<Utc>>,
+    created: Option<DateTime<Utc>>,
+    last_accessed: Option<DateTime<Utc>>,
+    file_id: Option<String>,
     version: Option<String>,
+    user_metadata: Option<HashMap<String, String>>,
+    raw_headers: Option<HeaderMap>,
 }
 
 impl Metadata {
@@ -68,9 +77,15 @@ impl Metadata {
             content_type: None,
             content_range: None,
             last_modified: None,
+            created: None,
+            last_accessed: None,
+            file_id: None,
             etag: None,
             content_disposition: None,
+            content_encoding: None,
             version: None,
+            user_metadata: None,
+            raw_headers: None,
         }
     }
 
@@ -356,6 +371,71 @@ impl Metadata {
         self
     }
 
+    /// Creation time of this entry.
+    ///
+    /// Not all services provide this value, for example, it's only returned by
+    /// `azfile` now. `None` will be returned if the service doesn't provide it.
+    ///
+    /// # Panics
+    ///
+    /// This value is only available when calling on result of `stat` or `list` with
+    /// [`Metakey::Created`], otherwise it will panic.
+    pub fn created(&self) -> Option<DateTime<Utc>> {
+        debug_assert!(
+            self.metakey.contains(Metakey::Created) || self.metakey.contains(Metakey::Complete),
+            "visiting not set metadata: created, maybe a bug"
+        );
+
+        self.created
+    }
+
+    /// Set creation time of this entry.
+    pub fn set_created(&mut self, v: DateTime<Utc>) -> &mut Self {
+        self.created = Some(v);
+        self.metakey |= Metakey::Created;
+        self
+    }
+
+    /// Set creation time of this entry.
+    pub fn with_created(mut self, v: DateTime<Utc>) -> Self {
+        self.created = Some(v);
+        self.metakey |= Metakey::Created;
+        self
+    }
+
+    /// Last access time of this entry.
+    ///
+    /// Not all services provide this value, for example, it's only returned by
+    /// `azfile` now. `None` will be returned if the service doesn't provide it.
+    ///
+    /// # Panics
+    ///
+    /// This value is only available when calling on result of `stat` or `list` with
+    /// [`Metakey::LastAccessed`], otherwise it will panic.
+    pub fn last_accessed(&self) -> Option<DateTime<Utc>> {
+        debug_assert!(
+            self.metakey.contains(Metakey::LastAccessed)
+                || self.metakey.contains(Metakey::Complete),
+            "visiting not set metadata: last_accessed, maybe a bug"
+        );
+
+        self.last_accessed
+    }
+
+    /// Set last access time of this entry.
+    pub fn set_last_accessed(&mut self, v: DateTime<Utc>) -> &mut Self {
+        self.last_accessed = Some(v);
+        self.metakey |= Metakey::LastAccessed;
+        self
+    }
+
+    /// Set last access time of this entry.
+    pub fn with_last_accessed(mut self, v: DateTime<Utc>) -> Self {
+        self.last_accessed = Some(v);
+        self.metakey |= Metakey::LastAccessed;
+        self
+    }
+
     /// ETag of this entry.
     ///
     /// `ETag` is defined by [RFC 7232](https://httpwg.org/specs/rfc7232.html#header.etag)
@@ -475,6 +555,128 @@ impl Metadata {
         self
     }
 
+    /// Content-Encoding of this entry.
+    ///
+    /// `Content-Encoding` is defined by [RFC 7231](https://httpwg.org/specs/rfc7231.html#header.content-encoding),
+    /// like `gzip` or `zstd`.
+    ///
+    /// # Panics
+    ///
+    /// This value is only available when calling on result of `stat` or `list` with
+    /// [`Metakey::ContentEncoding`], otherwise it will panic.
+    pub fn content_encoding(&self) -> Option<&str> {
+        debug_assert!(
+            self.metakey.contains(Metakey::ContentEncoding)
+                || self.metakey.contains(Metakey::Complete),
+            "visiting not set metadata: content_encoding, maybe a bug"
+        );
+
+        self.content_encoding.as_deref()
+    }
+
+    /// Set Content-Encoding of this entry.
+    pub fn with_content_encoding(mut self, v: String) -> Self {
+        self.content_encoding = Some(v);
+        self.metakey |= Metakey::ContentEncoding;
+        self
+    }
+
+    /// Set Content-Encoding of this entry.
+    pub fn set_content_encoding(&mut self, v: &str) -> &mut Self {
+        self.content_encoding = Some(v.to_string());
+        self.metakey |= Metakey::ContentEncoding;
+        self
+    }
+
+    /// User defined metadata of this entry.
+    ///
+    /// User defined metadata is set by `write_with(path).user_metadata(..)`,
+    /// services like `azfile` store them as `x-ms-meta-*` headers.
+    ///
+    /// # Panics
+    ///
+    /// This value is only available when calling on result of `stat` or `list` with
+    /// [`Metakey::UserMetadata`], otherwise it will panic.
+    pub fn user_metadata(&self) -> Option<&HashMap<String, String>> {
+        debug_assert!(
+            self.metakey.contains(Metakey::UserMetadata)
+                || self.metakey.contains(Metakey::Complete),
+            "visiting not set metadata: user_metadata, maybe a bug"
+        );
+
+        self.user_metadata.as_ref()
+    }
+
+    /// Set user defined metadata of this entry.
+    pub fn with_user_metadata(mut self, v: HashMap<String, String>) -> Self {
+        self.user_metadata = Some(v);
+        self.metakey |= Metakey::UserMetadata;
+        self
+    }
+
+    /// Set user defined metadata of this entry.
+    pub fn set_user_metadata(&mut self, v: HashMap<String, String>) -> &mut Self {
+        self.user_metadata = Some(v);
+        self.metakey |= Metakey::UserMetadata;
+        self
+    }
+
+    /// Raw headers of the response returned by service.
+    ///
+    /// Only returned by `stat_with(path).raw_headers(true)` on services with
+    /// `stat_with_raw_headers` capability, `None` otherwise. Headers are
+    /// service specific and not portable: the same information could be
+    /// carried by different headers, or not at all, on other services.
+    ///
+    /// Only `azfile` populates this value now.
+    pub fn raw_headers(&self) -> Option<&HeaderMap> {
+        self.raw_headers.as_ref()
+    }
+
+    /// Set raw headers of the response.
+    pub fn set_raw_headers(&mut self, v: HeaderMap) -> &mut Self {
+        self.raw_headers = Some(v);
+        self
+    }
+
+    /// File ID of this entry.
+    ///
+    /// File ID is a stable identifier assigned by the service, which survives
+    /// renames. It's useful for change detection since it's more reliable than path.
+    ///
+    /// Only `azfile` populates this value now.
+    ///
+    /// # Panics
+    ///
+    /// This value is only available when calling on result of `stat` or `list` with
+    /// [`Metakey::FileId`], otherwise it will panic.
+    pub fn file_id(&self) -> Option<&str> {
+        debug_assert!(
+            self.metakey.contains(Metakey::FileId) || self.metakey.contains(Metakey::Complete),
+            "visiting not set metadata: file_id, maybe a bug"
+        );
+
+        self.file_id.as_deref()
+    }
+
+    /// Set file ID of this entry.
+    ///
+    /// Only `azfile` populates this value now.
+    pub fn with_file_id(mut self, v: String) -> Self {
+        self.file_id = Some(v);
+        self.metakey |= Metakey::FileId;
+        self
+    }
+
+    /// Set file ID of this entry.
+    ///
+    /// Only `azfile` populates this value now.
+    pub fn set_file_id(&mut self, v: &str) -> &mut Self {
+        self.file_id = Some(v.to_string());
+        self.metakey |= Metakey::FileId;
+        self
+    }
+
     /// Version of this entry.
     ///
     /// Version is a string that can be used to identify the version of this entry.
@@ -554,7 +756,17 @@ flags! {
         Etag,
         /// Key for last last modified.
         LastModified,
+        /// Key for creation time.
+        Created,
+        /// Key for last access time.
+        LastAccessed,
+        /// Key for file id.
+        FileId,
         /// Key for version.
         Version,
+        /// Key for content encoding.
+        ContentEncoding,
+        /// Key for user defined metadata.
+        UserMetadata,
     }
 }
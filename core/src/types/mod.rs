@@ -26,7 +26,10 @@ pub use metadata::Metadata;
 pub use metadata::Metakey;
 
 mod reader;
+pub(crate) use reader::new_max_bytes_error;
 pub use reader::BlockingReader;
+pub(crate) use reader::ChecksumReader;
+pub(crate) use reader::LimitedReader;
 pub use reader::Reader;
 
 mod writer;
@@ -37,6 +40,9 @@ mod list;
 pub use list::BlockingLister;
 pub use list::Lister;
 
+mod list_result;
+pub use list_result::ListResult;
+
 mod operator;
 pub use operator::operator_functions;
 pub use operator::operator_futures;
@@ -58,3 +64,28 @@ pub use scheme::Scheme;
 
 mod capability;
 pub use capability::Capability;
+
+mod checksum;
+pub(crate) use checksum::read_verify_checksum;
+pub(crate) use checksum::stored_checksum;
+pub use checksum::Checksum;
+pub(crate) use checksum::ChecksumHasher;
+
+mod decompress;
+pub(crate) use decompress::decompress_blocking_reader;
+pub(crate) use decompress::decompress_reader;
+#[cfg(feature = "layers-compression")]
+pub(crate) use decompress::parse_encoding;
+pub(crate) use decompress::read_decompress;
+#[cfg(feature = "layers-compression")]
+pub(crate) use decompress::DecompressReader;
+#[cfg(feature = "layers-compression")]
+pub(crate) use decompress::Encoding;
+
+mod encryption;
+pub use encryption::ServerSideEncryption;
+
+mod cancel;
+pub(crate) use cancel::new_cancelled_error;
+pub(crate) use cancel::CancelWait;
+pub use cancel::CancellationToken;
@@ -18,10 +18,18 @@
 use std::io::Read;
 
 use bytes::Bytes;
+use log::debug;
 
 use super::operator_functions::*;
 use crate::raw::oio::WriteBuf;
 use crate::raw::*;
+use crate::types::decompress_blocking_reader;
+use crate::types::new_max_bytes_error;
+use crate::types::read_decompress;
+use crate::types::read_verify_checksum;
+use crate::types::stored_checksum;
+use crate::types::ChecksumReader;
+use crate::types::LimitedReader;
 use crate::*;
 
 /// BlockingOperator is the entry for all public blocking APIs.
@@ -220,18 +228,25 @@ impl BlockingOperator {
 
     /// Check if this path exists or not.
     ///
+    /// # Notes
+    ///
+    /// - Returns `Ok(true)` if the path exists.
+    /// - Returns `Ok(false)` if the path is not found.
+    /// - Returns `Err` for other failures like `PermissionDenied` or
+    ///   `ConfigInvalid`, so that a broken config won't be taken as absence.
+    ///
     /// # Example
     ///
     /// ```no_run
     /// use anyhow::Result;
     /// use opendal::BlockingOperator;
     /// fn test(op: BlockingOperator) -> Result<()> {
-    ///     let _ = op.is_exist("test")?;
+    ///     let _ = op.exists("test")?;
     ///
     ///     Ok(())
     /// }
     /// ```
-    pub fn is_exist(&self, path: &str) -> Result<bool> {
+    pub fn exists(&self, path: &str) -> Result<bool> {
         let r = self.stat(path);
         match r {
             Ok(_) => Ok(true),
@@ -242,6 +257,25 @@ impl BlockingOperator {
         }
     }
 
+    /// Check if this path exists or not.
+    ///
+    /// This is the same as [`BlockingOperator::exists`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use anyhow::Result;
+    /// use opendal::BlockingOperator;
+    /// fn test(op: BlockingOperator) -> Result<()> {
+    ///     let _ = op.is_exist("test")?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn is_exist(&self, path: &str) -> Result<bool> {
+        self.exists(path)
+    }
+
     /// Create a dir at given path.
     ///
     /// # Notes
@@ -305,6 +339,52 @@ impl BlockingOperator {
         self.read_with(path).call()
     }
 
+    /// Read the whole path and append the content into given buffer.
+    ///
+    /// Same as [`Operator::read_to`], returns the size of content that has
+    /// been appended.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::io::Result;
+    /// # use opendal::BlockingOperator;
+    /// #
+    /// # fn test(op: BlockingOperator) -> Result<()> {
+    /// let mut buf = Vec::with_capacity(4096);
+    /// let n = op.read_to("path/to/file", &mut buf)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read_to(&self, path: &str, buf: &mut Vec<u8>) -> Result<usize> {
+        let path = normalize_path(path);
+
+        if !validate_path(&path, EntryMode::FILE) {
+            return Err(
+                Error::new(ErrorKind::IsADirectory, "read path is a directory")
+                    .with_operation("BlockingOperator::read_to")
+                    .with_context("service", self.info().scheme().into_static())
+                    .with_context("path", &path),
+            );
+        }
+
+        let (rp, mut r) = self.inner().blocking_read(&path, OpRead::new())?;
+        buf.reserve(rp.into_metadata().content_length_raw().unwrap_or_default() as usize);
+
+        let start = buf.len();
+        match r.read_to_end(buf) {
+            Ok(n) => Ok(n),
+            Err(err) => {
+                buf.truncate(start);
+                Err(Error::new(ErrorKind::Unexpected, "blocking read_to failed")
+                    .with_operation("BlockingOperator::read_to")
+                    .with_context("service", self.info().scheme().into_static())
+                    .with_context("path", &path)
+                    .set_source(err))
+            }
+        }
+    }
+
     /// Read the whole path into a bytes with extra options.
     ///
     /// This function will allocate a new bytes internally. For more precise memory control or
@@ -339,22 +419,66 @@ impl BlockingOperator {
                     );
                 }
 
-                let (rp, mut s) = inner.blocking_read(&path, args)?;
-                let mut buffer = Vec::with_capacity(rp.into_metadata().content_length() as usize);
+                let decompress = read_decompress(&args)?;
+                let verify_checksum = read_verify_checksum(&args)?;
+                let max_bytes = args.max_bytes();
 
-                match s.read_to_end(&mut buffer) {
-                    Ok(n) => {
-                        buffer.truncate(n);
-                        Ok(buffer)
-                    }
-                    Err(err) => Err(
-                        Error::new(ErrorKind::Unexpected, "blocking read_with failed")
+                // Empty range can't be represented by `Range` header, we
+                // only need to make sure the file exists.
+                if args.range().size() == Some(0) {
+                    inner.blocking_stat(&path, OpStat::default())?;
+                    return Ok(Vec::new());
+                }
+
+                let (rp, s) = inner.blocking_read(&path, args)?;
+                let meta = rp.into_metadata();
+                // Content returned by services will be delivered as is,
+                // so we can fail before reading it.
+                if let Some(max_bytes) = max_bytes {
+                    if !decompress && matches!(meta.content_length_raw(), Some(v) if v > max_bytes)
+                    {
+                        return Err(new_max_bytes_error(max_bytes)
                             .with_operation("BlockingOperator::read_with")
+                            .with_context("service", inner.info().scheme().into_static())
+                            .with_context("path", &path));
+                    }
+                }
+
+                // Checksum is computed on the raw content returned by services.
+                let mut r: oio::BlockingReader = if verify_checksum {
+                    let checksum = stored_checksum(&meta)?;
+                    Box::new(ChecksumReader::new(s, checksum))
+                } else {
+                    s
+                };
+                if decompress {
+                    r = decompress_blocking_reader(r, &meta)?;
+                }
+                // Limit is applied while reading, so that decompressed content
+                // or content without known length will never be buffered
+                // beyond `max_bytes`.
+                if let Some(max_bytes) = max_bytes {
+                    r = Box::new(LimitedReader::new(r, max_bytes));
+                }
+
+                // Layers that transform content like `CompressionLayer` can't
+                // know the length in advance.
+                let capacity = match meta.content_length_raw() {
+                    Some(length) if !decompress => length as usize,
+                    _ => 0,
+                };
+                let mut buffer = Vec::with_capacity(capacity);
+                // Errors like `ChecksumMismatch` are returned as is.
+                while let Some(bs) = oio::BlockingRead::next(&mut r) {
+                    let bs = bs.map_err(|err| {
+                        err.with_operation("BlockingOperator::read_with")
                             .with_context("service", inner.info().scheme().into_static())
                             .with_context("path", &path)
-                            .set_source(err),
-                    ),
+                    })?;
+                    buffer.extend_from_slice(&bs);
                 }
+
+                Ok(buffer)
             },
         ))
     }
@@ -444,6 +568,9 @@ impl BlockingOperator {
     /// - `to` will be overwritten if it exists.
     /// - If `from` and `to` are the same, nothing will happen.
     /// - `copy` is idempotent. For same `from` and `to` input, the result will be the same.
+    /// - Services without native copy support will fall back to read `from`
+    ///   and write into `to` chunk by chunk, which costs the bandwidth of the
+    ///   whole content.
     ///
     /// # Examples
     ///
@@ -489,11 +616,41 @@ impl BlockingOperator {
             );
         }
 
+        if !self.info().full_capability().copy {
+            debug!("copy {from} to {to} via stream copy since native copy is not supported");
+            return self.stream_copy(&from, &to).map_err(|err| {
+                err.with_operation("BlockingOperator::copy")
+                    .with_context("from", &from)
+                    .with_context("to", &to)
+            });
+        }
+
+        debug!("copy {from} to {to} via native copy");
         self.inner().blocking_copy(&from, &to, OpCopy::new())?;
 
         Ok(())
     }
 
+    /// Copy by reading content from `from` and writing into `to`, only one
+    /// chunk will be held in memory at the same time.
+    ///
+    /// Services that can't accept multiple writes will get the whole content
+    /// in one write instead.
+    fn stream_copy(&self, from: &str, to: &str) -> Result<()> {
+        if !self.info().full_capability().write_can_multi {
+            let bs = self.read(from)?;
+            return self.write(to, bs);
+        }
+
+        let mut r = self.reader(from)?;
+        let mut w = self.writer(to)?;
+
+        while let Some(bs) = oio::BlockingRead::next(&mut r) {
+            w.write(bs?)?;
+        }
+        w.close()
+    }
+
     /// Rename a file from `from` to `to`.
     ///
     /// # Notes
@@ -582,7 +739,7 @@ impl BlockingOperator {
             self.inner().clone(),
             path,
             (OpWrite::default(), bs),
-            |inner, path, (args, mut bs)| {
+            |inner, path, (args, bs)| {
                 if !validate_path(&path, EntryMode::FILE) {
                     return Err(
                         Error::new(ErrorKind::IsADirectory, "write path is a directory")
@@ -592,11 +749,8 @@ impl BlockingOperator {
                     );
                 }
 
-                let (_, mut w) = inner.blocking_write(&path, args)?;
-                while bs.remaining() > 0 {
-                    let n = w.write(&bs)?;
-                    bs.advance(n);
-                }
+                let mut w = BlockingWriter::create(inner, &path, args)?;
+                w.write(bs)?;
                 w.close()?;
 
                 Ok(())
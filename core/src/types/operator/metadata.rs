@@ -43,10 +43,17 @@ impl OperatorInfo {
     ///
     /// - name for `s3` => bucket name
     /// - name for `azblob` => container name
+    /// - name for `azfile` => share name
     pub fn name(&self) -> &str {
         self.0.name()
     }
 
+    /// Endpoint of backend, could be empty if underlying backend doesn't
+    /// expose it.
+    pub fn endpoint(&self) -> &str {
+        self.0.endpoint()
+    }
+
     /// Get [`Full Capability`] of operator.
     pub fn full_capability(&self) -> Capability {
         self.0.full_capability()
@@ -56,4 +63,40 @@ impl OperatorInfo {
     pub fn native_capability(&self) -> Capability {
         self.0.native_capability()
     }
+
+    /// Normalize the path with operator's case semantics.
+    ///
+    /// The path will be normalized like what operator does for every
+    /// operation, and then lowercased if the operator's paths are case
+    /// insensitive (see [`Capability::path_case_insensitive`]).
+    ///
+    /// The result is used to compare paths, for example checking whether
+    /// `Foo.txt` returned by list is the same file as `foo.txt`. Paths sent
+    /// to services should still be the original ones to keep their case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use opendal::services;
+    /// use opendal::Operator;
+    ///
+    /// let op = Operator::new(services::Memory::default())
+    ///     .expect("must init")
+    ///     .finish();
+    ///
+    /// // Memory is case sensitive.
+    /// assert_ne!(
+    ///     op.info().normalize_path("Foo.txt"),
+    ///     op.info().normalize_path("foo.txt")
+    /// );
+    /// ```
+    pub fn normalize_path(&self, path: &str) -> String {
+        let path = normalize_path(path);
+
+        if self.native_capability().path_case_insensitive {
+            path.to_lowercase()
+        } else {
+            path
+        }
+    }
 }
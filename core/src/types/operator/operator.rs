@@ -15,21 +15,37 @@
 // specific language governing permissions and limitations
 // under the License.
 
+use std::fs;
+use std::io;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
 use std::time::Duration;
+use std::time::Instant;
 
-use bytes::Buf;
 use bytes::Bytes;
 use futures::stream;
 use futures::AsyncReadExt;
 use futures::Stream;
 use futures::StreamExt;
 use futures::TryStreamExt;
-use tokio::io::ReadBuf;
+use log::debug;
+use log::warn;
 
 use super::BlockingOperator;
 use crate::operator_futures::*;
 use crate::raw::oio::WriteExt;
 use crate::raw::*;
+use crate::types::decompress_reader;
+use crate::types::new_cancelled_error;
+use crate::types::new_max_bytes_error;
+use crate::types::read_decompress;
+use crate::types::read_verify_checksum;
+use crate::types::stored_checksum;
+use crate::types::ChecksumReader;
+use crate::types::LimitedReader;
 use crate::*;
 
 /// Operator is the entry for all public async APIs.
@@ -87,7 +103,7 @@ impl Operator {
         Self { accessor, limit }
     }
 
-    pub(super) fn into_inner(self) -> FusedAccessor {
+    pub(crate) fn into_inner(self) -> FusedAccessor {
         self.accessor
     }
 
@@ -137,7 +153,11 @@ impl Operator {
 impl Operator {
     /// Check if this operator can work correctly.
     ///
-    /// We will send a `list` request to path and return any errors we met.
+    /// We will send a `list` request to root with limit `1` and return any
+    /// errors we met. This is cheap and doesn't depend on any specific file,
+    /// so it can be used to surface invalid credentials or config at startup.
+    ///
+    /// `NotFound` will be ignored since root could be created lazily.
     ///
     /// ```
     /// # use std::sync::Arc;
@@ -151,7 +171,7 @@ impl Operator {
     /// # }
     /// ```
     pub async fn check(&self) -> Result<()> {
-        let mut ds = self.lister("/").await?;
+        let mut ds = self.lister_with("/").limit(1).await?;
 
         match ds.next().await {
             Some(Err(e)) if e.kind() != ErrorKind::NotFound => Err(e),
@@ -159,6 +179,37 @@ impl Operator {
         }
     }
 
+    /// Ping the service and return the latency of the round trip.
+    ///
+    /// It's designed to be called every few seconds as a liveness or
+    /// readiness probe. Like [`Operator::check`], only one `list` request to
+    /// root with limit `1` and no extra metadata will be sent, for example:
+    ///
+    /// - `azfile`: `List Directories and Files` with `maxresults=1`.
+    /// - `s3`: `ListObjectsV2` with `max-keys=1`.
+    /// - `fs`: read the first entry of root dir, no network involved.
+    /// - `memory`: no request at all.
+    ///
+    /// The latency includes the time of building and signing the request.
+    /// `NotFound` will be ignored since root could be created lazily.
+    ///
+    /// ```
+    /// # use anyhow::Result;
+    /// use opendal::Operator;
+    ///
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let latency = op.ping().await?;
+    /// println!("service is ready in {latency:?}");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn ping(&self) -> Result<Duration> {
+        let start = Instant::now();
+        self.check().await?;
+        Ok(start.elapsed())
+    }
+
     /// Get current path's metadata.
     ///
     /// # Notes
@@ -240,8 +291,67 @@ impl Operator {
         fut
     }
 
+    /// Get metadata of all given paths.
+    ///
+    /// Returns a stream of `(path, result)`, results are yielded in the same
+    /// order as input paths.
+    ///
+    /// # Notes
+    ///
+    /// - At most [`Operator::limit`] stats will be sent concurrently.
+    /// - Failing to stat one path won't abort others, errors like `NotFound`
+    ///   will be returned along with the path.
+    /// - Every path will be sent as a single `stat` request since there is
+    ///   no batch stat support for now.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// use futures::StreamExt;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let mut s = op.stat_many(vec!["abc".to_string(), "def".to_string()]);
+    /// while let Some((path, meta)) = s.next().await {
+    ///     match meta {
+    ///         Ok(meta) => println!("{path}: {}", meta.content_length()),
+    ///         Err(err) => println!("failed to stat {path}: {err}"),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stat_many(
+        &self,
+        paths: impl IntoIterator<Item = String>,
+    ) -> impl Stream<Item = (String, Result<Metadata>)> {
+        let op = self.clone();
+
+        stream::iter(paths)
+            .map(move |path| {
+                let op = op.clone();
+                async move {
+                    let res = op.stat(&path).await;
+                    (path, res)
+                }
+            })
+            .buffered(self.limit.max(1))
+    }
+
     /// Check if this path exists or not.
     ///
+    /// # Notes
+    ///
+    /// This function is built on `stat` which is usually a cheap `HEAD`
+    /// request like azfile's `Get File Properties`.
+    ///
+    /// - Returns `Ok(true)` if the path exists.
+    /// - Returns `Ok(false)` if the path is not found.
+    /// - Returns `Err` for other failures like `PermissionDenied` or
+    ///   `ConfigInvalid`, so that a broken config won't be taken as absence.
+    ///
     /// # Example
     ///
     /// ```
@@ -251,12 +361,12 @@ impl Operator {
     ///
     /// #[tokio::main]
     /// async fn test(op: Operator) -> Result<()> {
-    ///     let _ = op.is_exist("test").await?;
+    ///     let _ = op.exists("test").await?;
     ///
     ///     Ok(())
     /// }
     /// ```
-    pub async fn is_exist(&self, path: &str) -> Result<bool> {
+    pub async fn exists(&self, path: &str) -> Result<bool> {
         let r = self.stat(path).await;
         match r {
             Ok(_) => Ok(true),
@@ -267,6 +377,28 @@ impl Operator {
         }
     }
 
+    /// Check if this path exists or not.
+    ///
+    /// This is the same as [`Operator::exists`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anyhow::Result;
+    /// use futures::io;
+    /// use opendal::Operator;
+    ///
+    /// #[tokio::main]
+    /// async fn test(op: Operator) -> Result<()> {
+    ///     let _ = op.is_exist("test").await?;
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn is_exist(&self, path: &str) -> Result<bool> {
+        self.exists(path).await
+    }
+
     /// Create a dir at given path.
     ///
     /// # Notes
@@ -293,21 +425,56 @@ impl Operator {
     /// # }
     /// ```
     pub async fn create_dir(&self, path: &str) -> Result<()> {
+        self.create_dir_with(path).await
+    }
+
+    /// Create a dir at given path with extra options.
+    ///
+    /// # Notes
+    ///
+    /// Same as [`Operator::create_dir`], the path must end with `/`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// op.create_dir_with("path/to/dir/")
+    ///     .user_metadata([("owner", "alice")])
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn create_dir_with(&self, path: &str) -> FutureCreateDir {
         let path = normalize_path(path);
 
-        if !validate_path(&path, EntryMode::DIR) {
-            return Err(Error::new(
-                ErrorKind::NotADirectory,
-                "the path trying to create should end with `/`",
-            )
-            .with_operation("create_dir")
-            .with_context("service", self.inner().info().scheme())
-            .with_context("path", &path));
-        }
+        let fut = FutureCreateDir(OperatorFuture::new(
+            self.inner().clone(),
+            path,
+            OpCreateDir::default(),
+            |inner, path, args| {
+                let fut = async move {
+                    if !validate_path(&path, EntryMode::DIR) {
+                        return Err(Error::new(
+                            ErrorKind::NotADirectory,
+                            "the path trying to create should end with `/`",
+                        )
+                        .with_operation("create_dir")
+                        .with_context("service", inner.info().scheme())
+                        .with_context("path", &path));
+                    }
 
-        self.inner().create_dir(&path, OpCreateDir::new()).await?;
+                    inner.create_dir(&path, args).await?;
+                    Ok(())
+                };
 
-        Ok(())
+                Box::pin(fut)
+            },
+        ));
+
+        fut
     }
 
     /// Read the whole path into a bytes.
@@ -331,6 +498,66 @@ impl Operator {
         self.read_with(path).await
     }
 
+    /// Read the whole path and append the content into given buffer.
+    ///
+    /// Returns the size of content that has been appended. The buffer can be
+    /// reused across calls to avoid allocating a new one for every read. The
+    /// buffer will be kept as is if error happens.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io::Result;
+    /// # use opendal::Operator;
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let mut buf = Vec::with_capacity(4096);
+    /// for path in ["path/to/a", "path/to/b"] {
+    ///     buf.clear();
+    ///     let n = op.read_to(path, &mut buf).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn read_to(&self, path: &str, buf: &mut Vec<u8>) -> Result<usize> {
+        let path = normalize_path(path);
+
+        if !validate_path(&path, EntryMode::FILE) {
+            return Err(
+                Error::new(ErrorKind::IsADirectory, "read path is a directory")
+                    .with_operation("Operator::read_to")
+                    .with_context("service", self.info().scheme().into_static())
+                    .with_context("path", &path),
+            );
+        }
+
+        let (rp, mut r) = self.inner().read(&path, OpRead::new()).await?;
+
+        let start = buf.len();
+        let res = match rp.into_metadata().content_length_raw() {
+            Some(length) => {
+                let length = length as usize;
+                buf.resize(start + length, 0);
+                r.read_exact(&mut buf[start..]).await.map(|_| length)
+            }
+            // Layers that transform content like `CompressionLayer`
+            // can't know the length in advance, read until EOF.
+            None => r.read_to_end(buf).await,
+        };
+
+        match res {
+            Ok(n) => Ok(n),
+            Err(err) => {
+                buf.truncate(start);
+                Err(Error::new(ErrorKind::Unexpected, "read from storage")
+                    .with_operation("Operator::read_to")
+                    .with_context("service", self.info().scheme().into_static())
+                    .with_context("path", &path)
+                    .set_source(err))
+            }
+        }
+    }
+
     /// Read the whole path into a bytes with extra options.
     ///
     /// This function will allocate a new bytes internally. For more precise memory control or
@@ -357,6 +584,10 @@ impl Operator {
             path,
             OpRead::default(),
             |inner, path, args| {
+                let cancel_token = args.cancel_token().cloned();
+                let cancel_path = path.clone();
+                let inner_scheme = inner.info().scheme();
+
                 let fut = async move {
                     if !validate_path(&path, EntryMode::FILE) {
                         return Err(Error::new(
@@ -369,34 +600,83 @@ impl Operator {
                     }
 
                     let br = args.range();
-                    let (rp, mut s) = inner.read(&path, args).await?;
-
-                    let length = rp.into_metadata().content_length() as usize;
-                    let mut buffer = Vec::with_capacity(length);
-
-                    let dst = buffer.spare_capacity_mut();
-                    let mut buf = ReadBuf::uninit(dst);
+                    let decompress = read_decompress(&args)?;
+                    let verify_checksum = read_verify_checksum(&args)?;
+                    let max_bytes = args.max_bytes();
+
+                    // Empty range can't be represented by `Range` header, we
+                    // only need to make sure the file exists.
+                    if br.size() == Some(0) {
+                        inner.stat(&path, OpStat::default()).await?;
+                        return Ok(Vec::new());
+                    }
 
-                    // Safety: the input buffer is created with_capacity(length).
-                    unsafe { buf.assume_init(length) };
+                    let (rp, s) = inner.read(&path, args).await?;
+
+                    let meta = rp.into_metadata();
+                    // Content returned by services will be delivered as is,
+                    // so we can fail before reading it.
+                    if let Some(max_bytes) = max_bytes {
+                        if !decompress
+                            && matches!(meta.content_length_raw(), Some(v) if v > max_bytes)
+                        {
+                            return Err(new_max_bytes_error(max_bytes)
+                                .with_operation("read")
+                                .with_context("service", inner.info().scheme().into_static())
+                                .with_context("path", &path));
+                        }
+                    }
 
-                    // TODO: use native read api
-                    s.read_exact(buf.initialized_mut()).await.map_err(|err| {
-                        Error::new(ErrorKind::Unexpected, "read from storage")
-                            .with_operation("read")
-                            .with_context("service", inner.info().scheme().into_static())
-                            .with_context("path", &path)
-                            .with_context("range", br.to_string())
-                            .set_source(err)
-                    })?;
+                    // Checksum is computed on the raw content returned by services.
+                    let mut r: oio::Reader = if verify_checksum {
+                        let checksum = stored_checksum(&meta)?;
+                        Box::new(ChecksumReader::new(s, checksum))
+                    } else {
+                        s
+                    };
+                    if decompress {
+                        r = decompress_reader(r, &meta)?;
+                    }
+                    // Limit is applied while reading, so that decompressed content
+                    // or content without known length will never be buffered
+                    // beyond `max_bytes`.
+                    if let Some(max_bytes) = max_bytes {
+                        r = Box::new(LimitedReader::new(r, max_bytes));
+                    }
 
-                    // Safety: read_exact makes sure this buffer has been filled.
-                    unsafe { buffer.set_len(length) }
+                    // Layers that transform content like `CompressionLayer`
+                    // can't know the length in advance, read until EOF.
+                    let capacity = match meta.content_length_raw() {
+                        Some(length) if !decompress => length as usize,
+                        _ => 0,
+                    };
+                    let mut buffer = Vec::with_capacity(capacity);
+                    // Errors like `ChecksumMismatch` are returned as is.
+                    while let Some(bs) = oio::ReadExt::next(&mut r).await {
+                        let bs = bs.map_err(|err| {
+                            err.with_operation("read")
+                                .with_context("service", inner.info().scheme().into_static())
+                                .with_context("path", &path)
+                                .with_context("range", br.to_string())
+                        })?;
+                        buffer.extend_from_slice(&bs);
+                    }
 
                     Ok(buffer)
                 };
 
-                Box::pin(fut)
+                Box::pin(async move {
+                    match cancel_token {
+                        None => fut.await,
+                        // The whole read will be dropped once cancelled.
+                        Some(token) => token.run(fut).await.unwrap_or_else(|| {
+                            Err(new_cancelled_error()
+                                .with_operation("read")
+                                .with_context("service", inner_scheme)
+                                .with_context("path", cancel_path))
+                        }),
+                    }
+                })
             },
         ));
 
@@ -465,11 +745,207 @@ impl Operator {
         fut
     }
 
+    /// Download the file at path into local path, and resume from where the
+    /// last attempt stopped if possible.
+    ///
+    /// Returns the size of the local file after download.
+    ///
+    /// # Notes
+    ///
+    /// - The etag of remote file will be recorded in `{local_path}.etag`
+    ///   during download. Download will be resumed from the size of local
+    ///   file only if the recorded etag is the same as current one,
+    ///   otherwise it will be restarted from the beginning.
+    /// - Files without etag can't be verified, so they will always be
+    ///   downloaded from the beginning.
+    /// - Content transformed by layers like `CompressionLayer` can't be read
+    ///   by range, so it will always be downloaded from the beginning too.
+    /// - The local file will be synced to disk before return, and the etag
+    ///   record will be removed after download succeeded.
+    /// - Local file operations run in the blocking threads of tokio, so
+    ///   this function must be called within a tokio runtime.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let size = op.download_to("path/to/file", "/tmp/file").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn download_to(&self, path: &str, local_path: impl AsRef<Path>) -> Result<u64> {
+        let path = normalize_path(path);
+        let local_path = local_path.as_ref().to_path_buf();
+
+        let new_local_error = |(msg, err): (&'static str, io::Error)| {
+            Error::new(ErrorKind::Unexpected, msg)
+                .with_operation("Operator::download_to")
+                .with_context("path", &path)
+                .with_context("local_path", local_path.to_string_lossy())
+                .set_source(err)
+        };
+
+        let meta = self.stat(&path).await?;
+        if !meta.mode().is_file() {
+            return Err(
+                Error::new(ErrorKind::IsADirectory, "download path is not a file")
+                    .with_operation("Operator::download_to")
+                    .with_context("path", &path),
+            );
+        }
+        let total = meta.content_length();
+        let etag = meta.etag().map(|v| v.to_string());
+
+        let mut etag_path = local_path.as_os_str().to_owned();
+        etag_path.push(".etag");
+        let etag_path = PathBuf::from(etag_path);
+
+        // Only resume while the local file is known to be a prefix of
+        // current remote file.
+        let resume = {
+            let local_path = local_path.clone();
+            let etag_path = etag_path.clone();
+            let etag = etag.clone();
+            unblock(move || {
+                let recorded = fs::read_to_string(&etag_path).ok();
+                let local_size = fs::metadata(&local_path).map(|m| m.len()).unwrap_or(0);
+                Ok(match &etag {
+                    Some(etag) if recorded.as_deref() == Some(etag) && local_size <= total => {
+                        local_size
+                    }
+                    _ => 0,
+                })
+            })
+            .await
+            .map_err(new_local_error)?
+        };
+
+        let read = |offset: u64| {
+            let mut args = OpRead::new();
+            if offset > 0 {
+                args = args.with_range(BytesRange::new(Some(offset), None));
+            }
+            if let Some(etag) = &etag {
+                // Make sure the content has not been changed since stat.
+                if self.info().full_capability().read_with_if_match {
+                    args = args.with_if_match(etag);
+                }
+            }
+            self.inner().read(&path, args)
+        };
+
+        // Layers that transform content like `CompressionLayer` can't know
+        // the length of content in advance, skip the check for them.
+        let mut expect = Some(total);
+        let mut offset = resume;
+        let mut reader = None;
+        if offset < total {
+            let (rp, r) = match read(offset).await {
+                // Transformed content can't be read by range, and the size of
+                // local file can't be compared with the stored size either.
+                // Restart from the beginning for them.
+                Err(err) if offset > 0 && err.kind() == ErrorKind::InvalidInput => {
+                    offset = 0;
+                    read(0).await?
+                }
+                Ok((rp, _)) if offset > 0 && rp.metadata().content_length_raw().is_none() => {
+                    offset = 0;
+                    read(0).await?
+                }
+                res => res?,
+            };
+            if rp.metadata().content_length_raw().is_none() {
+                expect = None;
+            }
+            reader = Some(r);
+        }
+
+        let mut file = {
+            let local_path = local_path.clone();
+            let etag_path = etag_path.clone();
+            let etag = etag.clone();
+            unblock(move || {
+                let mut file = fs::OpenOptions::new()
+                    .create(true)
+                    .write(true)
+                    .open(&local_path)
+                    .map_err(|err| ("open local file", err))?;
+                file.set_len(offset)
+                    .map_err(|err| ("truncate local file", err))?;
+                file.seek(SeekFrom::Start(offset))
+                    .map_err(|err| ("seek local file", err))?;
+
+                match &etag {
+                    Some(etag) => fs::write(&etag_path, etag)
+                        .map_err(|err| ("record etag of download", err))?,
+                    None => {
+                        let _ = fs::remove_file(&etag_path);
+                    }
+                }
+                Ok(file)
+            })
+            .await
+            .map_err(new_local_error)?
+        };
+
+        let mut size = offset;
+        if let Some(mut r) = reader {
+            let mut buf = vec![0; 256 * 1024];
+            loop {
+                let n = r.read(&mut buf).await.map_err(|err| {
+                    Error::new(ErrorKind::Unexpected, "read from storage")
+                        .with_operation("Operator::download_to")
+                        .with_context("path", &path)
+                        .with_context("offset", size.to_string())
+                        .set_source(err)
+                })?;
+                if n == 0 {
+                    break;
+                }
+                (file, buf) = unblock(move || {
+                    file.write_all(&buf[..n])
+                        .map_err(|err| ("write local file", err))?;
+                    Ok((file, buf))
+                })
+                .await
+                .map_err(new_local_error)?;
+                size += n as u64;
+            }
+        }
+
+        if matches!(expect, Some(total) if size != total) {
+            return Err(Error::new(
+                ErrorKind::ContentIncomplete,
+                "downloaded size is not the same as file size",
+            )
+            .with_operation("Operator::download_to")
+            .with_context("path", &path)
+            .with_context("expect", total.to_string())
+            .with_context("actual", size.to_string())
+            .set_temporary());
+        }
+
+        unblock(move || {
+            file.sync_all().map_err(|err| ("sync local file", err))?;
+            let _ = fs::remove_file(&etag_path);
+            Ok(())
+        })
+        .await
+        .map_err(new_local_error)?;
+
+        Ok(size)
+    }
+
     /// Write bytes into path.
     ///
     /// # Notes
     ///
     /// - Write will make sure all bytes has been written, or an error will be returned.
+    /// - Metadata returned by service like `etag` will be returned, see
+    ///   [`Writer::close`] for details.
     ///
     /// # Examples
     ///
@@ -486,11 +962,91 @@ impl Operator {
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn write(&self, path: &str, bs: impl Into<Bytes>) -> Result<()> {
+    pub async fn write(&self, path: &str, bs: impl Into<Bytes>) -> Result<Metadata> {
         let bs = bs.into();
         self.write_with(path, bs).await
     }
 
+    /// Write all chunks from given stream into path, returns the number of
+    /// bytes written.
+    ///
+    /// # Notes
+    ///
+    /// - Content length doesn't need to be known in advance, chunks will be
+    ///   buffered and uploaded as they arrive by the chunked writer of
+    ///   services, like `Put Range` of azfile.
+    /// - The writer will be aborted if the stream or the write fails, so
+    ///   that no partial file will be left.
+    /// - Use [`Operator::writer_with`] and [`Writer::sink`] to write with
+    ///   more options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io::Result;
+    /// # use opendal::Operator;
+    /// use futures::stream;
+    /// use futures::StreamExt;
+    ///
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let stream = stream::iter(vec![vec![0; 4096], vec![1; 4096]]).map(Ok);
+    /// let written = op.write_from_stream("path/to/file", stream).await?;
+    /// assert_eq!(written, 8192);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn write_from_stream<S, T>(&self, path: &str, stream: S) -> Result<u64>
+    where
+        S: Stream<Item = Result<T>>,
+        T: Into<Bytes>,
+    {
+        let mut w = self.writer(path).await?;
+        let written = match w.sink(stream).await {
+            Ok(written) => written,
+            Err(err) => return Err(abort_writer(w, err).await),
+        };
+        w.close().await?;
+
+        Ok(written)
+    }
+
+    /// Copy all content from given reader into path, returns the number of
+    /// bytes written.
+    ///
+    /// This is the [`futures::AsyncRead`] version of
+    /// [`Operator::write_from_stream`], only one constant in-memory buffer
+    /// will be used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::io::Result;
+    /// # use opendal::Operator;
+    /// use futures::io::Cursor;
+    ///
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let reader = Cursor::new(vec![0; 4096]);
+    /// let written = op.write_from_reader("path/to/file", reader).await?;
+    /// assert_eq!(written, 4096);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn write_from_reader<R>(&self, path: &str, reader: R) -> Result<u64>
+    where
+        R: futures::AsyncRead,
+    {
+        let mut w = self.writer(path).await?;
+        let written = match w.copy(reader).await {
+            Ok(written) => written,
+            Err(err) => return Err(abort_writer(w, err).await),
+        };
+        w.close().await?;
+
+        Ok(written)
+    }
+
     /// Copy a file from `from` to `to`.
     ///
     /// # Notes
@@ -499,6 +1055,9 @@ impl Operator {
     /// - `to` will be overwritten if it exists.
     /// - If `from` and `to` are the same,  an `IsSameFile` error will occur.
     /// - `copy` is idempotent. For same `from` and `to` input, the result will be the same.
+    /// - Services without native copy support will fall back to read `from`
+    ///   and write into `to` chunk by chunk, which costs the bandwidth of the
+    ///   whole content.
     ///
     /// # Examples
     ///
@@ -545,11 +1104,53 @@ impl Operator {
             );
         }
 
+        if !self.info().full_capability().copy {
+            debug!("copy {from} to {to} via stream copy since native copy is not supported");
+            return self.stream_copy(&from, &to).await.map_err(|err| {
+                err.with_operation("Operator::copy")
+                    .with_context("from", &from)
+                    .with_context("to", &to)
+            });
+        }
+
+        debug!("copy {from} to {to} via native copy");
         self.inner().copy(&from, &to, OpCopy::new()).await?;
 
         Ok(())
     }
 
+    /// Copy by reading content from `from` and writing into `to`, only one
+    /// chunk will be held in memory at the same time.
+    ///
+    /// Services that can't accept multiple writes will get the whole content
+    /// in one write instead.
+    async fn stream_copy(&self, from: &str, to: &str) -> Result<()> {
+        if !self.info().full_capability().write_can_multi {
+            let bs = self.read(from).await?;
+            return self.write(to, bs).await;
+        }
+
+        let mut r = self.reader(from).await?;
+        let mut w = self.writer(to).await?;
+
+        let res = async {
+            while let Some(bs) = oio::ReadExt::next(&mut r).await {
+                w.write(bs?).await?;
+            }
+            Ok(())
+        }
+        .await;
+
+        match res {
+            Ok(()) => w.close().await.map(|_| ()),
+            Err(err) => {
+                // Failure of abort is ignored, the original error matters.
+                let _ = w.abort().await;
+                Err(err)
+            }
+        }
+    }
+
     /// Rename a file from `from` to `to`.
     ///
     /// # Notes
@@ -692,6 +1293,8 @@ impl Operator {
     /// # Notes
     ///
     /// - Write will make sure all bytes has been written, or an error will be returned.
+    /// - Metadata returned by service like `etag` will be returned, see
+    ///   [`Writer::close`] for details.
     ///
     /// # Examples
     ///
@@ -718,7 +1321,7 @@ impl Operator {
             self.inner().clone(),
             path,
             (OpWrite::default(), bs),
-            |inner, path, (args, mut bs)| {
+            |inner, path, (args, bs)| {
                 let fut = async move {
                     if !validate_path(&path, EntryMode::FILE) {
                         return Err(Error::new(
@@ -730,15 +1333,9 @@ impl Operator {
                         .with_context("path", &path));
                     }
 
-                    let (_, mut w) = inner.write(&path, args).await?;
-                    while bs.remaining() > 0 {
-                        let n = w.write(&bs).await?;
-                        bs.advance(n);
-                    }
-
-                    w.close().await?;
-
-                    Ok(())
+                    let mut w = Writer::create(inner, &path, args).await?;
+                    w.write(bs).await?;
+                    w.close().await
                 };
                 Box::pin(fut)
             },
@@ -888,6 +1485,88 @@ impl Operator {
         Ok(())
     }
 
+    /// Delete all given paths.
+    ///
+    /// # Notes
+    ///
+    /// If underlying services support delete in batch, paths will be grouped
+    /// into batches of [`Operator::limit`], otherwise they will be deleted one
+    /// by one.
+    ///
+    /// Failing to delete one path won't abort the whole deletion. Paths that
+    /// failed will be returned along with their errors. `Err` will only be
+    /// returned if the batch request itself failed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let failed = op
+    ///     .delete_iter(vec!["abc".to_string(), "def".to_string()])
+    ///     .await?;
+    /// for (path, err) in failed {
+    ///     println!("failed to delete {path}: {err}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn delete_iter(
+        &self,
+        iter: impl IntoIterator<Item = String>,
+    ) -> Result<Vec<(String, Error)>> {
+        let input = stream::iter(iter).map(|v| Ok(normalize_path(&v)));
+        self.delete_in_batch(input).await
+    }
+
+    /// Delete paths from the given stream and collect the failed ones.
+    ///
+    /// Errors returned by the input stream will abort the deletion.
+    async fn delete_in_batch(
+        &self,
+        input: impl Stream<Item = Result<String>>,
+    ) -> Result<Vec<(String, Error)>> {
+        let mut failed = Vec::new();
+
+        if self.info().full_capability().batch {
+            let input = input.try_chunks(self.limit());
+            futures::pin_mut!(input);
+
+            while let Some(batches) = input.next().await {
+                let batches = batches
+                    .map_err(|err| err.1)?
+                    .into_iter()
+                    .map(|v| (v, OpDelete::default().into()))
+                    .collect();
+
+                let results = self
+                    .inner()
+                    .batch(OpBatch::new(batches))
+                    .await?
+                    .into_results();
+
+                for (path, result) in results {
+                    if let Err(err) = result {
+                        failed.push((path, err));
+                    }
+                }
+            }
+        } else {
+            futures::pin_mut!(input);
+
+            while let Some(path) = input.try_next().await? {
+                if let Err(err) = self.inner().delete(&path, OpDelete::default()).await {
+                    failed.push((path, err));
+                }
+            }
+        }
+
+        Ok(failed)
+    }
+
     /// Remove the path and all nested dirs and files recursively.
     ///
     /// # Notes
@@ -925,35 +1604,156 @@ impl Operator {
         }
 
         let obs = self.lister_with(path).delimiter("").await?;
+        let failed = self
+            .delete_in_batch(obs.map_ok(|v| v.path().to_string()))
+            .await?;
+        if let Some(err) = new_paths_failed_error("delete", failed) {
+            return Err(err.with_operation("Operator::remove_all"));
+        }
 
-        if self.info().full_capability().batch {
-            let mut obs = obs.try_chunks(self.limit());
+        // Remove the directory itself.
+        self.delete(path).await?;
 
-            while let Some(batches) = obs.next().await {
-                let batches = batches
-                    .map_err(|err| err.1)?
-                    .into_iter()
-                    .map(|v| (v.path().to_string(), OpDelete::default().into()))
-                    .collect();
+        Ok(())
+    }
 
-                let results = self
-                    .inner()
-                    .batch(OpBatch::new(batches))
-                    .await?
-                    .into_results();
+    /// Copy the dir `from` and all nested dirs and files into `to`.
+    ///
+    /// Same as [`Operator::copy_dir_with_progress`] without progress
+    /// reporting.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// op.copy_dir("path/to/dir/", "path/to/dir2/").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn copy_dir(&self, from: &str, to: &str) -> Result<()> {
+        self.copy_dir_with_progress(from, to, |_, _| {}).await
+    }
 
-                // TODO: return error here directly seems not a good idea?
-                for (_, result) in results {
-                    let _ = result?;
+    /// Copy the dir `from` and all nested dirs and files into `to`, and call
+    /// `on_progress` with the source path and the count of copied entries
+    /// after every entry has been copied.
+    ///
+    /// # Notes
+    ///
+    /// - `from` and `to` must be dirs, and `to` must not be inside `from`.
+    /// - Files are copied by [`Operator::copy`], so native copy will be used
+    ///   if services support it, otherwise they will be copied via stream.
+    /// - Dirs will be created at `to` if services support `create_dir`.
+    /// - Failed entries will not stop the copy. After all entries have been
+    ///   visited, an error with all failed paths will be returned, the kind
+    ///   of the first failure will be used.
+    /// - Errors returned by listing `from` will stop the copy immediately.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anyhow::Result;
+    /// # use opendal::Operator;
+    /// #
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// op.copy_dir_with_progress("path/to/dir/", "path/to/dir2/", |path, copied| {
+    ///     println!("copied {path}, {copied} entries in total");
+    /// })
+    /// .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn copy_dir_with_progress(
+        &self,
+        from: &str,
+        to: &str,
+        mut on_progress: impl FnMut(&str, usize),
+    ) -> Result<()> {
+        let from = normalize_path(from);
+        let to = normalize_path(to);
+
+        let new_input_error = |kind, message| {
+            Error::new(kind, message)
+                .with_operation("Operator::copy_dir")
+                .with_context("service", self.info().scheme())
+                .with_context("from", &from)
+                .with_context("to", &to)
+        };
+
+        if !validate_path(&from, EntryMode::DIR) {
+            return Err(new_input_error(
+                ErrorKind::NotADirectory,
+                "from path is not a directory",
+            ));
+        }
+        if !validate_path(&to, EntryMode::DIR) {
+            return Err(new_input_error(
+                ErrorKind::NotADirectory,
+                "to path is not a directory",
+            ));
+        }
+        if from == to {
+            return Err(new_input_error(
+                ErrorKind::IsSameFile,
+                "from and to paths are same",
+            ));
+        }
+        // Entries copied into `to` would be listed again, the copy will
+        // never end.
+        if to.starts_with(&from) || from == "/" {
+            return Err(new_input_error(
+                ErrorKind::InvalidInput,
+                "to path is inside from path",
+            ));
+        }
+
+        self.stat(&from).await?;
+
+        let create_dir = self.info().full_capability().create_dir;
+        if create_dir {
+            self.create_dir(&to).await?;
+        }
+
+        let mut failed = Vec::new();
+        let mut copied = 0;
+
+        let mut lister = self.lister_with(&from).delimiter("").await?;
+        while let Some(entry) = lister.try_next().await? {
+            let source = entry.path();
+            let Some(rel) = source.strip_prefix(&from).filter(|v| !v.is_empty()) else {
+                continue;
+            };
+            let target = format!("{to}{rel}");
+
+            let res = if entry.metadata().mode().is_dir() {
+                if !create_dir {
+                    continue;
+                }
+                self.create_dir(&target).await
+            } else {
+                self.copy(source, &target).await
+            };
+
+            match res {
+                Ok(()) => {
+                    copied += 1;
+                    on_progress(source, copied);
                 }
+                Err(err) => failed.push((source.to_string(), err)),
             }
-        } else {
-            obs.try_for_each(|v| async move { self.delete(v.path()).await })
-                .await?;
         }
 
-        // Remove the directory itself.
-        self.delete(path).await?;
+        if let Some(err) = new_paths_failed_error("copy", failed) {
+            return Err(err
+                .with_operation("Operator::copy_dir")
+                .with_context("from", &from)
+                .with_context("to", &to));
+        }
 
         Ok(())
     }
@@ -1475,3 +2275,65 @@ impl Operator {
         fut
     }
 }
+
+/// The max number of failed paths carried by the error of batch operations.
+const MAX_FAILED_PATHS_IN_ERROR: usize = 10;
+
+/// Build an error for paths that failed to `action`, like `delete`.
+///
+/// The kind of the first error will be used so that users can still
+/// handle errors like `PermissionDenied` as before. Only the first
+/// [`MAX_FAILED_PATHS_IN_ERROR`] paths will be carried to keep the error
+/// readable, the total count is carried by `failed`.
+fn new_paths_failed_error(action: &str, failed: Vec<(String, Error)>) -> Option<Error> {
+    let count = failed.len();
+    let mut failed = failed.into_iter();
+    let (path, first) = failed.next()?;
+
+    let mut paths = vec![path];
+    paths.extend(
+        failed
+            .take(MAX_FAILED_PATHS_IN_ERROR - 1)
+            .map(|(path, _)| path),
+    );
+    let mut paths = paths.join(", ");
+    if count > MAX_FAILED_PATHS_IN_ERROR {
+        paths.push_str(&format!(
+            ", ... and {} more",
+            count - MAX_FAILED_PATHS_IN_ERROR
+        ));
+    }
+
+    Some(
+        Error::new(first.kind(), &format!("some paths failed to {action}"))
+            .with_context("failed", count.to_string())
+            .with_context("paths", paths)
+            .set_source(first),
+    )
+}
+
+/// Run blocking local file operations in a blocking thread, so that the
+/// async runtime will not be blocked.
+///
+/// Errors are returned with the message of the failed operation.
+async fn unblock<T, F>(f: F) -> std::result::Result<T, (&'static str, io::Error)>
+where
+    T: Send + 'static,
+    F: FnOnce() -> std::result::Result<T, (&'static str, io::Error)> + Send + 'static,
+{
+    tokio::task::spawn_blocking(f).await.unwrap_or_else(|err| {
+        Err((
+            "run blocking task",
+            io::Error::new(io::ErrorKind::Other, err),
+        ))
+    })
+}
+
+/// Abort the writer after a failed write, the original error will be
+/// returned while the abort error will only be logged.
+async fn abort_writer(mut w: Writer, err: Error) -> Error {
+    if let Err(abort_err) = w.abort().await {
+        warn!("abort writer after failed write: {abort_err}");
+    }
+    err
+}
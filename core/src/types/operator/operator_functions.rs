@@ -35,6 +35,9 @@ pub(crate) struct OperatorFunction<T, R> {
     path: String,
     args: T,
     f: fn(FusedAccessor, String, T) -> Result<R>,
+    /// The error of invalid input args, will be returned directly while
+    /// calling.
+    error: Option<Error>,
 }
 
 impl<T, R> OperatorFunction<T, R> {
@@ -49,6 +52,7 @@ impl<T, R> OperatorFunction<T, R> {
             path,
             args,
             f,
+            error: None,
         }
     }
 
@@ -58,10 +62,21 @@ impl<T, R> OperatorFunction<T, R> {
             path: self.path,
             args: f(self.args),
             f: self.f,
+            error: self.error,
         }
     }
 
+    /// Fail this function with given error, the inner function will not be
+    /// called anymore.
+    fn fail(mut self, err: Error) -> Self {
+        self.error.get_or_insert(err);
+        self
+    }
+
     fn call(self) -> Result<R> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
         (self.f)(self.inner, self.path, self.args)
     }
 }
@@ -125,6 +140,68 @@ impl FunctionWrite {
         self
     }
 
+    /// Set the checksum of the whole content.
+    ///
+    /// Service will reject the write if the content doesn't match, services
+    /// that don't support given checksum will return `Unsupported`.
+    pub fn checksum(mut self, v: Checksum) -> Self {
+        self.0 = self.0.map_args(|(args, bs)| (args.with_checksum(v), bs));
+        self
+    }
+
+    /// Set the server side encryption of the content.
+    ///
+    /// Services that don't support given encryption will return
+    /// `Unsupported`.
+    pub fn encryption(mut self, v: ServerSideEncryption) -> Self {
+        self.0 = self.0.map_args(|(args, bs)| (args.with_encryption(v), bs));
+        self
+    }
+
+    /// Set the user defined metadata of the content.
+    ///
+    /// Services that don't support user defined metadata will return
+    /// `Unsupported`.
+    pub fn user_metadata<K: Into<String>, V: Into<String>>(
+        mut self,
+        v: impl IntoIterator<Item = (K, V)>,
+    ) -> Self {
+        let v = v.into_iter().map(|(k, v)| (k.into(), v.into())).collect();
+        self.0 = self
+            .0
+            .map_args(|(args, bs)| (args.with_user_metadata(v), bs));
+        self
+    }
+
+    /// Only create the file if it doesn't exist.
+    ///
+    /// The write will fail with `AlreadyExists` if the file already
+    /// exists. Services that don't support conditional create will return
+    /// `Unsupported`.
+    pub fn if_not_exists(mut self, v: bool) -> Self {
+        self.0 = self
+            .0
+            .map_args(|(args, bs)| (args.with_if_not_exists(v), bs));
+        self
+    }
+
+    /// Write into a hidden temp file and rename it to the target path while closing.
+    ///
+    /// Readers will either see the old content or the complete new content,
+    /// but never a half-written file. The temp file will be removed if the
+    /// write fails.
+    ///
+    /// # Notes
+    ///
+    /// - Atomicity depends on the backend's rename being atomic. Services
+    ///   without native rename fall back to copy then delete, which is not
+    ///   atomic.
+    /// - `append` and `if_not_exists` can't be used together with `atomic`.
+    pub fn atomic(mut self, v: bool) -> Self {
+        self.0 = self.0.map_args(|(args, bs)| (args.with_atomic(v), bs));
+        self
+    }
+
     /// Call the function to consume all the input and generate a
     /// result.
     pub fn call(self) -> Result<()> {
@@ -185,6 +262,64 @@ impl FunctionWriter {
         self
     }
 
+    /// Set the checksum of the whole content.
+    ///
+    /// Service will reject the write if the content doesn't match, services
+    /// that don't support given checksum will return `Unsupported`.
+    pub fn checksum(mut self, v: Checksum) -> Self {
+        self.0 = self.0.map_args(|args| args.with_checksum(v));
+        self
+    }
+
+    /// Set the server side encryption of the content.
+    ///
+    /// Services that don't support given encryption will return
+    /// `Unsupported`.
+    pub fn encryption(mut self, v: ServerSideEncryption) -> Self {
+        self.0 = self.0.map_args(|args| args.with_encryption(v));
+        self
+    }
+
+    /// Set the user defined metadata of the content.
+    ///
+    /// Services that don't support user defined metadata will return
+    /// `Unsupported`.
+    pub fn user_metadata<K: Into<String>, V: Into<String>>(
+        mut self,
+        v: impl IntoIterator<Item = (K, V)>,
+    ) -> Self {
+        let v = v.into_iter().map(|(k, v)| (k.into(), v.into())).collect();
+        self.0 = self.0.map_args(|args| args.with_user_metadata(v));
+        self
+    }
+
+    /// Only create the file if it doesn't exist.
+    ///
+    /// The write will fail with `AlreadyExists` if the file already
+    /// exists. Services that don't support conditional create will return
+    /// `Unsupported`.
+    pub fn if_not_exists(mut self, v: bool) -> Self {
+        self.0 = self.0.map_args(|args| args.with_if_not_exists(v));
+        self
+    }
+
+    /// Write into a hidden temp file and rename it to the target path while closing.
+    ///
+    /// Readers will either see the old content or the complete new content,
+    /// but never a half-written file. The temp file will be removed if the
+    /// write fails.
+    ///
+    /// # Notes
+    ///
+    /// - Atomicity depends on the backend's rename being atomic. Services
+    ///   without native rename fall back to copy then delete, which is not
+    ///   atomic.
+    /// - `append` and `if_not_exists` can't be used together with `atomic`.
+    pub fn atomic(mut self, v: bool) -> Self {
+        self.0 = self.0.map_args(|args| args.with_atomic(v));
+        self
+    }
+
     /// Call the function to consume all the input and generate a
     /// result.
     pub fn call(self) -> Result<BlockingWriter> {
@@ -204,6 +339,18 @@ impl FunctionDelete {
         self
     }
 
+    /// Delete the path only if its etag matches `v`.
+    ///
+    /// Returns `ConditionNotMatch` and leaves the path untouched if the
+    /// etag doesn't match. Services that don't support it will return
+    /// `Unsupported`, check `delete_with_if_match` of [`Capability`].
+    ///
+    /// [`Capability`]: crate::Capability
+    pub fn if_match(mut self, v: &str) -> Self {
+        self.0 = self.0.map_args(|args| args.with_if_match(v));
+        self
+    }
+
     /// Call the function to consume all the input and generate a
     /// result.
     pub fn call(self) -> Result<()> {
@@ -223,12 +370,45 @@ impl FunctionList {
         self
     }
 
+    /// Change the page size of this list operation.
+    ///
+    /// Page size is the max number of entries returned by one request to
+    /// the underlying service, it takes precedence over limit which is only
+    /// a hint of page size too. Services will use their own default (usually
+    /// the max they allow) if not set.
+    ///
+    /// Like limit, page size is a hint and will not be rejected: services
+    /// without `list_with_page_size` capability will ignore it.
+    pub fn page_size(mut self, v: usize) -> Self {
+        self.0 = self.0.map_args(|args| args.with_page_size(v));
+        self
+    }
+
     /// Change the start_after of this list operation.
     pub fn start_after(mut self, v: &str) -> Self {
         self.0 = self.0.map_args(|args| args.with_start_after(v));
         self
     }
 
+    /// Change the start_after_token of this list operation.
+    ///
+    /// The token should be got from `continuation_token` of a previous
+    /// lister on the same path, listing will be resumed from there.
+    pub fn start_after_token(mut self, v: &str) -> Self {
+        self.0 = self.0.map_args(|args| args.with_start_after_token(v));
+        self
+    }
+
+    /// Return an entry for the listed path itself (as a dir) before its
+    /// children.
+    ///
+    /// The entry will only be returned once even while listing recursively.
+    /// Resuming from `start_after_token` will not return it again.
+    pub fn include_self(mut self, v: bool) -> Self {
+        self.0 = self.0.map_args(|args| args.with_include_self(v));
+        self
+    }
+
     /// Change the delimiter. The default delimiter is "/"
     pub fn delimiter(mut self, v: &str) -> Self {
         self.0 = self.0.map_args(|args| args.with_delimiter(v));
@@ -241,6 +421,33 @@ impl FunctionList {
         self
     }
 
+    /// Filter entries by a glob pattern.
+    ///
+    /// The pattern matches the portion of the entry path after the listed
+    /// path, without the trailing `/` of dirs. For example, listing `dir/`
+    /// with pattern `*.log` will return `dir/a.log` but not `dir/sub/b.log`.
+    ///
+    /// Supported syntax:
+    ///
+    /// - `?` matches any single character except `/`.
+    /// - `*` matches any sequence of characters except `/`.
+    /// - `**` matches any sequence of characters including `/`, which is
+    ///   useful while listing with `delimiter("")`.
+    pub fn pattern(mut self, v: &str) -> Self {
+        self.0 = self.0.map_args(|args| args.with_pattern(v));
+        self
+    }
+
+    /// Only return entries of given mode, for example, `EntryMode::DIR` to
+    /// list sub directories only.
+    ///
+    /// Services that can skip entries while parsing the response will do so,
+    /// others will filter entries after listing.
+    pub fn mode(mut self, v: EntryMode) -> Self {
+        self.0 = self.0.map_args(|args| args.with_mode(v));
+        self
+    }
+
     /// Call the function to consume all the input and generate a
     /// result.
     pub fn call(self) -> Result<Vec<Entry>> {
@@ -260,12 +467,45 @@ impl FunctionLister {
         self
     }
 
+    /// Change the page size of this list operation.
+    ///
+    /// Page size is the max number of entries returned by one request to
+    /// the underlying service, it takes precedence over limit which is only
+    /// a hint of page size too. Services will use their own default (usually
+    /// the max they allow) if not set.
+    ///
+    /// Like limit, page size is a hint and will not be rejected: services
+    /// without `list_with_page_size` capability will ignore it.
+    pub fn page_size(mut self, v: usize) -> Self {
+        self.0 = self.0.map_args(|args| args.with_page_size(v));
+        self
+    }
+
     /// Change the start_after of this list operation.
     pub fn start_after(mut self, v: &str) -> Self {
         self.0 = self.0.map_args(|args| args.with_start_after(v));
         self
     }
 
+    /// Change the start_after_token of this list operation.
+    ///
+    /// The token should be got from `continuation_token` of a previous
+    /// lister on the same path, listing will be resumed from there.
+    pub fn start_after_token(mut self, v: &str) -> Self {
+        self.0 = self.0.map_args(|args| args.with_start_after_token(v));
+        self
+    }
+
+    /// Return an entry for the listed path itself (as a dir) before its
+    /// children.
+    ///
+    /// The entry will only be returned once even while listing recursively.
+    /// Resuming from `start_after_token` will not return it again.
+    pub fn include_self(mut self, v: bool) -> Self {
+        self.0 = self.0.map_args(|args| args.with_include_self(v));
+        self
+    }
+
     /// Change the delimiter. The default delimiter is "/"
     pub fn delimiter(mut self, v: &str) -> Self {
         self.0 = self.0.map_args(|args| args.with_delimiter(v));
@@ -278,6 +518,33 @@ impl FunctionLister {
         self
     }
 
+    /// Filter entries by a glob pattern.
+    ///
+    /// The pattern matches the portion of the entry path after the listed
+    /// path, without the trailing `/` of dirs. For example, listing `dir/`
+    /// with pattern `*.log` will return `dir/a.log` but not `dir/sub/b.log`.
+    ///
+    /// Supported syntax:
+    ///
+    /// - `?` matches any single character except `/`.
+    /// - `*` matches any sequence of characters except `/`.
+    /// - `**` matches any sequence of characters including `/`, which is
+    ///   useful while listing with `delimiter("")`.
+    pub fn pattern(mut self, v: &str) -> Self {
+        self.0 = self.0.map_args(|args| args.with_pattern(v));
+        self
+    }
+
+    /// Only return entries of given mode, for example, `EntryMode::DIR` to
+    /// list sub directories only.
+    ///
+    /// Services that can skip entries while parsing the response will do so,
+    /// others will filter entries after listing.
+    pub fn mode(mut self, v: EntryMode) -> Self {
+        self.0 = self.0.map_args(|args| args.with_mode(v));
+        self
+    }
+
     /// Call the function to consume all the input and generate a
     /// result.
     pub fn call(self) -> Result<BlockingLister> {
@@ -292,8 +559,69 @@ pub struct FunctionRead(pub(crate) OperatorFunction<OpRead, Vec<u8>>);
 
 impl FunctionRead {
     /// Set the range for this operation.
+    ///
+    /// `InvalidInput` will be returned if range start is larger than end.
+    /// Range end larger than the content length will be clamped, so fewer
+    /// bytes will be returned.
     pub fn range(mut self, range: impl RangeBounds<u64>) -> Self {
-        self.0 = self.0.map_args(|args| args.with_range(range.into()));
+        self.0 = match BytesRange::try_from_range(range) {
+            Ok(range) => self.0.map_args(|args| args.with_range(range)),
+            Err(err) => self
+                .0
+                .fail(err.with_operation("BlockingOperator::read_with")),
+        };
+        self
+    }
+
+    /// Read the last `n` bytes of the file, a.k.a. `Range: bytes=-n`.
+    ///
+    /// The whole file will be returned if it's smaller than `n`.
+    /// `InvalidInput` will be returned if `n` is 0.
+    pub fn tail(mut self, n: u64) -> Self {
+        self.0 = match BytesRange::try_from_tail(n) {
+            Ok(range) => self.0.map_args(|args| args.with_range(range)),
+            Err(err) => self
+                .0
+                .fail(err.with_operation("BlockingOperator::read_with")),
+        };
+        self
+    }
+
+    /// Verify the whole content with the checksum stored by service.
+    ///
+    /// Checksum will be computed while reading and `ChecksumMismatch` will be
+    /// returned if it doesn't match the stored one. Services that don't return
+    /// the stored checksum will return `Unsupported`. Checksum can't be used
+    /// with range, and seeking is not allowed before content is verified.
+    pub fn verify_checksum(mut self, v: bool) -> Self {
+        self.0 = self.0.map_args(|args| args.with_verify_checksum(v));
+        self
+    }
+
+    /// Set the server side encryption key to read the content.
+    ///
+    /// This is required if the content is written with
+    /// [`ServerSideEncryption::CustomerKey`].
+    pub fn encryption(mut self, v: ServerSideEncryption) -> Self {
+        self.0 = self.0.map_args(|args| args.with_encryption(v));
+        self
+    }
+
+    /// Decompress the content based on its `Content-Encoding`.
+    ///
+    /// `gzip`, `deflate` and `zstd` are supported with feature `compress`
+    /// enabled. Decompressed content can't be read by range.
+    pub fn decompress(mut self, v: bool) -> Self {
+        self.0 = self.0.map_args(|args| args.with_decompress(v));
+        self
+    }
+
+    /// Set the max bytes that this read could deliver.
+    ///
+    /// Returns an `Unexpected` error once more than `v` bytes would be
+    /// delivered. No more data will be read from services after that.
+    pub fn max_bytes(mut self, v: u64) -> Self {
+        self.0 = self.0.map_args(|args| args.with_max_bytes(v));
         self
     }
 
@@ -311,8 +639,29 @@ pub struct FunctionReader(pub(crate) OperatorFunction<OpRead, BlockingReader>);
 
 impl FunctionReader {
     /// Set the range for this operation.
+    ///
+    /// `InvalidInput` will be returned if range start is larger than end.
     pub fn range(mut self, range: impl RangeBounds<u64>) -> Self {
-        self.0 = self.0.map_args(|args| args.with_range(range.into()));
+        self.0 = match BytesRange::try_from_range(range) {
+            Ok(range) => self.0.map_args(|args| args.with_range(range)),
+            Err(err) => self
+                .0
+                .fail(err.with_operation("BlockingOperator::reader_with")),
+        };
+        self
+    }
+
+    /// Read the last `n` bytes of the file, a.k.a. `Range: bytes=-n`.
+    ///
+    /// The whole file will be returned if it's smaller than `n`.
+    /// `InvalidInput` will be returned if `n` is 0.
+    pub fn tail(mut self, n: u64) -> Self {
+        self.0 = match BytesRange::try_from_tail(n) {
+            Ok(range) => self.0.map_args(|args| args.with_range(range)),
+            Err(err) => self
+                .0
+                .fail(err.with_operation("BlockingOperator::reader_with")),
+        };
         self
     }
 
@@ -358,6 +707,44 @@ impl FunctionReader {
         self
     }
 
+    /// Verify the whole content with the checksum stored by service.
+    ///
+    /// Checksum will be computed while reading and `ChecksumMismatch` will be
+    /// returned if it doesn't match the stored one. Services that don't return
+    /// the stored checksum will return `Unsupported`. Checksum can't be used
+    /// with range, and seeking is not allowed before content is verified.
+    pub fn verify_checksum(mut self, v: bool) -> Self {
+        self.0 = self.0.map_args(|args| args.with_verify_checksum(v));
+        self
+    }
+
+    /// Set the server side encryption key to read the content.
+    ///
+    /// This is required if the content is written with
+    /// [`ServerSideEncryption::CustomerKey`].
+    pub fn encryption(mut self, v: ServerSideEncryption) -> Self {
+        self.0 = self.0.map_args(|args| args.with_encryption(v));
+        self
+    }
+
+    /// Decompress the content based on its `Content-Encoding`.
+    ///
+    /// `gzip`, `deflate` and `zstd` are supported with feature `compress`
+    /// enabled. Decompressed content can't be read by range.
+    pub fn decompress(mut self, v: bool) -> Self {
+        self.0 = self.0.map_args(|args| args.with_decompress(v));
+        self
+    }
+
+    /// Set the max bytes that this read could deliver.
+    ///
+    /// Returns an `Unexpected` error once more than `v` bytes would be
+    /// delivered. No more data will be read from services after that.
+    pub fn max_bytes(mut self, v: u64) -> Self {
+        self.0 = self.0.map_args(|args| args.with_max_bytes(v));
+        self
+    }
+
     /// Call the function to consume all the input and generate a
     /// result.
     pub fn call(self) -> Result<BlockingReader> {
@@ -389,6 +776,18 @@ impl FunctionStat {
         self
     }
 
+    /// Return the raw headers of the response in [`Metadata::raw_headers`].
+    ///
+    /// This is an escape hatch for service specific headers that are not
+    /// carried by [`Metadata`], like `x-ms-server-encrypted` of azfile.
+    /// Headers are not portable across services, and only services with
+    /// `stat_with_raw_headers` capability will return them, others will
+    /// return `Unsupported`.
+    pub fn raw_headers(mut self, v: bool) -> Self {
+        self.0 = self.0.map_args(|args| args.with_raw_headers(v));
+        self
+    }
+
     /// Call the function to consume all the input and generate a
     /// result.
     pub fn call(self) -> Result<Metadata> {
@@ -22,6 +22,7 @@
 use std::mem;
 use std::ops::RangeBounds;
 use std::pin::Pin;
+use std::task::ready;
 use std::task::Context;
 use std::task::Poll;
 use std::time::Duration;
@@ -31,7 +32,9 @@ use flagset::FlagSet;
 use futures::future::BoxFuture;
 use futures::Future;
 use futures::FutureExt;
+use futures::Stream;
 
+use crate::raw::oio::WriteBuf;
 use crate::raw::*;
 use crate::*;
 
@@ -52,6 +55,9 @@ pub(crate) enum OperatorFuture<T, F> {
     ),
     /// Polling state, waiting for the future to be ready
     Poll(BoxFuture<'static, Result<F>>),
+    /// Failed state, the input args are invalid and the error will be
+    /// returned directly while polling.
+    Failed(Error),
     /// Empty state, the future has been polled and completed or
     /// something is broken during state switch.
     Empty,
@@ -72,6 +78,17 @@ impl<T, F> OperatorFuture<T, F> {
             OperatorFuture::Idle(inner, path, args, func) => {
                 OperatorFuture::Idle(inner, path, f(args), func)
             }
+            OperatorFuture::Failed(err) => OperatorFuture::Failed(err),
+            _ => unreachable!("future has been polled and should not be changed again"),
+        }
+    }
+
+    /// Fail this future with given error, the inner function will not be
+    /// called anymore.
+    fn fail(self, err: Error) -> Self {
+        match self {
+            OperatorFuture::Idle(..) => OperatorFuture::Failed(err),
+            OperatorFuture::Failed(err) => OperatorFuture::Failed(err),
             _ => unreachable!("future has been polled and should not be changed again"),
         }
     }
@@ -93,6 +110,7 @@ where
     ///     self state is `Empty`
     ///   - If future is `Pending`, we will set self state to `Poll`
     ///     and wait for next poll
+    /// - If the future is `Failed`, we will return the error directly
     ///
     /// In general, `Empty` state should not be polled.
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
@@ -107,6 +125,7 @@ where
                 Poll::Pending => OperatorFuture::Poll(fut),
                 Poll::Ready(v) => return Poll::Ready(v),
             },
+            OperatorFuture::Failed(err) => return Poll::Ready(Err(err)),
             OperatorFuture::Empty => {
                 panic!("future polled after completion");
             }
@@ -115,6 +134,34 @@ where
     }
 }
 
+/// Future that generated by [`Operator::create_dir_with`].
+///
+/// Users can add more options by public functions provided by this struct.
+pub struct FutureCreateDir(pub(crate) OperatorFuture<OpCreateDir, ()>);
+
+impl FutureCreateDir {
+    /// Set the user defined metadata of the dir.
+    ///
+    /// Services that don't support user defined metadata on dirs will
+    /// return `Unsupported`.
+    pub fn user_metadata<K: Into<String>, V: Into<String>>(
+        mut self,
+        v: impl IntoIterator<Item = (K, V)>,
+    ) -> Self {
+        let v = v.into_iter().map(|(k, v)| (k.into(), v.into())).collect();
+        self.0 = self.0.map_args(|args| args.with_user_metadata(v));
+        self
+    }
+}
+
+impl Future for FutureCreateDir {
+    type Output = Result<()>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.0.poll_unpin(cx)
+    }
+}
+
 /// Future that generated by [`Operator::stat_with`].
 ///
 /// Users can add more options by public functions provided by this struct.
@@ -138,6 +185,18 @@ impl FutureStat {
         self.0 = self.0.map_args(|args| args.with_version(v));
         self
     }
+
+    /// Return the raw headers of the response in [`Metadata::raw_headers`].
+    ///
+    /// This is an escape hatch for service specific headers that are not
+    /// carried by [`Metadata`], like `x-ms-server-encrypted` of azfile.
+    /// Headers are not portable across services, and only services with
+    /// `stat_with_raw_headers` capability will return them, others will
+    /// return `Unsupported`.
+    pub fn raw_headers(mut self, v: bool) -> Self {
+        self.0 = self.0.map_args(|args| args.with_raw_headers(v));
+        self
+    }
 }
 
 impl Future for FutureStat {
@@ -253,8 +312,27 @@ pub struct FutureRead(pub(crate) OperatorFuture<OpRead, Vec<u8>>);
 
 impl FutureRead {
     /// Set the range header for this operation.
+    ///
+    /// `InvalidInput` will be returned if range start is larger than end.
+    /// Range end larger than the content length will be clamped, so fewer
+    /// bytes will be returned.
     pub fn range(mut self, range: impl RangeBounds<u64>) -> Self {
-        self.0 = self.0.map_args(|args| args.with_range(range.into()));
+        self.0 = match BytesRange::try_from_range(range) {
+            Ok(range) => self.0.map_args(|args| args.with_range(range)),
+            Err(err) => self.0.fail(err.with_operation("read")),
+        };
+        self
+    }
+
+    /// Read the last `n` bytes of the file, a.k.a. `Range: bytes=-n`.
+    ///
+    /// The whole file will be returned if it's smaller than `n`.
+    /// `InvalidInput` will be returned if `n` is 0.
+    pub fn tail(mut self, n: u64) -> Self {
+        self.0 = match BytesRange::try_from_tail(n) {
+            Ok(range) => self.0.map_args(|args| args.with_range(range)),
+            Err(err) => self.0.fail(err.with_operation("read")),
+        };
         self
     }
 
@@ -299,6 +377,95 @@ impl FutureRead {
         self.0 = self.0.map_args(|args| args.with_version(v));
         self
     }
+
+    /// Verify the whole content with the checksum stored by service.
+    ///
+    /// Checksum will be computed while reading and `ChecksumMismatch` will be
+    /// returned if it doesn't match the stored one. Services that don't return
+    /// the stored checksum will return `Unsupported`. Checksum can't be used
+    /// with range, and seeking is not allowed before content is verified.
+    pub fn verify_checksum(mut self, v: bool) -> Self {
+        self.0 = self.0.map_args(|args| args.with_verify_checksum(v));
+        self
+    }
+
+    /// Set the server side encryption key to read the content.
+    ///
+    /// This is required if the content is written with
+    /// [`ServerSideEncryption::CustomerKey`].
+    pub fn encryption(mut self, v: ServerSideEncryption) -> Self {
+        self.0 = self.0.map_args(|args| args.with_encryption(v));
+        self
+    }
+
+    /// Decompress the content based on its `Content-Encoding`.
+    ///
+    /// `gzip`, `deflate` and `zstd` are supported with feature `compress`
+    /// enabled. Decompressed content can't be read by range.
+    pub fn decompress(mut self, v: bool) -> Self {
+        self.0 = self.0.map_args(|args| args.with_decompress(v));
+        self
+    }
+
+    /// Set the max bytes that this read could deliver.
+    ///
+    /// Returns an `Unexpected` error once more than `v` bytes would be
+    /// delivered. No more data will be read from services after that.
+    pub fn max_bytes(mut self, v: u64) -> Self {
+        self.0 = self.0.map_args(|args| args.with_max_bytes(v));
+        self
+    }
+
+    /// Set the token that could cancel this read.
+    ///
+    /// Once the token has been cancelled or reached its deadline, in-flight
+    /// requests will be dropped and `Cancelled` will be returned.
+    pub fn cancel_token(mut self, token: CancellationToken) -> Self {
+        self.0 = self.0.map_args(|args| args.with_cancel_token(token));
+        self
+    }
+
+    /// Read the content as a stream of chunks in `size` bytes.
+    ///
+    /// Every chunk except the last one will be exactly `size` bytes, the
+    /// last one contains the remaining bytes. All options like `range` and
+    /// `decompress` still apply. `InvalidInput` will be returned if `size`
+    /// is `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use anyhow::Result;
+    /// # use futures::TryStreamExt;
+    /// # use opendal::Operator;
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let mut chunks = op.read_with("path/to/file").chunked(64 * 1024);
+    /// while let Some(bs) = chunks.try_next().await? {
+    ///     // Handle every 64KiB chunk here.
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn chunked(self, size: usize) -> ChunkedRead {
+        let state = match self.0 {
+            _ if size == 0 => ChunkedReadState::Failed(
+                Error::new(ErrorKind::InvalidInput, "chunk size must be larger than 0")
+                    .with_operation("read"),
+            ),
+            OperatorFuture::Idle(inner, path, args, _) => {
+                ChunkedReadState::Opening(open_chunked_read(inner, path, args))
+            }
+            OperatorFuture::Failed(err) => ChunkedReadState::Failed(err),
+            _ => unreachable!("future has been polled and should not be changed again"),
+        };
+
+        ChunkedRead {
+            state,
+            size,
+            buf: oio::ChunkedBytes::default(),
+        }
+    }
 }
 
 impl Future for FutureRead {
@@ -309,6 +476,108 @@ impl Future for FutureRead {
     }
 }
 
+/// Open the reader for [`ChunkedRead`], `None` means there is nothing to read.
+fn open_chunked_read(
+    inner: FusedAccessor,
+    path: String,
+    args: OpRead,
+) -> BoxFuture<'static, Result<Option<Reader>>> {
+    let fut = async move {
+        if !validate_path(&path, EntryMode::FILE) {
+            return Err(
+                Error::new(ErrorKind::IsADirectory, "read path is a directory")
+                    .with_operation("read")
+                    .with_context("service", inner.info().scheme())
+                    .with_context("path", &path),
+            );
+        }
+
+        // Empty range can't be represented by `Range` header, we only need
+        // to make sure the file exists.
+        if args.range().size() == Some(0) {
+            inner.stat(&path, OpStat::default()).await?;
+            return Ok(None);
+        }
+
+        Reader::create(inner, &path, args).await.map(Some)
+    };
+
+    Box::pin(fut)
+}
+
+enum ChunkedReadState {
+    Opening(BoxFuture<'static, Result<Option<Reader>>>),
+    Reading(Reader),
+    Failed(Error),
+    Done,
+}
+
+/// Stream that generated by [`FutureRead::chunked`].
+///
+/// Content returned by services will be re-chunked into `size` bytes.
+pub struct ChunkedRead {
+    state: ChunkedReadState,
+    size: usize,
+    buf: oio::ChunkedBytes,
+}
+
+impl ChunkedRead {
+    /// Split the next chunk with at most `size` bytes from buffer.
+    fn split_chunk(&mut self) -> Bytes {
+        let size = self.size.min(self.buf.len());
+        let bs = self.buf.bytes(size);
+        self.buf.advance(size);
+        bs
+    }
+}
+
+impl Stream for ChunkedRead {
+    type Item = Result<Bytes>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            match &mut this.state {
+                ChunkedReadState::Opening(fut) => match ready!(fut.poll_unpin(cx)) {
+                    Ok(Some(r)) => this.state = ChunkedReadState::Reading(r),
+                    Ok(None) => this.state = ChunkedReadState::Done,
+                    Err(err) => {
+                        this.state = ChunkedReadState::Done;
+                        return Poll::Ready(Some(Err(err)));
+                    }
+                },
+                ChunkedReadState::Reading(r) => {
+                    if this.buf.len() >= this.size {
+                        return Poll::Ready(Some(Ok(this.split_chunk())));
+                    }
+
+                    match ready!(oio::Read::poll_next(r, cx)) {
+                        Some(Ok(bs)) => this.buf.push(bs),
+                        Some(Err(err)) => {
+                            this.state = ChunkedReadState::Done;
+                            return Poll::Ready(Some(Err(err)));
+                        }
+                        None => this.state = ChunkedReadState::Done,
+                    }
+                }
+                ChunkedReadState::Failed(_) => {
+                    let state = mem::replace(&mut this.state, ChunkedReadState::Done);
+                    if let ChunkedReadState::Failed(err) = state {
+                        return Poll::Ready(Some(Err(err)));
+                    }
+                }
+                ChunkedReadState::Done => {
+                    if this.buf.is_empty() {
+                        return Poll::Ready(None);
+                    }
+                    return Poll::Ready(Some(Ok(this.split_chunk())));
+                }
+            }
+        }
+    }
+}
+
 /// Future that generated by [`Operator::reader_with`].
 ///
 /// Users can add more options by public functions provided by this struct.
@@ -316,8 +585,25 @@ pub struct FutureReader(pub(crate) OperatorFuture<OpRead, Reader>);
 
 impl FutureReader {
     /// Set the range header for this operation.
+    ///
+    /// `InvalidInput` will be returned if range start is larger than end.
     pub fn range(mut self, range: impl RangeBounds<u64>) -> Self {
-        self.0 = self.0.map_args(|args| args.with_range(range.into()));
+        self.0 = match BytesRange::try_from_range(range) {
+            Ok(range) => self.0.map_args(|args| args.with_range(range)),
+            Err(err) => self.0.fail(err.with_operation("Operator::reader")),
+        };
+        self
+    }
+
+    /// Read the last `n` bytes of the file, a.k.a. `Range: bytes=-n`.
+    ///
+    /// The whole file will be returned if it's smaller than `n`.
+    /// `InvalidInput` will be returned if `n` is 0.
+    pub fn tail(mut self, n: u64) -> Self {
+        self.0 = match BytesRange::try_from_tail(n) {
+            Ok(range) => self.0.map_args(|args| args.with_range(range)),
+            Err(err) => self.0.fail(err.with_operation("Operator::reader")),
+        };
         self
     }
 
@@ -356,6 +642,53 @@ impl FutureReader {
         self.0 = self.0.map_args(|args| args.with_if_none_match(v));
         self
     }
+
+    /// Verify the whole content with the checksum stored by service.
+    ///
+    /// Checksum will be computed while reading and `ChecksumMismatch` will be
+    /// returned if it doesn't match the stored one. Services that don't return
+    /// the stored checksum will return `Unsupported`. Checksum can't be used
+    /// with range, and seeking is not allowed before content is verified.
+    pub fn verify_checksum(mut self, v: bool) -> Self {
+        self.0 = self.0.map_args(|args| args.with_verify_checksum(v));
+        self
+    }
+
+    /// Set the server side encryption key to read the content.
+    ///
+    /// This is required if the content is written with
+    /// [`ServerSideEncryption::CustomerKey`].
+    pub fn encryption(mut self, v: ServerSideEncryption) -> Self {
+        self.0 = self.0.map_args(|args| args.with_encryption(v));
+        self
+    }
+
+    /// Decompress the content based on its `Content-Encoding`.
+    ///
+    /// `gzip`, `deflate` and `zstd` are supported with feature `compress`
+    /// enabled. Decompressed content can't be read by range.
+    pub fn decompress(mut self, v: bool) -> Self {
+        self.0 = self.0.map_args(|args| args.with_decompress(v));
+        self
+    }
+
+    /// Set the max bytes that this read could deliver.
+    ///
+    /// Returns an `Unexpected` error once more than `v` bytes would be
+    /// delivered. No more data will be read from services after that.
+    pub fn max_bytes(mut self, v: u64) -> Self {
+        self.0 = self.0.map_args(|args| args.with_max_bytes(v));
+        self
+    }
+
+    /// Set the token that could cancel this read.
+    ///
+    /// Once the token has been cancelled or reached its deadline, in-flight
+    /// requests will be dropped and `Cancelled` will be returned.
+    pub fn cancel_token(mut self, token: CancellationToken) -> Self {
+        self.0 = self.0.map_args(|args| args.with_cancel_token(token));
+        self
+    }
 }
 
 impl Future for FutureReader {
@@ -369,7 +702,7 @@ impl Future for FutureReader {
 /// Future that generated by [`Operator::write_with`].
 ///
 /// Users can add more options by public functions provided by this struct.
-pub struct FutureWrite(pub(crate) OperatorFuture<(OpWrite, Bytes), ()>);
+pub struct FutureWrite(pub(crate) OperatorFuture<(OpWrite, Bytes), Metadata>);
 
 impl FutureWrite {
     /// Set the append mode of op.
@@ -392,11 +725,31 @@ impl FutureWrite {
     ///
     /// Service could have their own minimum buffer size while perform write operations like
     /// multipart uploads. So the buffer size may be larger than the given buffer size.
+    ///
+    /// Services that don't support multiple writes will return `Unsupported` if the
+    /// content exceeds the buffer size.
     pub fn buffer(mut self, v: usize) -> Self {
         self.0 = self.0.map_args(|(args, bs)| (args.with_buffer(v), bs));
         self
     }
 
+    /// Set the chunk size of op.
+    ///
+    /// This is the same as [`FutureWrite::buffer`], data will be uploaded in
+    /// chunks of this size.
+    pub fn chunk(self, v: usize) -> Self {
+        self.buffer(v)
+    }
+
+    /// Set the max chunks that could be uploaded concurrently.
+    ///
+    /// Services that don't support concurrent upload will upload chunks one
+    /// by one.
+    pub fn concurrent(mut self, v: usize) -> Self {
+        self.0 = self.0.map_args(|(args, bs)| (args.with_concurrent(v), bs));
+        self
+    }
+
     /// Set the content type of option
     pub fn content_type(mut self, v: &str) -> Self {
         self.0 = self
@@ -420,10 +773,72 @@ impl FutureWrite {
             .map_args(|(args, bs)| (args.with_cache_control(v), bs));
         self
     }
+
+    /// Set the checksum of the whole content.
+    ///
+    /// Service will reject the write if the content doesn't match, services
+    /// that don't support given checksum will return `Unsupported`.
+    pub fn checksum(mut self, v: Checksum) -> Self {
+        self.0 = self.0.map_args(|(args, bs)| (args.with_checksum(v), bs));
+        self
+    }
+
+    /// Set the server side encryption of the content.
+    ///
+    /// Services that don't support given encryption will return
+    /// `Unsupported`.
+    pub fn encryption(mut self, v: ServerSideEncryption) -> Self {
+        self.0 = self.0.map_args(|(args, bs)| (args.with_encryption(v), bs));
+        self
+    }
+
+    /// Set the user defined metadata of the content.
+    ///
+    /// Services that don't support user defined metadata will return
+    /// `Unsupported`.
+    pub fn user_metadata<K: Into<String>, V: Into<String>>(
+        mut self,
+        v: impl IntoIterator<Item = (K, V)>,
+    ) -> Self {
+        let v = v.into_iter().map(|(k, v)| (k.into(), v.into())).collect();
+        self.0 = self
+            .0
+            .map_args(|(args, bs)| (args.with_user_metadata(v), bs));
+        self
+    }
+
+    /// Only create the file if it doesn't exist.
+    ///
+    /// The write will fail with `AlreadyExists` if the file already
+    /// exists. Services that don't support conditional create will return
+    /// `Unsupported`.
+    pub fn if_not_exists(mut self, v: bool) -> Self {
+        self.0 = self
+            .0
+            .map_args(|(args, bs)| (args.with_if_not_exists(v), bs));
+        self
+    }
+
+    /// Write into a hidden temp file and rename it to the target path while closing.
+    ///
+    /// Readers will either see the old content or the complete new content,
+    /// but never a half-written file. The temp file will be removed if the
+    /// write fails.
+    ///
+    /// # Notes
+    ///
+    /// - Atomicity depends on the backend's rename being atomic. Services
+    ///   without native rename fall back to copy then delete, which is not
+    ///   atomic.
+    /// - `append` and `if_not_exists` can't be used together with `atomic`.
+    pub fn atomic(mut self, v: bool) -> Self {
+        self.0 = self.0.map_args(|(args, bs)| (args.with_atomic(v), bs));
+        self
+    }
 }
 
 impl Future for FutureWrite {
-    type Output = Result<()>;
+    type Output = Result<Metadata>;
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         self.0.poll_unpin(cx)
@@ -463,11 +878,33 @@ impl FutureWriter {
     /// - GCS requires the part size to be aligned with 256 KiB.
     ///
     /// The services will alter the buffer size to meet their requirements.
+    ///
+    /// Services that don't support multiple writes will buffer all data and
+    /// write them at once while closing, `Unsupported` will be returned if
+    /// the written data exceeds the buffer size. Appends will be flushed
+    /// once the buffered data exceeds the buffer size.
     pub fn buffer(mut self, v: usize) -> Self {
         self.0 = self.0.map_args(|args| args.with_buffer(v));
         self
     }
 
+    /// Set the chunk size of op.
+    ///
+    /// This is the same as [`FutureWriter::buffer`], data will be uploaded in
+    /// chunks of this size.
+    pub fn chunk(self, v: usize) -> Self {
+        self.buffer(v)
+    }
+
+    /// Set the max chunks that could be uploaded concurrently.
+    ///
+    /// Services that don't support concurrent upload will upload chunks one
+    /// by one.
+    pub fn concurrent(mut self, v: usize) -> Self {
+        self.0 = self.0.map_args(|args| args.with_concurrent(v));
+        self
+    }
+
     /// Set the content type of option
     pub fn content_type(mut self, v: &str) -> Self {
         self.0 = self.0.map_args(|args| args.with_content_type(v));
@@ -485,6 +922,64 @@ impl FutureWriter {
         self.0 = self.0.map_args(|args| args.with_cache_control(v));
         self
     }
+
+    /// Set the checksum of the whole content.
+    ///
+    /// Service will reject the write if the content doesn't match, services
+    /// that don't support given checksum will return `Unsupported`.
+    pub fn checksum(mut self, v: Checksum) -> Self {
+        self.0 = self.0.map_args(|args| args.with_checksum(v));
+        self
+    }
+
+    /// Set the server side encryption of the content.
+    ///
+    /// Services that don't support given encryption will return
+    /// `Unsupported`.
+    pub fn encryption(mut self, v: ServerSideEncryption) -> Self {
+        self.0 = self.0.map_args(|args| args.with_encryption(v));
+        self
+    }
+
+    /// Set the user defined metadata of the content.
+    ///
+    /// Services that don't support user defined metadata will return
+    /// `Unsupported`.
+    pub fn user_metadata<K: Into<String>, V: Into<String>>(
+        mut self,
+        v: impl IntoIterator<Item = (K, V)>,
+    ) -> Self {
+        let v = v.into_iter().map(|(k, v)| (k.into(), v.into())).collect();
+        self.0 = self.0.map_args(|args| args.with_user_metadata(v));
+        self
+    }
+
+    /// Only create the file if it doesn't exist.
+    ///
+    /// The write will fail with `AlreadyExists` if the file already
+    /// exists. Services that don't support conditional create will return
+    /// `Unsupported`.
+    pub fn if_not_exists(mut self, v: bool) -> Self {
+        self.0 = self.0.map_args(|args| args.with_if_not_exists(v));
+        self
+    }
+
+    /// Write into a hidden temp file and rename it to the target path while closing.
+    ///
+    /// Readers will either see the old content or the complete new content,
+    /// but never a half-written file. The temp file will be removed if the
+    /// write fails.
+    ///
+    /// # Notes
+    ///
+    /// - Atomicity depends on the backend's rename being atomic. Services
+    ///   without native rename fall back to copy then delete, which is not
+    ///   atomic.
+    /// - `append` and `if_not_exists` can't be used together with `atomic`.
+    pub fn atomic(mut self, v: bool) -> Self {
+        self.0 = self.0.map_args(|args| args.with_atomic(v));
+        self
+    }
 }
 
 impl Future for FutureWriter {
@@ -506,6 +1001,18 @@ impl FutureDelete {
         self.0 = self.0.map_args(|args| args.with_version(v));
         self
     }
+
+    /// Delete the path only if its etag matches `v`.
+    ///
+    /// Returns `ConditionNotMatch` and leaves the path untouched if the
+    /// etag doesn't match. Services that don't support it will return
+    /// `Unsupported`, check `delete_with_if_match` of [`Capability`].
+    ///
+    /// [`Capability`]: crate::Capability
+    pub fn if_match(mut self, v: &str) -> Self {
+        self.0 = self.0.map_args(|args| args.with_if_match(v));
+        self
+    }
 }
 
 impl Future for FutureDelete {
@@ -528,12 +1035,45 @@ impl FutureList {
         self
     }
 
+    /// Change the page size of this list operation.
+    ///
+    /// Page size is the max number of entries returned by one request to
+    /// the underlying service, it takes precedence over limit which is only
+    /// a hint of page size too. Services will use their own default (usually
+    /// the max they allow) if not set.
+    ///
+    /// Like limit, page size is a hint and will not be rejected: services
+    /// without `list_with_page_size` capability will ignore it.
+    pub fn page_size(mut self, v: usize) -> Self {
+        self.0 = self.0.map_args(|args| args.with_page_size(v));
+        self
+    }
+
     /// Change the start_after of this list operation.
     pub fn start_after(mut self, v: &str) -> Self {
         self.0 = self.0.map_args(|args| args.with_start_after(v));
         self
     }
 
+    /// Change the start_after_token of this list operation.
+    ///
+    /// The token should be got from `continuation_token` of a previous
+    /// lister on the same path, listing will be resumed from there.
+    pub fn start_after_token(mut self, v: &str) -> Self {
+        self.0 = self.0.map_args(|args| args.with_start_after_token(v));
+        self
+    }
+
+    /// Return an entry for the listed path itself (as a dir) before its
+    /// children.
+    ///
+    /// The entry will only be returned once even while listing recursively.
+    /// Resuming from `start_after_token` will not return it again.
+    pub fn include_self(mut self, v: bool) -> Self {
+        self.0 = self.0.map_args(|args| args.with_include_self(v));
+        self
+    }
+
     /// Change the delimiter. The default delimiter is "/"
     pub fn delimiter(mut self, v: &str) -> Self {
         self.0 = self.0.map_args(|args| args.with_delimiter(v));
@@ -545,6 +1085,46 @@ impl FutureList {
         self.0 = self.0.map_args(|args| args.with_metakey(v));
         self
     }
+
+    /// Filter entries by a glob pattern.
+    ///
+    /// The pattern matches the portion of the entry path after the listed
+    /// path, without the trailing `/` of dirs. For example, listing `dir/`
+    /// with pattern `*.log` will return `dir/a.log` but not `dir/sub/b.log`.
+    ///
+    /// Supported syntax:
+    ///
+    /// - `?` matches any single character except `/`.
+    /// - `*` matches any sequence of characters except `/`.
+    /// - `**` matches any sequence of characters including `/`, which is
+    ///   useful while listing with `delimiter("")`.
+    pub fn pattern(mut self, v: &str) -> Self {
+        self.0 = self.0.map_args(|args| args.with_pattern(v));
+        self
+    }
+
+    /// Only return entries of given mode, for example, `EntryMode::DIR` to
+    /// list sub directories only.
+    ///
+    /// Services that can skip entries while parsing the response will do so,
+    /// others will filter entries after listing.
+    pub fn mode(mut self, v: EntryMode) -> Self {
+        self.0 = self.0.map_args(|args| args.with_mode(v));
+        self
+    }
+
+    /// Set the max number of entries that could be stated at the same time.
+    ///
+    /// Services like s3 only return part of metadata in list, entries
+    /// missing the required metakey will be stated one by one, which
+    /// costs one extra request per entry. Setting a larger concurrent
+    /// sends those stats concurrently, entries are still returned in order.
+    ///
+    /// Default to be `1`.
+    pub fn concurrent(mut self, v: usize) -> Self {
+        self.0 = self.0.map_args(|args| args.with_concurrent(v));
+        self
+    }
 }
 
 impl Future for FutureList {
@@ -567,12 +1147,45 @@ impl FutureLister {
         self
     }
 
+    /// Change the page size of this list operation.
+    ///
+    /// Page size is the max number of entries returned by one request to
+    /// the underlying service, it takes precedence over limit which is only
+    /// a hint of page size too. Services will use their own default (usually
+    /// the max they allow) if not set.
+    ///
+    /// Like limit, page size is a hint and will not be rejected: services
+    /// without `list_with_page_size` capability will ignore it.
+    pub fn page_size(mut self, v: usize) -> Self {
+        self.0 = self.0.map_args(|args| args.with_page_size(v));
+        self
+    }
+
     /// Change the start_after of this list operation.
     pub fn start_after(mut self, v: &str) -> Self {
         self.0 = self.0.map_args(|args| args.with_start_after(v));
         self
     }
 
+    /// Change the start_after_token of this list operation.
+    ///
+    /// The token should be got from `continuation_token` of a previous
+    /// lister on the same path, listing will be resumed from there.
+    pub fn start_after_token(mut self, v: &str) -> Self {
+        self.0 = self.0.map_args(|args| args.with_start_after_token(v));
+        self
+    }
+
+    /// Return an entry for the listed path itself (as a dir) before its
+    /// children.
+    ///
+    /// The entry will only be returned once even while listing recursively.
+    /// Resuming from `start_after_token` will not return it again.
+    pub fn include_self(mut self, v: bool) -> Self {
+        self.0 = self.0.map_args(|args| args.with_include_self(v));
+        self
+    }
+
     /// Change the delimiter. The default delimiter is "/"
     pub fn delimiter(mut self, v: &str) -> Self {
         self.0 = self.0.map_args(|args| args.with_delimiter(v));
@@ -584,6 +1197,46 @@ impl FutureLister {
         self.0 = self.0.map_args(|args| args.with_metakey(v));
         self
     }
+
+    /// Filter entries by a glob pattern.
+    ///
+    /// The pattern matches the portion of the entry path after the listed
+    /// path, without the trailing `/` of dirs. For example, listing `dir/`
+    /// with pattern `*.log` will return `dir/a.log` but not `dir/sub/b.log`.
+    ///
+    /// Supported syntax:
+    ///
+    /// - `?` matches any single character except `/`.
+    /// - `*` matches any sequence of characters except `/`.
+    /// - `**` matches any sequence of characters including `/`, which is
+    ///   useful while listing with `delimiter("")`.
+    pub fn pattern(mut self, v: &str) -> Self {
+        self.0 = self.0.map_args(|args| args.with_pattern(v));
+        self
+    }
+
+    /// Only return entries of given mode, for example, `EntryMode::DIR` to
+    /// list sub directories only.
+    ///
+    /// Services that can skip entries while parsing the response will do so,
+    /// others will filter entries after listing.
+    pub fn mode(mut self, v: EntryMode) -> Self {
+        self.0 = self.0.map_args(|args| args.with_mode(v));
+        self
+    }
+
+    /// Set the max number of entries that could be stated at the same time.
+    ///
+    /// Services like s3 only return part of metadata in list, entries
+    /// missing the required metakey will be stated one by one, which
+    /// costs one extra request per entry. Setting a larger concurrent
+    /// sends those stats concurrently, entries are still returned in order.
+    ///
+    /// Default to be `1`.
+    pub fn concurrent(mut self, v: usize) -> Self {
+        self.0 = self.0.map_args(|args| args.with_concurrent(v));
+        self
+    }
 }
 
 impl Future for FutureLister {
@@ -27,6 +27,14 @@ use futures::AsyncSeek;
 use futures::Stream;
 
 use crate::raw::*;
+use crate::types::decompress_blocking_reader;
+use crate::types::decompress_reader;
+use crate::types::new_cancelled_error;
+use crate::types::read_decompress;
+use crate::types::read_verify_checksum;
+use crate::types::stored_checksum;
+use crate::types::CancelWait;
+use crate::types::ChecksumHasher;
 use crate::*;
 
 /// Reader is designed to read data from given path in an asynchronous
@@ -67,10 +75,39 @@ impl Reader {
     /// We don't want to expose those details to users so keep this function
     /// in crate only.
     pub(crate) async fn create(acc: FusedAccessor, path: &str, op: OpRead) -> Result<Self> {
-        let (_, r) = acc.read(path, op).await?;
+        let decompress = read_decompress(&op)?;
+        let verify_checksum = read_verify_checksum(&op)?;
+        let max_bytes = op.max_bytes();
+        let cancel_token = op.cancel_token().cloned();
+        let (rp, r) = match &cancel_token {
+            Some(token) => token
+                .run(acc.read(path, op))
+                .await
+                .ok_or_else(|| new_cancelled_error().with_context("path", path))??,
+            None => acc.read(path, op).await?,
+        };
+
+        // Checksum is computed on the raw content returned by services.
+        let mut inner: oio::Reader = if verify_checksum {
+            let checksum = stored_checksum(rp.metadata())?;
+            Box::new(ChecksumReader::new(r, checksum))
+        } else {
+            r
+        };
+        if decompress {
+            inner = decompress_reader(inner, rp.metadata())?;
+        }
+        // Limit is applied on the content delivered to users.
+        if let Some(max_bytes) = max_bytes {
+            inner = Box::new(LimitedReader::new(inner, max_bytes));
+        }
+        // Cancellation is checked before any other reads.
+        if let Some(token) = cancel_token {
+            inner = Box::new(CancelReader::new(inner, token));
+        }
 
         Ok(Reader {
-            inner: r,
+            inner,
             seek_state: SeekState::Init,
         })
     }
@@ -192,9 +229,27 @@ impl BlockingReader {
     /// We don't want to expose those details to users so keep this function
     /// in crate only.
     pub(crate) fn create(acc: FusedAccessor, path: &str, op: OpRead) -> Result<Self> {
-        let (_, r) = acc.blocking_read(path, op)?;
+        let decompress = read_decompress(&op)?;
+        let verify_checksum = read_verify_checksum(&op)?;
+        let max_bytes = op.max_bytes();
+        let (rp, r) = acc.blocking_read(path, op)?;
+
+        // Checksum is computed on the raw content returned by services.
+        let mut inner: oio::BlockingReader = if verify_checksum {
+            let checksum = stored_checksum(rp.metadata())?;
+            Box::new(ChecksumReader::new(r, checksum))
+        } else {
+            r
+        };
+        if decompress {
+            inner = decompress_blocking_reader(inner, rp.metadata())?;
+        }
+        // Limit is applied on the content delivered to users.
+        if let Some(max_bytes) = max_bytes {
+            inner = Box::new(LimitedReader::new(inner, max_bytes));
+        }
 
-        Ok(BlockingReader { inner: r })
+        Ok(BlockingReader { inner })
     }
 }
 
@@ -240,6 +295,275 @@ impl Iterator for BlockingReader {
     }
 }
 
+/// ChecksumReader computes the checksum while reading and verifies it
+/// at the end of content.
+///
+/// Seeking is not allowed before the content has been verified since we
+/// can't make sure all content has been read in order.
+pub(crate) struct ChecksumReader<R> {
+    inner: R,
+    hasher: Option<ChecksumHasher>,
+}
+
+impl<R> ChecksumReader<R> {
+    pub(crate) fn new(inner: R, checksum: Checksum) -> Self {
+        Self {
+            inner,
+            hasher: Some(ChecksumHasher::new(checksum)),
+        }
+    }
+
+    fn check_seek(&self, pos: io::SeekFrom) -> Result<()> {
+        // Query the current position doesn't affect the checksum.
+        if self.hasher.is_none() || pos == io::SeekFrom::Current(0) {
+            return Ok(());
+        }
+
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "seek is not supported while verifying checksum",
+        ))
+    }
+
+    fn update(&mut self, bs: &[u8]) -> Result<()> {
+        if bs.is_empty() {
+            return match self.hasher.take() {
+                Some(hasher) => hasher.verify(),
+                None => Ok(()),
+            };
+        }
+
+        if let Some(hasher) = self.hasher.as_mut() {
+            hasher.update(bs);
+        }
+        Ok(())
+    }
+}
+
+impl<R: oio::Read> oio::Read for ChecksumReader<R> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        let n = ready!(self.inner.poll_read(cx, buf))?;
+        // Empty buf can't be used to detect the end of content.
+        if n == 0 && buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        self.update(&buf[..n])?;
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_seek(&mut self, cx: &mut Context<'_>, pos: io::SeekFrom) -> Poll<Result<u64>> {
+        self.check_seek(pos)?;
+        self.inner.poll_seek(cx, pos)
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        match ready!(self.inner.poll_next(cx)) {
+            Some(Ok(bs)) => {
+                if let Some(hasher) = self.hasher.as_mut() {
+                    hasher.update(&bs);
+                }
+                Poll::Ready(Some(Ok(bs)))
+            }
+            Some(Err(err)) => Poll::Ready(Some(Err(err))),
+            None => match self.update(&[]) {
+                Ok(()) => Poll::Ready(None),
+                Err(err) => Poll::Ready(Some(Err(err))),
+            },
+        }
+    }
+}
+
+impl<R: oio::BlockingRead> oio::BlockingRead for ChecksumReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let n = self.inner.read(buf)?;
+        // Empty buf can't be used to detect the end of content.
+        if n == 0 && buf.is_empty() {
+            return Ok(0);
+        }
+
+        self.update(&buf[..n])?;
+        Ok(n)
+    }
+
+    fn seek(&mut self, pos: io::SeekFrom) -> Result<u64> {
+        self.check_seek(pos)?;
+        self.inner.seek(pos)
+    }
+
+    fn next(&mut self) -> Option<Result<Bytes>> {
+        match self.inner.next() {
+            Some(Ok(bs)) => {
+                if let Some(hasher) = self.hasher.as_mut() {
+                    hasher.update(&bs);
+                }
+                Some(Ok(bs))
+            }
+            Some(Err(err)) => Some(Err(err)),
+            None => self.update(&[]).err().map(Err),
+        }
+    }
+}
+
+/// CancelReader returns `Cancelled` once the token has been cancelled.
+///
+/// Inner reader will be dropped at that time, so that in-flight requests
+/// will be aborted instead of waiting for the reader to be dropped.
+struct CancelReader<R> {
+    inner: Option<R>,
+    cancelled: CancelWait,
+}
+
+impl<R> CancelReader<R> {
+    fn new(inner: R, token: CancellationToken) -> Self {
+        Self {
+            inner: Some(inner),
+            cancelled: token.wait(),
+        }
+    }
+
+    /// Get the inner reader if not cancelled.
+    fn poll_inner(&mut self, cx: &mut Context<'_>) -> Result<&mut R> {
+        if self.cancelled.poll_cancelled(cx).is_ready() {
+            self.inner = None;
+        }
+        self.inner.as_mut().ok_or_else(new_cancelled_error)
+    }
+}
+
+impl<R: oio::Read> oio::Read for CancelReader<R> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        self.poll_inner(cx)?.poll_read(cx, buf)
+    }
+
+    fn poll_seek(&mut self, cx: &mut Context<'_>, pos: io::SeekFrom) -> Poll<Result<u64>> {
+        self.poll_inner(cx)?.poll_seek(cx, pos)
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        match self.poll_inner(cx) {
+            Ok(r) => r.poll_next(cx),
+            Err(err) => Poll::Ready(Some(Err(err))),
+        }
+    }
+}
+
+/// Build the error returned while content exceeds `max_bytes`.
+pub(crate) fn new_max_bytes_error(max_bytes: u64) -> Error {
+    Error::new(
+        ErrorKind::Unexpected,
+        "content exceeds the max bytes of read",
+    )
+    .with_context("max_bytes", max_bytes.to_string())
+}
+
+/// LimitedReader returns an error once more than `max_bytes` would be
+/// delivered to users.
+///
+/// The limit is counted on all delivered bytes, seeking will not reset it.
+/// After the limit has been hit, no more reads will be sent to inner.
+pub(crate) struct LimitedReader<R> {
+    inner: R,
+    max_bytes: u64,
+    remaining: u64,
+    exceeded: bool,
+}
+
+impl<R> LimitedReader<R> {
+    pub(crate) fn new(inner: R, max_bytes: u64) -> Self {
+        Self {
+            inner,
+            max_bytes,
+            remaining: max_bytes,
+            exceeded: false,
+        }
+    }
+
+    /// Read at most one byte more than remaining so that we can tell
+    /// whether content ends exactly at the limit.
+    fn limit_buf(&self, len: usize) -> usize {
+        len.min(usize::try_from(self.remaining.saturating_add(1)).unwrap_or(usize::MAX))
+    }
+
+    fn consume(&mut self, n: usize) -> Result<()> {
+        if n as u64 > self.remaining {
+            self.exceeded = true;
+            return Err(new_max_bytes_error(self.max_bytes));
+        }
+        self.remaining -= n as u64;
+        Ok(())
+    }
+
+    fn check(&self) -> Result<()> {
+        if self.exceeded {
+            return Err(new_max_bytes_error(self.max_bytes));
+        }
+        Ok(())
+    }
+}
+
+impl<R: oio::Read> oio::Read for LimitedReader<R> {
+    fn poll_read(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<Result<usize>> {
+        self.check()?;
+
+        let size = self.limit_buf(buf.len());
+        let n = ready!(self.inner.poll_read(cx, &mut buf[..size]))?;
+        self.consume(n)?;
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_seek(&mut self, cx: &mut Context<'_>, pos: io::SeekFrom) -> Poll<Result<u64>> {
+        self.check()?;
+
+        self.inner.poll_seek(cx, pos)
+    }
+
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes>>> {
+        if let Err(err) = self.check() {
+            return Poll::Ready(Some(Err(err)));
+        }
+
+        match ready!(self.inner.poll_next(cx)) {
+            Some(Ok(bs)) => match self.consume(bs.len()) {
+                Ok(()) => Poll::Ready(Some(Ok(bs))),
+                Err(err) => Poll::Ready(Some(Err(err))),
+            },
+            v => Poll::Ready(v),
+        }
+    }
+}
+
+impl<R: oio::BlockingRead> oio::BlockingRead for LimitedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.check()?;
+
+        let size = self.limit_buf(buf.len());
+        let n = self.inner.read(&mut buf[..size])?;
+        self.consume(n)?;
+        Ok(n)
+    }
+
+    fn seek(&mut self, pos: io::SeekFrom) -> Result<u64> {
+        self.check()?;
+
+        self.inner.seek(pos)
+    }
+
+    fn next(&mut self) -> Option<Result<Bytes>> {
+        if let Err(err) = self.check() {
+            return Some(Err(err));
+        }
+
+        match self.inner.next() {
+            Some(Ok(bs)) => match self.consume(bs.len()) {
+                Ok(()) => Some(Ok(bs)),
+                Err(err) => Some(Err(err)),
+            },
+            v => v,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use rand::rngs::ThreadRng;
@@ -249,6 +573,7 @@ mod tests {
     use tokio::io::AsyncSeekExt;
 
     use crate::services;
+    use crate::ErrorKind;
     use crate::Operator;
 
     fn gen_random_bytes() -> Vec<u8> {
@@ -308,4 +633,41 @@ mod tests {
             .expect("read to end must succeed");
         assert_eq!(buf, content);
     }
+
+    #[tokio::test]
+    async fn test_reader_max_bytes() {
+        let op = Operator::new(services::Memory::default()).unwrap().finish();
+        let path = "test_file";
+
+        let content = vec![1; 1024];
+        op.write(path, content.clone())
+            .await
+            .expect("write must succeed");
+
+        let mut reader = op.reader_with(path).max_bytes(1024).await.unwrap();
+        let mut buf = Vec::new();
+        reader
+            .read_to_end(&mut buf)
+            .await
+            .expect("read exactly max bytes must succeed");
+        assert_eq!(buf, content);
+
+        let mut reader = op.reader_with(path).max_bytes(512).await.unwrap();
+        let mut buf = Vec::new();
+        reader
+            .read_to_end(&mut buf)
+            .await
+            .expect_err("read over max bytes must fail");
+        assert!(
+            buf.len() <= 512,
+            "delivered bytes must not exceed max bytes"
+        );
+
+        let err = op
+            .read_with(path)
+            .max_bytes(512)
+            .await
+            .expect_err("read over max bytes must fail");
+        assert_eq!(err.kind(), ErrorKind::Unexpected);
+    }
 }
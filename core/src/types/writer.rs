@@ -21,8 +21,12 @@ use std::task::Context;
 use std::task::Poll;
 
 use bytes::Bytes;
+use futures::future::BoxFuture;
+use futures::ready;
 use futures::AsyncWrite;
+use futures::FutureExt;
 use futures::TryStreamExt;
+use log::warn;
 
 use crate::raw::oio::Write;
 use crate::raw::oio::WriteBuf;
@@ -71,6 +75,14 @@ use crate::*;
 ///   on non-appendable file could return [`ErrorKind::ConditionNotMatch`] error.
 /// - Services that doesn't support append will return [`ErrorKind::Unsupported`] error when
 ///   creating writer with `append` enabled.
+///
+/// ### Abort
+///
+/// Call [`Writer::abort`] to cancel the write and clean up the server side
+/// state, like aborting the multipart upload of s3.
+///
+/// Writer will not be aborted while dropping. Users should call
+/// [`Writer::abort`] explicitly if the written data needs to be cleaned up.
 pub struct Writer {
     inner: oio::Writer,
 }
@@ -84,6 +96,15 @@ impl Writer {
     /// We don't want to expose those details to users so keep this function
     /// in crate only.
     pub(crate) async fn create(acc: FusedAccessor, path: &str, op: OpWrite) -> Result<Self> {
+        if op.atomic() {
+            check_atomic_write(&acc, path, &op)?;
+
+            let tmp = build_atomic_temp_path(path);
+            let (_, w) = acc.write(&tmp, op.with_atomic(false)).await?;
+            let w = AtomicWriter::new(w, acc, tmp, path.to_string());
+            return Ok(Writer { inner: Box::new(w) });
+        }
+
         let (_, w) = acc.write(path, op).await?;
 
         Ok(Writer { inner: w })
@@ -108,7 +129,6 @@ impl Writer {
     /// # Notes
     ///
     /// - Sink doesn't support to be used with write concurrently.
-    /// - Sink doesn't support to be used without content length now.
     ///
     /// # Examples
     ///
@@ -155,7 +175,6 @@ impl Writer {
     /// # Notes
     ///
     /// - Copy doesn't support to be used with write concurrently.
-    /// - Copy doesn't support to be used without content length now.
     ///
     /// # Examples
     ///
@@ -204,8 +223,36 @@ impl Writer {
     ///
     /// Close should only be called when the writer is not closed or
     /// aborted, otherwise an unexpected error could be returned.
-    pub async fn close(&mut self) -> Result<()> {
-        self.inner.close().await
+    ///
+    /// ## Metadata
+    ///
+    /// Metadata returned by service while committing will be returned, like
+    /// `etag` and `content_length` of azfile, so that callers don't need to
+    /// `stat` again. Only the returned fields are set, please check
+    /// [`Metadata::metakey`] before visiting them:
+    ///
+    /// ```no_run
+    /// # use anyhow::Result;
+    /// use opendal::Metakey;
+    /// use opendal::Operator;
+    /// # #[tokio::main]
+    /// # async fn test(op: Operator) -> Result<()> {
+    /// let mut w = op.writer("path/to/file").await?;
+    /// w.write(vec![0; 4096]).await?;
+    /// let meta = w.close().await?;
+    /// if meta.metakey().contains(Metakey::Etag) {
+    ///     println!("written with etag {:?}", meta.etag());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn close(&mut self) -> Result<Metadata> {
+        self.inner.close().await?;
+
+        Ok(self
+            .inner
+            .metadata()
+            .unwrap_or_else(|| Metadata::new(EntryMode::FILE)))
     }
 }
 
@@ -269,6 +316,15 @@ impl BlockingWriter {
     /// We don't want to expose those details to users so keep this function
     /// in crate only.
     pub(crate) fn create(acc: FusedAccessor, path: &str, op: OpWrite) -> Result<Self> {
+        if op.atomic() {
+            check_atomic_write(&acc, path, &op)?;
+
+            let tmp = build_atomic_temp_path(path);
+            let (_, w) = acc.blocking_write(&tmp, op.with_atomic(false))?;
+            let w = AtomicWriter::new(w, acc, tmp, path.to_string());
+            return Ok(BlockingWriter { inner: Box::new(w) });
+        }
+
         let (_, w) = acc.blocking_write(path, op)?;
 
         Ok(BlockingWriter { inner: w })
@@ -302,3 +358,205 @@ impl io::Write for BlockingWriter {
         Ok(())
     }
 }
+
+/// Check if atomic write could be performed on given accessor.
+fn check_atomic_write(acc: &FusedAccessor, path: &str, op: &OpWrite) -> Result<()> {
+    let cap = acc.info().full_capability();
+
+    let reason = if op.append() {
+        Some("atomic write can't be used with append")
+    } else if op.if_not_exists() {
+        Some("atomic write can't be used with if_not_exists")
+    } else if !cap.rename && !(cap.copy && cap.delete) {
+        Some("atomic write requires either rename or copy and delete")
+    } else {
+        None
+    };
+
+    match reason {
+        Some(reason) => Err(Error::new(ErrorKind::Unsupported, reason)
+            .with_operation(Operation::Write)
+            .with_context("service", acc.info().scheme())
+            .with_context("path", path)),
+        None => Ok(()),
+    }
+}
+
+/// Build a hidden temp path in the same dir of given path, so that rename
+/// won't move files across dirs.
+fn build_atomic_temp_path(path: &str) -> String {
+    let (parent, name) = match path.rsplit_once('/') {
+        Some((parent, name)) => (&path[..parent.len() + 1], name),
+        None => ("", path),
+    };
+
+    format!("{parent}.{name}.opendal-{}.tmp", uuid::Uuid::new_v4())
+}
+
+/// AtomicWriter writes content into a temp file, and moves it to the target
+/// path after content has been closed.
+///
+/// The temp file will be removed if the write fails or is aborted.
+struct AtomicWriter<W> {
+    inner: W,
+    acc: FusedAccessor,
+    tmp: String,
+    path: String,
+    fut: Option<BoxFuture<'static, Result<()>>>,
+}
+
+/// # Safety
+///
+/// We will only take `&mut Self` reference for AtomicWriter.
+unsafe impl<W: Send> Sync for AtomicWriter<W> {}
+
+impl<W> AtomicWriter<W> {
+    fn new(inner: W, acc: FusedAccessor, tmp: String, path: String) -> Self {
+        Self {
+            inner,
+            acc,
+            tmp,
+            path,
+            fut: None,
+        }
+    }
+}
+
+/// Move the temp file to target path.
+///
+/// Native rename will be used if possible, otherwise we fall back to copy
+/// then delete which is not atomic.
+async fn commit_atomic_write(acc: FusedAccessor, tmp: String, path: String) -> Result<()> {
+    if acc.info().full_capability().rename {
+        acc.rename(&tmp, &path, OpRename::new()).await?;
+        return Ok(());
+    }
+
+    acc.copy(&tmp, &path, OpCopy::new()).await?;
+    // Content has been committed, failing to remove temp file should not
+    // fail the write.
+    if let Err(err) = acc.delete(&tmp, OpDelete::new()).await {
+        warn!("atomic write failed to remove temp file {tmp}: {err}");
+    }
+    Ok(())
+}
+
+async fn cleanup_atomic_write(acc: FusedAccessor, tmp: String) {
+    if let Err(err) = acc.delete(&tmp, OpDelete::new()).await {
+        warn!("atomic write failed to remove temp file {tmp}: {err}");
+    }
+}
+
+impl oio::Write for AtomicWriter<oio::Writer> {
+    fn poll_write(&mut self, cx: &mut Context<'_>, bs: &dyn oio::WriteBuf) -> Poll<Result<usize>> {
+        self.inner.poll_write(cx, bs)
+    }
+
+    fn poll_close(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        if self.fut.is_none() {
+            let res = ready!(self.inner.poll_close(cx));
+
+            let acc = self.acc.clone();
+            let tmp = self.tmp.clone();
+            let path = self.path.clone();
+            self.fut = Some(Box::pin(async move {
+                let res = match res {
+                    Ok(()) => commit_atomic_write(acc.clone(), tmp.clone(), path).await,
+                    Err(err) => Err(err),
+                };
+                if res.is_err() {
+                    cleanup_atomic_write(acc, tmp).await;
+                }
+                res
+            }));
+        }
+
+        let fut = self.fut.as_mut().expect("close future must be valid");
+        let res = ready!(fut.poll_unpin(cx));
+        self.fut = None;
+        Poll::Ready(res)
+    }
+
+    fn poll_abort(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        if self.fut.is_none() {
+            let res = ready!(self.inner.poll_abort(cx));
+
+            let acc = self.acc.clone();
+            let tmp = self.tmp.clone();
+            self.fut = Some(Box::pin(async move {
+                cleanup_atomic_write(acc, tmp).await;
+                res
+            }));
+        }
+
+        let fut = self.fut.as_mut().expect("abort future must be valid");
+        let res = ready!(fut.poll_unpin(cx));
+        self.fut = None;
+        Poll::Ready(res)
+    }
+
+    fn metadata(&self) -> Option<Metadata> {
+        self.inner.metadata()
+    }
+}
+
+impl oio::BlockingWrite for AtomicWriter<oio::BlockingWriter> {
+    fn write(&mut self, bs: &dyn oio::WriteBuf) -> Result<usize> {
+        self.inner.write(bs)
+    }
+
+    fn close(&mut self) -> Result<()> {
+        let res = self.inner.close().and_then(|_| {
+            if self.acc.info().full_capability().rename {
+                self.acc
+                    .blocking_rename(&self.tmp, &self.path, OpRename::new())?;
+                return Ok(());
+            }
+
+            self.acc
+                .blocking_copy(&self.tmp, &self.path, OpCopy::new())?;
+            if let Err(err) = self.acc.blocking_delete(&self.tmp, OpDelete::new()) {
+                warn!(
+                    "atomic write failed to remove temp file {}: {err}",
+                    self.tmp
+                );
+            }
+            Ok(())
+        });
+
+        if res.is_err() {
+            if let Err(err) = self.acc.blocking_delete(&self.tmp, OpDelete::new()) {
+                warn!(
+                    "atomic write failed to remove temp file {}: {err}",
+                    self.tmp
+                );
+            }
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_atomic_temp_path() {
+        let cases = vec![
+            ("file", "file", ""),
+            ("file in dir", "dir/file", "dir/"),
+            ("file in nested dir", "a/b/file", "a/b/"),
+        ];
+
+        for (desc, path, parent) in cases {
+            let tmp = build_atomic_temp_path(path);
+            let name = path.rsplit('/').next().unwrap();
+            assert!(
+                tmp.starts_with(&format!("{parent}.{name}.opendal-")),
+                "{desc}: {tmp}"
+            );
+            assert!(tmp.ends_with(".tmp"), "{desc}: {tmp}");
+            assert_ne!(tmp, build_atomic_temp_path(path), "{desc}");
+        }
+    }
+}
@@ -22,7 +22,9 @@ use crate::*;
 pub fn behavior_blocking_copy_tests(op: &Operator) -> Vec<Trial> {
     let cap = op.info().full_capability();
 
-    if !(cap.read && cap.write && cap.copy && cap.blocking) {
+    // Services without native copy will fall back to stream copy, tests
+    // also require `create_dir` and `delete` to prepare and clean up.
+    if !(cap.read && cap.write && cap.create_dir && cap.delete && cap.blocking) {
         return vec![];
     }
 
@@ -45,6 +45,7 @@ pub fn behavior_blocking_write_tests(op: &Operator) -> Vec<Trial> {
         test_blocking_stat_with_special_chars,
         test_blocking_stat_not_exist,
         test_blocking_read_full,
+        test_blocking_read_to,
         test_blocking_read_range,
         test_blocking_read_large_range,
         test_blocking_read_not_exist,
@@ -225,6 +226,28 @@ pub fn test_blocking_read_full(op: BlockingOperator) -> Result<()> {
     Ok(())
 }
 
+/// Read into an existing buffer should append the content.
+pub fn test_blocking_read_to(op: BlockingOperator) -> Result<()> {
+    let path = uuid::Uuid::new_v4().to_string();
+    let (content, size) = gen_bytes(op.info().full_capability());
+
+    op.write(&path, content.clone())
+        .expect("write must succeed");
+
+    let mut buf = b"prefix".to_vec();
+    let n = op.read_to(&path, &mut buf)?;
+    assert_eq!(n, size, "read size");
+    assert_eq!(&buf[..6], b"prefix", "existing content must be kept");
+    assert_eq!(
+        format!("{:x}", Sha256::digest(&buf[6..])),
+        format!("{:x}", Sha256::digest(&content)),
+        "read content"
+    );
+
+    op.delete(&path).expect("delete must succeed");
+    Ok(())
+}
+
 /// Read range content should match.
 pub fn test_blocking_read_range(op: BlockingOperator) -> Result<()> {
     if !op.info().full_capability().read_with_range {
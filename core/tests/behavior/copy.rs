@@ -22,7 +22,9 @@ use crate::*;
 pub fn behavior_copy_tests(op: &Operator) -> Vec<Trial> {
     let cap = op.info().full_capability();
 
-    if !(cap.read && cap.write && cap.copy) {
+    // Services without native copy will fall back to stream copy, tests
+    // also require `create_dir` and `delete` to prepare and clean up.
+    if !(cap.read && cap.write && cap.create_dir && cap.delete) {
         return vec![];
     }
 
@@ -35,7 +37,8 @@ pub fn behavior_copy_tests(op: &Operator) -> Vec<Trial> {
         test_copy_target_dir,
         test_copy_self,
         test_copy_nested,
-        test_copy_overwrite
+        test_copy_overwrite,
+        test_copy_dir
     )
 }
 
@@ -188,3 +191,49 @@ pub async fn test_copy_overwrite(op: Operator) -> Result<()> {
     op.delete(&target_path).await.expect("delete must succeed");
     Ok(())
 }
+
+/// Copy a dir should copy all nested files.
+pub async fn test_copy_dir(op: Operator) -> Result<()> {
+    if !op.info().full_capability().list {
+        return Ok(());
+    }
+
+    let source_dir = format!("{}/", uuid::Uuid::new_v4());
+    let target_dir = format!("{}/", uuid::Uuid::new_v4());
+    let (content, _) = gen_bytes(op.info().full_capability());
+
+    let files = ["a", "b/c", "b/d/e"];
+    for file in files {
+        op.write(&format!("{source_dir}{file}"), content.clone())
+            .await?;
+    }
+
+    let mut copied = Vec::new();
+    op.copy_dir_with_progress(&source_dir, &target_dir, |path, _| {
+        copied.push(path.to_string())
+    })
+    .await?;
+
+    for file in files {
+        assert!(
+            copied.contains(&format!("{source_dir}{file}")),
+            "{file} must be reported"
+        );
+        let target_content = op
+            .read(&format!("{target_dir}{file}"))
+            .await
+            .expect("read must succeed");
+        assert_eq!(target_content, content, "{file}");
+    }
+
+    // Copy into itself is not allowed.
+    let err = op
+        .copy_dir(&source_dir, &format!("{source_dir}inner/"))
+        .await
+        .expect_err("copy into itself must fail");
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+    op.remove_all(&source_dir).await?;
+    op.remove_all(&target_dir).await?;
+    Ok(())
+}
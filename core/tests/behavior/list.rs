@@ -17,6 +17,7 @@
 
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::time::Duration;
 
 use anyhow::Result;
 use futures::stream::FuturesUnordered;
@@ -36,16 +37,21 @@ pub fn behavior_list_tests(op: &Operator) -> Vec<Trial> {
     async_trials!(
         op,
         test_check,
+        test_ping,
         test_list_dir,
         test_list_dir_with_metakey,
         test_list_dir_with_metakey_complete,
+        test_list_dir_with_metakey_complete_concurrent,
         test_list_rich_dir,
         test_list_empty_dir,
         test_list_non_exist_dir,
         test_list_sub_dir,
         test_list_nested_dir,
+        test_list_result,
         test_list_dir_with_file_path,
         test_list_with_start_after,
+        test_list_with_start_after_token,
+        test_list_with_include_self,
         test_scan,
         test_scan_root,
         test_remove_all
@@ -59,6 +65,14 @@ pub async fn test_check(op: Operator) -> Result<()> {
     Ok(())
 }
 
+/// Ping should be OK and return the latency.
+pub async fn test_ping(op: Operator) -> Result<()> {
+    let latency = op.ping().await.expect("operator ping is ok");
+    assert!(latency < Duration::from_secs(60), "latency: {latency:?}");
+
+    Ok(())
+}
+
 /// List dir should return newly created file.
 pub async fn test_list_dir(op: Operator) -> Result<()> {
     let parent = uuid::Uuid::new_v4().to_string();
@@ -175,6 +189,35 @@ pub async fn test_list_dir_with_metakey_complete(op: Operator) -> Result<()> {
     Ok(())
 }
 
+/// List dir with metakey complete and concurrent stats
+pub async fn test_list_dir_with_metakey_complete_concurrent(op: Operator) -> Result<()> {
+    let parent = uuid::Uuid::new_v4().to_string();
+    let mut expected: Vec<(String, u64)> = Vec::new();
+    for idx in 0..5 {
+        let path = format!("{parent}/file-{idx}");
+        let content = vec![0; idx + 1];
+        op.write(&path, content).await.expect("write must succeed");
+        expected.push((path, idx as u64 + 1));
+    }
+
+    let mut actual: Vec<(String, u64)> = op
+        .list_with(&format!("{parent}/"))
+        .metakey(Metakey::Complete)
+        .concurrent(3)
+        .await?
+        .into_iter()
+        .filter(|de| de.metadata().mode() == EntryMode::FILE)
+        .map(|de| (de.path().to_string(), de.metadata().content_length()))
+        .collect();
+    actual.sort();
+    assert_eq!(actual, expected);
+
+    op.remove_all(&format!("{parent}/"))
+        .await
+        .expect("remove must succeed");
+    Ok(())
+}
+
 /// listing a directory, which contains more objects than a single page can take.
 pub async fn test_list_rich_dir(op: Operator) -> Result<()> {
     op.create_dir("test_list_rich_dir/").await?;
@@ -313,6 +356,32 @@ pub async fn test_list_nested_dir(op: Operator) -> Result<()> {
     Ok(())
 }
 
+/// List result should split common prefixes and entries.
+pub async fn test_list_result(op: Operator) -> Result<()> {
+    let dir = format!("{}/", uuid::Uuid::new_v4());
+
+    let file_path = format!("{dir}{}", uuid::Uuid::new_v4());
+    let dir_path = format!("{dir}{}/", uuid::Uuid::new_v4());
+
+    op.write(&file_path, "test_list_result")
+        .await
+        .expect("write must succeed");
+    op.create_dir(&dir_path).await.expect("create must succeed");
+
+    let result = ListResult::from(op.list(&dir).await?);
+    debug!("got list result: {:?}", result);
+
+    let prefixes: Vec<_> = result.common_prefixes().iter().map(|e| e.path()).collect();
+    let entries: Vec<_> = result.entries().iter().map(|e| e.path()).collect();
+    assert_eq!(prefixes, vec![dir_path.as_str()]);
+    assert_eq!(entries, vec![file_path.as_str()]);
+
+    op.delete(&file_path).await.expect("delete must succeed");
+    op.delete(&dir_path).await.expect("delete must succeed");
+    op.delete(&dir).await.expect("delete must succeed");
+    Ok(())
+}
+
 /// List with path file should auto add / suffix.
 pub async fn test_list_dir_with_file_path(op: Operator) -> Result<()> {
     let parent = uuid::Uuid::new_v4().to_string();
@@ -365,6 +434,91 @@ pub async fn test_list_with_start_after(op: Operator) -> Result<()> {
     Ok(())
 }
 
+/// List with include self should return the listed dir first and only once.
+pub async fn test_list_with_include_self(op: Operator) -> Result<()> {
+    if !op.info().full_capability().list_with_include_self {
+        return Ok(());
+    }
+
+    let dir = &format!("{}/", uuid::Uuid::new_v4());
+    let path = format!("{dir}sub/file-{}", uuid::Uuid::new_v4());
+    op.write(&path, "content")
+        .await
+        .expect("write must succeed");
+
+    for recursive in [false, true] {
+        let delimiter = if recursive { "" } else { "/" };
+        let entries = op
+            .list_with(dir)
+            .delimiter(delimiter)
+            .include_self(true)
+            .await?;
+
+        assert_eq!(entries[0].path(), dir);
+        assert_eq!(entries[0].metadata().mode(), EntryMode::DIR);
+        assert_eq!(entries.iter().filter(|e| e.path() == dir).count(), 1);
+    }
+
+    op.remove_all(dir).await?;
+
+    Ok(())
+}
+
+/// Listing resumed by continuation token should return the remaining entries.
+pub async fn test_list_with_start_after_token(op: Operator) -> Result<()> {
+    if !op.info().full_capability().list_with_start_after_token {
+        return Ok(());
+    }
+
+    let dir = &format!("{}/", uuid::Uuid::new_v4());
+    op.create_dir(dir).await?;
+
+    let given: HashSet<String> = (0..10)
+        .map(|idx| format!("{dir}file-{idx}-{}", uuid::Uuid::new_v4()))
+        .collect();
+
+    given
+        .iter()
+        .map(|name| async {
+            op.write(name, "content")
+                .await
+                .expect("create must succeed");
+        })
+        .collect::<FuturesUnordered<_>>()
+        .collect::<Vec<_>>()
+        .await;
+
+    // Consume entries until we get a token.
+    let mut actual = HashSet::new();
+    let mut objects = op.lister_with(dir).limit(3).await?;
+    let mut token = None;
+    while let Some(o) = objects.try_next().await? {
+        actual.insert(o.path().to_string());
+        token = objects.continuation_token();
+        if token.is_some() {
+            break;
+        }
+    }
+    let token = token.expect("list with limit must return a continuation token");
+
+    // Resume from the token in a new lister.
+    let mut objects = op
+        .lister_with(dir)
+        .limit(3)
+        .start_after_token(&token)
+        .await?;
+    while let Some(o) = objects.try_next().await? {
+        actual.insert(o.path().to_string());
+    }
+    actual.remove(dir);
+
+    assert_eq!(given, actual);
+
+    op.remove_all(dir).await?;
+
+    Ok(())
+}
+
 pub async fn test_scan_root(op: Operator) -> Result<()> {
     let w = op.lister_with("").delimiter("").await?;
     let actual = w
@@ -27,9 +27,11 @@ use futures::stream;
 use futures::AsyncReadExt;
 use futures::AsyncSeekExt;
 use futures::StreamExt;
+use futures::TryStreamExt;
 use http::StatusCode;
 use log::debug;
 use log::warn;
+use md5::Md5;
 use reqwest::Url;
 use sha2::Digest;
 use sha2::Sha256;
@@ -47,24 +49,40 @@ pub fn behavior_write_tests(op: &Operator) -> Vec<Trial> {
         op,
         test_create_dir,
         test_create_dir_existing,
+        test_create_dir_nested,
+        test_create_dir_with_user_metadata,
         test_write_only,
         test_write_with_empty_content,
         test_write_with_dir_path,
         test_write_with_special_chars,
         test_write_with_cache_control,
         test_write_with_content_type,
+        test_write_with_unsupported_content_type,
         test_write_with_content_disposition,
+        test_write_with_user_metadata,
+        test_write_with_if_not_exists,
+        test_write_with_atomic,
+        test_write_with_checksum,
+        test_write_with_encryption,
         test_stat_file,
         test_stat_dir,
         test_stat_with_special_chars,
         test_stat_not_cleaned_path,
         test_stat_not_exist,
+        test_exists,
+        test_stat_many,
         test_stat_with_if_match,
         test_stat_with_if_none_match,
         test_stat_root,
         test_read_full,
+        test_read_chunked,
+        test_download_to,
+        test_read_with_cancel_token,
+        test_read_to,
         test_read_range,
+        test_read_tail,
         test_read_large_range,
+        test_read_with_invalid_range,
         test_reader_range,
         test_reader_from,
         test_reader_tail,
@@ -79,15 +97,21 @@ pub fn behavior_write_tests(op: &Operator) -> Vec<Trial> {
         test_read_with_override_cache_control,
         test_read_with_override_content_disposition,
         test_read_with_override_content_type,
+        test_read_with_checksum,
         test_delete_file,
         test_delete_empty_dir,
         test_delete_with_special_chars,
         test_delete_not_existing,
+        test_delete_with_if_match,
         test_delete_stream,
+        test_delete_iter,
         test_remove_one_file,
         test_writer_write,
+        test_writer_close_with_metadata,
         test_writer_sink,
+        test_write_from_stream,
         test_writer_copy,
+        test_writer_with_concurrent,
         test_writer_abort,
         test_writer_futures_copy,
         test_fuzz_unsized_writer,
@@ -123,6 +147,56 @@ pub async fn test_create_dir_existing(op: Operator) -> Result<()> {
     Ok(())
 }
 
+/// Create nested dir should create all parents.
+pub async fn test_create_dir_nested(op: Operator) -> Result<()> {
+    let parent = format!("{}/", uuid::Uuid::new_v4());
+    let path = format!("{parent}a/b/");
+
+    op.create_dir(&path).await?;
+
+    for p in [parent.clone(), format!("{parent}a/"), path.clone()] {
+        let meta = op.stat(&p).await?;
+        assert_eq!(meta.mode(), EntryMode::DIR, "{p}");
+    }
+
+    for p in [path, format!("{parent}a/"), parent] {
+        op.delete(&p).await.expect("delete must succeed");
+    }
+    Ok(())
+}
+
+/// Create dir with user metadata, and update it on the existing dir.
+pub async fn test_create_dir_with_user_metadata(op: Operator) -> Result<()> {
+    if !op.info().full_capability().create_dir_with_user_metadata {
+        return Ok(());
+    }
+
+    let path = format!("{}/", uuid::Uuid::new_v4());
+
+    op.create_dir_with(&path)
+        .user_metadata([("owner", "alice")])
+        .await?;
+
+    let meta = op.stat(&path).await.expect("stat must succeed");
+    assert_eq!(meta.mode(), EntryMode::DIR);
+    let user_metadata = meta.user_metadata().expect("user metadata must exist");
+    assert_eq!(
+        user_metadata.get("owner").map(|v| v.as_str()),
+        Some("alice")
+    );
+
+    op.create_dir_with(&path)
+        .user_metadata([("owner", "bob")])
+        .await?;
+
+    let meta = op.stat(&path).await.expect("stat must succeed");
+    let user_metadata = meta.user_metadata().expect("user metadata must exist");
+    assert_eq!(user_metadata.get("owner").map(|v| v.as_str()), Some("bob"));
+
+    op.delete(&path).await.expect("delete must succeed");
+    Ok(())
+}
+
 /// Write a single file and test with stat.
 pub async fn test_write_only(op: Operator) -> Result<()> {
     let path = uuid::Uuid::new_v4().to_string();
@@ -244,6 +318,28 @@ pub async fn test_write_with_content_type(op: Operator) -> Result<()> {
     Ok(())
 }
 
+/// Write a single file with content type should fail fast if not supported.
+pub async fn test_write_with_unsupported_content_type(op: Operator) -> Result<()> {
+    if op.info().full_capability().write_with_content_type {
+        return Ok(());
+    }
+
+    let path = uuid::Uuid::new_v4().to_string();
+    let (content, _) = gen_bytes(op.info().full_capability());
+
+    let err = op
+        .write_with(&path, content)
+        .content_type("application/json")
+        .await
+        .expect_err("write with content type must fail");
+    assert_eq!(err.kind(), ErrorKind::Unsupported);
+
+    let res = op.stat(&path).await;
+    assert_eq!(res.unwrap_err().kind(), ErrorKind::NotFound);
+
+    Ok(())
+}
+
 /// Write a single file with content disposition should succeed.
 pub async fn test_write_with_content_disposition(op: Operator) -> Result<()> {
     if !op.info().full_capability().write_with_content_disposition {
@@ -271,6 +367,148 @@ pub async fn test_write_with_content_disposition(op: Operator) -> Result<()> {
     Ok(())
 }
 
+/// Write a single file with user metadata should succeed.
+pub async fn test_write_with_user_metadata(op: Operator) -> Result<()> {
+    if !op.info().full_capability().write_with_user_metadata {
+        return Ok(());
+    }
+
+    let path = uuid::Uuid::new_v4().to_string();
+    let (content, _) = gen_bytes(op.info().full_capability());
+
+    op.write_with(&path, content)
+        .user_metadata([("owner", "alice")])
+        .await?;
+
+    let meta = op.stat(&path).await.expect("stat must succeed");
+    let user_metadata = meta.user_metadata().expect("user metadata must exist");
+    assert_eq!(
+        user_metadata.get("owner").map(|v| v.as_str()),
+        Some("alice")
+    );
+
+    op.delete(&path).await.expect("delete must succeed");
+
+    Ok(())
+}
+
+/// Write a single file with if_not_exists should fail if the file exists.
+pub async fn test_write_with_if_not_exists(op: Operator) -> Result<()> {
+    if !op.info().full_capability().write_with_if_not_exists {
+        return Ok(());
+    }
+
+    let path = uuid::Uuid::new_v4().to_string();
+    let (content, size) = gen_bytes(op.info().full_capability());
+
+    op.write_with(&path, content.clone())
+        .if_not_exists(true)
+        .await?;
+
+    let res = op
+        .write_with(&path, vec![0; size])
+        .if_not_exists(true)
+        .await;
+    assert!(res.is_err());
+    assert_eq!(res.unwrap_err().kind(), ErrorKind::AlreadyExists);
+
+    // The existing content must not be touched.
+    let bs = op.read(&path).await.expect("read must succeed");
+    assert_eq!(bs, content);
+
+    op.delete(&path).await.expect("delete must succeed");
+
+    Ok(())
+}
+
+/// Write a file atomically should succeed without leaving temp files behind.
+pub async fn test_write_with_atomic(op: Operator) -> Result<()> {
+    let cap = op.info().full_capability();
+    if !(cap.rename || (cap.copy && cap.delete)) || !cap.list {
+        return Ok(());
+    }
+
+    let dir = format!("{}/", uuid::Uuid::new_v4());
+    let path = format!("{dir}file");
+    let (content, _) = gen_bytes(cap);
+
+    op.write_with(&path, content.clone()).atomic(true).await?;
+
+    let bs = op.read(&path).await.expect("read must succeed");
+    assert_eq!(bs, content);
+
+    let entries = op.list(&dir).await?;
+    let paths: Vec<_> = entries
+        .iter()
+        .map(|e| e.path().to_string())
+        .filter(|p| p != &dir)
+        .collect();
+    assert_eq!(paths, vec![path.clone()], "temp file must be removed");
+
+    op.remove_all(&dir).await.expect("remove must succeed");
+
+    Ok(())
+}
+
+/// Write a single file with checksum should succeed.
+pub async fn test_write_with_checksum(op: Operator) -> Result<()> {
+    if !op.info().full_capability().write_with_checksum {
+        return Ok(());
+    }
+
+    let path = uuid::Uuid::new_v4().to_string();
+    let (content, size) = gen_bytes(op.info().full_capability());
+
+    op.write_with(&path, content.clone())
+        .checksum(Checksum::Md5(Md5::digest(&content).into()))
+        .await?;
+
+    let meta = op.stat(&path).await.expect("stat must succeed");
+    assert_eq!(meta.content_length(), size as u64);
+
+    // Write with mismatched checksum should be rejected.
+    let res = op
+        .write_with(&path, content)
+        .checksum(Checksum::Md5([0; 16]))
+        .await;
+    assert!(res.is_err());
+
+    op.delete(&path).await.expect("delete must succeed");
+
+    Ok(())
+}
+
+/// Write a single file with customer provided key and read it back.
+pub async fn test_write_with_encryption(op: Operator) -> Result<()> {
+    let cap = op.info().full_capability();
+    if !(cap.write_with_encryption && cap.read_with_encryption) {
+        return Ok(());
+    }
+
+    let path = uuid::Uuid::new_v4().to_string();
+    let (content, _) = gen_bytes(op.info().full_capability());
+
+    let mut key = [0; 32];
+    key[..16].copy_from_slice(uuid::Uuid::new_v4().as_bytes());
+    key[16..].copy_from_slice(uuid::Uuid::new_v4().as_bytes());
+    let encryption = ServerSideEncryption::CustomerKey(key);
+
+    op.write_with(&path, content.clone())
+        .encryption(encryption.clone())
+        .await?;
+
+    let bs = op.read_with(&path).encryption(encryption).await?;
+    assert_eq!(
+        format!("{:x}", Sha256::digest(&bs)),
+        format!("{:x}", Sha256::digest(&content)),
+        "read content"
+    );
+
+    op.delete(&path).await.expect("delete must succeed");
+
+    Ok(())
+}
+
 /// Stat existing file should return metadata
 pub async fn test_stat_file(op: Operator) -> Result<()> {
     let path = uuid::Uuid::new_v4().to_string();
@@ -352,6 +590,53 @@ pub async fn test_stat_not_exist(op: Operator) -> Result<()> {
     Ok(())
 }
 
+/// Stat many should return results of all paths in order.
+pub async fn test_stat_many(op: Operator) -> Result<()> {
+    let path = uuid::Uuid::new_v4().to_string();
+    let absent = uuid::Uuid::new_v4().to_string();
+    let (content, size) = gen_bytes(op.info().full_capability());
+
+    op.write(&path, content).await.expect("write must succeed");
+
+    let results: Vec<_> = op
+        .stat_many(vec![path.clone(), absent.clone()])
+        .collect()
+        .await;
+    assert_eq!(results.len(), 2);
+
+    let (p, meta) = &results[0];
+    assert_eq!(p, &path);
+    let meta = meta.as_ref().expect("stat must succeed");
+    assert_eq!(meta.mode(), EntryMode::FILE);
+    assert_eq!(meta.content_length(), size as u64);
+
+    let (p, meta) = &results[1];
+    assert_eq!(p, &absent);
+    assert_eq!(
+        meta.as_ref().expect_err("stat must fail").kind(),
+        ErrorKind::NotFound
+    );
+
+    op.delete(&path).await.expect("delete must succeed");
+    Ok(())
+}
+
+/// Exists should return true for existing file and false for absent one.
+pub async fn test_exists(op: Operator) -> Result<()> {
+    let path = uuid::Uuid::new_v4().to_string();
+    let (content, _) = gen_bytes(op.info().full_capability());
+
+    assert!(!op.exists(&path).await?);
+
+    op.write(&path, content).await.expect("write must succeed");
+    assert!(op.exists(&path).await?);
+
+    op.delete(&path).await.expect("delete must succeed");
+    assert!(!op.exists(&path).await?);
+
+    Ok(())
+}
+
 /// Stat with if_match should succeed, else get a ConditionNotMatch error.
 pub async fn test_stat_with_if_match(op: Operator) -> Result<()> {
     if !op.info().full_capability().stat_with_if_match {
@@ -453,6 +738,152 @@ pub async fn test_read_full(op: Operator) -> Result<()> {
     Ok(())
 }
 
+/// Read full content as fixed-size chunks.
+pub async fn test_read_chunked(op: Operator) -> Result<()> {
+    let path = uuid::Uuid::new_v4().to_string();
+    debug!("Generate a random file: {}", &path);
+    let (content, size) = gen_bytes(op.info().full_capability());
+
+    op.write(&path, content.clone())
+        .await
+        .expect("write must succeed");
+
+    let chunk_size = 1000;
+    let chunks: Vec<_> = op
+        .read_with(&path)
+        .chunked(chunk_size)
+        .try_collect()
+        .await?;
+    if let Some((last, chunks)) = chunks.split_last() {
+        for bs in chunks {
+            assert_eq!(bs.len(), chunk_size, "chunk size");
+        }
+        assert!(
+            !last.is_empty() && last.len() <= chunk_size,
+            "last chunk size"
+        );
+    }
+
+    let bs = chunks.concat();
+    assert_eq!(size, bs.len(), "read size");
+    assert_eq!(
+        format!("{:x}", Sha256::digest(&bs)),
+        format!("{:x}", Sha256::digest(&content)),
+        "read content"
+    );
+
+    op.delete(&path).await.expect("delete must succeed");
+    Ok(())
+}
+
+/// Download should resume from local file if etag is not changed.
+pub async fn test_download_to(op: Operator) -> Result<()> {
+    let path = uuid::Uuid::new_v4().to_string();
+    debug!("Generate a random file: {}", &path);
+    let (content, size) = gen_bytes(op.info().full_capability());
+
+    op.write(&path, content.clone())
+        .await
+        .expect("write must succeed");
+
+    let local = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+    let etag_path = format!("{}.etag", local.display());
+
+    // Fresh download.
+    let n = op.download_to(&path, &local).await?;
+    assert_eq!(n, size as u64);
+    assert_eq!(std::fs::read(&local)?, content);
+    assert!(
+        std::fs::metadata(&etag_path).is_err(),
+        "etag must be removed"
+    );
+
+    // Simulate an interrupted download.
+    let meta = op.stat(&path).await?;
+    if let Some(etag) = meta.etag() {
+        std::fs::write(&local, &content[..size / 2])?;
+        std::fs::write(&etag_path, etag)?;
+
+        let n = op.download_to(&path, &local).await?;
+        assert_eq!(n, size as u64);
+        assert_eq!(std::fs::read(&local)?, content);
+    }
+
+    // Stale etag should restart download.
+    std::fs::write(&local, vec![0; size])?;
+    std::fs::write(&etag_path, "\"stale\"")?;
+    let n = op.download_to(&path, &local).await?;
+    assert_eq!(n, size as u64);
+    assert_eq!(std::fs::read(&local)?, content);
+
+    std::fs::remove_file(&local)?;
+    op.delete(&path).await.expect("delete must succeed");
+    Ok(())
+}
+
+/// Read with a cancelled token should return Cancelled.
+pub async fn test_read_with_cancel_token(op: Operator) -> Result<()> {
+    let path = uuid::Uuid::new_v4().to_string();
+    debug!("Generate a random file: {}", &path);
+    let (content, size) = gen_bytes(op.info().full_capability());
+
+    op.write(&path, content.clone())
+        .await
+        .expect("write must succeed");
+
+    let token = CancellationToken::new();
+    let bs = op.read_with(&path).cancel_token(token.clone()).await?;
+    assert_eq!(bs.len(), size, "read size");
+
+    let mut r = op.reader_with(&path).cancel_token(token.clone()).await?;
+    token.cancel();
+
+    let err = op
+        .read_with(&path)
+        .cancel_token(token.clone())
+        .await
+        .expect_err("read must be cancelled");
+    assert_eq!(err.kind(), ErrorKind::Cancelled);
+
+    let mut buf = Vec::new();
+    let err = r
+        .read_to_end(&mut buf)
+        .await
+        .expect_err("reader must be cancelled");
+    assert_eq!(
+        err.into_inner()
+            .and_then(|err| err.downcast::<Error>().ok())
+            .map(|err| err.kind()),
+        Some(ErrorKind::Cancelled)
+    );
+
+    op.delete(&path).await.expect("delete must succeed");
+    Ok(())
+}
+
+/// Read into an existing buffer should append the content.
+pub async fn test_read_to(op: Operator) -> Result<()> {
+    let path = uuid::Uuid::new_v4().to_string();
+    let (content, size) = gen_bytes(op.info().full_capability());
+
+    op.write(&path, content.clone())
+        .await
+        .expect("write must succeed");
+
+    let mut buf = b"prefix".to_vec();
+    let n = op.read_to(&path, &mut buf).await?;
+    assert_eq!(n, size, "read size");
+    assert_eq!(&buf[..6], b"prefix", "existing content must be kept");
+    assert_eq!(
+        format!("{:x}", Sha256::digest(&buf[6..])),
+        format!("{:x}", Sha256::digest(&content)),
+        "read content"
+    );
+
+    op.delete(&path).await.expect("delete must succeed");
+    Ok(())
+}
+
 /// Read range content should match.
 pub async fn test_read_range(op: Operator) -> Result<()> {
     if !op.info().full_capability().read_with_range {
@@ -483,6 +914,41 @@ pub async fn test_read_range(op: Operator) -> Result<()> {
     Ok(())
 }
 
+/// Read the tail of file should match.
+pub async fn test_read_tail(op: Operator) -> Result<()> {
+    if !op.info().full_capability().read_with_range {
+        return Ok(());
+    }
+
+    let path = uuid::Uuid::new_v4().to_string();
+    debug!("Generate a random file: {}", &path);
+    let (content, size) = gen_bytes(op.info().full_capability());
+    let (_, length) = gen_offset_length(size);
+
+    op.write(&path, content.clone())
+        .await
+        .expect("write must succeed");
+
+    let bs = match op.read_with(&path).tail(length).await {
+        Ok(bs) => bs,
+        // Services that can't express suffix range must return `Unsupported`.
+        Err(err) if err.kind() == ErrorKind::Unsupported => {
+            op.delete(&path).await.expect("delete must succeed");
+            return Ok(());
+        }
+        Err(err) => return Err(err.into()),
+    };
+    assert_eq!(bs.len() as u64, length, "read size");
+    assert_eq!(
+        format!("{:x}", Sha256::digest(&bs)),
+        format!("{:x}", Sha256::digest(&content[size - length as usize..])),
+        "read content"
+    );
+
+    op.delete(&path).await.expect("delete must succeed");
+    Ok(())
+}
+
 /// Read large range content should match.
 pub async fn test_read_large_range(op: Operator) -> Result<()> {
     if !op.info().full_capability().read_with_range {
@@ -514,6 +980,30 @@ pub async fn test_read_large_range(op: Operator) -> Result<()> {
     Ok(())
 }
 
+/// Read with range start larger than end should get `InvalidInput`, and
+/// empty range should get empty content.
+pub async fn test_read_with_invalid_range(op: Operator) -> Result<()> {
+    if !op.info().full_capability().read_with_range {
+        return Ok(());
+    }
+
+    let path = uuid::Uuid::new_v4().to_string();
+    debug!("Generate a random file: {}", &path);
+    let (content, _) = gen_bytes(op.info().full_capability());
+
+    op.write(&path, content).await.expect("write must succeed");
+
+    let res = op.read_with(&path).range(1024..512).await;
+    assert!(res.is_err());
+    assert_eq!(res.unwrap_err().kind(), ErrorKind::InvalidInput);
+
+    let bs = op.read_with(&path).range(512..512).await?;
+    assert!(bs.is_empty(), "read with empty range");
+
+    op.delete(&path).await.expect("delete must succeed");
+    Ok(())
+}
+
 /// Read range content should match.
 pub async fn test_reader_range(op: Operator) -> Result<()> {
     if !op.info().full_capability().read_with_range {
@@ -1043,6 +1533,48 @@ pub async fn test_writer_abort(op: Operator) -> Result<()> {
     Ok(())
 }
 
+/// Read file with checksum should verify the content with the stored one.
+pub async fn test_read_with_checksum(op: Operator) -> Result<()> {
+    let path = uuid::Uuid::new_v4().to_string();
+    let (content, size) = gen_bytes(op.info().full_capability());
+
+    op.write(&path, content.clone())
+        .await
+        .expect("write must succeed");
+
+    // Services that don't return the stored checksum can't verify content.
+    let bs = match op.read_with(&path).verify_checksum(true).await {
+        Err(e) if e.kind() == ErrorKind::Unsupported => {
+            op.delete(&path).await.expect("delete must succeed");
+            return Ok(());
+        }
+        v => v?,
+    };
+    assert_eq!(bs.len(), size, "read size");
+    assert_eq!(
+        format!("{:x}", Sha256::digest(&bs)),
+        format!("{:x}", Sha256::digest(&content)),
+        "read content"
+    );
+
+    let mut r = op.reader_with(&path).verify_checksum(true).await?;
+    // Seeking before content is verified is not allowed.
+    let res = r.seek(std::io::SeekFrom::Start(1)).await;
+    assert!(res.is_err());
+    let mut bs = Vec::new();
+    r.read_to_end(&mut bs).await?;
+    assert_eq!(bs.len(), size, "read size");
+
+    // Checksum can't be used with range.
+    let res = op.read_with(&path).range(0..1).verify_checksum(true).await;
+    assert!(res.is_err());
+    assert_eq!(res.unwrap_err().kind(), ErrorKind::InvalidInput);
+
+    op.delete(&path).await.expect("delete must succeed");
+
+    Ok(())
+}
+
 /// Delete existing file should succeed.
 pub async fn test_delete_file(op: Operator) -> Result<()> {
     let path = uuid::Uuid::new_v4().to_string();
@@ -1105,6 +1637,36 @@ pub async fn test_delete_not_existing(op: Operator) -> Result<()> {
     Ok(())
 }
 
+/// Delete with if_match should only succeed if the etag is matched.
+pub async fn test_delete_with_if_match(op: Operator) -> Result<()> {
+    if !op.info().full_capability().delete_with_if_match {
+        return Ok(());
+    }
+
+    let path = uuid::Uuid::new_v4().to_string();
+    let (content, _) = gen_bytes(op.info().full_capability());
+
+    op.write(&path, content.clone())
+        .await
+        .expect("write must succeed");
+
+    let meta = op.stat(&path).await?;
+    let etag = meta.etag().expect("etag must exist");
+
+    let res = op.delete_with(&path).if_match("\"invalid_etag\"").await;
+    assert!(res.is_err());
+    assert_eq!(res.unwrap_err().kind(), ErrorKind::ConditionNotMatch);
+    assert!(
+        op.is_exist(&path).await?,
+        "file must be kept if etag is not matched"
+    );
+
+    op.delete_with(&path).if_match(etag).await?;
+    assert!(!op.is_exist(&path).await?);
+
+    Ok(())
+}
+
 /// Remove one file
 pub async fn test_remove_one_file(op: Operator) -> Result<()> {
     let path = uuid::Uuid::new_v4().to_string();
@@ -1147,6 +1709,29 @@ pub async fn test_delete_stream(op: Operator) -> Result<()> {
     Ok(())
 }
 
+/// Delete via iter.
+pub async fn test_delete_iter(op: Operator) -> Result<()> {
+    let dir = uuid::Uuid::new_v4().to_string();
+    op.create_dir(&format!("{dir}/"))
+        .await
+        .expect("creat must succeed");
+
+    let expected: Vec<_> = (0..100).map(|v| format!("{dir}/{v}")).collect();
+    for path in expected.iter() {
+        op.write(path, "delete_iter").await?;
+    }
+
+    let failed = op.with_limit(30).delete_iter(expected.clone()).await?;
+    assert!(failed.is_empty(), "no path should fail: {failed:?}");
+
+    // Stat it again to check.
+    for path in expected.iter() {
+        assert!(!op.is_exist(path).await?, "{path} should be removed")
+    }
+
+    Ok(())
+}
+
 /// Append data into writer
 pub async fn test_writer_write(op: Operator) -> Result<()> {
     if !(op.info().full_capability().write_can_multi) {
@@ -1183,6 +1768,41 @@ pub async fn test_writer_write(op: Operator) -> Result<()> {
     Ok(())
 }
 
+/// Metadata returned by writer close should match stat.
+pub async fn test_writer_close_with_metadata(op: Operator) -> Result<()> {
+    let path = uuid::Uuid::new_v4().to_string();
+    let (content, size) = gen_bytes(op.info().full_capability());
+
+    let mut w = op.writer(&path).await?;
+    w.write(content.clone()).await?;
+    let written = w.close().await?;
+    assert_eq!(written.mode(), EntryMode::FILE);
+
+    // Azfile returns etag and content length while committing.
+    if op.info().scheme() == opendal::Scheme::Azfile {
+        assert!(written.metakey().contains(Metakey::Etag));
+        assert!(written.metakey().contains(Metakey::ContentLength));
+    }
+
+    let meta = op.stat(&path).await.expect("stat must succeed");
+    if written.metakey().contains(Metakey::ContentLength) {
+        assert_eq!(written.content_length(), size as u64);
+    }
+    if written.metakey().contains(Metakey::Etag) {
+        assert_eq!(written.etag(), meta.etag());
+    }
+
+    // Metadata should be returned by write too.
+    let written = op.write(&path, content).await?;
+    let meta = op.stat(&path).await.expect("stat must succeed");
+    if written.metakey().contains(Metakey::Etag) {
+        assert_eq!(written.etag(), meta.etag());
+    }
+
+    op.delete(&path).await.expect("delete must succeed");
+    Ok(())
+}
+
 /// Streaming data into writer
 pub async fn test_writer_sink(op: Operator) -> Result<()> {
     let cap = op.info().full_capability();
@@ -1220,6 +1840,68 @@ pub async fn test_writer_sink(op: Operator) -> Result<()> {
     Ok(())
 }
 
+/// Write from stream with chunks of different sizes should succeed.
+pub async fn test_write_from_stream(op: Operator) -> Result<()> {
+    let cap = op.info().full_capability();
+    if !(cap.write && cap.write_can_multi) {
+        return Ok(());
+    }
+
+    let path = uuid::Uuid::new_v4().to_string();
+    let chunks: Vec<Vec<u8>> = [1024, 5 * 1024 * 1024, 1, 3 * 1024 * 1024]
+        .into_iter()
+        .map(gen_fixed_bytes)
+        .collect();
+    let content = chunks.concat();
+    let stream = stream::iter(chunks).map(Ok);
+
+    let written = op.write_from_stream(&path, stream).await?;
+    assert_eq!(written, content.len() as u64);
+
+    let bs = op.read(&path).await?;
+    assert_eq!(bs.len(), content.len(), "read size");
+    assert_eq!(
+        format!("{:x}", Sha256::digest(&bs)),
+        format!("{:x}", Sha256::digest(&content)),
+        "read content"
+    );
+
+    op.delete(&path).await.expect("delete must succeed");
+    Ok(())
+}
+
+/// Write chunks concurrently should keep content in order.
+pub async fn test_writer_with_concurrent(op: Operator) -> Result<()> {
+    let cap = op.info().full_capability();
+    if !(cap.write && cap.write_can_multi) {
+        return Ok(());
+    }
+
+    let path = uuid::Uuid::new_v4().to_string();
+    let chunk = 5 * 1024 * 1024;
+    // Make sure the content will be split into several chunks with a tail.
+    let size = chunk * 3 + 1024;
+    let content = gen_fixed_bytes(size);
+
+    let mut w = op.writer_with(&path).chunk(chunk).concurrent(4).await?;
+    w.write(content.clone()).await?;
+    w.close().await?;
+
+    let meta = op.stat(&path).await.expect("stat must succeed");
+    assert_eq!(meta.content_length(), size as u64);
+
+    let bs = op.read(&path).await?;
+    assert_eq!(bs.len(), size, "read size");
+    assert_eq!(
+        format!("{:x}", Sha256::digest(&bs)),
+        format!("{:x}", Sha256::digest(&content)),
+        "read content"
+    );
+
+    op.delete(&path).await.expect("delete must succeed");
+    Ok(())
+}
+
 /// Reading data into writer
 pub async fn test_writer_copy(op: Operator) -> Result<()> {
     let cap = op.info().full_capability();